@@ -26,6 +26,7 @@ const PING_STATE_SAVE_PATH: &str = const_format::formatcp!(
 );
 
 fn main() {
+    tracing_subscriber::fmt::init();
     let ping_settings = PingSettings {
         state_save_path: String::from(PING_STATE_SAVE_PATH),
     };
@@ -1,7 +1,7 @@
 // STD
 use std::fmt::Debug;
 // Crates
-use overwatch_rs::services::state::{ServiceState, StateOperator};
+use overwatch_rs::services::state::{PersistContext, ServiceState, StateOperator};
 // Internal
 use crate::states::PingState;
 
@@ -29,7 +29,7 @@ impl StateOperator for StateSaveOperator {
         }
     }
 
-    async fn run(&mut self, state: Self::StateInput) {
+    async fn run(&mut self, state: Self::StateInput, _context: PersistContext) {
         let json_state = serde_json::to_string(&state).expect("Failed to serialize state");
         std::fs::write(&self.save_path, json_state).unwrap();
     }
@@ -1,4 +1,5 @@
 // Crates
+use overwatch_rs::service_println;
 use overwatch_rs::services::handle::ServiceStateHandle;
 use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
 use overwatch_rs::DynError;
@@ -42,6 +43,7 @@ impl ServiceCore for PingService {
             initial_state,
         } = self;
 
+        let output = service_state_handle.output();
         let mut inbound_relay = service_state_handle.inbound_relay;
         let pong_outbound_relay = service_state_handle
             .overwatch_handle
@@ -54,7 +56,7 @@ impl ServiceCore for PingService {
         loop {
             tokio::select! {
                 _ = sleep(Duration::from_secs(1)) => {
-                    println!("Sending Ping");
+                    service_println!(output, "Sending Ping");
                     pong_outbound_relay.send(PongMessage::Ping).await.unwrap();
                 }
                 Some(message) = inbound_relay.recv() => {
@@ -63,15 +65,15 @@ impl ServiceCore for PingService {
                             pong_count += 1;
                             service_state_handle.state_updater.update(
                                 Self::State { pong_count }
-                            );
-                            println!("Received Pong. Total: {}", pong_count);
+                            )?;
+                            service_println!(output, "Received Pong. Total: {}", pong_count);
                         }
                     }
                 }
                 true = async {
                     pong_count >= 30
                 } => {
-                    println!("Received {} Pongs. Exiting...", pong_count);
+                    service_println!(output, "Received {} Pongs. Exiting...", pong_count);
                     break;
                 }
             }
@@ -1,10 +1,7 @@
 // Crates
 use crate::messages::{PingMessage, PongMessage};
 use crate::service_ping::PingService;
-use overwatch_rs::services::handle::ServiceStateHandle;
-use overwatch_rs::services::state::{NoOperator, NoState};
-use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
-use overwatch_rs::DynError;
+use overwatch_rs::prelude::*;
 
 pub struct PongService {
     service_state_handle: ServiceStateHandle<Self>,
@@ -34,6 +31,7 @@ impl ServiceCore for PongService {
             service_state_handle,
         } = self;
 
+        let output = service_state_handle.output();
         let mut inbound_relay = service_state_handle.inbound_relay;
         let ping_outbound_relay = service_state_handle
             .overwatch_handle
@@ -44,7 +42,7 @@ impl ServiceCore for PongService {
         while let Some(message) = inbound_relay.recv().await {
             match message {
                 PongMessage::Ping => {
-                    println!("Received Ping. Sending Pong.");
+                    service_println!(output, "Received Ping. Sending Pong.");
                     ping_outbound_relay.send(PingMessage::Pong).await.unwrap();
                 }
             }
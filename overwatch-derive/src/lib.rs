@@ -1,30 +1,27 @@
 mod utils;
 
 use proc_macro_error::{abort_call_site, proc_macro_error};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{punctuated::Punctuated, token::Comma, Data, DeriveInput, Field, Generics};
 
+/// Arguments of the `tracing::instrument` attribute wrapping generated methods
+/// Methods are wrapped in `overwatch_rs::utils::instrumentation::instrumented!`, which only applies
+/// it when `overwatch-rs` is built with its `instrumentation` feature.
 fn get_default_instrumentation() -> proc_macro2::TokenStream {
-    #[cfg(feature = "instrumentation")]
-    quote! {
-        #[tracing::instrument(skip(self), err)]
-    }
-
-    #[cfg(not(feature = "instrumentation"))]
-    quote! {}
+    quote! { skip(self), err }
 }
 
 fn get_default_instrumentation_without_settings() -> proc_macro2::TokenStream {
-    #[cfg(feature = "instrumentation")]
-    quote! {
-        #[tracing::instrument(skip(self, settings), err)]
-    }
-
-    #[cfg(not(feature = "instrumentation"))]
-    quote! {}
+    quote! { skip(self, settings), err }
 }
 
-#[proc_macro_derive(Services)]
+/// Fields marked `#[bundle]` hold another `Services` struct, whose services are attached along
+/// with the other ones. Their settings are nested under the field name, which is also their
+/// namespace: they run under `<field>.<service id>`, see `OverwatchHandle::resolve`.
+/// `Option<ServiceHandle<S>>` fields hold services that can be disabled from their settings, see
+/// `OptionalSettings`.
+#[proc_macro_derive(Services, attributes(bundle))]
 #[proc_macro_error]
 pub fn derive_services(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input: DeriveInput = syn::parse(input).expect("A syn parseable token stream");
@@ -44,6 +41,89 @@ fn service_settings_field_identifier_from(
     format_ident!("{}_settings", field_identifier)
 }
 
+/// Whether a field holds a nested `Services` bundle instead of a service
+fn is_bundle(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| attr.path.is_ident("bundle"))
+}
+
+/// Namespace of the services of a bundle field, the field name
+fn bundle_namespace(field: &Field) -> String {
+    field
+        .ident
+        .as_ref()
+        .expect("A struct attribute identifier")
+        .to_string()
+}
+
+/// Id of the matched `service_id` relative to the bundle of a field, which the bundle matches
+/// its services with
+fn bundle_service_id(field: &Field) -> proc_macro2::TokenStream {
+    let namespace = bundle_namespace(field);
+    quote! {
+        ::overwatch_rs::services::ids::strip_namespace(service_id, ::std::option::Option::Some(#namespace))
+            .expect("Matched bundle service")
+    }
+}
+
+/// Pattern matching the ids of the services a field holds, and expressions reaching the matched
+/// service handle, or bundle, from `&self` and `&mut self` methods
+/// Disabled optional services return the `ServiceDisabled` variant of `error` instead. Bundles
+/// are given the matched id relative to them, see [`bundle_service_id`].
+fn service_match(
+    field: &Field,
+    error: &proc_macro2::TokenStream,
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+) {
+    let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+    if is_bundle(field) {
+        let namespace = bundle_namespace(field);
+        return (
+            quote! {
+                service_id if ::overwatch_rs::services::ids::strip_namespace(service_id, ::std::option::Option::Some(#namespace))
+                    .is_some_and(|service_id| self.#field_identifier.has_service(service_id))
+            },
+            quote!(self.#field_identifier),
+            quote!(self.#field_identifier),
+        );
+    }
+    let type_id = utils::extract_type_from(&field.ty);
+    if utils::is_optional(&field.ty) {
+        let disabled = quote! {
+            return ::std::result::Result::Err(#error::ServiceDisabled { service_id })
+        };
+        (
+            quote!(<#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID),
+            quote! {
+                (match self.#field_identifier.as_ref() {
+                    ::std::option::Option::Some(service) => service,
+                    ::std::option::Option::None => #disabled,
+                })
+            },
+            quote! {
+                (match self.#field_identifier.as_mut() {
+                    ::std::option::Option::Some(service) => service,
+                    ::std::option::Option::None => #disabled,
+                })
+            },
+        )
+    } else if utils::is_service_pool(&field.ty) {
+        (
+            quote!(service_id if self.#field_identifier.contains(service_id)),
+            quote!(self.#field_identifier.member(service_id).expect("Matched pool member")),
+            quote!(self.#field_identifier.member_mut(service_id).expect("Matched pool member")),
+        )
+    } else {
+        (
+            quote!(<#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID),
+            quote!(self.#field_identifier),
+            quote!(self.#field_identifier),
+        )
+    }
+}
+
 fn impl_services(input: &DeriveInput) -> proc_macro2::TokenStream {
     use syn::DataStruct;
 
@@ -54,7 +134,12 @@ fn impl_services(input: &DeriveInput) -> proc_macro2::TokenStream {
         Data::Struct(DataStruct {
             fields: syn::Fields::Named(fields),
             ..
-        }) => impl_services_for_struct(struct_identifier, generics, &fields.named),
+        }) => {
+            if !generics.params.is_empty() && fields.named.iter().any(is_bundle) {
+                abort_call_site!("Bundles can't be attached to generic Services structs");
+            }
+            impl_services_for_struct(struct_identifier, generics, &fields.named)
+        }
         _ => {
             abort_call_site!("Deriving Services is only supported for named Structs");
         }
@@ -67,11 +152,11 @@ fn impl_services_for_struct(
     fields: &Punctuated<Field, Comma>,
 ) -> proc_macro2::TokenStream {
     let settings = generate_services_settings(identifier, generics, fields);
-    let unique_ids_check = generate_assert_unique_identifiers(identifier, generics, fields);
+    let const_checks = generate_const_checks(identifier, generics, fields);
     let services_impl = generate_services_impl(identifier, generics, fields);
 
     quote! {
-        #unique_ids_check
+        #const_checks
 
         #settings
 
@@ -86,40 +171,166 @@ fn generate_services_settings(
 ) -> proc_macro2::TokenStream {
     let services_settings = fields.iter().map(|field| {
         let service_name = field.ident.as_ref().expect("A named struct attribute");
+        if is_bundle(field) {
+            let bundle = &field.ty;
+            return quote!(pub #service_name: <#bundle as ::overwatch_rs::overwatch::Services>::Settings);
+        }
         let _type = utils::extract_type_from(&field.ty);
 
-        quote!(pub #service_name: <#_type as ::overwatch_rs::services::ServiceData>::Settings)
+        if utils::is_optional(&field.ty) {
+            quote!(pub #service_name: ::overwatch_rs::services::settings::OptionalSettings<<#_type as ::overwatch_rs::services::ServiceData>::Settings>)
+        } else if utils::is_service_pool(&field.ty) {
+            quote!(pub #service_name: ::std::vec::Vec<<#_type as ::overwatch_rs::services::ServiceData>::Settings>)
+        } else {
+            quote!(pub #service_name: <#_type as ::overwatch_rs::services::ServiceData>::Settings)
+        }
     });
+    // bounds mention a lifetime, `'de` or a higher-ranked one, so that the impls are left out
+    // instead of failing when a service settings can't be (de)serialized
+    let settings_bounds = |binder: proc_macro2::TokenStream, bound: proc_macro2::TokenStream| {
+        fields
+            .iter()
+            .map(|field| {
+                if is_bundle(field) {
+                    let bundle = &field.ty;
+                    return quote!(#binder <#bundle as ::overwatch_rs::overwatch::Services>::Settings: #bound).to_string();
+                }
+                let _type = utils::extract_type_from(&field.ty);
+                quote!(#binder <#_type as ::overwatch_rs::services::ServiceData>::Settings: #bound).to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let deserialize_bounds = settings_bounds(
+        quote!(),
+        quote!(::overwatch_rs::overwatch::config::serde::Deserialize<'de>),
+    );
+    let serialize_bounds = settings_bounds(
+        quote!(for<'ser>),
+        quote!(::overwatch_rs::overwatch::config::serde::Serialize),
+    );
     let services_settings_identifier = service_settings_identifier_from(services_identifier);
     let where_clause = &generics.where_clause;
+    // settings are always `Clone`, a derived impl would require it from the generic parameters
+    let fields_clones = fields.iter().map(|field| {
+        let field_identifier = field.ident.as_ref().expect("A named struct attribute");
+        quote!(#field_identifier: ::std::clone::Clone::clone(&self.#field_identifier))
+    });
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    // (de)serializing the settings depends on the `config` feature of overwatch-rs, which is
+    // only known there
     quote! {
-        #[derive(::std::clone::Clone, ::std::fmt::Debug)]
-        pub struct #services_settings_identifier #generics #where_clause {
-            #( #services_settings ),*
+        ::overwatch_rs::__services_settings! {
+            bound(deserialize = #deserialize_bounds, serialize = #serialize_bounds)
+            pub struct #services_settings_identifier #generics #where_clause {
+                #( #services_settings ),*
+            }
+        }
+
+        impl #impl_generics ::std::clone::Clone for #services_settings_identifier #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self {
+                    #( #fields_clones ),*
+                }
+            }
         }
     }
 }
 
-fn generate_assert_unique_identifiers(
+fn const_checks_identifier_from(services_identifier: &proc_macro2::Ident) -> proc_macro2::Ident {
+    format_ident!(
+        "__{}__CONST_CHECKS",
+        services_identifier.to_string().to_uppercase()
+    )
+}
+
+/// Compile time checks of the services configuration
+/// Each check is spanned at the field it is about, so a failing one points at the offending
+/// service. They are all gathered in a single constant referenced from `Services::new`, as
+/// associated constants are only evaluated when used.
+fn generate_const_checks(
     services_identifier: &proc_macro2::Ident,
     generics: &Generics,
     fields: &Punctuated<Field, Comma>,
 ) -> proc_macro2::TokenStream {
-    let services_ids = fields.iter().map(|field| {
-        let _type = utils::extract_type_from(&field.ty);
-        quote! {
-            <#_type as ::overwatch_rs::services::ServiceData>::SERVICE_ID
-        }
-    });
-    let services_ids_check = format_ident!(
-        "__{}__CONST_CHECK_UNIQUE_SERVICES_IDS",
-        services_identifier.to_string().to_uppercase()
-    );
+    let struct_prefix = services_identifier.to_string().to_uppercase();
+    let services_ids_check = format_ident!("__{}__CONST_CHECK_UNIQUE_SERVICES_IDS", struct_prefix);
+    // bundles check the services they hold themselves
+    let services_fields: Vec<_> = fields.iter().filter(|field| !is_bundle(field)).collect();
+    let service_checks_identifiers = services_fields
+        .iter()
+        .map(|field| {
+            let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+            format_ident!(
+                "__{}__CONST_CHECK_{}",
+                struct_prefix,
+                field_identifier.to_string().to_uppercase()
+            )
+        })
+        .collect::<Vec<_>>();
+    let service_checks = services_fields
+        .iter()
+        .zip(&service_checks_identifiers)
+        .map(|(field, check)| {
+            let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+            let _type = utils::extract_type_from(&field.ty);
+            let message = format!("Service `{field_identifier}` relay buffer size must be nonzero");
+            let priority_message =
+                format!("Service `{field_identifier}` priority relay buffer size must be nonzero");
+            let runtime_message =
+                format!("Service `{field_identifier}` dedicated runtime needs worker threads");
+            let broadcast_message =
+                format!("Service `{field_identifier}` broadcast buffer size must be nonzero");
+            let id_message = format!(
+                "Service `{field_identifier}` id can't contain `.` or `#`, the separators of bundled services and pool members"
+            );
+            quote_spanned! {field.ty.span()=>
+                const #check: () = {
+                    assert!(
+                        <#_type as ::overwatch_rs::services::ServiceData>::SERVICE_RELAY_BUFFER_SIZE > 0,
+                        #message
+                    );
+                    assert!(
+                        <#_type as ::overwatch_rs::services::ServiceData>::PRIORITY_RELAY_BUFFER_SIZE > 0,
+                        #priority_message
+                    );
+                    assert!(
+                        <#_type as ::overwatch_rs::services::ServiceData>::RUNTIME_POLICY.is_valid(),
+                        #runtime_message
+                    );
+                    assert!(
+                        <#_type as ::overwatch_rs::services::ServiceData>::BROADCAST_BUFFER_SIZE > 0,
+                        #broadcast_message
+                    );
+                    assert!(
+                        ::overwatch_rs::utils::const_checks::unnamespaced(
+                            <#_type as ::overwatch_rs::services::ServiceData>::SERVICE_ID
+                        ),
+                        #id_message
+                    );
+                };
+            }
+        });
+    let const_checks = const_checks_identifier_from(services_identifier);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     quote! {
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
         impl #impl_generics #services_identifier #ty_generics #where_clause {
-            const #services_ids_check: () = assert!(::overwatch_rs::utils::const_checks::unique_ids(&[#( #services_ids ),*]));
+            const #services_ids_check: () = assert!(
+                ::overwatch_rs::utils::const_checks::unique_ids(
+                    <Self as ::overwatch_rs::overwatch::Services>::SERVICES_IDS
+                ),
+                "Services ids must be unique"
+            );
+
+            #( #service_checks )*
+
+            const #const_checks: () = {
+                Self::#services_ids_check;
+                #( Self::#service_checks_identifiers; )*
+            };
         }
     }
 }
@@ -130,13 +341,127 @@ fn generate_services_impl(
     fields: &Punctuated<Field, Comma>,
 ) -> proc_macro2::TokenStream {
     let services_settings_identifier = service_settings_identifier_from(services_identifier);
-    let impl_new = generate_new_impl(fields);
+    let impl_new = generate_new_impl(services_identifier, fields);
     let impl_start_all = generate_start_all_impl(fields);
     let impl_start = generate_start_impl(fields);
     let impl_stop = generate_stop_impl(fields);
     let impl_relay = generate_request_relay_impl(fields);
     let impl_status = generate_request_status_watcher_impl(fields);
+    let impl_is_running = generate_is_running_impl(fields);
+    let impl_broadcast = generate_request_broadcast_impl(fields);
+    let impl_validate_settings = generate_validate_settings_impl(fields);
     let impl_update_settings = generate_update_settings_impl(fields);
+    let impl_update_service_settings = generate_update_service_settings_impl(fields);
+    let impl_failover = generate_failover_impl(fields);
+    let impl_scale = generate_scale_impl(fields);
+    let impl_teardown = generate_teardown_impl(fields);
+    let impl_current_settings = generate_current_settings_impl(fields);
+    let impl_request_snapshots = generate_request_snapshots_impl(fields);
+
+    let service_id = quote!(::overwatch_rs::services::ServiceId);
+    let services_ids = services_slice(
+        fields,
+        &service_id,
+        quote!(""),
+        |_, ids| ids,
+        |_type, _| Some(quote!(<#_type as ::overwatch_rs::services::ServiceData>::SERVICE_ID)),
+    );
+    let priority_type = quote!((#service_id, ::overwatch_rs::services::ServicePriority));
+    let services_priorities = services_slice(
+        fields,
+        &priority_type,
+        quote!(("", ::overwatch_rs::services::ServicePriority::Normal)),
+        |bundle, ids| {
+            bundle_items(
+                bundle,
+                ids,
+                &priority_type,
+                quote!(::overwatch_rs::services::ServicePriority::Normal),
+                quote!(SERVICES_PRIORITIES),
+            )
+        },
+        |_type, _| {
+            Some(quote! {
+                (
+                    <#_type as ::overwatch_rs::services::ServiceData>::SERVICE_ID,
+                    <#_type as ::overwatch_rs::services::ServiceData>::PRIORITY,
+                )
+            })
+        },
+    );
+    let buffer_size_type = quote!((#service_id, usize));
+    let services_relay_buffer_sizes = services_slice(
+        fields,
+        &buffer_size_type,
+        quote!(("", 0)),
+        |bundle, ids| {
+            bundle_items(
+                bundle,
+                ids,
+                &buffer_size_type,
+                quote!(0),
+                quote!(SERVICES_RELAY_BUFFER_SIZES),
+            )
+        },
+        |_type, _| {
+            Some(quote! {
+                (
+                    <#_type as ::overwatch_rs::services::ServiceData>::SERVICE_ID,
+                    <#_type as ::overwatch_rs::services::ServiceData>::SERVICE_RELAY_BUFFER_SIZE,
+                )
+            })
+        },
+    );
+    let services_pools = services_slice(
+        fields,
+        &service_id,
+        quote!(""),
+        |bundle, ids| {
+            quote!({
+                const POOLS: &[::overwatch_rs::services::ServiceId] =
+                    <#bundle as ::overwatch_rs::overwatch::Services>::SERVICES_POOLS;
+                const ITEMS: [::overwatch_rs::services::ServiceId; POOLS.len()] =
+                    ::overwatch_rs::utils::const_concat::select(
+                        POOLS,
+                        <#bundle as ::overwatch_rs::overwatch::Services>::SERVICES_IDS,
+                        #ids,
+                    );
+                &ITEMS
+            })
+        },
+        |_type, field| {
+            utils::is_service_pool(&field.ty)
+                .then(|| quote!(<#_type as ::overwatch_rs::services::ServiceData>::SERVICE_ID))
+        },
+    );
+    let settings_type = quote!((#service_id, &'static str));
+    let services_settings = services_slice(
+        fields,
+        &settings_type,
+        quote!(("", "")),
+        |bundle, ids| {
+            bundle_items(
+                bundle,
+                ids,
+                &settings_type,
+                quote!(""),
+                quote!(SERVICES_SETTINGS),
+            )
+        },
+        |_type, field| {
+            let mut section = field
+                .ident
+                .as_ref()
+                .expect("A struct attribute identifier")
+                .to_string();
+            // optional services settings are wrapped in `OptionalSettings`
+            if utils::is_optional(&field.ty) {
+                section.push_str(".settings");
+            }
+            Some(quote!((<#_type as ::overwatch_rs::services::ServiceData>::SERVICE_ID, #section)))
+        },
+    );
+    let impl_has_service = generate_has_service_impl(fields);
 
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
@@ -144,6 +469,18 @@ fn generate_services_impl(
         impl #impl_generics ::overwatch_rs::overwatch::Services for #services_identifier #ty_generics #where_clause {
             type Settings = #services_settings_identifier #ty_generics;
 
+            const SERVICES_IDS: &'static [::overwatch_rs::services::ServiceId] = #services_ids;
+
+            const SERVICES_PRIORITIES: &'static [(::overwatch_rs::services::ServiceId, ::overwatch_rs::services::ServicePriority)] = #services_priorities;
+
+            const SERVICES_RELAY_BUFFER_SIZES: &'static [(::overwatch_rs::services::ServiceId, usize)] = #services_relay_buffer_sizes;
+
+            const SERVICES_POOLS: &'static [::overwatch_rs::services::ServiceId] = #services_pools;
+
+            const SERVICES_SETTINGS: &'static [(::overwatch_rs::services::ServiceId, &'static str)] = #services_settings;
+
+            #impl_has_service
+
             #impl_new
 
             #impl_start_all
@@ -156,12 +493,112 @@ fn generate_services_impl(
 
             #impl_status
 
+            #impl_is_running
+
+            #impl_broadcast
+
+            #impl_validate_settings
+
             #impl_update_settings
+
+            #impl_update_service_settings
+
+            #impl_failover
+
+            #impl_scale
+
+            #impl_teardown
+
+            #impl_current_settings
+
+            #impl_request_snapshots
+        }
+    }
+}
+
+/// Value of a `Services` slice constant, with the `item` of each service field and the items of
+/// each bundle from `bundle_part`, given the bundle type and the ids of its services within its
+/// namespace
+/// Bundles items are only known once their own constants are evaluated, so they are
+/// namespaced and concatenated at compile time.
+fn services_slice(
+    fields: &Punctuated<Field, Comma>,
+    item_type: &proc_macro2::TokenStream,
+    fill: proc_macro2::TokenStream,
+    bundle_part: impl Fn(&syn::Type, proc_macro2::TokenStream) -> proc_macro2::TokenStream,
+    item: impl Fn(syn::Type, &Field) -> Option<proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let service_item = |field: &Field| item(utils::extract_type_from(&field.ty), field);
+    if !fields.iter().any(is_bundle) {
+        let items = fields.iter().filter_map(service_item);
+        return quote!(&[#( #items ),*]);
+    }
+    let parts = fields.iter().filter_map(|field| {
+        if is_bundle(field) {
+            let ids = namespaced_ids(&field.ty, &bundle_namespace(field));
+            Some(bundle_part(&field.ty, ids))
+        } else {
+            service_item(field).map(|item| quote!(&[#item]))
+        }
+    });
+    quote!({
+        const PARTS: &[&[#item_type]] = &[#( #parts ),*];
+        const ITEMS: [#item_type; ::overwatch_rs::utils::const_concat::concat_len(PARTS)] =
+            ::overwatch_rs::utils::const_concat::concat(PARTS, #fill);
+        &ITEMS
+    })
+}
+
+/// Ids of the services of `bundle`, prefixed with its `namespace` at compile time
+fn namespaced_ids(bundle: &syn::Type, namespace: &str) -> proc_macro2::TokenStream {
+    quote!({
+        const IDS: &[::overwatch_rs::services::ServiceId] =
+            <#bundle as ::overwatch_rs::overwatch::Services>::SERVICES_IDS;
+        const BYTES: [u8; ::overwatch_rs::utils::const_concat::prefixed_len(#namespace, IDS)] =
+            ::overwatch_rs::utils::const_concat::prefixed_bytes(#namespace, IDS);
+        const NAMESPACED: [::overwatch_rs::services::ServiceId; IDS.len()] =
+            ::overwatch_rs::utils::const_concat::prefixed(&BYTES, #namespace, IDS);
+        &NAMESPACED
+    })
+}
+
+/// Items of the `constant` of `bundle`, with the `ids` of its services within its namespace
+fn bundle_items(
+    bundle: &syn::Type,
+    ids: proc_macro2::TokenStream,
+    item_type: &proc_macro2::TokenStream,
+    fill: proc_macro2::TokenStream,
+    constant: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote!({
+        const BUNDLE_ITEMS: &[#item_type] =
+            <#bundle as ::overwatch_rs::overwatch::Services>::#constant;
+        const ITEMS: [#item_type; BUNDLE_ITEMS.len()] =
+            ::overwatch_rs::utils::const_concat::with_ids(BUNDLE_ITEMS, #ids, #fill);
+        &ITEMS
+    })
+}
+
+fn generate_has_service_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let patterns = fields
+        .iter()
+        .map(|field| service_match(field, &quote!(::overwatch_rs::overwatch::Error)).0);
+
+    quote! {
+        fn has_service(&self, service_id: ::overwatch_rs::services::ServiceId) -> bool {
+            match service_id {
+                #( #patterns => true, )*
+                _ => false,
+            }
         }
     }
 }
 
-fn generate_new_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+fn generate_new_impl(
+    services_identifier: &proc_macro2::Ident,
+    fields: &Punctuated<Field, Comma>,
+) -> proc_macro2::TokenStream {
+    let const_checks = const_checks_identifier_from(services_identifier);
     let fields_settings = fields.iter().map(|field| {
         let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
         let settings_field_identifier = service_settings_field_identifier_from(field_identifier);
@@ -172,13 +609,37 @@ fn generate_new_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStr
 
     let managers = fields.iter().map(|field| {
         let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
-        let service_type = utils::extract_type_from(&field.ty);
         let settings_field_identifier = service_settings_field_identifier_from(field_identifier);
+        if is_bundle(field) {
+            let bundle = &field.ty;
+            let namespace = bundle_namespace(field);
+            return quote! {
+                #field_identifier: <#bundle as ::overwatch_rs::overwatch::Services>::new(
+                    #settings_field_identifier, overwatch_handle.namespaced(#namespace),
+                )?
+            };
+        }
+        let service_type = utils::extract_type_from(&field.ty);
+        if utils::is_optional(&field.ty) {
+            return quote! {
+                #field_identifier: if #settings_field_identifier.enabled {
+                    ::std::option::Option::Some(::overwatch_rs::services::handle::ServiceHandle::<#service_type>::new(
+                        #settings_field_identifier.settings, overwatch_handle.clone(),
+                    )?)
+                } else {
+                    ::std::option::Option::None
+                }
+            };
+        }
+        let manager_type = if utils::is_service_pool(&field.ty) {
+            quote!(::overwatch_rs::services::pool::ServicePool::<#service_type>)
+        } else {
+            quote!(::overwatch_rs::services::handle::ServiceHandle::<#service_type>)
+        };
         quote! {
             #field_identifier: {
-                let manager =
-                    ::overwatch_rs::services::handle::ServiceHandle::<#service_type>::new(
-                        #settings_field_identifier, overwatch_handle.clone(),
+                let manager = #manager_type::new(
+                    #settings_field_identifier, overwatch_handle.clone(),
                 )?;
                 manager
             }
@@ -187,6 +648,8 @@ fn generate_new_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStr
 
     quote! {
         fn new(settings: Self::Settings, overwatch_handle: ::overwatch_rs::overwatch::handle::OverwatchHandle) -> ::std::result::Result<Self, ::overwatch_rs::DynError> {
+            // fails to compile if any of the services configuration checks fails
+            let () = Self::#const_checks;
             let Self::Settings {
                 #( #fields_settings ),*
             } = settings;
@@ -203,27 +666,59 @@ fn generate_new_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStr
 fn generate_start_all_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
     let call_start = fields.iter().map(|field| {
         let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
-        quote! {
-            self.#field_identifier.service_runner().run()?
+        if is_bundle(field) {
+            quote! {
+                lifecycle_handles.extend(self.#field_identifier.start_all_except(stopped)?);
+            }
+        } else if utils::is_service_pool(&field.ty) {
+            quote! {
+                lifecycle_handles.extend(self.#field_identifier.start_all()?);
+            }
+        } else if utils::is_optional(&field.ty) {
+            // disabled services are left out
+            quote! {
+                if let ::std::option::Option::Some(service) = self.#field_identifier.as_mut() {
+                    if stopped.contains(&service.id()) {
+                        lifecycle_handles.push(service.keep_stopped());
+                    } else {
+                        lifecycle_handles.push(service.start()?);
+                    }
+                }
+            }
+        } else {
+            quote! {
+                if stopped.contains(&self.#field_identifier.id()) {
+                    lifecycle_handles.push(self.#field_identifier.keep_stopped());
+                } else {
+                    lifecycle_handles.push(self.#field_identifier.start()?);
+                }
+            }
         }
     });
 
     let instrumentation = get_default_instrumentation();
     quote! {
-        #instrumentation
-        fn start_all(&mut self) -> Result<::overwatch_rs::overwatch::ServicesLifeCycleHandle, ::overwatch_rs::overwatch::Error> {
-            ::std::result::Result::Ok([#( #call_start ),*].try_into()?)
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn start_all_except(&mut self, stopped: &[::overwatch_rs::services::ServiceId]) -> Result<::overwatch_rs::overwatch::ServicesLifeCycleHandle, ::overwatch_rs::overwatch::Error> {
+                let mut lifecycle_handles = ::std::vec::Vec::new();
+                #( #call_start )*
+                ::std::result::Result::Ok(lifecycle_handles.try_into()?)
+            }
         }
     }
 }
 
 fn generate_start_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
     let cases = fields.iter().map(|field| {
-        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
-        let type_id = utils::extract_type_from(&field.ty);
+        let (pattern, _, service) = service_match(field, &quote!(::overwatch_rs::overwatch::Error));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => { #service.start(#service_id) });
+        }
         quote! {
-            <#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID => {
-                self.#field_identifier.service_runner().run()?;
+            #pattern => {
+                #service.start()?;
                 ::std::result::Result::Ok(())
             }
         }
@@ -231,11 +726,13 @@ fn generate_start_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenS
 
     let instrumentation = get_default_instrumentation();
     quote! {
-        #instrumentation
-        fn start(&mut self, service_id: ::overwatch_rs::services::ServiceId) -> Result<(), ::overwatch_rs::overwatch::Error> {
-            match service_id {
-                #( #cases ),*
-                service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn start(&mut self, service_id: ::overwatch_rs::services::ServiceId) -> Result<(), ::overwatch_rs::overwatch::Error> {
+                match service_id {
+                    #( #cases ),*
+                    service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+                }
             }
         }
     }
@@ -243,21 +740,28 @@ fn generate_start_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenS
 
 fn generate_stop_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
     let cases = fields.iter().map(|field| {
-        let _field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
-        let type_id = utils::extract_type_from(&field.ty);
-        // TODO: actually stop them here once service lifecycle is implemented
+        let (pattern, _, service) = service_match(field, &quote!(::overwatch_rs::overwatch::Error));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => { #service.stop(#service_id, reason) });
+        }
         quote! {
-            <#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID => { unimplemented!() }
+            #pattern => {
+                #service.stop(reason);
+                ::std::result::Result::Ok(())
+            }
         }
     });
 
     let instrumentation = get_default_instrumentation();
     quote! {
-        #instrumentation
-        fn stop(&mut self, service_id: ::overwatch_rs::services::ServiceId) -> Result<(), ::overwatch_rs::overwatch::Error> {
-            match service_id {
-                #( #cases ),*
-                service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn stop(&mut self, service_id: ::overwatch_rs::services::ServiceId, reason: ::overwatch_rs::services::life_cycle::StopReason) -> Result<(), ::overwatch_rs::overwatch::Error> {
+                match service_id {
+                    #( #cases ),*
+                    service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+                }
             }
         }
     }
@@ -265,14 +769,16 @@ fn generate_stop_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenSt
 
 fn generate_request_relay_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
     let cases = fields.iter().map(|field| {
-        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
-        let type_id = utils::extract_type_from(&field.ty);
+        let (pattern, service, _) =
+            service_match(field, &quote!(::overwatch_rs::services::relay::RelayError));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => #service.request_relay(#service_id),);
+        }
         quote! {
-            <#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID => {
+            #pattern => {
                 ::std::result::Result::Ok(::std::boxed::Box::new(
-                    self.#field_identifier
-                        .relay_with()
-                        .ok_or(::overwatch_rs::services::relay::RelayError::AlreadyConnected)?
+                    #service.request_relay()?
                 ) as ::overwatch_rs::services::relay::AnyMessage)
             }
         }
@@ -280,11 +786,13 @@ fn generate_request_relay_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2
 
     let instrumentation = get_default_instrumentation();
     quote! {
-        #instrumentation
-        fn request_relay(&mut self, service_id: ::overwatch_rs::services::ServiceId) -> ::overwatch_rs::services::relay::RelayResult {
-            match service_id {
-                #( #cases )*
-                service_id => ::std::result::Result::Err(::overwatch_rs::services::relay::RelayError::Unavailable { service_id })
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn request_relay(&mut self, service_id: ::overwatch_rs::services::ServiceId) -> ::overwatch_rs::services::relay::RelayResult {
+                match service_id {
+                    #( #cases )*
+                    service_id => ::std::result::Result::Err(::overwatch_rs::services::relay::RelayError::Unavailable { service_id })
+                }
             }
         }
     }
@@ -294,19 +802,26 @@ fn generate_request_status_watcher_impl(
     fields: &Punctuated<Field, Comma>,
 ) -> proc_macro2::TokenStream {
     let cases = fields.iter().map(|field| {
-        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
-        let type_id = utils::extract_type_from(&field.ty);
+        let (pattern, service, _) = service_match(
+            field,
+            &quote!(::overwatch_rs::services::status::ServiceStatusError),
+        );
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => #service.request_status_watcher(#service_id),);
+        }
         quote! {
-            <#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID => {
-                    ::std::result::Result::Ok(self.#field_identifier.status_watcher())
+            #pattern => {
+                    ::std::result::Result::Ok(#service.status_watcher())
             }
         }
     });
 
+    let instrumentation = get_default_instrumentation();
     quote! {
-        #[::tracing::instrument(skip(self), err)]
-        fn request_status_watcher(&self, service_id: ::overwatch_rs::services::ServiceId) -> ::overwatch_rs::services::status::ServiceStatusResult {
-            {
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn request_status_watcher(&self, service_id: ::overwatch_rs::services::ServiceId) -> ::overwatch_rs::services::status::ServiceStatusResult {
                 match service_id {
                     #( #cases )*
                     service_id => ::std::result::Result::Err(::overwatch_rs::services::status::ServiceStatusError::Unavailable { service_id })
@@ -316,6 +831,104 @@ fn generate_request_status_watcher_impl(
     }
 }
 
+fn generate_is_running_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let cases = fields.iter().map(|field| {
+        let (pattern, service, _) = service_match(field, &quote!(::overwatch_rs::overwatch::Error));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => #service.is_running(#service_id),);
+        }
+        quote! {
+            #pattern => ::std::result::Result::Ok(#service.is_running()),
+        }
+    });
+
+    quote! {
+        fn is_running(&self, service_id: ::overwatch_rs::services::ServiceId) -> Result<bool, ::overwatch_rs::overwatch::Error> {
+            match service_id {
+                #( #cases )*
+                service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+            }
+        }
+    }
+}
+
+fn generate_request_broadcast_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let cases = fields.iter().map(|field| {
+        let (pattern, service, _) =
+            service_match(field, &quote!(::overwatch_rs::services::relay::RelayError));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => #service.request_broadcast(#service_id),);
+        }
+        quote! {
+            #pattern => {
+                    ::std::result::Result::Ok(#service.broadcast())
+            }
+        }
+    });
+
+    let instrumentation = get_default_instrumentation();
+    quote! {
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn request_broadcast(&self, service_id: ::overwatch_rs::services::ServiceId) -> ::overwatch_rs::services::broadcast::BroadcastResult {
+                match service_id {
+                    #( #cases )*
+                    service_id => ::std::result::Result::Err(::overwatch_rs::services::relay::RelayError::Unavailable { service_id })
+                }
+            }
+        }
+    }
+}
+
+/// Check every service settings before any of them is applied
+fn generate_validate_settings_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let validate_calls = fields.iter().map(|field| {
+        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+        if is_bundle(field) {
+            let bundle = &field.ty;
+            return quote! {
+                <#bundle as ::overwatch_rs::overwatch::Services>::validate_settings(&settings.#field_identifier)?;
+            };
+        }
+        let type_id = utils::extract_type_from(&field.ty);
+        let validate = |settings: proc_macro2::TokenStream| {
+            quote! {
+                <#type_id as ::overwatch_rs::services::ServiceData>::validate_settings(#settings)
+                    .map_err(|source| ::overwatch_rs::overwatch::Error::RejectedSettings {
+                        service_id: <#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID,
+                        source,
+                    })?;
+            }
+        };
+        if utils::is_optional(&field.ty) {
+            let validate = validate(quote!(&settings.#field_identifier.settings));
+            quote! {
+                if settings.#field_identifier.enabled {
+                    #validate
+                }
+            }
+        } else if utils::is_service_pool(&field.ty) {
+            let validate = validate(quote!(member_settings));
+            quote! {
+                for member_settings in &settings.#field_identifier {
+                    #validate
+                }
+            }
+        } else {
+            validate(quote!(&settings.#field_identifier))
+        }
+    });
+
+    quote! {
+        fn validate_settings(settings: &Self::Settings) -> Result<(), ::overwatch_rs::overwatch::Error> {
+            #( #validate_calls )*
+            ::std::result::Result::Ok(())
+        }
+    }
+}
+
 fn generate_update_settings_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
     let fields_settings = fields.iter().map(|field| {
         let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
@@ -328,22 +941,232 @@ fn generate_update_settings_impl(fields: &Punctuated<Field, Comma>) -> proc_macr
     let update_settings_call = fields.iter().map(|field| {
         let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
         let settings_field_identifier = service_settings_field_identifier_from(field_identifier);
+        if is_bundle(field) {
+            quote! {
+                self.#field_identifier.update_settings(#settings_field_identifier)?;
+            }
+        } else if utils::is_optional(&field.ty) {
+            // services are enabled or disabled when Overwatch starts only
+            quote! {
+                if let ::std::option::Option::Some(service) = self.#field_identifier.as_ref() {
+                    service.update_settings(#settings_field_identifier.settings);
+                }
+            }
+        } else {
+            quote! {
+                self.#field_identifier.update_settings(#settings_field_identifier);
+            }
+        }
+    });
+
+    let instrumentation = get_default_instrumentation_without_settings();
+    quote! {
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn update_settings(&mut self, settings: Self::Settings) -> Result<(), ::overwatch_rs::overwatch::Error> {
+                let Self::Settings {
+                    #( #fields_settings ),*
+                } = settings;
+
+                #( #update_settings_call )*
+
+                ::std::result::Result::Ok(())
+            }
+        }
+    }
+}
+
+fn generate_update_service_settings_impl(
+    fields: &Punctuated<Field, Comma>,
+) -> proc_macro2::TokenStream {
+    let cases = fields.iter().map(|field| {
+        let (pattern, _, service) = service_match(field, &quote!(::overwatch_rs::overwatch::Error));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => { #service.update_service_settings(#service_id, settings) });
+        }
+        let type_id = utils::extract_type_from(&field.ty);
         quote! {
-            self.#field_identifier.update_settings(#settings_field_identifier);
+            #pattern => {
+                let settings = settings
+                    .downcast::<<#type_id as ::overwatch_rs::services::ServiceData>::Settings>()
+                    .map_err(|_| ::overwatch_rs::overwatch::Error::InvalidSettings { service_id })?;
+                <#type_id as ::overwatch_rs::services::ServiceData>::validate_settings(&settings)
+                    .map_err(|source| ::overwatch_rs::overwatch::Error::RejectedSettings { service_id, source })?;
+                #service.reconfigure(*settings)
+            }
         }
     });
 
     let instrumentation = get_default_instrumentation_without_settings();
     quote! {
-        #instrumentation
-        fn update_settings(&mut self, settings: Self::Settings) -> Result<(), ::overwatch_rs::overwatch::Error> {
-            let Self::Settings {
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn update_service_settings(&mut self, service_id: ::overwatch_rs::services::ServiceId, settings: ::overwatch_rs::overwatch::AnySettings) -> Result<(), ::overwatch_rs::overwatch::Error> {
+                match service_id {
+                    #( #cases ),*
+                    service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+                }
+            }
+        }
+    }
+}
+
+fn generate_failover_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let cases = fields.iter().map(|field| {
+        let (pattern, _, service) = service_match(field, &quote!(::overwatch_rs::overwatch::Error));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => { #service.failover(#service_id) });
+        }
+        quote! {
+            #pattern => {
+                #service.failover()
+            }
+        }
+    });
+
+    let instrumentation = get_default_instrumentation();
+    quote! {
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn failover(&mut self, service_id: ::overwatch_rs::services::ServiceId) -> Result<(), ::overwatch_rs::overwatch::Error> {
+                match service_id {
+                    #( #cases ),*
+                    service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+                }
+            }
+        }
+    }
+}
+
+fn generate_scale_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let cases = fields.iter().map(|field| {
+        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+        let (pattern, _, _) = service_match(field, &quote!(::overwatch_rs::overwatch::Error));
+        if is_bundle(field) {
+            let service_id = bundle_service_id(field);
+            return quote!(#pattern => { self.#field_identifier.scale(#service_id, members) });
+        }
+        let type_id = utils::extract_type_from(&field.ty);
+        if utils::is_service_pool(&field.ty) {
+            quote! {
+                <#type_id as ::overwatch_rs::services::ServiceData>::SERVICE_ID => {
+                    self.#field_identifier.scale(members)
+                }
+            }
+        } else {
+            quote! {
+                #pattern => {
+                    ::std::result::Result::Err(::overwatch_rs::overwatch::Error::NotAPool { service_id })
+                }
+            }
+        }
+    });
+
+    let instrumentation = get_default_instrumentation();
+    quote! {
+        ::overwatch_rs::utils::instrumentation::instrumented! {
+            (#instrumentation)
+            fn scale(&mut self, service_id: ::overwatch_rs::services::ServiceId, members: usize) -> Result<::overwatch_rs::services::pool::Scaled, ::overwatch_rs::overwatch::Error> {
+                match service_id {
+                    #( #cases ),*
+                    service_id => ::std::result::Result::Err(::overwatch_rs::overwatch::Error::Unavailable { service_id })
+                }
+            }
+        }
+    }
+}
+
+fn generate_teardown_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let take_tasks = fields.iter().map(|field| {
+        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+        if is_bundle(field) {
+            quote! {
+                tasks.extend(self.#field_identifier.teardown());
+            }
+        } else if utils::is_service_pool(&field.ty) {
+            quote! {
+                tasks.extend(self.#field_identifier.take_tasks());
+            }
+        } else if utils::is_optional(&field.ty) {
+            quote! {
+                tasks.extend(self.#field_identifier.as_mut().and_then(|service| service.take_task()));
+            }
+        } else {
+            quote! {
+                tasks.extend(self.#field_identifier.take_task());
+            }
+        }
+    });
+
+    quote! {
+        fn teardown(&mut self) -> ::std::vec::Vec<::overwatch_rs::services::handle::ServiceTask> {
+            let mut tasks = ::std::vec::Vec::new();
+            #( #take_tasks )*
+            tasks
+        }
+    }
+}
+
+fn generate_current_settings_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let fields_settings = fields.iter().map(|field| {
+        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+        if is_bundle(field) {
+            return quote! {
+                #field_identifier: self.#field_identifier.current_settings(&settings.#field_identifier)
+            };
+        }
+        if utils::is_optional(&field.ty) {
+            // disabled services don't run, they keep the settings they were given
+            return quote! {
+                #field_identifier: match self.#field_identifier.as_ref() {
+                    ::std::option::Option::Some(service) => {
+                        ::overwatch_rs::services::settings::OptionalSettings::new(service.settings())
+                    }
+                    ::std::option::Option::None => {
+                        ::overwatch_rs::services::settings::OptionalSettings::disabled(
+                            settings.#field_identifier.settings.clone(),
+                        )
+                    }
+                }
+            };
+        }
+        quote!(#field_identifier: self.#field_identifier.settings())
+    });
+
+    quote! {
+        fn current_settings(&self, settings: &Self::Settings) -> Self::Settings {
+            Self::Settings {
                 #( #fields_settings ),*
-            } = settings;
+            }
+        }
+    }
+}
 
-            #( #update_settings_call )*
+fn generate_request_snapshots_impl(fields: &Punctuated<Field, Comma>) -> proc_macro2::TokenStream {
+    let snapshots = fields.iter().map(|field| {
+        let field_identifier = field.ident.as_ref().expect("A struct attribute identifier");
+        if is_bundle(field) || utils::is_service_pool(&field.ty) {
+            quote! {
+                requests.extend(self.#field_identifier.request_snapshots());
+            }
+        } else if utils::is_optional(&field.ty) {
+            quote! {
+                requests.extend(self.#field_identifier.as_ref().and_then(|service| service.request_snapshot()));
+            }
+        } else {
+            quote! {
+                requests.extend(self.#field_identifier.request_snapshot());
+            }
+        }
+    });
 
-            ::std::result::Result::Ok(())
+    quote! {
+        fn request_snapshots(&self) -> ::std::vec::Vec<::overwatch_rs::services::state::SnapshotRequest> {
+            let mut requests = ::std::vec::Vec::new();
+            #( #snapshots )*
+            requests
         }
     }
 }
@@ -2,20 +2,27 @@ use proc_macro_error::abort_call_site;
 use quote::ToTokens;
 use syn::{GenericArgument, PathArguments, Type};
 
+/// Service type of a `ServiceHandle<S>`, `ServicePool<S>` or `Option<ServiceHandle<S>>` field
 pub fn extract_type_from(ty: &Type) -> Type {
     let stringify_type = ty.clone().into_token_stream().to_string();
+    if option_argument(ty).is_some_and(is_service_pool) {
+        abort_call_site!(
+            "Optional service pools are not supported, found {}",
+            stringify_type
+        );
+    }
+    if is_optional(ty) {
+        let handle = extract_type_from_generic(ty, &stringify_type);
+        return extract_type_from_generic(&handle, &stringify_type);
+    }
+    extract_type_from_generic(ty, &stringify_type)
+}
 
+fn extract_type_from_generic(ty: &Type, stringify_type: &str) -> Type {
     match ty {
         Type::Path(type_path) if type_path.qself.is_none() => {
-            // Get the first segment of the path:
-            let type_params = type_path
-                .path
-                .segments
-                .iter()
-                .next()
-                .cloned()
-                .unwrap()
-                .arguments;
+            // Get the last segment of the path:
+            let type_params = type_path.path.segments.last().cloned().unwrap().arguments;
             // It should have only on angle-bracketed param ("<Foo>"):
             let generic_arg = match type_params {
                 PathArguments::AngleBracketed(params) => {
@@ -32,3 +39,43 @@ pub fn extract_type_from(ty: &Type) -> Type {
         _ => abort_call_site!("Expected single type argument, found {}", stringify_type),
     }
 }
+
+/// Check if a field type is a `ServicePool<S>` rather than a `ServiceHandle<S>`
+pub fn is_service_pool(ty: &Type) -> bool {
+    is_named(ty, "ServicePool")
+}
+
+/// Check if a field type is an `Option<ServiceHandle<S>>`, a service that can be disabled
+pub fn is_optional(ty: &Type) -> bool {
+    option_argument(ty).is_some_and(|handle| is_named(handle, "ServiceHandle"))
+}
+
+/// Type `T` of an `Option<T>` type
+fn option_argument(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.iter().collect::<Vec<_>>().as_slice() {
+        [GenericArgument::Type(argument)] => Some(argument),
+        _ => None,
+    }
+}
+
+/// Check if the last segment of a type path is `name`
+fn is_named(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name),
+        _ => false,
+    }
+}
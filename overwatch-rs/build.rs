@@ -0,0 +1,24 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    // set with `RUSTFLAGS="--cfg tokio_unstable"` along with the `tokio-console` feature,
+    // declaring expected cfgs is stable from Rust 1.80, older Cargo warns about it on every build
+    if rustc_minor_version().is_some_and(|minor| minor >= 80) {
+        println!("cargo:rustc-check-cfg=cfg(tokio_unstable)");
+    }
+}
+
+/// Minor version of the compiler building the crate, `80` for Rust 1.80
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    version
+        .split_whitespace()
+        .nth(1)?
+        .split('.')
+        .nth(1)?
+        .parse()
+        .ok()
+}
@@ -25,10 +25,19 @@
 //! - Services (handled by the *overwatch*)
 
 pub mod overwatch;
+pub mod prelude;
 pub mod services;
+// only meant to be used by the code generated from `overwatch-derive`
+#[doc(hidden)]
 pub mod utils;
 
 pub type DynError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 #[cfg(feature = "derive")]
 pub use overwatch_derive::*;
+
+/// Tasks of every service are named after its id, calling `console_subscriber::init()` before
+/// running the application is enough for tokio-console to break them down per service.
+/// Tokio only exposes tasks to the console with `RUSTFLAGS="--cfg tokio_unstable"`.
+#[cfg(feature = "tokio-console")]
+pub use console_subscriber;
@@ -0,0 +1,128 @@
+//! Integration point for admin APIs controlling a running application from outside
+//!
+//! [`AdminApi`] gathers the operations such an API exposes, so HTTP, gRPC or any other transport
+//! maps its endpoints onto it instead of wrapping the [`OverwatchHandle`] itself.
+//! [`OverwatchAdmin`] implements it on top of the handle, and the `admin-http` feature brings a
+//! reference HTTP server, see [`crate::overwatch::admin_http`].
+
+// std
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+// crates
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+// internal
+use crate::overwatch::commands::ServiceAction;
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::{Error as OverwatchError, Services};
+use crate::services::status::{ServiceStatus, ServiceStatusError};
+use crate::services::ServiceId;
+
+#[derive(Debug, Error)]
+pub enum AdminError {
+    #[error("unknown service {0}")]
+    UnknownService(String),
+    #[error("invalid settings: {0}")]
+    InvalidSettings(String),
+    #[error(transparent)]
+    Status(#[from] ServiceStatusError),
+    #[error(transparent)]
+    Overwatch(#[from] OverwatchError),
+}
+
+/// Operations of an admin API, services are named as in
+/// [`Services::service_id_from_str`]
+#[async_trait]
+pub trait AdminApi: Send + Sync + 'static {
+    /// Every service with its status, `None` if it couldn't be watched
+    async fn services(&self) -> Result<Vec<(ServiceId, Option<ServiceStatus>)>, AdminError>;
+
+    async fn status(&self, service: &str) -> Result<ServiceStatus, AdminError>;
+
+    async fn start(&self, service: &str) -> Result<(), AdminError>;
+
+    async fn stop(&self, service: &str) -> Result<(), AdminError>;
+
+    /// Apply new application settings, given in their serialized form
+    async fn update_settings(&self, settings: serde_json::Value) -> Result<(), AdminError>;
+}
+
+/// [`AdminApi`] of the application running the `S` services behind a handle
+pub struct OverwatchAdmin<S> {
+    handle: OverwatchHandle,
+    _services: PhantomData<fn() -> S>,
+}
+
+// auto derive introduces an unnecessary Clone bound on S
+impl<S> Clone for OverwatchAdmin<S> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            _services: PhantomData,
+        }
+    }
+}
+
+impl<S> Debug for OverwatchAdmin<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverwatchAdmin").finish_non_exhaustive()
+    }
+}
+
+impl<S: Services> OverwatchAdmin<S> {
+    pub fn new(handle: OverwatchHandle) -> Self {
+        Self {
+            handle,
+            _services: PhantomData,
+        }
+    }
+
+    fn service_id(service: &str) -> Result<ServiceId, AdminError> {
+        S::service_id_from_str(service)
+            .ok_or_else(|| AdminError::UnknownService(service.to_owned()))
+    }
+}
+
+#[async_trait]
+impl<S> AdminApi for OverwatchAdmin<S>
+where
+    S: Services + 'static,
+    S::Settings: DeserializeOwned + Send,
+{
+    async fn services(&self) -> Result<Vec<(ServiceId, Option<ServiceStatus>)>, AdminError> {
+        let topology = self.handle.topology().await?;
+        Ok(topology
+            .services
+            .into_iter()
+            .map(|service| (service.service_id, service.status))
+            .collect())
+    }
+
+    async fn status(&self, service: &str) -> Result<ServiceStatus, AdminError> {
+        let service_id = Self::service_id(service)?;
+        Ok(self.handle.status_watcher_for(service_id).await?.current())
+    }
+
+    async fn start(&self, service: &str) -> Result<(), AdminError> {
+        let service_id = Self::service_id(service)?;
+        Ok(self
+            .handle
+            .control_service(service_id, ServiceAction::Start)
+            .await?)
+    }
+
+    async fn stop(&self, service: &str) -> Result<(), AdminError> {
+        let service_id = Self::service_id(service)?;
+        Ok(self
+            .handle
+            .control_service(service_id, ServiceAction::Stop)
+            .await?)
+    }
+
+    async fn update_settings(&self, settings: serde_json::Value) -> Result<(), AdminError> {
+        let settings = serde_json::from_value::<S::Settings>(settings)
+            .map_err(|e| AdminError::InvalidSettings(e.to_string()))?;
+        Ok(self.handle.update_settings_and_wait::<S>(settings).await?)
+    }
+}
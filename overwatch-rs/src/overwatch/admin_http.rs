@@ -0,0 +1,182 @@
+//! Reference HTTP server for an [`AdminApi`]
+//!
+//! | Endpoint | Operation |
+//! |---|---|
+//! | `GET /services` | [`AdminApi::services`] |
+//! | `GET /services/{id}/status` | [`AdminApi::status`] |
+//! | `POST /services/{id}/start` | [`AdminApi::start`] |
+//! | `POST /services/{id}/stop` | [`AdminApi::stop`] |
+//! | `PUT /settings` | [`AdminApi::update_settings`], with the JSON settings as body |
+//!
+//! Statuses are given as `{"id": "store", "status": "running"}`, errors as
+//! `{"error": "unknown service api"}`. Nothing is authenticated, so [`serve`] only listens on
+//! loopback addresses unless [`AdminHttp::allow_non_loopback`] is set, otherwise put the
+//! [`router`] behind the application own authentication layer.
+
+// std
+use std::net::SocketAddr;
+use std::sync::Arc;
+// crates
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tracing::{error, info};
+// internal
+use crate::overwatch::admin::{AdminApi, AdminError};
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::server::ServerHandle;
+use crate::overwatch::topology::status_name;
+use crate::overwatch::Error;
+use crate::services::status::{ServiceStatus, ServiceStatusError};
+use crate::utils::runtime::{spawn_checked, ADMIN_HTTP_TASK};
+
+type Api = Arc<dyn AdminApi>;
+
+/// Routes of the admin endpoints, to be served or nested in the application own router
+pub fn router<A: AdminApi>(api: A) -> Router {
+    Router::new()
+        .route("/services", get(services))
+        .route("/services/:id/status", get(status))
+        .route("/services/:id/start", post(start))
+        .route("/services/:id/stop", post(stop))
+        .route("/settings", put(update_settings))
+        .with_state(Arc::new(api) as Api)
+}
+
+/// Serve the [`router`] of `api` on the loopback `address` until Overwatch shuts down or the
+/// server is aborted, see [`AdminHttp::serve`]
+pub async fn serve<A: AdminApi>(
+    api: A,
+    address: SocketAddr,
+    handle: &OverwatchHandle,
+) -> Result<AdminServer, Error> {
+    AdminHttp::new(api, address).serve(handle).await
+}
+
+/// Admin API HTTP server to be started with [`AdminHttp::serve`]
+#[derive(Debug)]
+pub struct AdminHttp<A> {
+    api: A,
+    address: SocketAddr,
+    non_loopback: bool,
+}
+
+impl<A: AdminApi> AdminHttp<A> {
+    /// Serve `api` on `address`, which has to be a loopback one as requests are not
+    /// authenticated unless [`AdminHttp::allow_non_loopback`] is set.
+    pub fn new(api: A, address: SocketAddr) -> Self {
+        Self {
+            api,
+            address,
+            non_loopback: false,
+        }
+    }
+
+    /// Listen on an address reachable from other hosts, anyone able to connect can then
+    /// operate the application
+    pub fn allow_non_loopback(mut self) -> Self {
+        self.non_loopback = true;
+        self
+    }
+
+    /// Serve the [`router`] of the api until Overwatch shuts down or the server is aborted
+    /// Requests in progress are completed before the server stops.
+    pub async fn serve(self, handle: &OverwatchHandle) -> Result<AdminServer, Error> {
+        let Self {
+            api,
+            address,
+            non_loopback,
+        } = self;
+        if !non_loopback && !address.ip().is_loopback() {
+            return Err(Error::any(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("admin API address {address} is not a loopback one"),
+            )));
+        }
+        let listener = TcpListener::bind(address).await.map_err(Error::any)?;
+        let address = listener.local_addr().map_err(Error::any)?;
+        let server = AdminServer::new(address, handle);
+        let shutdown = server.cancellation_token().clone();
+        spawn_checked(handle.runtime(), ADMIN_HTTP_TASK, async move {
+            if let Err(e) = axum::serve(listener, router(api))
+                .with_graceful_shutdown(async move { shutdown.cancelled().await })
+                .await
+            {
+                error!("Admin API server failed: {e}");
+            }
+        })?;
+        info!("Admin API listening on {address}");
+        Ok(server)
+    }
+}
+
+/// Listening admin API server, see [`serve`]
+pub type AdminServer = ServerHandle<SocketAddr>;
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::UnknownService(_) => StatusCode::NOT_FOUND,
+            Self::InvalidSettings(_)
+            | Self::Overwatch(Error::InvalidSettings { .. } | Error::RejectedSettings { .. }) => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::Status(ServiceStatusError::Unavailable { .. })
+            | Self::Overwatch(Error::Unavailable { .. } | Error::Busy | Error::Disconnected) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            Self::Status(ServiceStatusError::ServiceDisabled { .. })
+            | Self::Overwatch(Error::ServiceDisabled { .. }) => StatusCode::CONFLICT,
+            Self::Status(_) | Self::Overwatch(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
+fn status_json(service_id: &str, status: Option<&ServiceStatus>) -> Value {
+    let mut service = json!({
+        "id": service_id,
+        "status": status.map(status_name),
+    });
+    if let Some(ServiceStatus::Failed(reason)) = status {
+        service["reason"] = json!(reason);
+    }
+    service
+}
+
+async fn services(State(api): State<Api>) -> Result<Json<Value>, AdminError> {
+    let services = api.services().await?;
+    Ok(Json(Value::Array(
+        services
+            .iter()
+            .map(|(service_id, status)| status_json(service_id, status.as_ref()))
+            .collect(),
+    )))
+}
+
+async fn status(State(api): State<Api>, Path(id): Path<String>) -> Result<Json<Value>, AdminError> {
+    let status = api.status(&id).await?;
+    Ok(Json(status_json(&id, Some(&status))))
+}
+
+async fn start(State(api): State<Api>, Path(id): Path<String>) -> Result<StatusCode, AdminError> {
+    api.start(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn stop(State(api): State<Api>, Path(id): Path<String>) -> Result<StatusCode, AdminError> {
+    api.stop(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn update_settings(
+    State(api): State<Api>,
+    Json(settings): Json<Value>,
+) -> Result<StatusCode, AdminError> {
+    api.update_settings(settings).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
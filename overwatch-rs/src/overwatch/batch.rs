@@ -0,0 +1,153 @@
+// std
+// crates
+use tokio::sync::oneshot;
+// internal
+use crate::overwatch::commands::{
+    BatchCommand, BatchedCommand, CommandSpan, FailoverCommand, ReconfigureCommand, ReplyChannel,
+    RestartMode, ScaleCommand, ServiceAction, ServiceControlCommand, SettingsCommand,
+};
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::{Error, Services};
+use crate::services::{ServiceData, ServiceId};
+
+/// Lifecycle and settings commands sent to the runner at once, built with
+/// [`OverwatchHandle::batch`]
+///
+/// The runner handles the whole batch in order without interleaving other commands, so callers
+/// orchestrating many services pay a single round trip through the commands channel.
+/// Reconfigurations and failovers queue behind operations already running on their service,
+/// as they do when sent on their own.
+#[derive(Debug)]
+pub struct CommandBatch {
+    handle: OverwatchHandle,
+    commands: Vec<BatchedCommand>,
+    replies: Vec<oneshot::Receiver<Result<(), Error>>>,
+}
+
+impl CommandBatch {
+    pub(crate) fn new(handle: OverwatchHandle) -> Self {
+        Self {
+            handle,
+            commands: Vec::new(),
+            replies: Vec::new(),
+        }
+    }
+
+    /// Start `S`, a no-op if it is already running
+    pub fn start<S: ServiceData>(self) -> Self {
+        self.control(S::SERVICE_ID, ServiceAction::Start)
+    }
+
+    /// Stop the running instance of `S`, if any
+    pub fn stop<S: ServiceData>(self) -> Self {
+        self.control(S::SERVICE_ID, ServiceAction::Stop)
+    }
+
+    /// See [`OverwatchHandle::reconfigure_service`]
+    pub fn reconfigure<S: ServiceData>(self, settings: S::Settings, mode: RestartMode) -> Self
+    where
+        S::Settings: Send + 'static,
+    {
+        let service_id = self.handle.resolve(S::SERVICE_ID);
+        self.push(|reply_channel, span| {
+            BatchedCommand::Reconfigure(ReconfigureCommand {
+                service_id,
+                settings: Box::new(settings),
+                mode,
+                reply_channel,
+                span,
+            })
+        })
+    }
+
+    /// See [`OverwatchHandle::failover`]
+    pub fn failover<S: ServiceData>(self) -> Self {
+        let service_id = self.handle.resolve(S::SERVICE_ID);
+        self.push(|reply_channel, span| {
+            BatchedCommand::Failover(FailoverCommand {
+                service_id,
+                reply_channel,
+                span,
+            })
+        })
+    }
+
+    /// See [`OverwatchHandle::scale`]
+    pub fn scale<S: ServiceData>(self, members: usize) -> Self {
+        let service_id = self.handle.resolve(S::SERVICE_ID);
+        self.push(|reply_channel, span| {
+            BatchedCommand::Scale(ScaleCommand {
+                service_id,
+                members,
+                reply_channel,
+                span,
+            })
+        })
+    }
+
+    /// See [`OverwatchHandle::update_settings_and_wait`]
+    pub fn update_settings<S: Services>(self, settings: S::Settings) -> Self
+    where
+        S::Settings: Send,
+    {
+        self.push(|reply_channel, _| {
+            BatchedCommand::Settings(SettingsCommand(Box::new(settings), Some(reply_channel)))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Send the batch and wait until every command in it finished
+    /// Returns the outcome of each command, in the order they were queued, or
+    /// [`Error::Disconnected`] if the runner couldn't be reached.
+    pub async fn submit(self) -> Result<Vec<Result<(), Error>>, Error> {
+        let Self {
+            handle,
+            commands,
+            replies,
+        } = self;
+        if commands.is_empty() {
+            return Ok(Vec::new());
+        }
+        handle
+            .send_batch(BatchCommand {
+                commands,
+                span: CommandSpan::current(),
+            })
+            .await?;
+        let mut results = Vec::with_capacity(replies.len());
+        for reply in replies {
+            results.push(reply.await.unwrap_or(Err(Error::Disconnected)));
+        }
+        Ok(results)
+    }
+
+    fn control(self, service_id: ServiceId, action: ServiceAction) -> Self {
+        let service_id = self.handle.resolve(service_id);
+        self.push(|reply_channel, span| {
+            BatchedCommand::Control(ServiceControlCommand {
+                service_id,
+                action,
+                reply_channel,
+                span,
+            })
+        })
+    }
+
+    fn push(
+        mut self,
+        command: impl FnOnce(ReplyChannel<Result<(), Error>>, CommandSpan) -> BatchedCommand,
+    ) -> Self {
+        let (sender, receiver) = oneshot::channel();
+        self.commands
+            .push(command(ReplyChannel::from(sender), CommandSpan::current()));
+        self.replies.push(receiver);
+        self
+    }
+}
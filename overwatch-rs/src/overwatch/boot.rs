@@ -0,0 +1,82 @@
+// std
+use std::time::{Duration, Instant};
+// crates
+use futures::future::join_all;
+use tracing::info;
+// internal
+use crate::services::context::ContextConfig;
+use crate::services::status::{ServiceStatus, StatusWatcher};
+use crate::services::ServiceId;
+
+/// Boot outcome of a started service
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServiceBoot {
+    pub service_id: ServiceId,
+    /// Time from starting the services until this one reported [`ServiceStatus::Running`], or
+    /// [`ServiceStatus::Degraded`], `None` if it did not within [`ContextConfig::boot_report_timeout`]
+    pub time_to_ready: Option<Duration>,
+    /// The service was marked [`ServiceStatus::Failed`] before reporting it is running, e.g.
+    /// for exceeding its [`START_BUDGET`](crate::services::ServiceData::START_BUDGET)
+    pub failed: bool,
+}
+
+/// Summary of the services startup, see
+/// [`OverwatchHandle::boot_report`](crate::overwatch::handle::OverwatchHandle::boot_report)
+#[derive(Clone, Debug)]
+pub struct BootReport {
+    pub started: Vec<ServiceBoot>,
+    /// Attached services that were not started, like empty service pools
+    pub skipped: Vec<ServiceId>,
+    /// See [`ContextConfig::with_config_source`]
+    pub config_sources: Vec<String>,
+    /// Time from starting the services until the report was ready
+    pub elapsed: Duration,
+}
+
+/// Wait for the started services to be running, up to the configured timeout, and build the
+/// report
+pub(crate) async fn collect(
+    started_at: Instant,
+    watchers: Vec<(ServiceId, StatusWatcher)>,
+    skipped: Vec<ServiceId>,
+    config: &ContextConfig,
+) -> BootReport {
+    let clock = config.clock.as_ref();
+    let deadline = started_at + config.boot_report_timeout;
+    let started = join_all(
+        watchers
+            .into_iter()
+            .map(|(service_id, mut watcher)| async move {
+                let remaining = deadline.saturating_duration_since(clock.now());
+                let mut failure_watcher = watcher.clone();
+                let outcome = async {
+                    tokio::select! {
+                        running = watcher.wait_until(ServiceStatus::is_up, None) => running.is_ok(),
+                        _ = failure_watcher.wait_until(ServiceStatus::is_failed, None) => false,
+                    }
+                };
+                let outcome = clock.timeout(remaining, outcome).await;
+                ServiceBoot {
+                    service_id,
+                    time_to_ready: matches!(outcome, Ok(true))
+                        .then(|| clock.now().saturating_duration_since(started_at)),
+                    failed: outcome == Ok(false),
+                }
+            }),
+    )
+    .await;
+    let report = BootReport {
+        started,
+        skipped,
+        config_sources: config.config_sources.clone(),
+        elapsed: clock.now().saturating_duration_since(started_at),
+    };
+    info!(
+        started = ?report.started,
+        skipped = ?report.skipped,
+        config_sources = ?report.config_sources,
+        elapsed = ?report.elapsed,
+        "Boot report"
+    );
+    report
+}
@@ -0,0 +1,121 @@
+//! Checkpoints of a whole Overwatch instance, to start it again as it was
+//! A checkpoint is a JSON document, versioned with [`CHECKPOINT_VERSION`], holding the settings
+//! the services run with and the ids of the stopped ones. Taking it also runs the
+//! [`StateOperator`](crate::services::state::StateOperator) of every running service with
+//! [`PersistContext::Snapshot`](crate::services::state::PersistContext::Snapshot), services whose
+//! operator persists their state load it back when restored, see
+//! [`StateOperator::try_load`](crate::services::state::StateOperator::try_load).
+//! Checkpoints require the `checkpoint` feature.
+// std
+use std::path::{Path, PathBuf};
+// crates
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+// internal
+use crate::overwatch::Error as OverwatchError;
+use crate::utils::atomic_file;
+
+/// Version of the checkpoint documents written, checkpoints of other versions can't be restored
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum CheckpointError {
+    #[error("checkpoint file {path:?} couldn't be accessed: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("checkpoint file {path:?} is malformed: {source}")]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("checkpoint version {0} is not supported, only version {CHECKPOINT_VERSION} is")]
+    UnsupportedVersion(u32),
+
+    #[error("checkpoint doesn't match the services settings: {0}")]
+    Settings(#[source] serde_json::Error),
+
+    #[error("checkpoint lists service {0}, which is not attached")]
+    UnknownService(String),
+
+    #[error(transparent)]
+    Overwatch(#[from] OverwatchError),
+}
+
+/// Settings and lifecycle of the services of an Overwatch instance
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    /// Settings of the services, as the derived `*ServiceSettings` struct serializes them
+    pub settings: Value,
+    /// Ids of the services that were stopped, pool members aside
+    pub stopped: Vec<String>,
+}
+
+impl Checkpoint {
+    /// Read the checkpoint at `path`, checking it is of a supported version
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CheckpointError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| CheckpointError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let checkpoint: Self =
+            serde_json::from_str(&content).map_err(|source| CheckpointError::Malformed {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(checkpoint.version));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Write the checkpoint to `path`, replacing any previous one atomically
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        let path = path.as_ref();
+        let content =
+            serde_json::to_vec_pretty(self).expect("Checkpoint to be serializable to JSON");
+        atomic_file::write(path, &content, true).map_err(|source| CheckpointError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::checkpoint::{Checkpoint, CheckpointError, CHECKPOINT_VERSION};
+    use crate::overwatch::testing::EphemeralDir;
+
+    #[test]
+    fn checkpoints_of_other_versions_are_refused() {
+        let dir = EphemeralDir::create().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        let checkpoint = Checkpoint {
+            version: CHECKPOINT_VERSION,
+            settings: serde_json::json!({ "counter": 3 }),
+            stopped: vec!["printer".to_string()],
+        };
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+
+        Checkpoint {
+            version: CHECKPOINT_VERSION + 1,
+            ..checkpoint
+        }
+        .save(&path)
+        .unwrap();
+        assert!(matches!(
+            Checkpoint::load(&path),
+            Err(CheckpointError::UnsupportedVersion(version)) if version == CHECKPOINT_VERSION + 1
+        ));
+        dir.remove().unwrap();
+    }
+}
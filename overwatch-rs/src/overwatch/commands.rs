@@ -1,13 +1,20 @@
 // std
-
+use std::any::Any;
+use std::future::Future;
+use std::time::Duration;
 // crates
-use crate::overwatch::AnySettings;
+#[cfg(feature = "checkpoint")]
+use crate::overwatch::checkpoint::Checkpoint;
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::topology::Topology;
+use crate::overwatch::{AnySettings, Error};
 use crate::services::life_cycle::LifecycleMessage;
 use tokio::sync::oneshot;
 
 // internal
+use crate::services::broadcast::BroadcastResult;
 use crate::services::relay::RelayResult;
-use crate::services::status::StatusWatcher;
+use crate::services::status::ServiceStatusResult;
 use crate::services::ServiceId;
 
 #[derive(Debug)]
@@ -25,10 +32,37 @@ impl<M> ReplyChannel<M> {
     }
 }
 
+/// Span a lifecycle command was sent from, the runner handles the command within it so
+/// tracing shows the chain from the caller to the service instance it affects
+/// Only carries the span with the `instrumentation` feature, it is a no-op otherwise.
+#[derive(Clone, Debug)]
+pub(crate) struct CommandSpan {
+    #[cfg(feature = "instrumentation")]
+    span: tracing::Span,
+}
+
+impl CommandSpan {
+    pub(crate) fn current() -> Self {
+        Self {
+            #[cfg(feature = "instrumentation")]
+            span: tracing::Span::current(),
+        }
+    }
+
+    pub(crate) async fn instrument<F: Future>(self, future: F) -> F::Output {
+        #[cfg(feature = "instrumentation")]
+        return tracing::Instrument::instrument(future, self.span).await;
+        #[cfg(not(feature = "instrumentation"))]
+        future.await
+    }
+}
+
 /// Command for requesting communications with another service
 #[derive(Debug)]
 pub struct RelayCommand {
     pub(crate) service_id: ServiceId,
+    /// Service asking for the relay, `None` if requested from outside any service
+    pub(crate) requester: Option<ServiceId>,
     pub(crate) reply_channel: ReplyChannel<RelayResult>,
 }
 
@@ -36,7 +70,16 @@ pub struct RelayCommand {
 #[derive(Debug)]
 pub struct StatusCommand {
     pub(crate) service_id: ServiceId,
-    pub(crate) reply_channel: ReplyChannel<StatusWatcher>,
+    pub(crate) reply_channel: ReplyChannel<ServiceStatusResult>,
+}
+
+/// Command for requesting the broadcast relay of a service
+#[derive(Debug)]
+pub struct BroadcastCommand {
+    pub(crate) service_id: ServiceId,
+    /// Service subscribing to the broadcast, `None` if requested from outside any service
+    pub(crate) requester: Option<ServiceId>,
+    pub(crate) reply_channel: ReplyChannel<BroadcastResult>,
 }
 
 /// Command for managing [`ServiceCore`](crate::services::ServiceCore) lifecycle
@@ -52,18 +95,174 @@ pub struct ServiceLifeCycleCommand {
 pub enum OverwatchLifeCycleCommand {
     Shutdown,
     Kill,
+    /// Start every service that is not running, disabled optional ones aside
+    StartAll,
+    /// Stop every running service, Overwatch keeps running
+    StopAll,
 }
 
 /// [`Overwatch`](crate::overwatch::Overwatch) settings update command
+/// The reply channel, if any, gets the outcome once the settings are applied.
+#[derive(Debug)]
+pub struct SettingsCommand(
+    pub(crate) AnySettings,
+    pub(crate) Option<ReplyChannel<Result<(), Error>>>,
+);
+
+/// How a running service is brought down when it is restarted
+#[derive(Clone, Copy, Debug)]
+pub enum RestartMode {
+    /// Send a `Shutdown` lifecycle message and wait up to `timeout` for the service to finish
+    /// before aborting it
+    Graceful { timeout: Duration },
+    /// Abort the running service right away
+    Immediate,
+}
+
+/// Command for updating a single service settings and restarting it with them
+#[derive(Debug)]
+pub struct ReconfigureCommand {
+    pub(crate) service_id: ServiceId,
+    pub(crate) settings: AnySettings,
+    pub(crate) mode: RestartMode,
+    pub(crate) reply_channel: ReplyChannel<Result<(), Error>>,
+    pub(crate) span: CommandSpan,
+}
+
+/// Command for promoting a service standby instance
+#[derive(Debug)]
+pub struct FailoverCommand {
+    pub(crate) service_id: ServiceId,
+    pub(crate) reply_channel: ReplyChannel<Result<(), Error>>,
+    pub(crate) span: CommandSpan,
+}
+
+/// Command for changing the number of members of a service pool
+#[derive(Debug)]
+pub struct ScaleCommand {
+    pub(crate) service_id: ServiceId,
+    pub(crate) members: usize,
+    pub(crate) reply_channel: ReplyChannel<Result<(), Error>>,
+    pub(crate) span: CommandSpan,
+}
+
+/// Action of a [`ServiceControlCommand`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServiceAction {
+    /// Start the service, a no-op if it is already running
+    Start,
+    /// Stop the running service instance, if any
+    Stop,
+    /// Start again a service waiting for it, see
+    /// [`RestartPolicy`](crate::services::supervisor::RestartPolicy), a no-op otherwise
+    Restart,
+}
+
+/// Command for starting or stopping a single service
 #[derive(Debug)]
-pub struct SettingsCommand(pub(crate) AnySettings);
+pub struct ServiceControlCommand {
+    pub(crate) service_id: ServiceId,
+    pub(crate) action: ServiceAction,
+    pub(crate) reply_channel: ReplyChannel<Result<(), Error>>,
+    pub(crate) span: CommandSpan,
+}
+
+/// Command for describing the services, see [`OverwatchHandle::topology`]
+#[derive(Debug)]
+pub struct TopologyCommand {
+    pub(crate) reply_channel: ReplyChannel<Topology>,
+}
+
+/// Command for taking a [`Checkpoint`], see [`OverwatchHandle::checkpoint`]
+#[cfg(feature = "checkpoint")]
+#[derive(Debug)]
+pub struct CheckpointCommand {
+    /// Serialize the services settings, whose type the runner can't require to be serializable
+    pub(crate) serialize_settings: fn(&dyn Any) -> Result<serde_json::Value, Error>,
+    pub(crate) reply_channel: ReplyChannel<Result<Checkpoint, Error>>,
+}
+
+/// Command queued in a [`BatchCommand`]
+#[derive(Debug)]
+pub enum BatchedCommand {
+    Settings(SettingsCommand),
+    Reconfigure(ReconfigureCommand),
+    Failover(FailoverCommand),
+    Control(ServiceControlCommand),
+    Scale(ScaleCommand),
+}
+
+/// Lifecycle and settings commands handled by the runner in order, within a single command loop
+/// iteration so no other command is interleaved with them
+/// Built with [`OverwatchHandle::batch`].
+#[derive(Debug)]
+pub struct BatchCommand {
+    pub(crate) commands: Vec<BatchedCommand>,
+    pub(crate) span: CommandSpan,
+}
+
+/// Application defined command, handled by the [`CustomCommandHandler`]s registered with
+/// [`ContextConfig::with_command_handler`](crate::services::context::ContextConfig::with_command_handler)
+/// Replies, if any, go through channels carried by the command itself.
+#[derive(Debug)]
+pub struct CustomCommand(Box<dyn Any + Send>);
+
+impl CustomCommand {
+    pub fn new<C: Any + Send>(command: C) -> Self {
+        Self(Box::new(command))
+    }
+
+    pub fn is<C: Any + Send>(&self) -> bool {
+        self.0.is::<C>()
+    }
+
+    /// Take the command back if it is a `C`
+    pub fn downcast<C: Any + Send>(self) -> Result<C, Self> {
+        self.0.downcast::<C>().map(|command| *command).map_err(Self)
+    }
+}
+
+/// Extension point of the overwatch runner for [`CustomCommand`]s
+/// Handlers are run within the runner loop, in registration order, until one of them takes the
+/// command, so long running work has to be spawned.
+pub trait CustomCommandHandler: Send + Sync + 'static {
+    /// Handle `command`, or give it back if it is not one of this handler commands
+    fn handle(
+        &self,
+        command: CustomCommand,
+        overwatch_handle: &OverwatchHandle,
+    ) -> Result<(), CustomCommand>;
+}
+
+impl<F> CustomCommandHandler for F
+where
+    F: Fn(CustomCommand, &OverwatchHandle) -> Result<(), CustomCommand> + Send + Sync + 'static,
+{
+    fn handle(
+        &self,
+        command: CustomCommand,
+        overwatch_handle: &OverwatchHandle,
+    ) -> Result<(), CustomCommand> {
+        self(command, overwatch_handle)
+    }
+}
 
 /// [`Overwatch`](crate::overwatch::Overwatch) tasks related commands
 #[derive(Debug)]
 pub enum OverwatchCommand {
     Relay(RelayCommand),
     Status(StatusCommand),
+    Broadcast(BroadcastCommand),
     ServiceLifeCycle(ServiceLifeCycleCommand),
     OverwatchLifeCycle(OverwatchLifeCycleCommand),
     Settings(SettingsCommand),
+    Reconfigure(ReconfigureCommand),
+    Failover(FailoverCommand),
+    Control(ServiceControlCommand),
+    Scale(ScaleCommand),
+    Batch(BatchCommand),
+    Custom(CustomCommand),
+    Topology(TopologyCommand),
+    #[cfg(feature = "checkpoint")]
+    Checkpoint(CheckpointCommand),
 }
@@ -0,0 +1,242 @@
+//! Load the settings of all services from a single configuration file
+//! The file has a section per service, named after its field in the [`Services`] struct, holding
+//! the service settings. The derived `*ServiceSettings` struct implements [`serde::Deserialize`]
+//! as long as the settings of every service do. It requires the `config` feature, which supports
+//! JSON files, TOML and YAML ones are supported with the `config-toml` and `config-yaml` features.
+// std
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+// crates
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+// internal
+use crate::overwatch::Services;
+use crate::services::ids::{same_name, to_case, IdCase, NAMESPACE_SEPARATOR};
+use crate::DynError;
+
+// only meant to be used by the code generated from `overwatch-derive`
+#[doc(hidden)]
+pub use serde;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Format of a configuration file, told from its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(self, contents: &str) -> Result<Value, ConfigError> {
+        let parsed: Result<Value, DynError> = match self {
+            Self::Json => serde_json::from_str(contents).map_err(Into::into),
+            #[cfg(feature = "config-toml")]
+            Self::Toml => toml::from_str(contents).map_err(Into::into),
+            #[cfg(feature = "config-yaml")]
+            Self::Yaml => serde_yaml::from_str(contents).map_err(Into::into),
+            #[allow(unreachable_patterns)]
+            _ => return Err(ConfigError::Unsupported(self)),
+        };
+        parsed.map_err(|source| ConfigError::Parse {
+            format: self,
+            source,
+        })
+    }
+}
+
+impl Display for ConfigFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Toml => write!(f, "toml"),
+            Self::Yaml => write!(f, "yaml"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("config file {path:?} couldn't be read: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("format of config file {0:?} is unknown")]
+    UnknownFormat(PathBuf),
+    #[error("{0} config files require the `config-{0}` feature")]
+    Unsupported(ConfigFormat),
+    #[error("invalid {format} config: {source}")]
+    Parse {
+        format: ConfigFormat,
+        #[source]
+        source: DynError,
+    },
+    #[error("config doesn't match the services settings: {0}")]
+    Settings(#[from] serde_json::Error),
+}
+
+enum Source {
+    File(PathBuf),
+    Contents(String, ConfigFormat),
+}
+
+/// Builder of the aggregated settings of the services `S`
+///
+/// With an environment prefix, say `APP`, variables override parts of the loaded configuration:
+/// `APP_FOO_SERVICE` replaces the whole section of the service with id `FooService` and
+/// `APP_FOO_SERVICE__LIMITS__MAX` only its `limits.max` setting. Service ids are written in
+/// uppercase snake case. Values replacing strings are taken as they are, other ones are parsed as
+/// JSON and taken as plain strings if they aren't valid JSON. Settings are named in any naming
+/// convention, `__MAX_ENTRIES` reaches a `maxEntries` setting as well, missing ones are added in
+/// lowercase.
+/// Ids of bundled services are prefixed with their namespace, `APP_CORE_STORE` is the section of
+/// the `store` service of the `core` bundle.
+pub struct ConfigLoader<S> {
+    source: Source,
+    env_prefix: Option<String>,
+    /// Variables overrides are read from, the process environment if `None`
+    env_vars: Option<Vec<(String, String)>>,
+    _services: PhantomData<S>,
+}
+
+impl<S: Services> ConfigLoader<S>
+where
+    S::Settings: DeserializeOwned,
+{
+    /// Load from the file at `path`, its format is told from its extension
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: Source::File(path.into()),
+            env_prefix: None,
+            env_vars: None,
+            _services: PhantomData,
+        }
+    }
+
+    /// Load from already read `contents`
+    pub fn contents(contents: impl Into<String>, format: ConfigFormat) -> Self {
+        Self {
+            source: Source::Contents(contents.into(), format),
+            env_prefix: None,
+            env_vars: None,
+            _services: PhantomData,
+        }
+    }
+
+    /// Apply overrides from the environment variables starting with `prefix`
+    pub fn with_env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Read overrides from `vars` instead of the process environment, see
+    /// [`ConfigLoader::with_env_prefix`]
+    pub fn with_env_vars(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env_vars = Some(vars.into_iter().collect());
+        self
+    }
+
+    pub fn load(self) -> Result<S::Settings, ConfigError> {
+        let mut config = match self.source {
+            Source::File(path) => {
+                let format = ConfigFormat::from_path(&path)
+                    .ok_or_else(|| ConfigError::UnknownFormat(path.clone()))?;
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|source| ConfigError::Io { path, source })?;
+                format.parse(&contents)?
+            }
+            Source::Contents(contents, format) => format.parse(&contents)?,
+        };
+        if let Some(prefix) = &self.env_prefix {
+            match self.env_vars {
+                Some(vars) => apply_overrides::<S>(&mut config, prefix, vars),
+                None => apply_overrides::<S>(&mut config, prefix, std::env::vars()),
+            }
+        }
+        Ok(serde_json::from_value(config)?)
+    }
+}
+
+fn apply_overrides<S: Services>(
+    config: &mut Value,
+    prefix: &str,
+    vars: impl IntoIterator<Item = (String, String)>,
+) {
+    let sections: Vec<_> = S::SERVICES_SETTINGS
+        .iter()
+        .map(|(service_id, section)| {
+            let mut namespaces: Vec<_> = service_id.split(NAMESPACE_SEPARATOR).collect();
+            namespaces.pop();
+            let service = service_id
+                .split(NAMESPACE_SEPARATOR)
+                .map(|id| to_case(id, IdCase::Snake).to_uppercase())
+                .collect::<Vec<_>>()
+                .join("_");
+            // settings of bundled services are nested in the settings of their bundles
+            let keys: Vec<_> = namespaces
+                .into_iter()
+                .chain(section.split('.'))
+                .map(str::to_owned)
+                .collect();
+            (format!("{prefix}_{service}"), keys)
+        })
+        .collect();
+    for (name, value) in vars {
+        let Some((path, section)) = sections.iter().find_map(|(variable, section)| {
+            let path = name.strip_prefix(variable.as_str())?;
+            (path.is_empty() || path.starts_with("__")).then_some((path, section))
+        }) else {
+            continue;
+        };
+        let keys = section
+            .iter()
+            .map(String::as_str)
+            .chain(path.split("__").filter(|key| !key.is_empty()));
+        let setting = keys.fold(&mut *config, entry);
+        *setting = override_value(setting, value);
+    }
+}
+
+/// Value under `key`, matched in any naming convention and inserted in lowercase if missing
+fn entry<'v>(value: &'v mut Value, key: &str) -> &'v mut Value {
+    let index = key
+        .parse::<usize>()
+        .ok()
+        .filter(|index| value.as_array().is_some_and(|items| *index < items.len()));
+    if let Some(index) = index {
+        return &mut value[index];
+    }
+    if !value.is_object() {
+        *value = Value::Object(serde_json::Map::new());
+    }
+    let Value::Object(entries) = value else {
+        unreachable!("value was just made an object");
+    };
+    let key = entries
+        .keys()
+        .find(|existing| same_name(existing, key))
+        .cloned()
+        .unwrap_or_else(|| key.to_lowercase());
+    entries.entry(key).or_insert(Value::Null)
+}
+
+/// `raw` override of the `existing` value, kept as a string if it replaces one and parsed as JSON
+/// otherwise, falling back to a string if it isn't valid JSON
+fn override_value(existing: &Value, raw: String) -> Value {
+    match existing {
+        Value::String(_) => Value::String(raw),
+        _ => serde_json::from_str(&raw).unwrap_or(Value::String(raw)),
+    }
+}
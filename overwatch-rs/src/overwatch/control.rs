@@ -0,0 +1,309 @@
+//! Control plane to operate a running application from the command line
+//!
+//! A [`ControlPlane`] listens on a local TCP port or Unix socket and takes one command per line,
+//! answering with the requested lines followed by `ok`, or by `error <reason>`. Sessions sending
+//! a line longer than [`MAX_COMMAND_LENGTH`] are closed.
+//!
+//! - `list`: each service id with its status
+//! - `status <service>`: the status of a service
+//! - `start <service>` / `stop <service>`: start or stop a service
+//! - `reload`: load and apply the application settings again, if a loader was given
+//!
+//! ```text
+//! $ echo list | nc -q1 localhost 7070
+//! store running
+//! api stopped
+//! ok
+//! ```
+
+// std
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::Arc;
+// crates
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{FramedRead, LinesCodec, LinesCodecError};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+// internal
+use crate::overwatch::commands::ServiceAction;
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::reload::SettingsLoader;
+use crate::overwatch::server::ServerHandle;
+use crate::overwatch::topology::status_name;
+use crate::overwatch::{Error, Services};
+use crate::services::ServiceId;
+use crate::utils::listener::{Connection, ListenAddress, Listener};
+use crate::utils::runtime::{spawn_checked, CONTROL_TASK};
+
+/// Longest command line taken, in bytes
+pub const MAX_COMMAND_LENGTH: usize = 4096;
+
+/// Where a [`ControlPlane`] listens
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ControlAddress {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Reloads the settings of the application, typed where its services are known
+type Reload = Arc<dyn Fn(OverwatchHandle) -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Serves the control commands of a running application, see [`crate::overwatch::control`]
+#[derive(Clone)]
+pub struct ControlPlane {
+    address: ControlAddress,
+    reload: Option<Reload>,
+    non_loopback: bool,
+}
+
+impl std::fmt::Debug for ControlPlane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ControlPlane")
+            .field("address", &self.address)
+            .field("reload", &self.reload.is_some())
+            .field("non_loopback", &self.non_loopback)
+            .finish()
+    }
+}
+
+impl ControlPlane {
+    /// Listen on `address`, which has to be a loopback one as commands are not authenticated
+    /// unless [`ControlPlane::allow_non_loopback`] is set.
+    pub fn tcp(address: SocketAddr) -> Self {
+        Self {
+            address: ControlAddress::Tcp(address),
+            reload: None,
+            non_loopback: false,
+        }
+    }
+
+    /// Listen on the Unix socket at `path`, replacing a stale socket left there
+    /// The socket is only accessible to the current user and removed once the server stops.
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self {
+            address: ControlAddress::Unix(path.into()),
+            reload: None,
+            non_loopback: false,
+        }
+    }
+
+    /// Listen on a TCP address reachable from other hosts, anyone able to connect can then
+    /// operate the application
+    pub fn allow_non_loopback(mut self) -> Self {
+        self.non_loopback = true;
+        self
+    }
+
+    /// Serve the `reload` command with `loader`, see [`OverwatchHandle::reload_settings`]
+    pub fn with_reload<S, L>(mut self, loader: L) -> Self
+    where
+        S: Services,
+        S::Settings: Send,
+        L: SettingsLoader<S>,
+    {
+        let loader = Arc::new(loader);
+        self.reload = Some(Arc::new(move |handle: OverwatchHandle| {
+            let loader = Arc::clone(&loader);
+            async move {
+                handle
+                    .reload_settings::<S>(loader.as_ref())
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            .boxed()
+        }));
+        self
+    }
+
+    /// Start listening, serving commands until Overwatch shuts down or the server is aborted
+    pub async fn serve(self, handle: &OverwatchHandle) -> Result<ControlServer, Error> {
+        let Self {
+            address,
+            reload,
+            non_loopback,
+        } = self;
+        if let ControlAddress::Tcp(address) = address {
+            if !non_loopback && !address.ip().is_loopback() {
+                return Err(Error::any(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("control plane address {address} is not a loopback one"),
+                )));
+            }
+        }
+        let listener = Listener::bind(address.into()).await.map_err(Error::any)?;
+        let address = ControlAddress::from(listener.address().map_err(Error::any)?);
+        let control_server = ControlServer::new(address, handle);
+        let server = Server {
+            handle: handle.clone(),
+            reload,
+            cancellation_token: control_server.cancellation_token().clone(),
+        };
+        spawn_checked(handle.runtime(), CONTROL_TASK, async move {
+            loop {
+                tokio::select! {
+                    _ = server.cancellation_token.cancelled() => return,
+                    accepted = listener.accept() => match accepted {
+                        Ok(connection) => server.spawn_session(connection),
+                        Err(e) => error!("Control connection couldn't be accepted: {e}"),
+                    },
+                }
+            }
+        })?;
+        info!("Control plane listening on {:?}", control_server.address());
+        Ok(control_server)
+    }
+}
+
+/// Listening [`ControlPlane`]
+pub type ControlServer = ServerHandle<ControlAddress>;
+
+impl From<ControlAddress> for ListenAddress {
+    fn from(address: ControlAddress) -> Self {
+        match address {
+            ControlAddress::Tcp(address) => Self::Tcp(address),
+            #[cfg(unix)]
+            ControlAddress::Unix(path) => Self::Unix(path),
+        }
+    }
+}
+
+impl From<ListenAddress> for ControlAddress {
+    fn from(address: ListenAddress) -> Self {
+        match address {
+            ListenAddress::Tcp(address) => Self::Tcp(address),
+            #[cfg(unix)]
+            ListenAddress::Unix(path) => Self::Unix(path),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Server {
+    handle: OverwatchHandle,
+    reload: Option<Reload>,
+    cancellation_token: CancellationToken,
+}
+
+impl Server {
+    fn spawn_session(&self, stream: Box<dyn Connection>) {
+        let server = self.clone();
+        if let Err(e) = spawn_checked(self.handle.runtime(), CONTROL_TASK, async move {
+            tokio::select! {
+                _ = server.cancellation_token.cancelled() => {}
+                ended = server.session(stream) => if let Err(e) = ended {
+                    debug!("Control session ended: {e}");
+                },
+            }
+        }) {
+            error!("Control session couldn't be started: {e}");
+        }
+    }
+
+    async fn session<T>(&self, stream: T) -> std::io::Result<()>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+    {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines =
+            FramedRead::new(reader, LinesCodec::new_with_max_length(MAX_COMMAND_LENGTH));
+        while let Some(line) = lines.next().await {
+            let line = match line {
+                Ok(line) => line,
+                Err(LinesCodecError::MaxLineLengthExceeded) => {
+                    writer.write_all(b"error command too long\n").await?;
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "command too long",
+                    ));
+                }
+                Err(LinesCodecError::Io(e)) => return Err(e),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match self.execute(&line).await {
+                Ok(mut output) => {
+                    output.push("ok".to_owned());
+                    output
+                }
+                Err(reason) => vec![format!("error {reason}")],
+            };
+            for line in response {
+                writer.write_all(line.as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+            }
+            writer.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Output lines of the command on `line`, or why it failed
+    async fn execute(&self, line: &str) -> Result<Vec<String>, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let argument = words.next();
+        if words.next().is_some() {
+            return Err(format!("too many arguments for {command}"));
+        }
+        match (command, argument) {
+            ("list", None) => {
+                let topology = self.handle.topology().await.map_err(|e| e.to_string())?;
+                Ok(topology
+                    .services
+                    .iter()
+                    .map(|service| {
+                        let status = service.status.as_ref().map_or("unknown", status_name);
+                        format!("{} {status}", service.service_id)
+                    })
+                    .collect())
+            }
+            ("status", Some(name)) => {
+                let service_id = self.service_id(name).await?;
+                let watcher = self
+                    .handle
+                    .status_watcher_for(service_id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(vec![status_name(&watcher.current()).to_owned()])
+            }
+            ("start", Some(name)) => self.control(name, ServiceAction::Start).await,
+            ("stop", Some(name)) => self.control(name, ServiceAction::Stop).await,
+            ("reload", None) => {
+                let reload = self
+                    .reload
+                    .as_ref()
+                    .ok_or("settings reload is not enabled")?;
+                reload(self.handle.clone()).await?;
+                Ok(Vec::new())
+            }
+            ("list" | "reload", Some(_)) => Err(format!("{command} takes no argument")),
+            ("status" | "start" | "stop", None) => Err(format!("{command} takes a service id")),
+            _ => Err(format!("unknown command {command}")),
+        }
+    }
+
+    async fn control(&self, name: &str, action: ServiceAction) -> Result<Vec<String>, String> {
+        let service_id = self.service_id(name).await?;
+        self.handle
+            .control_service(service_id, action)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Vec::new())
+    }
+
+    async fn service_id(&self, name: &str) -> Result<ServiceId, String> {
+        let topology = self.handle.topology().await.map_err(|e| e.to_string())?;
+        topology
+            .services
+            .iter()
+            .map(|service| service.service_id)
+            .find(|&service_id| service_id == name)
+            .ok_or_else(|| format!("unknown service {name}"))
+    }
+}
@@ -0,0 +1,94 @@
+//! Why services last failed, kept for supervisors and tests to look at
+//! With [`ContextConfig::panic_backtraces`](crate::services::context::ContextConfig::panic_backtraces)
+//! set, panics raised by services capture a backtrace (as `RUST_BACKTRACE` allows), which is
+//! attached to the crash of the service that panicked.
+// std
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, Once};
+// internal
+use crate::services::ServiceId;
+
+/// Failure of a service, see
+/// [`OverwatchHandle::last_error`](crate::overwatch::handle::OverwatchHandle::last_error)
+#[derive(Clone, Debug)]
+pub struct ServiceCrash {
+    pub service_id: ServiceId,
+    /// Error returned by the service, or message of its panic
+    pub error: String,
+    /// Whether the service panicked instead of returning an error
+    pub panicked: bool,
+    /// Where the service panicked, `None` if it failed without panicking or backtraces are not
+    /// captured
+    pub backtrace: Option<Arc<Backtrace>>,
+}
+
+impl ServiceCrash {
+    pub fn panicked(&self) -> bool {
+        self.panicked
+    }
+}
+
+/// Last crash of each service, shared by all handle clones
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CrashRegistry(Arc<Mutex<HashMap<ServiceId, ServiceCrash>>>);
+
+impl CrashRegistry {
+    pub(crate) fn record(&self, crash: ServiceCrash) {
+        self.0
+            .lock()
+            .expect("Crashes lock not poisoned")
+            .insert(crash.service_id, crash);
+    }
+
+    pub(crate) fn last(&self, service_id: ServiceId) -> Option<ServiceCrash> {
+        self.0
+            .lock()
+            .expect("Crashes lock not poisoned")
+            .get(service_id)
+            .cloned()
+    }
+}
+
+thread_local! {
+    static PANIC_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+tokio::task_local! {
+    /// Set while service code runs, panics raised elsewhere in the process are left alone
+    static IN_SERVICE: ();
+}
+
+/// Capture a backtrace on panics raised by services, then run the previously installed hook
+/// Panics are caught on the thread they are raised, where [`take_panic_backtrace`] picks it up.
+pub(crate) fn capture_panic_backtraces() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if IN_SERVICE.try_with(|()| ()).is_ok() {
+                PANIC_BACKTRACE.with(|backtrace| {
+                    backtrace.replace(Some(Backtrace::capture()));
+                });
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Run service code, its panics get a backtrace if the hook is installed
+pub(crate) fn in_service<R>(f: impl FnOnce() -> R) -> R {
+    IN_SERVICE.sync_scope((), f)
+}
+
+/// Same as [`in_service`] for an async service task
+pub(crate) fn in_service_task<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    IN_SERVICE.scope((), future)
+}
+
+/// Backtrace of the last panic raised on this thread, if not taken yet
+pub(crate) fn take_panic_backtrace() -> Option<Backtrace> {
+    PANIC_BACKTRACE.with(RefCell::take)
+}
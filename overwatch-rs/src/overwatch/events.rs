@@ -0,0 +1,271 @@
+// std
+use std::error::Error;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+// crates
+use futures::Stream;
+use tokio::sync::{broadcast, watch};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+// internal
+use crate::overwatch::boot::BootReport;
+use crate::overwatch::reload::ReloadEvent;
+use crate::overwatch::teardown::TeardownReport;
+use crate::services::ServiceId;
+
+/// Non fatal error reported by a service, see
+/// [`ServiceStateHandle::report_error`](crate::services::handle::ServiceStateHandle::report_error)
+#[derive(Clone, Debug)]
+pub struct ServiceErrorEvent {
+    pub service_id: ServiceId,
+    pub error: Arc<dyn Error + Send + Sync + 'static>,
+}
+
+/// How full the inbound relay buffer of a service is
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum BackpressureLevel {
+    /// Below the service [`BACKPRESSURE_THRESHOLD`](crate::services::ServiceData::BACKPRESSURE_THRESHOLD)
+    Normal,
+    /// At or above the threshold, senders should slow down
+    High,
+    /// Buffer is full, senders are waiting for the service
+    Full,
+}
+
+impl BackpressureLevel {
+    pub(crate) fn from_depth(depth: usize, threshold: usize, capacity: usize) -> Self {
+        if depth >= capacity {
+            Self::Full
+        } else if depth >= threshold {
+            Self::High
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Published whenever the inbound relay of a service changes its [`BackpressureLevel`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Backpressure(pub ServiceId, pub BackpressureLevel);
+
+/// How close the process is to the limits of a [`MemoryMonitor`](crate::overwatch::memory::MemoryMonitor)
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum MemoryPressureLevel {
+    Normal,
+    /// Above the elevated threshold, services should shed caches and similar
+    Elevated,
+    /// Above the critical threshold, low priority services may be paused
+    Critical,
+}
+
+/// Published whenever the process memory usage changes its [`MemoryPressureLevel`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemoryPressure(pub MemoryPressureLevel);
+
+/// Stall found by the [`Watchdog`](crate::overwatch::watchdog::Watchdog), published once per stall
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WatchdogEvent {
+    /// The inbound relay buffer of the service has been full for `since`
+    RelayFull {
+        service_id: ServiceId,
+        since: Duration,
+    },
+    /// Messages have been waiting in the inbound relay of the service without it polling the
+    /// relay for `since`
+    Unresponsive {
+        service_id: ServiceId,
+        since: Duration,
+    },
+}
+
+/// Change of a [`ServicePool`](crate::services::pool::ServicePool) member while scaling it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScalingChange {
+    Started,
+    Stopped,
+}
+
+/// Progress of scaling a pool with
+/// [`OverwatchHandle::scale`](crate::overwatch::handle::OverwatchHandle::scale), one event per
+/// started or stopped member
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScalingEvent {
+    pub pool: ServiceId,
+    pub member: ServiceId,
+    pub change: ScalingChange,
+    /// Number of members after this change
+    pub members: usize,
+    pub target: usize,
+}
+
+/// Lifecycle transition of a service, or of Overwatch itself
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LifecycleEvent {
+    /// The service is being started, its initialization comes next
+    ServiceStarting { service_id: ServiceId },
+    /// The service reported [`ServiceStatus::Running`](crate::services::status::ServiceStatus::Running)
+    ServiceStarted { service_id: ServiceId },
+    /// The service was stopped, or its main loop ended and it isn't restarted
+    ServiceStopped { service_id: ServiceId },
+    /// The service failed to start, or its main loop failed or panicked
+    ServiceCrashed {
+        service_id: ServiceId,
+        error: String,
+    },
+    /// All services were torn down and Overwatch stopped
+    OverwatchShutdown,
+}
+
+/// Stream of the [`LifecycleEvent`]s published after subscribing, see
+/// [`OverwatchHandle::lifecycle_events`](crate::overwatch::handle::OverwatchHandle::lifecycle_events)
+/// Events missed by a lagging subscriber are skipped.
+pub struct LifecycleEventStream(BroadcastStream<LifecycleEvent>);
+
+impl Stream for LifecycleEventStream {
+    type Item = LifecycleEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.0).poll_next(cx) {
+                Poll::Ready(Some(Err(_))) => continue,
+                Poll::Ready(Some(Ok(event))) => return Poll::Ready(Some(event)),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Broadcasting side of the overwatch events streams
+#[derive(Clone, Debug)]
+pub(crate) struct EventsSender {
+    errors: broadcast::Sender<ServiceErrorEvent>,
+    backpressure: broadcast::Sender<Backpressure>,
+    reloads: broadcast::Sender<ReloadEvent>,
+    scaling: broadcast::Sender<ScalingEvent>,
+    memory_pressure: broadcast::Sender<MemoryPressure>,
+    watchdog: broadcast::Sender<WatchdogEvent>,
+    lifecycle: broadcast::Sender<LifecycleEvent>,
+    /// Sent once, kept for late subscribers
+    boot: watch::Sender<Option<BootReport>>,
+    /// Sent once the services are torn down on shutdown
+    teardown: watch::Sender<Option<TeardownReport>>,
+}
+
+impl EventsSender {
+    pub(crate) fn new() -> Self {
+        let (errors, _) = broadcast::channel(64);
+        let (backpressure, _) = broadcast::channel(64);
+        let (reloads, _) = broadcast::channel(16);
+        let (scaling, _) = broadcast::channel(64);
+        let (memory_pressure, _) = broadcast::channel(16);
+        let (watchdog, _) = broadcast::channel(64);
+        let (lifecycle, _) = broadcast::channel(256);
+        let (boot, _) = watch::channel(None);
+        let (teardown, _) = watch::channel(None);
+        Self {
+            errors,
+            backpressure,
+            reloads,
+            scaling,
+            memory_pressure,
+            watchdog,
+            lifecycle,
+            boot,
+            teardown,
+        }
+    }
+
+    pub(crate) fn report_error(&self, event: ServiceErrorEvent) {
+        // nobody listening is fine, events are only delivered to current subscribers
+        let _ = self.errors.send(event);
+    }
+
+    pub(crate) fn error_events(&self) -> impl Stream<Item = ServiceErrorEvent> {
+        BroadcastStream::new(self.errors.subscribe()).filter_map(Result::ok)
+    }
+
+    pub(crate) fn report_backpressure(&self, event: Backpressure) {
+        let _ = self.backpressure.send(event);
+    }
+
+    pub(crate) fn backpressure_events(&self) -> impl Stream<Item = Backpressure> {
+        BroadcastStream::new(self.backpressure.subscribe()).filter_map(Result::ok)
+    }
+
+    pub(crate) fn report_reload(&self, event: ReloadEvent) {
+        let _ = self.reloads.send(event);
+    }
+
+    pub(crate) fn reload_events(&self) -> impl Stream<Item = ReloadEvent> {
+        BroadcastStream::new(self.reloads.subscribe()).filter_map(Result::ok)
+    }
+
+    pub(crate) fn report_memory_pressure(&self, event: MemoryPressure) {
+        let _ = self.memory_pressure.send(event);
+    }
+
+    pub(crate) fn memory_pressure_events(&self) -> impl Stream<Item = MemoryPressure> {
+        BroadcastStream::new(self.memory_pressure.subscribe()).filter_map(Result::ok)
+    }
+
+    pub(crate) fn report_watchdog(&self, event: WatchdogEvent) {
+        let _ = self.watchdog.send(event);
+    }
+
+    pub(crate) fn watchdog_events(&self) -> impl Stream<Item = WatchdogEvent> {
+        BroadcastStream::new(self.watchdog.subscribe()).filter_map(Result::ok)
+    }
+
+    pub(crate) fn report_scaling(&self, event: ScalingEvent) {
+        let _ = self.scaling.send(event);
+    }
+
+    pub(crate) fn scaling_events(&self) -> impl Stream<Item = ScalingEvent> {
+        BroadcastStream::new(self.scaling.subscribe()).filter_map(Result::ok)
+    }
+
+    pub(crate) fn report_lifecycle(&self, event: LifecycleEvent) {
+        let _ = self.lifecycle.send(event);
+    }
+
+    pub(crate) fn lifecycle_events(&self) -> LifecycleEventStream {
+        LifecycleEventStream(BroadcastStream::new(self.lifecycle.subscribe()))
+    }
+
+    pub(crate) fn report_boot(&self, report: BootReport) {
+        self.boot.send_replace(Some(report));
+    }
+
+    pub(crate) fn boot_report(&self) -> Option<BootReport> {
+        self.boot.borrow().clone()
+    }
+
+    pub(crate) async fn wait_for_boot_report(&self) -> BootReport {
+        let mut receiver = self.boot.subscribe();
+        let report = receiver
+            .wait_for(Option::is_some)
+            .await
+            .expect("Boot report sender is owned by self");
+        report.clone().expect("Waited for the report to be set")
+    }
+
+    pub(crate) fn report_teardown(&self, report: TeardownReport) {
+        self.teardown.send_replace(Some(report));
+    }
+
+    pub(crate) fn teardown_report(&self) -> Option<TeardownReport> {
+        self.teardown.borrow().clone()
+    }
+
+    pub(crate) async fn wait_for_teardown_report(&self) -> TeardownReport {
+        let mut receiver = self.teardown.subscribe();
+        let report = receiver
+            .wait_for(Option::is_some)
+            .await
+            .expect("Teardown report sender is owned by self");
+        report.clone().expect("Waited for the report to be set")
+    }
+}
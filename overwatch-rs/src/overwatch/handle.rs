@@ -1,20 +1,63 @@
 // std
-
+use std::any::Any;
+#[cfg(feature = "checkpoint")]
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 // crates
+use crate::overwatch::batch::CommandBatch;
+use crate::overwatch::boot::BootReport;
+#[cfg(feature = "checkpoint")]
+use crate::overwatch::checkpoint::CheckpointError;
+#[cfg(feature = "checkpoint")]
+use crate::overwatch::commands::CheckpointCommand;
 use crate::overwatch::commands::{
-    OverwatchCommand, OverwatchLifeCycleCommand, ReplyChannel, SettingsCommand, StatusCommand,
+    BatchCommand, BatchedCommand, BroadcastCommand, CommandSpan, CustomCommand, FailoverCommand,
+    OverwatchCommand, OverwatchLifeCycleCommand, ReconfigureCommand, ReplyChannel, RestartMode,
+    ScaleCommand, ServiceAction, ServiceControlCommand, SettingsCommand, StatusCommand,
+    TopologyCommand,
+};
+use crate::overwatch::crash::{CrashRegistry, ServiceCrash};
+use crate::overwatch::events::{
+    Backpressure, EventsSender, LifecycleEvent, LifecycleEventStream, MemoryPressure, ScalingEvent,
+    ServiceErrorEvent, WatchdogEvent,
 };
-use crate::overwatch::Services;
+use crate::overwatch::health::{HealthOverview, HealthRegistry};
+use crate::overwatch::history::{History, HistoryEntry};
+use crate::overwatch::latency::{CommandKind, CommandLatencies, LatencySummary};
+use crate::overwatch::life_cycle::LifecycleQueueDepths;
+use crate::overwatch::metrics::{MetricsRegistry, MetricsSnapshot};
+use crate::overwatch::reload::{ReloadEvent, SettingsLoader};
+use crate::overwatch::sequence::{Sequence, SequenceError};
+use crate::overwatch::teardown::TeardownReport;
+use crate::overwatch::topology::Topology;
+use crate::overwatch::{Error, Services};
 use crate::services::ServiceData;
+use crate::services::ServiceId;
+use crate::DynError;
+use futures::Stream;
+#[cfg(feature = "checkpoint")]
+use serde::Serialize;
 use tokio::runtime::Handle;
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::Sender;
+use tokio_util::sync::CancellationToken;
 #[cfg(feature = "instrumentation")]
 use tracing::instrument;
 use tracing::{error, info};
 
 // internal
-use crate::services::relay::Relay;
-use crate::services::status::StatusWatcher;
+use crate::services::broadcast::{BroadcastSubscription, BroadcastingService, ServiceBroadcast};
+use crate::services::context::ContextConfig;
+use crate::services::ids::{namespaced, resolve};
+use crate::services::pool::{member_id, BalanceStrategy, PooledOutboundRelay};
+use crate::services::registry::ServiceRegistry;
+use crate::services::relay::{OutboundRelay, Relay, RelayError, RetryPolicy};
+use crate::services::relay_cache::RelayCache;
+use crate::services::relay_pause::{RelayPause, RelayPauses};
+use crate::services::runtime::ServiceRuntimes;
+use crate::services::status::{ServiceStatusError, StatusWatcher};
 
 /// Handler object over the main Overwatch runner
 /// It handles communications to the main Overwatch runner.
@@ -23,43 +66,251 @@ pub struct OverwatchHandle {
     #[allow(unused)]
     runtime_handle: Handle,
     sender: Sender<OverwatchCommand>,
+    context_config: ContextConfig,
+    registry: ServiceRegistry,
+    relays: RelayCache,
+    pauses: RelayPauses,
+    events: EventsSender,
+    crashes: CrashRegistry,
+    settings_stats: Arc<SettingsCounters>,
+    /// Service this handle was given to, if any
+    owner: Option<ServiceId>,
+    /// Namespace of the bundle this handle was given to, if any
+    namespace: Option<ServiceId>,
+    /// Ids of the attached services, what service ids are resolved against
+    services: &'static [ServiceId],
+    /// Ids of the attached pools, whose members ids are resolved as well
+    pools: &'static [ServiceId],
+    history: History,
+    health: HealthRegistry,
+    lifecycle_queue_depths: LifecycleQueueDepths,
+    command_latencies: CommandLatencies,
+    metrics: MetricsRegistry,
+    runtimes: ServiceRuntimes,
+    /// Cancelled by the runner once Overwatch starts shutting down
+    shutdown: CancellationToken,
+    /// Cancelled once Overwatch is aborted, stopping the services without the runner
+    aborted: CancellationToken,
+}
+
+/// Snapshot of settings updates counters
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SettingsCommandStats {
+    /// Updates queued for the overwatch runner
+    pub queued: u64,
+    /// Updates that couldn't be queued, because the channel was full or the runner is gone
+    pub dropped: u64,
+}
+
+#[derive(Debug, Default)]
+struct SettingsCounters {
+    queued: AtomicU64,
+    dropped: AtomicU64,
 }
 
 impl OverwatchHandle {
     pub fn new(runtime_handle: Handle, sender: Sender<OverwatchCommand>) -> Self {
+        Self::with_context_config(runtime_handle, sender, ContextConfig::default())
+    }
+
+    pub fn with_context_config(
+        runtime_handle: Handle,
+        sender: Sender<OverwatchCommand>,
+        context_config: ContextConfig,
+    ) -> Self {
+        let history = History::new(
+            context_config.history_capacity,
+            context_config.clock.clone(),
+        );
+        let metrics = MetricsRegistry::new(context_config.clock.clone());
         Self {
             runtime_handle,
             sender,
+            context_config,
+            history,
+            metrics,
+            health: HealthRegistry::default(),
+            registry: ServiceRegistry::new(),
+            relays: RelayCache::default(),
+            pauses: RelayPauses::default(),
+            events: EventsSender::new(),
+            crashes: CrashRegistry::default(),
+            settings_stats: Default::default(),
+            owner: None,
+            namespace: None,
+            services: &[],
+            pools: &[],
+            lifecycle_queue_depths: Default::default(),
+            command_latencies: Default::default(),
+            runtimes: ServiceRuntimes::default(),
+            shutdown: CancellationToken::new(),
+            aborted: CancellationToken::new(),
         }
     }
 
+    /// Clone of this handle attributed to the given service
+    /// Relays requested through it are checked against the configured
+    /// [`RelayPolicy`](crate::services::relay::RelayPolicy).
+    pub(crate) fn for_service(&self, service_id: ServiceId) -> Self {
+        Self {
+            owner: Some(service_id),
+            ..self.clone()
+        }
+    }
+
+    /// Service this handle belongs to, `None` for the application handle
+    pub fn owner(&self) -> Option<ServiceId> {
+        self.owner
+    }
+
+    /// Clone of this handle resolving service ids against the services of `S`
+    pub(crate) fn for_services<S: Services>(self) -> Self {
+        Self {
+            services: S::SERVICES_IDS,
+            pools: S::SERVICES_POOLS,
+            ..self
+        }
+    }
+
+    /// Clone of this handle given to the services of the `namespace` bundle, nested in the
+    /// namespace of this handle
+    #[doc(hidden)]
+    pub fn namespaced(&self, namespace: &'static str) -> Self {
+        Self {
+            namespace: Some(namespaced(self.namespace, namespace)),
+            ..self.clone()
+        }
+    }
+
+    /// Namespace of the bundle this handle was given to, `None` outside of bundles
+    pub fn namespace(&self) -> Option<ServiceId> {
+        self.namespace
+    }
+
+    /// Id of the attached service `service_id` refers to from the namespace of this handle
+    /// Ids are taken as they are if a service runs under them, otherwise services of the closest
+    /// enclosing bundle are picked first, like the siblings of the service owning this handle,
+    /// then the single service of any bundle with that id. Ambiguous ids are returned as they
+    /// are.
+    pub fn resolve(&self, service_id: ServiceId) -> ServiceId {
+        resolve(self.services, self.pools, self.namespace, service_id)
+    }
+
     /// Request for a relay
     pub fn relay<S: ServiceData>(&self) -> Relay<S> {
         Relay::new(self.clone())
     }
 
-    // Request a status watcher for a service
-    pub async fn status_watcher<S: ServiceData>(&self) -> StatusWatcher {
-        info!("Requesting status watcher for {}", S::SERVICE_ID);
+    /// Request a relay, retrying with `policy` while the target service is still starting
+    pub async fn relay_with_retry<S: ServiceData>(
+        &self,
+        policy: RetryPolicy,
+    ) -> Result<OutboundRelay<S::Message>, RelayError> {
+        self.relay::<S>().connect_with_retry(policy).await
+    }
+
+    /// Request a relay once the `S` service is running, waiting up to `timeout` for it to be
+    /// ready, see [`Relay::connect_when_ready`]
+    pub async fn relay_when_ready<S: ServiceData>(
+        &self,
+        timeout: Duration,
+    ) -> Result<OutboundRelay<S::Message>, RelayError> {
+        self.relay::<S>().connect_when_ready(timeout).await
+    }
+
+    /// Request a relay to a service of type `S` running under `service_id`, like
+    /// [`ServicePool`](crate::services::pool::ServicePool) members do
+    pub fn relay_to<S: ServiceData>(&self, service_id: ServiceId) -> Relay<S> {
+        Relay::with_service_id(self.clone(), service_id)
+    }
+
+    /// Connect to every member of the [`ServicePool`](crate::services::pool::ServicePool) of `S`
+    /// services, spreading messages across them with the given strategy
+    pub async fn pool_relay<S: ServiceData>(
+        &self,
+        strategy: BalanceStrategy,
+    ) -> Result<PooledOutboundRelay<S::Message>, RelayError> {
+        let mut members = Vec::new();
+        // pool members ids are contiguous, the first missing one is the end of the pool
+        loop {
+            let service_id = member_id::<S>(members.len());
+            match self.relay_to::<S>(service_id).connect().await {
+                Ok(relay) => members.push(relay),
+                Err(RelayError::Unavailable { .. }) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(PooledOutboundRelay::new(members, strategy))
+    }
+
+    /// Request a status watcher for a service
+    pub async fn status_watcher<S: ServiceData>(
+        &self,
+    ) -> Result<StatusWatcher, ServiceStatusError> {
+        self.status_watcher_for(S::SERVICE_ID).await
+    }
+
+    /// Request a status watcher for the service running under `service_id`
+    pub async fn status_watcher_for(
+        &self,
+        service_id: ServiceId,
+    ) -> Result<StatusWatcher, ServiceStatusError> {
+        let service_id = self.resolve(service_id);
+        info!("Requesting status watcher for {}", service_id);
         let (sender, receiver) = tokio::sync::oneshot::channel();
-        let watcher_request = self
-            .sender
+        self.sender
             .send(OverwatchCommand::Status(StatusCommand {
-                service_id: S::SERVICE_ID,
+                service_id,
                 reply_channel: ReplyChannel::from(sender),
             }))
-            .await;
-        match watcher_request {
-            Ok(_) => receiver.await.unwrap_or_else(|_| {
-                panic!(
-                    "Service {} watcher should always be available",
-                    S::SERVICE_ID
-                )
-            }),
-            Err(_) => {
-                unreachable!("Service watcher should always be available");
-            }
-        }
+            .await
+            .map_err(|_| ServiceStatusError::Disconnected { service_id })?;
+        receiver
+            .await
+            .map_err(|_| ServiceStatusError::Disconnected { service_id })?
+    }
+
+    /// Same as [`OverwatchHandle::status_watcher`] but gives up if the watcher is not obtained
+    /// within `timeout`.
+    pub async fn status_watcher_with_timeout<S: ServiceData>(
+        &self,
+        timeout: Duration,
+    ) -> Result<StatusWatcher, ServiceStatusError> {
+        self.context_config
+            .clock
+            .timeout(timeout, self.status_watcher::<S>())
+            .await
+            .map_err(|_| ServiceStatusError::Timeout {
+                service_id: self.resolve(S::SERVICE_ID),
+            })?
+    }
+
+    /// Subscribe to the messages the `S` service broadcasts, see
+    /// [`ServiceStateHandle::broadcast`](crate::services::handle::ServiceStateHandle::broadcast)
+    /// Subscribing doesn't wait for the service to be started.
+    pub async fn subscribe<S: BroadcastingService>(
+        &self,
+    ) -> Result<BroadcastSubscription<S::Broadcast>, RelayError> {
+        self.broadcast_for(S::SERVICE_ID).await?.subscribe()
+    }
+
+    /// Broadcast relay of the service running under `service_id`
+    pub async fn broadcast_for(
+        &self,
+        service_id: ServiceId,
+    ) -> Result<ServiceBroadcast, RelayError> {
+        let service_id = self.resolve(service_id);
+        info!("Requesting broadcast relay for {}", service_id);
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Broadcast(BroadcastCommand {
+                service_id,
+                requester: self.owner(),
+                reply_channel: ReplyChannel::from(sender),
+            }))
+            .await
+            .map_err(|_| RelayError::Disconnected)?;
+        receiver.await.map_err(|_| RelayError::Disconnected)?
     }
 
     /// Send a shutdown signal to the overwatch runner
@@ -76,6 +327,18 @@ impl OverwatchHandle {
         }
     }
 
+    /// Send a shutdown signal to the overwatch runner and wait for it to tear the services down
+    /// It returns `None` if the runner stopped without reporting the teardown, e.g. it was aborted.
+    /// Services must not call it from their own task, teardown waits for that task to finish.
+    pub async fn shutdown_and_wait(&self) -> Option<TeardownReport> {
+        self.shutdown().await;
+        tokio::select! {
+            biased;
+            report = self.events.wait_for_teardown_report() => Some(report),
+            () = self.sender.closed() => self.events.teardown_report(),
+        }
+    }
+
     /// Send a kill signal to the overwatch runner
     pub async fn kill(&self) {
         info!("Killing Overwatch");
@@ -90,6 +353,30 @@ impl OverwatchHandle {
         }
     }
 
+    /// Start every service that is not running, disabled optional ones aside
+    /// Services are started by the runner in the background, failures are logged.
+    pub async fn start_all(&self) -> Result<(), Error> {
+        info!("Starting all services");
+        self.sender
+            .send(OverwatchCommand::OverwatchLifeCycle(
+                OverwatchLifeCycleCommand::StartAll,
+            ))
+            .await
+            .map_err(|_| Error::Disconnected)
+    }
+
+    /// Stop every running service, Overwatch keeps running and they can be started again with
+    /// [`OverwatchHandle::start_all`]
+    pub async fn stop_all(&self) -> Result<(), Error> {
+        info!("Stopping all services");
+        self.sender
+            .send(OverwatchCommand::OverwatchLifeCycle(
+                OverwatchLifeCycleCommand::StopAll,
+            ))
+            .await
+            .map_err(|_| Error::Disconnected)
+    }
+
     /// Send an overwatch command to the overwatch runner
     #[cfg_attr(
         feature = "instrumentation",
@@ -100,23 +387,547 @@ impl OverwatchHandle {
             error!(error=?e, "Error sending overwatch command");
         }
     }
-    #[cfg_attr(feature = "instrumentation", instrument(skip(self)))]
-    pub async fn update_settings<S: Services>(&self, settings: S::Settings)
+
+    /// Send an overwatch command to the overwatch runner without waiting for channel capacity
+    pub fn try_send(&self, command: OverwatchCommand) {
+        if let Err(e) = self.sender.try_send(command) {
+            error!(error=?e, "Error sending overwatch command");
+        }
+    }
+
+    /// Send a settings update to the overwatch runner, waiting for room in the commands channel
+    /// Settings rejected by [`ValidateSettings`](crate::services::settings::ValidateSettings) are
+    /// not sent, none of the services gets them.
+    #[cfg_attr(feature = "instrumentation", instrument(skip(self), err))]
+    pub async fn update_settings<S: Services>(&self, settings: S::Settings) -> Result<(), Error>
     where
         S::Settings: Send,
     {
-        if let Err(e) = self
-            .sender
-            .send(OverwatchCommand::Settings(SettingsCommand(Box::new(
-                settings,
-            ))))
+        S::validate_settings(&settings)?;
+        self.sender
+            .send(OverwatchCommand::Settings(SettingsCommand(
+                Box::new(settings),
+                None,
+            )))
             .await
-        {
-            error!(error=?e, "Error updating settings")
+            .map_err(|_| {
+                error!("Error updating settings, overwatch runner is not available");
+                self.settings_stats.dropped.fetch_add(1, Ordering::Relaxed);
+                Error::Disconnected
+            })?;
+        self.settings_stats.queued.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Same as [`OverwatchHandle::update_settings`] but fails right away if the commands channel
+    /// is full instead of waiting.
+    #[cfg_attr(feature = "instrumentation", instrument(skip(self), err))]
+    pub fn try_update_settings<S: Services>(&self, settings: S::Settings) -> Result<(), Error>
+    where
+        S::Settings: Send,
+    {
+        S::validate_settings(&settings)?;
+        self.sender
+            .try_send(OverwatchCommand::Settings(SettingsCommand(
+                Box::new(settings),
+                None,
+            )))
+            .map_err(|e| {
+                self.settings_stats.dropped.fetch_add(1, Ordering::Relaxed);
+                match e {
+                    TrySendError::Full(_) => Error::Busy,
+                    TrySendError::Closed(_) => Error::Disconnected,
+                }
+            })?;
+        self.settings_stats.queued.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Same as [`OverwatchHandle::update_settings`] but waits until the runner applied the
+    /// settings, returning the outcome
+    #[cfg_attr(feature = "instrumentation", instrument(skip(self), err))]
+    pub async fn update_settings_and_wait<S: Services>(
+        &self,
+        settings: S::Settings,
+    ) -> Result<(), Error>
+    where
+        S::Settings: Send,
+    {
+        S::validate_settings(&settings)?;
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Settings(SettingsCommand(
+                Box::new(settings),
+                Some(ReplyChannel::from(sender)),
+            )))
+            .await
+            .map_err(|_| {
+                self.settings_stats.dropped.fetch_add(1, Ordering::Relaxed);
+                Error::Disconnected
+            })?;
+        self.settings_stats.queued.fetch_add(1, Ordering::Relaxed);
+        receiver.await.map_err(|_| Error::Disconnected)?
+    }
+
+    /// Load the application settings with `loader` and apply them, publishing the outcome to
+    /// [`OverwatchHandle::reload_events`] subscribers
+    pub async fn reload_settings<S: Services>(
+        &self,
+        loader: &impl SettingsLoader<S>,
+    ) -> Result<(), Arc<Error>>
+    where
+        S::Settings: Send,
+    {
+        let result = match loader.load() {
+            Ok(settings) => self.update_settings_and_wait::<S>(settings).await,
+            Err(e) => Err(Error::Any(e)),
+        };
+        match result {
+            Ok(()) => {
+                info!("Settings reloaded");
+                self.events.report_reload(ReloadEvent::Applied);
+                Ok(())
+            }
+            Err(e) => {
+                error!(error = %e, "Settings reload failed, keeping current settings");
+                let e = Arc::new(e);
+                self.events.report_reload(ReloadEvent::Failed(e.clone()));
+                Err(e)
+            }
+        }
+    }
+
+    /// Reload the application settings with `loader` whenever the process gets a `SIGHUP`
+    #[cfg(all(unix, feature = "signal"))]
+    pub fn reload_on_sighup<S: Services>(
+        &self,
+        loader: impl SettingsLoader<S>,
+    ) -> Result<tokio::task::JoinHandle<()>, Error>
+    where
+        S::Settings: Send,
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangups = {
+            let _guard = self.runtime_handle.enter();
+            signal(SignalKind::hangup()).map_err(Error::any)?
+        };
+        let handle = self.clone();
+        crate::utils::runtime::spawn_checked(&self.runtime_handle, "overwatch-sighup", async move {
+            while hangups.recv().await.is_some() {
+                info!("SIGHUP received, reloading settings");
+                // failures are already reported to reload events subscribers
+                let _ = handle.reload_settings(&loader).await;
+            }
+        })
+    }
+
+    /// Shut overwatch down gracefully when the process gets a `SIGTERM` or `SIGINT`
+    /// This is how launchd (and systemd) stop daemons, so together with
+    /// [`OverwatchHandle::reload_on_sighup`] it gives them the usual daemon lifecycle.
+    #[cfg(all(unix, feature = "signal"))]
+    pub fn shutdown_on_terminate(&self) -> Result<tokio::task::JoinHandle<()>, Error> {
+        use tokio::signal::unix::{signal, SignalKind};
+        let (mut terminate, mut interrupt) = {
+            let _guard = self.runtime_handle.enter();
+            (
+                signal(SignalKind::terminate()).map_err(Error::any)?,
+                signal(SignalKind::interrupt()).map_err(Error::any)?,
+            )
+        };
+        let handle = self.clone();
+        crate::utils::runtime::spawn_checked(
+            &self.runtime_handle,
+            "overwatch-terminate",
+            async move {
+                tokio::select! {
+                    _ = terminate.recv() => info!("SIGTERM received"),
+                    _ = interrupt.recv() => info!("SIGINT received"),
+                }
+                handle.shutdown().await;
+            },
+        )
+    }
+
+    /// Summary of the services startup, `None` until all of them are running or the
+    /// [`boot_report_timeout`](ContextConfig::boot_report_timeout) elapsed
+    pub fn boot_report(&self) -> Option<BootReport> {
+        self.events.boot_report()
+    }
+
+    /// Wait for the [`BootReport`], it never comes if the services could not be started
+    pub async fn wait_for_boot_report(&self) -> BootReport {
+        self.events.wait_for_boot_report().await
+    }
+
+    /// Stream of settings reloads outcomes after subscribing
+    pub fn reload_events(&self) -> impl Stream<Item = ReloadEvent> {
+        self.events.reload_events()
+    }
+
+    /// Stream of pool members started and stopped by [`OverwatchHandle::scale`] after subscribing
+    pub fn scaling_events(&self) -> impl Stream<Item = ScalingEvent> {
+        self.events.scaling_events()
+    }
+
+    /// Counters of settings updates sent through this handle and its clones
+    pub fn settings_stats(&self) -> SettingsCommandStats {
+        SettingsCommandStats {
+            queued: self.settings_stats.queued.load(Ordering::Relaxed),
+            dropped: self.settings_stats.dropped.load(Ordering::Relaxed),
         }
     }
 
+    /// Update a single service settings and restart it, as a single operation handled by the
+    /// overwatch runner.
+    /// The restarted instance builds its state and state operator from `settings`.
+    #[cfg_attr(feature = "instrumentation", instrument(skip(self, settings), err))]
+    pub async fn reconfigure_service<S: ServiceData>(
+        &self,
+        settings: S::Settings,
+        mode: RestartMode,
+    ) -> Result<(), Error>
+    where
+        S::Settings: Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Reconfigure(ReconfigureCommand {
+                service_id: self.resolve(S::SERVICE_ID),
+                settings: Box::new(settings),
+                mode,
+                reply_channel: ReplyChannel::from(sender),
+                span: CommandSpan::current(),
+            }))
+            .await
+            .map_err(|_| Error::Disconnected)?;
+        receiver.await.map_err(|_| Error::Disconnected)?
+    }
+
+    /// Replace the running instance of a service with its standby one
+    /// See [`ServiceHandle::failover`](crate::services::handle::ServiceHandle::failover).
+    pub async fn failover<S: ServiceData>(&self) -> Result<(), Error> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Failover(FailoverCommand {
+                service_id: self.resolve(S::SERVICE_ID),
+                reply_channel: ReplyChannel::from(sender),
+                span: CommandSpan::current(),
+            }))
+            .await
+            .map_err(|_| Error::Disconnected)?;
+        receiver.await.map_err(|_| Error::Disconnected)?
+    }
+
+    /// Start or stop members of the `S` pool until it has `members` of them
+    /// Progress is reported to [`OverwatchHandle::scaling_events`] subscribers. Relays from
+    /// [`OverwatchHandle::pool_relay`] keep the members they had, get a new one to reach the new
+    /// members.
+    pub async fn scale<S: ServiceData>(&self, members: usize) -> Result<(), Error> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Scale(ScaleCommand {
+                service_id: self.resolve(S::SERVICE_ID),
+                members,
+                reply_channel: ReplyChannel::from(sender),
+                span: CommandSpan::current(),
+            }))
+            .await
+            .map_err(|_| Error::Disconnected)?;
+        receiver.await.map_err(|_| Error::Disconnected)?
+    }
+
+    /// Start a service that is not running, it is a no-op if it already is
+    pub async fn start_service<S: ServiceData>(&self) -> Result<(), Error> {
+        self.control_service(S::SERVICE_ID, ServiceAction::Start)
+            .await
+    }
+
+    /// Stop the running instance of a service, it can be started again with
+    /// [`OverwatchHandle::start_service`]
+    pub async fn stop_service<S: ServiceData>(&self) -> Result<(), Error> {
+        self.control_service(S::SERVICE_ID, ServiceAction::Stop)
+            .await
+    }
+
+    pub(crate) async fn control_service(
+        &self,
+        service_id: ServiceId,
+        action: ServiceAction,
+    ) -> Result<(), Error> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Control(ServiceControlCommand {
+                service_id: self.resolve(service_id),
+                action,
+                reply_channel: ReplyChannel::from(sender),
+                span: CommandSpan::current(),
+            }))
+            .await
+            .map_err(|_| Error::Disconnected)?;
+        receiver.await.map_err(|_| Error::Disconnected)?
+    }
+
+    /// Queue lifecycle and settings commands to send them to the runner at once with
+    /// [`CommandBatch::submit`]
+    pub fn batch(&self) -> CommandBatch {
+        CommandBatch::new(self.clone())
+    }
+
+    pub(crate) async fn send_batch(&self, batch: BatchCommand) -> Result<(), Error> {
+        let settings = batch
+            .commands
+            .iter()
+            .filter(|command| matches!(command, BatchedCommand::Settings(_)))
+            .count() as u64;
+        self.sender
+            .send(OverwatchCommand::Batch(batch))
+            .await
+            .map_err(|_| {
+                self.settings_stats
+                    .dropped
+                    .fetch_add(settings, Ordering::Relaxed);
+                Error::Disconnected
+            })?;
+        self.settings_stats
+            .queued
+            .fetch_add(settings, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Send an application defined command to the
+    /// [`CustomCommandHandler`](crate::overwatch::commands::CustomCommandHandler)s registered
+    /// with [`ContextConfig::with_command_handler`]
+    pub async fn send_custom<C: Any + Send>(&self, command: C) -> Result<(), Error> {
+        self.sender
+            .send(OverwatchCommand::Custom(CustomCommand::new(command)))
+            .await
+            .map_err(|_| Error::Disconnected)
+    }
+
+    /// Run the steps of `sequence` in order, stopping at the first one that fails
+    #[cfg_attr(feature = "instrumentation", instrument(skip_all, err))]
+    pub async fn run_sequence(&self, sequence: &Sequence) -> Result<(), SequenceError> {
+        sequence.run(self).await
+    }
+
     pub fn runtime(&self) -> &Handle {
         &self.runtime_handle
     }
+
+    /// Stream of non fatal errors reported by any service after subscribing
+    pub fn error_events(&self) -> impl Stream<Item = ServiceErrorEvent> {
+        self.events.error_events()
+    }
+
+    /// Stream of [`Backpressure`] level changes of services inbound relays after subscribing
+    /// Upstream services can use it to slow down before the downstream buffers fill up.
+    pub fn backpressure_events(&self) -> impl Stream<Item = Backpressure> {
+        self.events.backpressure_events()
+    }
+
+    /// Stream of [`MemoryPressure`] level changes after subscribing, published when a
+    /// [`MemoryMonitor`](crate::overwatch::memory::MemoryMonitor) is configured
+    pub fn memory_pressure_events(&self) -> impl Stream<Item = MemoryPressure> {
+        self.events.memory_pressure_events()
+    }
+
+    /// Stream of the [`WatchdogEvent`]s after subscribing, published when a
+    /// [`Watchdog`](crate::overwatch::watchdog::Watchdog) is configured
+    pub fn watchdog_events(&self) -> impl Stream<Item = WatchdogEvent> {
+        self.events.watchdog_events()
+    }
+
+    /// Stream of the [`LifecycleEvent`](crate::overwatch::events::LifecycleEvent)s of all
+    /// services after subscribing, the last one being
+    /// [`OverwatchShutdown`](crate::overwatch::events::LifecycleEvent::OverwatchShutdown)
+    pub fn lifecycle_events(&self) -> LifecycleEventStream {
+        self.events.lifecycle_events()
+    }
+
+    /// Token cancelled once Overwatch starts shutting down, or its runner is gone
+    /// Code living outside Overwatch, like embedded HTTP servers, can await it to shut down
+    /// along with the services. Cancelling the returned token doesn't affect Overwatch.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.child_token()
+    }
+
+    pub(crate) fn signal_shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Token the service instances stop on, see [`Overwatch::abort`](crate::overwatch::Overwatch::abort)
+    pub(crate) fn abort_token(&self) -> CancellationToken {
+        self.aborted.clone()
+    }
+
+    pub(crate) fn signal_abort(&self) {
+        self.aborted.cancel();
+        self.shutdown.cancel();
+    }
+
+    pub(crate) fn events(&self) -> &EventsSender {
+        &self.events
+    }
+
+    pub(crate) fn report_error(&self, service_id: ServiceId, error: DynError) {
+        error!(error = %error, "Service {service_id} reported an error");
+        self.events.report_error(ServiceErrorEvent {
+            service_id,
+            error: Arc::from(error),
+        });
+    }
+
+    /// Last time service `S` failed to start, or its main loop failed or panicked
+    pub fn last_error<S: ServiceData>(&self) -> Option<ServiceCrash> {
+        self.last_error_for(S::SERVICE_ID)
+    }
+
+    /// Same as [`OverwatchHandle::last_error`] for the service running under `service_id`
+    pub fn last_error_for(&self, service_id: ServiceId) -> Option<ServiceCrash> {
+        self.crashes.last(self.resolve(service_id))
+    }
+
+    pub(crate) fn report_crash(&self, crash: ServiceCrash) {
+        self.events
+            .report_lifecycle(LifecycleEvent::ServiceCrashed {
+                service_id: crash.service_id,
+                error: crash.error.clone(),
+            });
+        self.crashes.record(crash);
+    }
+
+    pub(crate) fn relay_cache(&self) -> &RelayCache {
+        &self.relays
+    }
+
+    /// Switch pausing the inbound relay of `service_id`, shared by all its instances
+    pub(crate) fn relay_pause(&self, service_id: ServiceId) -> RelayPause {
+        self.pauses.get(service_id)
+    }
+
+    /// Registry of services relays added at runtime, shared by all handle clones
+    pub fn registry(&self) -> &ServiceRegistry {
+        &self.registry
+    }
+
+    /// Status transitions and state snapshots recorded from `since` onwards, oldest first
+    /// Only available if enabled with
+    /// [`ContextConfig::with_history_capacity`](crate::services::context::ContextConfig::with_history_capacity),
+    /// the oldest entries are dropped once the capacity is reached.
+    pub fn history_since(&self, since: Instant) -> Vec<HistoryEntry> {
+        self.history.since(since)
+    }
+
+    pub(crate) fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Last health reported by the liveness probe of each running service, see
+    /// [`ServiceContext::register_liveness_probe`](crate::services::context::ServiceContext::register_liveness_probe)
+    /// Probes are polled every
+    /// [`ContextConfig::health_check_interval`](crate::services::context::ContextConfig::health_check_interval),
+    /// services not probed yet are missing from the report.
+    pub fn health_report(&self) -> HealthOverview {
+        self.health.overview()
+    }
+
+    pub(crate) fn health(&self) -> &HealthRegistry {
+        &self.health
+    }
+
+    /// Relay and lifecycle metrics collected for every service started so far
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    pub(crate) fn metrics_registry(&self) -> &MetricsRegistry {
+        &self.metrics
+    }
+
+    /// Runtime the `service_id` instance of `S` runs on, see [`ServiceData::RUNTIME_POLICY`]
+    pub(crate) fn service_runtime<S: ServiceData>(
+        &self,
+        service_id: ServiceId,
+    ) -> Result<Handle, Error> {
+        self.runtimes
+            .handle_for::<S>(service_id, &self.runtime_handle, &self.context_config)
+    }
+
+    /// Services, pools and declared dependencies with the current status of each service
+    pub async fn topology(&self) -> Result<Topology, Error> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Topology(TopologyCommand {
+                reply_channel: ReplyChannel::from(sender),
+            }))
+            .await
+            .map_err(|_| Error::Disconnected)?;
+        receiver.await.map_err(|_| Error::Disconnected)
+    }
+
+    /// Save the settings the services `S` run with and the ones that are stopped to `path`,
+    /// and snapshot the states of the running services, see [`checkpoint`](crate::overwatch::checkpoint)
+    /// Overwatch can be started again from it with
+    /// [`OverwatchRunner::restore`](crate::overwatch::OverwatchRunner::restore).
+    #[cfg(feature = "checkpoint")]
+    pub async fn checkpoint<S: Services>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), CheckpointError>
+    where
+        S::Settings: Serialize,
+    {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(OverwatchCommand::Checkpoint(CheckpointCommand {
+                serialize_settings: |settings| {
+                    // `S` may not be the services Overwatch runs, which is not worth a panic
+                    let settings = settings
+                        .downcast_ref::<S::Settings>()
+                        .ok_or(Error::InvalidServicesSettings)?;
+                    serde_json::to_value(settings).map_err(Error::any)
+                },
+                reply_channel: ReplyChannel::from(sender),
+            }))
+            .await
+            .map_err(|_| Error::Disconnected)?;
+        let checkpoint = receiver.await.map_err(|_| Error::Disconnected)??;
+        let path = path.as_ref().to_path_buf();
+        tokio::task::spawn_blocking(move || checkpoint.save(path))
+            .await
+            .map_err(Error::any)?
+    }
+
+    /// [`OverwatchHandle::topology`] as a versioned JSON document, see
+    /// [`Topology::to_json`]
+    #[cfg(feature = "topology-json")]
+    pub async fn topology_json(&self) -> Result<String, Error> {
+        Ok(self.topology().await?.to_json().to_string())
+    }
+
+    /// Number of lifecycle operations (reconfigure, failover...) waiting for the one in
+    /// progress for the service to finish
+    pub fn lifecycle_queue_depth<S: ServiceData>(&self) -> usize {
+        self.lifecycle_queue_depths.get(self.resolve(S::SERVICE_ID))
+    }
+
+    pub(crate) fn lifecycle_queue_depths(&self) -> &LifecycleQueueDepths {
+        &self.lifecycle_queue_depths
+    }
+
+    /// Time the runner took to handle the latest commands of `kind`, `None` if it got none yet
+    /// The same percentiles are recorded as gauges through the
+    /// [`MetricsRecorder`](crate::services::context::MetricsRecorder).
+    pub fn command_latency(&self, kind: CommandKind) -> Option<LatencySummary> {
+        self.command_latencies.summary(kind)
+    }
+
+    pub(crate) fn command_latencies(&self) -> &CommandLatencies {
+        &self.command_latencies
+    }
+
+    /// Framework utilities services contexts are built from
+    pub fn context_config(&self) -> &ContextConfig {
+        &self.context_config
+    }
 }
@@ -0,0 +1,264 @@
+// std
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+// crates
+use async_trait::async_trait;
+use futures::future::join_all;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+// internal
+use crate::overwatch::handle::OverwatchHandle;
+use crate::services::ServiceId;
+
+/// How well a service is doing, ordered from best to worst
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Health {
+    Healthy,
+    /// Working, but with reduced capabilities or performance
+    Degraded,
+    Unhealthy,
+}
+
+/// Outcome of a [`LivenessProbe`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthReport {
+    pub health: Health,
+    /// Why the service is not healthy, or any other detail worth exposing
+    pub detail: Option<String>,
+}
+
+impl HealthReport {
+    pub fn healthy() -> Self {
+        Self {
+            health: Health::Healthy,
+            detail: None,
+        }
+    }
+
+    pub fn degraded(detail: impl Into<String>) -> Self {
+        Self {
+            health: Health::Degraded,
+            detail: Some(detail.into()),
+        }
+    }
+
+    pub fn unhealthy(detail: impl Into<String>) -> Self {
+        Self {
+            health: Health::Unhealthy,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Check of a service health, polled by the runner every
+/// [`health_check_interval`](crate::services::context::ContextConfig::health_check_interval)
+/// Registered with
+/// [`ServiceContext::register_liveness_probe`](crate::services::context::ServiceContext::register_liveness_probe).
+/// A probe not answering within the interval is reported unhealthy.
+#[async_trait]
+pub trait LivenessProbe: Send + Sync + 'static {
+    async fn health(&self) -> HealthReport;
+}
+
+/// Last [`HealthReport`] of a service and when it was produced
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceHealth {
+    pub service_id: ServiceId,
+    pub report: HealthReport,
+    pub checked_at: Instant,
+}
+
+/// Health of every service with a liveness probe, see [`OverwatchHandle::health_report`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HealthOverview {
+    /// Last report of each service, sorted by service id
+    pub services: Vec<ServiceHealth>,
+}
+
+impl HealthOverview {
+    /// Worst health among the services, healthy if none of them has a probe
+    pub fn overall(&self) -> Health {
+        self.services
+            .iter()
+            .map(|service| service.report.health)
+            .max()
+            .unwrap_or(Health::Healthy)
+    }
+
+    pub fn service(&self, service_id: ServiceId) -> Option<&ServiceHealth> {
+        self.services
+            .iter()
+            .find(|service| service.service_id == service_id)
+    }
+}
+
+struct RegisteredProbe {
+    probe: Arc<dyn LivenessProbe>,
+    /// Cancelled once the service instance that registered the probe is stopped
+    instance: CancellationToken,
+}
+
+#[derive(Default)]
+struct HealthState {
+    probes: HashMap<ServiceId, RegisteredProbe>,
+    reports: HashMap<ServiceId, ServiceHealth>,
+}
+
+/// Liveness probes of the services and their last reports, shared by the overwatch handles
+#[derive(Clone, Default)]
+pub(crate) struct HealthRegistry(Arc<Mutex<HealthState>>);
+
+impl Debug for HealthRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthRegistry").finish_non_exhaustive()
+    }
+}
+
+impl HealthRegistry {
+    /// Register the probe of `service_id`, replacing its previous one
+    /// The probe is dropped along with its report once `instance` is cancelled.
+    pub(crate) fn register(
+        &self,
+        service_id: ServiceId,
+        probe: Arc<dyn LivenessProbe>,
+        instance: CancellationToken,
+    ) {
+        let mut state = self.0.lock().expect("Health lock not poisoned");
+        state
+            .probes
+            .insert(service_id, RegisteredProbe { probe, instance });
+    }
+
+    pub(crate) fn overview(&self) -> HealthOverview {
+        let state = self.0.lock().expect("Health lock not poisoned");
+        let mut services: Vec<_> = state.reports.values().cloned().collect();
+        services.sort_unstable_by_key(|service| service.service_id);
+        HealthOverview { services }
+    }
+
+    /// Probes of the running service instances, forgetting the stopped ones
+    fn live_probes(&self) -> Vec<(ServiceId, Arc<dyn LivenessProbe>)> {
+        let mut state = self.0.lock().expect("Health lock not poisoned");
+        let HealthState { probes, reports } = &mut *state;
+        probes.retain(|service_id, registered| {
+            let live = !registered.instance.is_cancelled();
+            if !live {
+                reports.remove(service_id);
+            }
+            live
+        });
+        probes
+            .iter()
+            .map(|(&service_id, registered)| (service_id, Arc::clone(&registered.probe)))
+            .collect()
+    }
+
+    fn record(&self, health: ServiceHealth) {
+        let mut state = self.0.lock().expect("Health lock not poisoned");
+        // the service may have stopped while being probed
+        if state.probes.contains_key(health.service_id) {
+            state.reports.insert(health.service_id, health);
+        }
+    }
+}
+
+/// Poll the registered probes every `interval` until Overwatch shuts down
+pub(crate) async fn monitor(handle: OverwatchHandle, interval: Duration) {
+    let shutdown = handle.shutdown_token();
+    let clock = Arc::clone(&handle.context_config().clock);
+    loop {
+        let probes = handle.health().live_probes();
+        let checks = probes.into_iter().map(|(service_id, probe)| {
+            let clock = Arc::clone(&clock);
+            async move {
+                let report = clock
+                    .timeout(interval, probe.health())
+                    .await
+                    .unwrap_or_else(|_| {
+                        HealthReport::unhealthy(format!(
+                            "liveness probe timed out after {interval:?}"
+                        ))
+                    });
+                if report.health != Health::Healthy {
+                    info!(
+                        "Service {service_id} is {:?}: {:?}",
+                        report.health, report.detail
+                    );
+                }
+                ServiceHealth {
+                    service_id,
+                    report,
+                    checked_at: clock.now(),
+                }
+            }
+        });
+        for health in join_all(checks).await {
+            handle.health().record(health);
+        }
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = clock.sleep(interval) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::health::{
+        Health, HealthOverview, HealthRegistry, HealthReport, LivenessProbe, ServiceHealth,
+    };
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use tokio_util::sync::CancellationToken;
+
+    struct Fixed(HealthReport);
+
+    #[async_trait]
+    impl LivenessProbe for Fixed {
+        async fn health(&self) -> HealthReport {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn overview_reports_worst_health() {
+        let service = |service_id, report| ServiceHealth {
+            service_id,
+            report,
+            checked_at: Instant::now(),
+        };
+        let overview = HealthOverview {
+            services: vec![
+                service("a", HealthReport::healthy()),
+                service("b", HealthReport::degraded("slow")),
+            ],
+        };
+        assert_eq!(overview.overall(), Health::Degraded);
+        assert_eq!(HealthOverview::default().overall(), Health::Healthy);
+    }
+
+    #[test]
+    fn stopped_instances_probes_are_forgotten() {
+        let registry = HealthRegistry::default();
+        let instance = CancellationToken::new();
+        registry.register(
+            "service",
+            Arc::new(Fixed(HealthReport::healthy())),
+            instance.clone(),
+        );
+        assert_eq!(registry.live_probes().len(), 1);
+        registry.record(ServiceHealth {
+            service_id: "service",
+            report: HealthReport::healthy(),
+            checked_at: Instant::now(),
+        });
+        assert_eq!(registry.overview().services.len(), 1);
+
+        instance.cancel();
+        assert!(registry.live_probes().is_empty());
+        assert!(registry.overview().services.is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+// std
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+// crates
+// internal
+use crate::services::clock::Clock;
+use crate::services::status::ServiceStatus;
+use crate::services::ServiceId;
+
+/// Something that happened to a service, as recorded in the overwatch history
+#[derive(Clone)]
+pub enum HistoryEvent {
+    /// The service reported a new status
+    Status(ServiceStatus),
+    /// Snapshot of the service state, of type [`ServiceData::State`](crate::services::ServiceData::State)
+    State(Arc<dyn Any + Send + Sync>),
+}
+
+impl HistoryEvent {
+    /// State snapshot, if this is a state event of the given state type
+    pub fn state<T: 'static>(&self) -> Option<&T> {
+        match self {
+            HistoryEvent::State(state) => state.downcast_ref(),
+            HistoryEvent::Status(_) => None,
+        }
+    }
+}
+
+impl Debug for HistoryEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryEvent::Status(status) => f.debug_tuple("Status").field(status).finish(),
+            HistoryEvent::State(_) => f.write_str("State(..)"),
+        }
+    }
+}
+
+/// Timestamped [`HistoryEvent`]
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub service_id: ServiceId,
+    pub at: Instant,
+    pub event: HistoryEvent,
+}
+
+/// Bounded log of services status transitions and state snapshots
+/// Oldest entries are dropped once `capacity` is reached, a capacity of 0 disables recording.
+#[derive(Clone)]
+pub(crate) struct History {
+    entries: Arc<Mutex<VecDeque<HistoryEntry>>>,
+    capacity: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl History {
+    pub(crate) fn new(capacity: usize, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+            clock,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub(crate) fn record(&self, service_id: ServiceId, event: HistoryEvent) {
+        if !self.is_enabled() {
+            return;
+        }
+        let entry = HistoryEntry {
+            service_id,
+            at: self.clock.now(),
+            event,
+        };
+        let mut entries = self.entries.lock().expect("History lock not poisoned");
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Recorded entries from `since` onwards, oldest first
+    pub(crate) fn since(&self, since: Instant) -> Vec<HistoryEntry> {
+        self.entries
+            .lock()
+            .expect("History lock not poisoned")
+            .iter()
+            .filter(|entry| entry.at >= since)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Debug for History {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("History")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::history::{History, HistoryEvent};
+    use crate::services::clock::SystemClock;
+    use crate::services::status::ServiceStatus;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    #[test]
+    fn history_is_bounded_and_sliced_by_time() {
+        let history = History::new(2, Arc::new(SystemClock));
+        let start = Instant::now();
+        history.record("a", HistoryEvent::Status(ServiceStatus::Running));
+        history.record("a", HistoryEvent::State(Arc::new(1usize)));
+        let middle = Instant::now();
+        history.record("a", HistoryEvent::State(Arc::new(2usize)));
+
+        let entries = history.since(start);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].event.state::<usize>(), Some(&1));
+        assert_eq!(entries[1].event.state::<usize>(), Some(&2));
+        assert_eq!(history.since(middle).len(), 1);
+
+        let disabled = History::new(0, Arc::new(SystemClock));
+        disabled.record("a", HistoryEvent::Status(ServiceStatus::Running));
+        assert!(disabled.since(start).is_empty());
+    }
+}
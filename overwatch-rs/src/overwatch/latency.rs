@@ -0,0 +1,160 @@
+// std
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+// crates
+// internal
+use crate::overwatch::commands::OverwatchCommand;
+use crate::services::context::MetricsRecorder;
+use crate::services::ServiceId;
+
+/// Id command loop metrics are recorded under, as the runner is not a service itself
+pub const RUNNER_METRICS_ID: ServiceId = "overwatch-runner";
+
+/// Number of latest samples percentiles are computed from, per command kind
+const LATENCY_WINDOW: usize = 1024;
+
+/// Kinds of [`OverwatchCommand`] the runner handles
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CommandKind {
+    Relay,
+    Status,
+    Broadcast,
+    ServiceLifeCycle,
+    OverwatchLifeCycle,
+    Settings,
+    Reconfigure,
+    Failover,
+    Control,
+    Scale,
+    Batch,
+    Custom,
+    Topology,
+    #[cfg(feature = "checkpoint")]
+    Checkpoint,
+}
+
+impl CommandKind {
+    pub fn of(command: &OverwatchCommand) -> Self {
+        match command {
+            OverwatchCommand::Relay(_) => Self::Relay,
+            OverwatchCommand::Status(_) => Self::Status,
+            OverwatchCommand::Broadcast(_) => Self::Broadcast,
+            OverwatchCommand::ServiceLifeCycle(_) => Self::ServiceLifeCycle,
+            OverwatchCommand::OverwatchLifeCycle(_) => Self::OverwatchLifeCycle,
+            OverwatchCommand::Settings(_) => Self::Settings,
+            OverwatchCommand::Reconfigure(_) => Self::Reconfigure,
+            OverwatchCommand::Failover(_) => Self::Failover,
+            OverwatchCommand::Control(_) => Self::Control,
+            OverwatchCommand::Scale(_) => Self::Scale,
+            OverwatchCommand::Batch(_) => Self::Batch,
+            OverwatchCommand::Custom(_) => Self::Custom,
+            OverwatchCommand::Topology(_) => Self::Topology,
+            #[cfg(feature = "checkpoint")]
+            OverwatchCommand::Checkpoint(_) => Self::Checkpoint,
+        }
+    }
+
+    /// Names of the p50 and p99 gauges, in microseconds
+    fn gauges(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Relay => ("command_relay_p50_us", "command_relay_p99_us"),
+            Self::Status => ("command_status_p50_us", "command_status_p99_us"),
+            Self::Broadcast => ("command_broadcast_p50_us", "command_broadcast_p99_us"),
+            Self::ServiceLifeCycle => (
+                "command_service_lifecycle_p50_us",
+                "command_service_lifecycle_p99_us",
+            ),
+            Self::OverwatchLifeCycle => (
+                "command_overwatch_lifecycle_p50_us",
+                "command_overwatch_lifecycle_p99_us",
+            ),
+            Self::Settings => ("command_settings_p50_us", "command_settings_p99_us"),
+            Self::Reconfigure => ("command_reconfigure_p50_us", "command_reconfigure_p99_us"),
+            Self::Failover => ("command_failover_p50_us", "command_failover_p99_us"),
+            Self::Control => ("command_control_p50_us", "command_control_p99_us"),
+            Self::Scale => ("command_scale_p50_us", "command_scale_p99_us"),
+            Self::Batch => ("command_batch_p50_us", "command_batch_p99_us"),
+            Self::Custom => ("command_custom_p50_us", "command_custom_p99_us"),
+            Self::Topology => ("command_topology_p50_us", "command_topology_p99_us"),
+            #[cfg(feature = "checkpoint")]
+            Self::Checkpoint => ("command_checkpoint_p50_us", "command_checkpoint_p99_us"),
+        }
+    }
+}
+
+/// Latency percentiles of a command kind, from receiving a command to finishing handling it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LatencySummary {
+    pub p50: Duration,
+    pub p99: Duration,
+    /// Number of samples the percentiles were computed from
+    pub samples: usize,
+}
+
+/// Latest command loop latencies per command kind, shared with the overwatch handles
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CommandLatencies(Arc<Mutex<HashMap<CommandKind, VecDeque<Duration>>>>);
+
+impl CommandLatencies {
+    /// Record a handled command and publish the updated percentiles as gauges
+    pub(crate) fn record(
+        &self,
+        kind: CommandKind,
+        latency: Duration,
+        metrics: &dyn MetricsRecorder,
+    ) {
+        let summary = {
+            let mut latencies = self.0.lock().expect("Latencies lock not poisoned");
+            let samples = latencies.entry(kind).or_default();
+            if samples.len() == LATENCY_WINDOW {
+                samples.pop_front();
+            }
+            samples.push_back(latency);
+            summarize(samples)
+        };
+        let (p50, p99) = kind.gauges();
+        metrics.record_gauge(RUNNER_METRICS_ID, p50, summary.p50.as_micros() as f64);
+        metrics.record_gauge(RUNNER_METRICS_ID, p99, summary.p99.as_micros() as f64);
+    }
+
+    pub(crate) fn summary(&self, kind: CommandKind) -> Option<LatencySummary> {
+        let latencies = self.0.lock().expect("Latencies lock not poisoned");
+        latencies
+            .get(&kind)
+            .filter(|samples| !samples.is_empty())
+            .map(summarize)
+    }
+}
+
+fn summarize(samples: &VecDeque<Duration>) -> LatencySummary {
+    let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    // nearest rank percentile
+    let percentile = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+    LatencySummary {
+        p50: percentile(50),
+        p99: percentile(99),
+        samples: sorted.len(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::latency::{CommandKind, CommandLatencies};
+    use crate::services::context::NoMetrics;
+    use std::time::Duration;
+
+    #[test]
+    fn percentiles_per_command_kind() {
+        let latencies = CommandLatencies::default();
+        for ms in 1..=100 {
+            latencies.record(CommandKind::Relay, Duration::from_millis(ms), &NoMetrics);
+        }
+        let summary = latencies.summary(CommandKind::Relay).unwrap();
+        assert_eq!(summary.p50, Duration::from_millis(50));
+        assert_eq!(summary.p99, Duration::from_millis(99));
+        assert_eq!(summary.samples, 100);
+        assert!(latencies.summary(CommandKind::Status).is_none());
+    }
+}
@@ -1,13 +1,15 @@
 // std
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::default::Default;
-use std::error::Error;
+use std::sync::{Arc, Mutex};
 // crates
-use tokio::sync::broadcast::Sender;
 // internal
-use crate::services::life_cycle::{FinishedSignal, LifecycleHandle, LifecycleMessage};
+use crate::overwatch::commands::{FailoverCommand, ReconfigureCommand, ServiceControlCommand};
+use crate::overwatch::Error;
+use crate::services::life_cycle::{LifecycleHandle, LifecycleMessage, StopReason};
 use crate::services::ServiceId;
+use crate::utils::finished_signal;
 use crate::DynError;
 
 /// Grouper handle for the `LifecycleHandle` of each spawned service.
@@ -28,17 +30,17 @@ impl ServicesLifeCycleHandle {
     /// # Arguments
     ///
     /// `service` - The `ServiceId` of the target service
-    /// `sender` - A sender side of a broadcast channel. A return signal when finished handling the
-    /// message will be sent.
+    /// `sender` - A sender side of a [`finished_signal`]. It is sent when finished handling the
+    /// message.
+    /// `reason` - Why the service is shut down
     pub fn shutdown(
         &self,
         service: ServiceId,
-        sender: Sender<FinishedSignal>,
+        sender: finished_signal::Sender,
+        reason: StopReason,
     ) -> Result<(), DynError> {
-        self.handlers
-            .get(service)
-            .unwrap()
-            .send(LifecycleMessage::Shutdown(sender))?;
+        self.handle(service)?
+            .send(LifecycleMessage::Shutdown(sender, reason))?;
         Ok(())
     }
 
@@ -47,36 +49,68 @@ impl ServicesLifeCycleHandle {
     /// # Arguments
     ///
     /// `service` - The `ServiceId` of the target service
-    pub fn kill(&self, service: ServiceId) -> Result<(), DynError> {
-        self.handlers
-            .get(service)
-            .unwrap()
-            .send(LifecycleMessage::Kill)
+    /// `reason` - Why the service is killed
+    pub fn kill(&self, service: ServiceId, reason: StopReason) -> Result<(), DynError> {
+        self.handle(service)?.send(LifecycleMessage::Kill(reason))
+    }
+
+    fn handle(&self, service: ServiceId) -> Result<&LifecycleHandle, DynError> {
+        self.handlers.get(service).ok_or_else(|| {
+            Box::new(Error::Unavailable {
+                service_id: service,
+            }) as DynError
+        })
     }
 
     /// Send a `Kill` message to all services registered in this handle
-    pub fn kill_all(&self) -> Result<(), DynError> {
+    pub fn kill_all(&self, reason: StopReason) -> Result<(), DynError> {
         for service_id in self.services_ids() {
-            self.kill(service_id)?;
+            self.kill(service_id, reason)?;
         }
         Ok(())
     }
 
+    /// Track the lifecycle of a service started after this handle was built
+    pub(crate) fn insert(&mut self, service_id: ServiceId, handle: LifecycleHandle) {
+        self.handlers.insert(service_id, handle);
+    }
+
+    pub(crate) fn remove(&mut self, service_id: ServiceId) {
+        self.handlers.remove(service_id);
+    }
+
     /// Get all services ids registered in this handle
     pub fn services_ids(&self) -> impl Iterator<Item = ServiceId> + '_ {
         self.handlers.keys().copied()
     }
 }
 
+impl IntoIterator for ServicesLifeCycleHandle {
+    type Item = (ServiceId, LifecycleHandle);
+    type IntoIter = std::collections::hash_map::IntoIter<ServiceId, LifecycleHandle>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.handlers.into_iter()
+    }
+}
+
 impl<const N: usize> TryFrom<[(ServiceId, LifecycleHandle); N]> for ServicesLifeCycleHandle {
     // TODO: On errors refactor extract into a concrete error type with `thiserror`
-    type Error = Box<dyn Error + Send + Sync>;
+    type Error = DynError;
 
     fn try_from(value: [(ServiceId, LifecycleHandle); N]) -> Result<Self, Self::Error> {
+        Self::try_from(Vec::from(value))
+    }
+}
+
+impl TryFrom<Vec<(ServiceId, LifecycleHandle)>> for ServicesLifeCycleHandle {
+    type Error = DynError;
+
+    fn try_from(value: Vec<(ServiceId, LifecycleHandle)>) -> Result<Self, Self::Error> {
         let mut handlers = HashMap::new();
         for (service_id, handle) in value {
             if handlers.contains_key(service_id) {
-                return Err(Box::<dyn Error + Send + Sync>::from(Cow::Owned(format!(
+                return Err(DynError::from(Cow::Owned(format!(
                     "Duplicated serviceId: {service_id}"
                 ))));
             }
@@ -85,3 +119,138 @@ impl<const N: usize> TryFrom<[(ServiceId, LifecycleHandle); N]> for ServicesLife
         Ok(Self { handlers })
     }
 }
+
+/// Lifecycle operations that must not interleave for a given service
+#[derive(Debug)]
+pub(crate) enum LifecycleOperation {
+    Reconfigure(ReconfigureCommand),
+    Failover(FailoverCommand),
+    Control(ServiceControlCommand),
+}
+
+impl LifecycleOperation {
+    pub(crate) fn service_id(&self) -> ServiceId {
+        match self {
+            LifecycleOperation::Reconfigure(command) => command.service_id,
+            LifecycleOperation::Failover(command) => command.service_id,
+            LifecycleOperation::Control(command) => command.service_id,
+        }
+    }
+
+    /// Reply to the requester with `error` without running the operation
+    pub(crate) async fn reject(self, error: Error) {
+        let result = match self {
+            LifecycleOperation::Reconfigure(command) => command.reply_channel.reply(Err(error)),
+            LifecycleOperation::Failover(command) => command.reply_channel.reply(Err(error)),
+            LifecycleOperation::Control(command) => command.reply_channel.reply(Err(error)),
+        };
+        if result.await.is_err() {
+            tracing::error!("Error reporting back rejected lifecycle operation");
+        }
+    }
+}
+
+/// Number of lifecycle operations waiting for each service, shared with the overwatch handles
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LifecycleQueueDepths(Arc<Mutex<HashMap<ServiceId, usize>>>);
+
+impl LifecycleQueueDepths {
+    pub(crate) fn get(&self, service_id: ServiceId) -> usize {
+        self.0
+            .lock()
+            .expect("Queue depths lock not poisoned")
+            .get(service_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn set(&self, service_id: ServiceId, depth: usize) {
+        self.0
+            .lock()
+            .expect("Queue depths lock not poisoned")
+            .insert(service_id, depth);
+    }
+}
+
+/// Per service queues serializing lifecycle operations
+/// At most one operation is in progress for a service, the following ones wait in a queue of
+/// up to `capacity` operations.
+pub(crate) struct LifecycleQueues {
+    capacity: usize,
+    /// Queued operations of services with an operation in progress
+    queues: HashMap<ServiceId, VecDeque<LifecycleOperation>>,
+    depths: LifecycleQueueDepths,
+}
+
+impl LifecycleQueues {
+    pub(crate) fn new(capacity: usize, depths: LifecycleQueueDepths) -> Self {
+        Self {
+            capacity,
+            queues: HashMap::new(),
+            depths,
+        }
+    }
+
+    /// Returns the operation back if it can run right away, queueing it otherwise
+    /// Fails with the operation if the service queue is full.
+    pub(crate) fn submit(
+        &mut self,
+        operation: LifecycleOperation,
+    ) -> Result<Option<LifecycleOperation>, LifecycleOperation> {
+        let service_id = operation.service_id();
+        let Some(queue) = self.queues.get_mut(service_id) else {
+            self.queues.insert(service_id, VecDeque::new());
+            return Ok(Some(operation));
+        };
+        if queue.len() >= self.capacity {
+            return Err(operation);
+        }
+        queue.push_back(operation);
+        self.depths.set(service_id, queue.len());
+        Ok(None)
+    }
+
+    /// Mark the operation in progress for `service_id` as finished, returning the next one
+    pub(crate) fn finish(&mut self, service_id: ServiceId) -> Option<LifecycleOperation> {
+        let queue = self.queues.get_mut(service_id)?;
+        let next = queue.pop_front();
+        self.depths.set(service_id, queue.len());
+        if next.is_none() {
+            self.queues.remove(service_id);
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::commands::{CommandSpan, FailoverCommand, ReplyChannel};
+    use crate::overwatch::life_cycle::{LifecycleOperation, LifecycleQueueDepths, LifecycleQueues};
+
+    fn failover(service_id: &'static str) -> LifecycleOperation {
+        let (sender, _) = tokio::sync::oneshot::channel();
+        LifecycleOperation::Failover(FailoverCommand {
+            service_id,
+            reply_channel: ReplyChannel::from(sender),
+            span: CommandSpan::current(),
+        })
+    }
+
+    #[test]
+    fn operations_are_serialized_per_service() {
+        let depths = LifecycleQueueDepths::default();
+        let mut queues = LifecycleQueues::new(1, depths.clone());
+
+        assert!(matches!(queues.submit(failover("a")), Ok(Some(_))));
+        // other services are not affected
+        assert!(matches!(queues.submit(failover("b")), Ok(Some(_))));
+        assert!(matches!(queues.submit(failover("a")), Ok(None)));
+        assert!(queues.submit(failover("a")).is_err());
+        assert_eq!(depths.get("a"), 1);
+
+        assert!(queues.finish("a").is_some());
+        assert_eq!(depths.get("a"), 0);
+        assert!(queues.finish("a").is_none());
+        assert!(matches!(queues.submit(failover("a")), Ok(Some(_))));
+    }
+}
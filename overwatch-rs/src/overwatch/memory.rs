@@ -0,0 +1,192 @@
+// std
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+// crates
+use tracing::info;
+// internal
+use crate::overwatch::events::{MemoryPressure, MemoryPressureLevel};
+use crate::overwatch::handle::OverwatchHandle;
+use crate::services::ServiceId;
+
+const DEFAULT_SAMPLING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Source of the memory used by the process
+pub trait MemoryProbe: Send + Sync + 'static {
+    /// Bytes in use, `None` if unknown
+    fn used_bytes(&self) -> Option<u64>;
+}
+
+impl<F> MemoryProbe for F
+where
+    F: Fn() -> Option<u64> + Send + Sync + 'static,
+{
+    fn used_bytes(&self) -> Option<u64> {
+        self()
+    }
+}
+
+/// [`MemoryProbe`] reading the resident set size of the process from `/proc/self/status`
+/// It knows nothing on platforms without procfs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResidentMemory;
+
+impl MemoryProbe for ResidentMemory {
+    fn used_bytes(&self) -> Option<u64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let kilobytes = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))?
+            .trim()
+            .strip_suffix("kB")?
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        Some(kilobytes * 1024)
+    }
+}
+
+/// Samples the process memory usage and publishes [`MemoryPressure`] changes to
+/// [`OverwatchHandle::memory_pressure_events`] subscribers
+///
+/// Enabled with
+/// [`ContextConfig::with_memory_monitor`](crate::services::context::ContextConfig::with_memory_monitor).
+/// Optionally, the inbound relays of services with
+/// [`ServicePriority::Low`](crate::services::ServicePriority::Low) are paused while the pressure
+/// is critical, and resumed once it drops. Paused services keep running, their senders wait
+/// once the relay buffer is full.
+#[derive(Clone)]
+pub struct MemoryMonitor {
+    probe: Arc<dyn MemoryProbe>,
+    interval: Duration,
+    elevated: u64,
+    critical: u64,
+    pause_low_priority: bool,
+}
+
+impl Debug for MemoryMonitor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryMonitor")
+            .field("interval", &self.interval)
+            .field("elevated", &self.elevated)
+            .field("critical", &self.critical)
+            .field("pause_low_priority", &self.pause_low_priority)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MemoryMonitor {
+    /// Monitor the [`ResidentMemory`] of the process against `elevated` and `critical` bytes
+    pub fn new(elevated: u64, critical: u64) -> Self {
+        Self {
+            probe: Arc::new(ResidentMemory),
+            interval: DEFAULT_SAMPLING_INTERVAL,
+            elevated,
+            critical,
+            pause_low_priority: false,
+        }
+    }
+
+    pub fn with_probe<P: MemoryProbe>(mut self, probe: P) -> Self {
+        self.probe = Arc::new(probe);
+        self
+    }
+
+    /// How often memory is sampled, 5 seconds by default
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Pause the inbound relay of low priority services while the pressure is critical
+    pub fn pausing_low_priority(mut self) -> Self {
+        self.pause_low_priority = true;
+        self
+    }
+
+    pub fn level(&self, used_bytes: u64) -> MemoryPressureLevel {
+        if used_bytes >= self.critical {
+            MemoryPressureLevel::Critical
+        } else if used_bytes >= self.elevated {
+            MemoryPressureLevel::Elevated
+        } else {
+            MemoryPressureLevel::Normal
+        }
+    }
+
+    /// Sample memory until Overwatch shuts down, pausing `low_priority` services if enabled
+    pub(crate) async fn run(self, handle: OverwatchHandle, low_priority: Vec<ServiceId>) {
+        let shutdown = handle.shutdown_token();
+        let clock = Arc::clone(&handle.context_config().clock);
+        let mut level = MemoryPressureLevel::Normal;
+        let mut paused = Vec::new();
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = clock.sleep(self.interval) => {}
+            }
+            let Some(used_bytes) = self.probe.used_bytes() else {
+                continue;
+            };
+            let sampled = self.level(used_bytes);
+            if sampled == level {
+                continue;
+            }
+            info!("Memory pressure is {sampled:?}, {used_bytes} bytes in use");
+            level = sampled;
+            // subscribers learn about the pressure once low priority services are paused
+            if self.pause_low_priority {
+                if level == MemoryPressureLevel::Critical {
+                    paused = Self::pause(&handle, &low_priority).await;
+                } else {
+                    Self::resume(&handle, std::mem::take(&mut paused));
+                }
+            }
+            handle
+                .events()
+                .report_memory_pressure(MemoryPressure(level));
+        }
+    }
+
+    /// Pause the inbound relay of the running services among `services`, returning them
+    async fn pause(handle: &OverwatchHandle, services: &[ServiceId]) -> Vec<ServiceId> {
+        let mut paused = Vec::new();
+        for &service_id in services {
+            let running = handle
+                .status_watcher_for(service_id)
+                .await
+                .is_ok_and(|watcher| watcher.current().is_up());
+            if !running {
+                continue;
+            }
+            info!("Pausing low priority service {service_id}");
+            handle.relay_pause(handle.resolve(service_id)).pause();
+            paused.push(service_id);
+        }
+        paused
+    }
+
+    fn resume(handle: &OverwatchHandle, services: Vec<ServiceId>) {
+        for service_id in services {
+            info!("Resuming low priority service {service_id}");
+            handle.relay_pause(handle.resolve(service_id)).resume();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::events::MemoryPressureLevel;
+    use crate::overwatch::memory::{MemoryMonitor, MemoryProbe, ResidentMemory};
+
+    #[test]
+    fn levels_follow_thresholds() {
+        let monitor = MemoryMonitor::new(100, 200);
+        assert_eq!(monitor.level(99), MemoryPressureLevel::Normal);
+        assert_eq!(monitor.level(100), MemoryPressureLevel::Elevated);
+        assert_eq!(monitor.level(250), MemoryPressureLevel::Critical);
+        if cfg!(target_os = "linux") {
+            assert!(ResidentMemory.used_bytes().is_some_and(|used| used > 0));
+        }
+    }
+}
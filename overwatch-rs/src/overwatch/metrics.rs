@@ -0,0 +1,551 @@
+//! Metrics Overwatch collects on its own for every service
+//! Relays count the messages going through them and how long sends take, service instances
+//! count their starts and stops. Unlike the
+//! [`MetricsRecorder`](crate::services::context::MetricsRecorder) nothing has to be wired, the
+//! latest values are read with [`OverwatchHandle::metrics`].
+// std
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+// internal
+use crate::overwatch::handle::OverwatchHandle;
+use crate::services::clock::Clock;
+use crate::services::ServiceId;
+
+/// Counters of a service inbound relay, across all its instances
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RelayMetrics {
+    pub sent: u64,
+    pub received: u64,
+    /// Messages that couldn't be sent because the service relay was closed
+    pub dropped: u64,
+    /// Messages discarded or rejected because the relay buffer was full, see
+    /// [`BackpressurePolicy`](crate::services::relay::BackpressurePolicy)
+    pub overflowed: u64,
+    /// Messages held back by the service [`RateLimit`](crate::services::rate_limit::RateLimit)
+    pub throttled: u64,
+    /// Messages dropped by a rejecting [`RateLimit`](crate::services::rate_limit::RateLimit)
+    pub rate_limited: u64,
+    /// Messages waiting in the relay buffer when last observed
+    pub queue_depth: usize,
+    /// Mean time taken by sends, including waiting for room in the buffer
+    pub mean_send_latency: Duration,
+    pub max_send_latency: Duration,
+}
+
+/// Starts and stops of a service instances
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LifecycleMetrics {
+    pub starts: u64,
+    pub stops: u64,
+    /// Time the running instance has been running for, `None` if none is
+    pub uptime: Option<Duration>,
+}
+
+/// Tasks spawned by the instances of a service through their
+/// [`ScopedSpawner`](crate::services::context::ScopedSpawner)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaskMetrics {
+    pub spawned: u64,
+    /// Tasks not finished yet, blocking ones included
+    pub running: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ServiceMetrics {
+    pub service_id: ServiceId,
+    pub relay: RelayMetrics,
+    pub lifecycle: LifecycleMetrics,
+    pub tasks: TaskMetrics,
+}
+
+/// Metrics of every service started at least once, see [`OverwatchHandle::metrics`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Sorted by service id
+    pub services: Vec<ServiceMetrics>,
+}
+
+impl MetricsSnapshot {
+    pub fn service(&self, service_id: ServiceId) -> Option<&ServiceMetrics> {
+        self.services
+            .iter()
+            .find(|service| service.service_id == service_id)
+    }
+}
+
+/// Destination of the collected metrics, e.g. a monitoring system
+/// Registered with
+/// [`ContextConfig::with_metrics_exporter`](crate::services::context::ContextConfig::with_metrics_exporter),
+/// exporters get a snapshot every
+/// [`metrics_export_interval`](crate::services::context::ContextConfig::metrics_export_interval)
+/// and a last one once Overwatch shuts down.
+pub trait MetricsExporter: Send + Sync + 'static {
+    fn export(&self, snapshot: &MetricsSnapshot);
+}
+
+/// How a send to a relay ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SendOutcome {
+    Delivered,
+    /// The service relay was closed
+    Closed,
+    /// The relay buffer was full and the policy didn't let the message in
+    Rejected,
+}
+
+/// Stands for no time in the relay stats atomics, see [`RelayStats::full_since_us`]
+const NEVER: u64 = u64::MAX;
+
+/// Relay counters updated by both sides of the relays of a service
+pub(crate) struct RelayStats {
+    clock: Arc<dyn Clock>,
+    /// Times below are kept as microseconds since then
+    origin: Instant,
+    sent: AtomicU64,
+    received: AtomicU64,
+    dropped: AtomicU64,
+    overflowed: AtomicU64,
+    throttled: AtomicU64,
+    rate_limited: AtomicU64,
+    queue_depth: AtomicUsize,
+    /// Size of the relay buffer, 0 until the service inbound relay is built
+    capacity: AtomicUsize,
+    /// Since when the buffer is full, [`NEVER`] if it isn't
+    full_since_us: AtomicU64,
+    /// Last time the service polled its inbound relay, [`NEVER`] if it didn't yet
+    polled_at_us: AtomicU64,
+    /// Since when messages are waiting in the buffer, [`NEVER`] if it is empty
+    pending_since_us: AtomicU64,
+    send_latency_total_us: AtomicU64,
+    send_latency_max_us: AtomicU64,
+}
+
+impl Debug for RelayStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelayStats").finish_non_exhaustive()
+    }
+}
+
+impl RelayStats {
+    fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            origin: clock.now(),
+            clock,
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            overflowed: AtomicU64::new(0),
+            throttled: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0),
+            queue_depth: AtomicUsize::new(0),
+            capacity: AtomicUsize::new(0),
+            full_since_us: AtomicU64::new(NEVER),
+            polled_at_us: AtomicU64::new(NEVER),
+            pending_since_us: AtomicU64::new(NEVER),
+            send_latency_total_us: AtomicU64::new(0),
+            send_latency_max_us: AtomicU64::new(0),
+        }
+    }
+
+    /// Start of a send, to be given back to [`RelayStats::record_send`]
+    pub(crate) fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Record a send started at `started_at`
+    pub(crate) fn record_send(
+        &self,
+        started_at: Instant,
+        outcome: SendOutcome,
+        queue_depth: usize,
+    ) {
+        let latency = self.clock.now().saturating_duration_since(started_at);
+        let latency_us = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.send_latency_total_us
+            .fetch_add(latency_us, Ordering::Relaxed);
+        self.send_latency_max_us
+            .fetch_max(latency_us, Ordering::Relaxed);
+        let counter = match outcome {
+            SendOutcome::Delivered => &self.sent,
+            SendOutcome::Closed => &self.dropped,
+            SendOutcome::Rejected => &self.overflowed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.observe_depth(queue_depth);
+    }
+
+    /// Record a buffered message discarded to make room for a new one
+    pub(crate) fn record_overflow(&self) {
+        self.overflowed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a received message held back by the service rate limit
+    pub(crate) fn record_throttled(&self) {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a received message dropped by the service rate limit
+    pub(crate) fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_receive(&self, queue_depth: usize) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        self.observe_depth(queue_depth);
+    }
+
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+    }
+
+    /// Record the service polling its inbound relay, whether a message was there or not
+    pub(crate) fn record_poll(&self) {
+        self.polled_at_us
+            .store(self.elapsed_us(), Ordering::Relaxed);
+    }
+
+    fn observe_depth(&self, queue_depth: usize) {
+        self.queue_depth.store(queue_depth, Ordering::Relaxed);
+        if queue_depth > 0 {
+            let _ = self.pending_since_us.compare_exchange(
+                NEVER,
+                self.elapsed_us(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+        } else {
+            self.pending_since_us.store(NEVER, Ordering::Relaxed);
+        }
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        if capacity > 0 && queue_depth >= capacity {
+            let _ = self.full_since_us.compare_exchange(
+                NEVER,
+                self.elapsed_us(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+        } else {
+            self.full_since_us.store(NEVER, Ordering::Relaxed);
+        }
+    }
+
+    fn elapsed_us(&self) -> u64 {
+        let elapsed = self.clock.now().saturating_duration_since(self.origin);
+        u64::try_from(elapsed.as_micros()).unwrap_or(NEVER - 1)
+    }
+
+    /// Time since `since_us`, or `None` for [`NEVER`]
+    fn elapsed_since(&self, since_us: u64) -> Option<Duration> {
+        (since_us != NEVER)
+            .then(|| Duration::from_micros(self.elapsed_us().saturating_sub(since_us)))
+    }
+
+    /// How long the relay buffer has been full for, `None` if it isn't
+    pub(crate) fn full_for(&self) -> Option<Duration> {
+        self.elapsed_since(self.full_since_us.load(Ordering::Relaxed))
+    }
+
+    /// How long messages have been waiting without the service polling its relay, `None` if
+    /// none are waiting
+    /// It is measured from the last poll, or from when messages started waiting if the service
+    /// was idle until then, so a service waiting for messages isn't unresponsive yet.
+    pub(crate) fn unpolled_for(&self) -> Option<Duration> {
+        let pending_since = self.pending_since_us.load(Ordering::Relaxed);
+        if self.queue_depth.load(Ordering::Relaxed) == 0 || pending_since == NEVER {
+            return None;
+        }
+        let polled_at = match self.polled_at_us.load(Ordering::Relaxed) {
+            // never polled, the service could still be initializing since it was registered
+            NEVER => 0,
+            polled_at => polled_at,
+        };
+        self.elapsed_since(polled_at.max(pending_since))
+    }
+
+    fn snapshot(&self) -> RelayMetrics {
+        let sent = self.sent.load(Ordering::Relaxed);
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        let overflowed = self.overflowed.load(Ordering::Relaxed);
+        let total = self.send_latency_total_us.load(Ordering::Relaxed);
+        RelayMetrics {
+            sent,
+            received: self.received.load(Ordering::Relaxed),
+            dropped,
+            overflowed,
+            throttled: self.throttled.load(Ordering::Relaxed),
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            mean_send_latency: Duration::from_micros(
+                total
+                    .checked_div(sent + dropped + overflowed)
+                    .unwrap_or_default(),
+            ),
+            max_send_latency: Duration::from_micros(
+                self.send_latency_max_us.load(Ordering::Relaxed),
+            ),
+        }
+    }
+}
+
+/// Start and stop counters of the instances of a service
+pub(crate) struct LifecycleStats {
+    clock: Arc<dyn Clock>,
+    starts: AtomicU64,
+    stops: AtomicU64,
+    /// When the running instance started, if any
+    running_since: Mutex<Option<Instant>>,
+}
+
+impl Debug for LifecycleStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LifecycleStats").finish_non_exhaustive()
+    }
+}
+
+impl LifecycleStats {
+    fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            starts: AtomicU64::new(0),
+            stops: AtomicU64::new(0),
+            running_since: Mutex::new(None),
+        }
+    }
+
+    /// Record an instance start, its stop is recorded once the returned guard is dropped
+    pub(crate) fn started(self: &Arc<Self>) -> RunningInstance {
+        self.starts.fetch_add(1, Ordering::Relaxed);
+        *self.running_since.lock().expect("Uptime lock not poisoned") = Some(self.clock.now());
+        RunningInstance(Arc::clone(self))
+    }
+
+    fn snapshot(&self) -> LifecycleMetrics {
+        let running_since = *self.running_since.lock().expect("Uptime lock not poisoned");
+        LifecycleMetrics {
+            starts: self.starts.load(Ordering::Relaxed),
+            stops: self.stops.load(Ordering::Relaxed),
+            uptime: running_since.map(|since| self.clock.now().saturating_duration_since(since)),
+        }
+    }
+}
+
+/// Held by a running service instance, see [`LifecycleStats::started`]
+pub(crate) struct RunningInstance(Arc<LifecycleStats>);
+
+impl Drop for RunningInstance {
+    fn drop(&mut self) {
+        self.0.stops.fetch_add(1, Ordering::Relaxed);
+        *self
+            .0
+            .running_since
+            .lock()
+            .expect("Uptime lock not poisoned") = None;
+    }
+}
+
+/// Counters behind [`TaskMetrics`]
+#[derive(Debug, Default)]
+pub(crate) struct TaskStats {
+    spawned: AtomicU64,
+    running: AtomicU64,
+}
+
+impl TaskStats {
+    /// Record a spawned task, it is counted as running until the returned guard is dropped
+    pub(crate) fn spawned(self: &Arc<Self>) -> RunningTask {
+        self.spawned.fetch_add(1, Ordering::Relaxed);
+        self.running.fetch_add(1, Ordering::Relaxed);
+        RunningTask(Arc::clone(self))
+    }
+
+    fn snapshot(&self) -> TaskMetrics {
+        TaskMetrics {
+            spawned: self.spawned.load(Ordering::Relaxed),
+            running: self.running.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Held by a running task, see [`TaskStats::spawned`]
+pub(crate) struct RunningTask(Arc<TaskStats>);
+
+impl Drop for RunningTask {
+    fn drop(&mut self) {
+        self.0.running.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Collected metrics of a single service
+#[derive(Debug)]
+pub(crate) struct ServiceStats {
+    relay: Arc<RelayStats>,
+    lifecycle: Arc<LifecycleStats>,
+    tasks: Arc<TaskStats>,
+}
+
+impl ServiceStats {
+    pub(crate) fn relay(&self) -> &Arc<RelayStats> {
+        &self.relay
+    }
+
+    pub(crate) fn lifecycle(&self) -> &Arc<LifecycleStats> {
+        &self.lifecycle
+    }
+
+    pub(crate) fn tasks(&self) -> &Arc<TaskStats> {
+        &self.tasks
+    }
+}
+
+/// Collected metrics of every service, shared by the overwatch handles
+#[derive(Clone)]
+pub(crate) struct MetricsRegistry {
+    clock: Arc<dyn Clock>,
+    services: Arc<Mutex<HashMap<ServiceId, Arc<ServiceStats>>>>,
+}
+
+impl Debug for MetricsRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsRegistry").finish_non_exhaustive()
+    }
+}
+
+impl MetricsRegistry {
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            services: Arc::default(),
+        }
+    }
+
+    /// Metrics of `service_id`, registered on first use
+    pub(crate) fn service(&self, service_id: ServiceId) -> Arc<ServiceStats> {
+        let mut services = self.services.lock().expect("Metrics lock not poisoned");
+        let stats = services.entry(service_id).or_insert_with(|| {
+            Arc::new(ServiceStats {
+                relay: Arc::new(RelayStats::new(Arc::clone(&self.clock))),
+                lifecycle: Arc::new(LifecycleStats::new(Arc::clone(&self.clock))),
+                tasks: Arc::default(),
+            })
+        });
+        Arc::clone(stats)
+    }
+
+    /// Relay stats of every registered service
+    pub(crate) fn relays(&self) -> Vec<(ServiceId, Arc<RelayStats>)> {
+        let services = self.services.lock().expect("Metrics lock not poisoned");
+        services
+            .iter()
+            .map(|(&service_id, stats)| (service_id, Arc::clone(&stats.relay)))
+            .collect()
+    }
+
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        let services = self.services.lock().expect("Metrics lock not poisoned");
+        let mut services: Vec<_> = services
+            .iter()
+            .map(|(&service_id, stats)| ServiceMetrics {
+                service_id,
+                relay: stats.relay.snapshot(),
+                lifecycle: stats.lifecycle.snapshot(),
+                tasks: stats.tasks.snapshot(),
+            })
+            .collect();
+        services.sort_unstable_by_key(|service| service.service_id);
+        MetricsSnapshot { services }
+    }
+}
+
+/// Hand a snapshot to the configured exporters every `interval` until Overwatch shuts down
+/// The last snapshot is exported by the runner once the services are stopped.
+pub(crate) async fn publish(handle: OverwatchHandle, interval: Duration) {
+    let shutdown = handle.shutdown_token();
+    let clock = Arc::clone(&handle.context_config().clock);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = clock.sleep(interval) => export(&handle),
+        }
+    }
+}
+
+/// Hand the current snapshot to the configured exporters
+pub(crate) fn export(handle: &OverwatchHandle) {
+    let exporters = &handle.context_config().metrics_exporters;
+    if exporters.is_empty() {
+        return;
+    }
+    let snapshot = handle.metrics();
+    for exporter in exporters {
+        exporter.export(&snapshot);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::metrics::{MetricsRegistry, SendOutcome};
+    use crate::services::clock::ManualClock;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn instances_uptime_is_tracked_until_stopped() {
+        let clock = ManualClock::new();
+        let registry = MetricsRegistry::new(Arc::new(clock.clone()));
+        let stats = registry.service("service");
+        let running = stats.lifecycle().started();
+        clock.advance(Duration::from_secs(3));
+        let lifecycle = registry.snapshot().service("service").unwrap().lifecycle;
+        assert_eq!(lifecycle.starts, 1);
+        assert_eq!(lifecycle.uptime, Some(Duration::from_secs(3)));
+
+        drop(running);
+        let lifecycle = registry.snapshot().service("service").unwrap().lifecycle;
+        assert_eq!((lifecycle.starts, lifecycle.stops), (1, 1));
+        assert_eq!(lifecycle.uptime, None);
+    }
+
+    #[test]
+    fn send_latencies_are_averaged() {
+        let clock = ManualClock::new();
+        let registry = MetricsRegistry::new(Arc::new(clock.clone()));
+        let relay = Arc::clone(registry.service("service").relay());
+        let started_at = relay.now();
+        clock.advance(Duration::from_millis(30));
+        relay.record_send(started_at, SendOutcome::Delivered, 1);
+        relay.record_send(relay.now(), SendOutcome::Closed, 1);
+        relay.record_receive(0);
+        let metrics = registry.snapshot().service("service").unwrap().relay;
+        assert_eq!((metrics.sent, metrics.dropped, metrics.received), (1, 1, 1));
+        assert_eq!(metrics.queue_depth, 0);
+        assert_eq!(metrics.mean_send_latency, Duration::from_millis(15));
+        assert_eq!(metrics.max_send_latency, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn idle_services_are_unpolled_from_their_first_waiting_message() {
+        let clock = ManualClock::new();
+        let registry = MetricsRegistry::new(Arc::new(clock.clone()));
+        let relay = Arc::clone(registry.service("service").relay());
+        relay.record_poll();
+        assert_eq!(relay.unpolled_for(), None);
+
+        // a message lands long after the service started waiting for one
+        clock.advance(Duration::from_secs(10));
+        relay.record_send(relay.now(), SendOutcome::Delivered, 1);
+        assert_eq!(relay.unpolled_for(), Some(Duration::ZERO));
+        clock.advance(Duration::from_secs(1));
+        relay.record_send(relay.now(), SendOutcome::Delivered, 2);
+        assert_eq!(relay.unpolled_for(), Some(Duration::from_secs(1)));
+
+        relay.record_poll();
+        relay.record_receive(1);
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(relay.unpolled_for(), Some(Duration::from_secs(2)));
+        relay.record_receive(0);
+        assert_eq!(relay.unpolled_for(), None);
+    }
+}
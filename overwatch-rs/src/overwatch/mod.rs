@@ -1,36 +1,100 @@
+#[cfg(feature = "config")]
+pub mod admin;
+#[cfg(feature = "admin-http")]
+pub mod admin_http;
+pub mod batch;
+pub mod boot;
+#[cfg(feature = "checkpoint")]
+pub mod checkpoint;
 pub mod commands;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "cli")]
+pub mod control;
+pub mod crash;
+pub mod events;
 pub mod handle;
+pub mod health;
+pub mod history;
+pub mod latency;
 pub mod life_cycle;
+pub mod memory;
+pub mod metrics;
+#[cfg(feature = "metrics-prometheus")]
+pub mod prometheus;
+pub mod reload;
+pub mod sequence;
+#[cfg(any(feature = "admin-http", feature = "cli", feature = "remote"))]
+pub mod server;
+pub mod teardown;
+pub mod testing;
+pub mod topology;
+pub mod watchdog;
+#[cfg(all(windows, feature = "windows-service"))]
+pub mod windows;
 // std
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::future::Future;
+#[cfg(feature = "checkpoint")]
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 // crates
 
 use async_trait::async_trait;
+#[cfg(feature = "checkpoint")]
+use serde::{de::DeserializeOwned, Serialize};
 use thiserror::Error;
 use tokio::runtime::{Handle, Runtime};
-use tokio::sync::mpsc::Receiver;
-use tokio::sync::oneshot;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 #[cfg(feature = "instrumentation")]
 use tracing::instrument;
 use tracing::{error, info};
 
 // internal
+#[cfg(feature = "checkpoint")]
+use crate::overwatch::checkpoint::{Checkpoint, CheckpointError, CHECKPOINT_VERSION};
+#[cfg(feature = "checkpoint")]
+use crate::overwatch::commands::CheckpointCommand;
 use crate::overwatch::commands::{
-    OverwatchCommand, OverwatchLifeCycleCommand, RelayCommand, ServiceLifeCycleCommand,
-    SettingsCommand, StatusCommand,
+    BatchCommand, BatchedCommand, BroadcastCommand, CustomCommand, FailoverCommand,
+    OverwatchCommand, OverwatchLifeCycleCommand, ReconfigureCommand, RelayCommand, RestartMode,
+    ScaleCommand, ServiceAction, ServiceControlCommand, ServiceLifeCycleCommand, SettingsCommand,
+    StatusCommand, TopologyCommand,
 };
+use crate::overwatch::events::{LifecycleEvent, ScalingChange, ScalingEvent};
 use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::latency::CommandKind;
 pub use crate::overwatch::life_cycle::ServicesLifeCycleHandle;
-use crate::services::life_cycle::LifecycleMessage;
-use crate::services::relay::RelayResult;
-use crate::services::status::ServiceStatusResult;
-use crate::services::{ServiceError, ServiceId};
-use crate::utils::runtime::default_multithread_runtime;
+use crate::overwatch::life_cycle::{LifecycleOperation, LifecycleQueues};
+use crate::overwatch::teardown::{ServiceExit, ShutdownOrder, TeardownReport};
+use crate::overwatch::topology::Topology;
+use crate::services::broadcast::BroadcastResult;
+use crate::services::context::ContextConfig;
+use crate::services::handle::ServiceTask;
+use crate::services::life_cycle::{LifecycleMessage, StopReason};
+#[cfg(feature = "persistent-metrics")]
+use crate::services::persistent_metrics;
+use crate::services::pool::{is_member_of, Scaled};
+use crate::services::relay::{RelayError, RelayPolicy, RelayResult};
+use crate::services::state::SnapshotRequest;
+use crate::services::status::{ServiceStatus, ServiceStatusResult};
+use crate::services::{ServiceError, ServiceId, ServicePriority, ServiceRuntime};
+use crate::utils::finished_signal::{self, RecvError};
+#[cfg(feature = "checkpoint")]
+use crate::utils::runtime::CHECKPOINT_TASK;
+#[cfg(feature = "persistent-metrics")]
+use crate::utils::runtime::PERSISTENT_METRICS_TASK;
+use crate::utils::runtime::{
+    default_multithread_runtime, spawn_checked, spawn_named, BOOT_REPORT_TASK, HEALTH_MONITOR_TASK,
+    MEMORY_MONITOR_TASK, METRICS_EXPORT_TASK, RUNNER_TASK, WATCHDOG_TASK,
+};
 
 /// Overwatch base error type
 #[derive(Error, Debug)]
@@ -41,6 +105,58 @@ pub enum Error {
     #[error("Service {service_id} is unavailable")]
     Unavailable { service_id: ServiceId },
 
+    #[error("Service {service_id} is disabled")]
+    ServiceDisabled { service_id: ServiceId },
+
+    #[error("Overwatch runner is not listening to commands anymore")]
+    Disconnected,
+
+    #[error("Overwatch commands channel is full")]
+    Busy,
+
+    #[error("Too many lifecycle operations waiting for service {service_id}")]
+    LifecycleQueueFull { service_id: ServiceId },
+
+    #[error("Async runtime is shutting down, no more tasks can be spawned")]
+    RuntimeUnavailable,
+
+    #[error("Runtime {runtime} has no worker threads")]
+    NoWorkerThreads { runtime: &'static str },
+
+    #[error("Service {service_id} has no standby instance to fail over to")]
+    NoStandby { service_id: ServiceId },
+
+    #[error("Invalid settings type for service {service_id}")]
+    InvalidSettings { service_id: ServiceId },
+
+    #[error("Invalid settings type for the running services")]
+    InvalidServicesSettings,
+
+    #[error("Settings of service {service_id} were rejected: {source}")]
+    RejectedSettings {
+        service_id: ServiceId,
+        #[source]
+        source: super::DynError,
+    },
+
+    #[error("Service {service_id} panicked while being initialized: {message}")]
+    Panicked {
+        service_id: ServiceId,
+        message: String,
+    },
+
+    #[error("Service {service_id} is not a pool")]
+    NotAPool { service_id: ServiceId },
+
+    #[error("Pool {service_id} has no settings to start new members from")]
+    NoPoolSettings { service_id: ServiceId },
+
+    #[error("Service {service_id} wasn't running within its start budget of {budget:?}")]
+    StartTimeout {
+        service_id: ServiceId,
+        budget: Duration,
+    },
+
     #[error(transparent)]
     Any(super::DynError),
 }
@@ -57,9 +173,6 @@ impl From<super::DynError> for Error {
     }
 }
 
-/// Signal sent so overwatch finish execution
-type FinishOverwatchSignal = ();
-
 /// Marker trait for settings related elements
 pub type AnySettings = Box<dyn Any + Send>;
 
@@ -69,7 +182,54 @@ pub type AnySettings = Box<dyn Any + Send>;
 pub trait Services: Sized {
     /// Inner [`ServiceCore::Settings`](crate::services::ServiceCore) grouping type.
     /// Normally this will be a settings object that group all the inner services settings.
-    type Settings: Debug + 'static; // 'static is required for cast to `AnySetting`
+    type Settings: Clone + Debug + Send + 'static; // 'static is required for cast to `AnySetting`
+
+    /// Ids of all the services attached to the trait implementer
+    /// Services of nested bundles run within the namespace of the `#[bundle]` fields leading to
+    /// them, `<bundle>.<service>`.
+    const SERVICES_IDS: &'static [ServiceId];
+
+    /// [`ServiceData::PRIORITY`](crate::services::ServiceData::PRIORITY) of the attached services
+    const SERVICES_PRIORITIES: &'static [(ServiceId, ServicePriority)] = &[];
+
+    /// [`ServiceData::SERVICE_RELAY_BUFFER_SIZE`](crate::services::ServiceData::SERVICE_RELAY_BUFFER_SIZE)
+    /// of the attached services
+    const SERVICES_RELAY_BUFFER_SIZES: &'static [(ServiceId, usize)] = &[];
+
+    /// Ids of the attached [`ServicePool`](crate::services::pool::ServicePool)s
+    const SERVICES_POOLS: &'static [ServiceId] = &[];
+
+    /// Path of the settings of each attached service in [`Services::Settings`], in the settings of
+    /// their bundle for bundled services
+    /// It is the service field, followed by `.settings` for services that can be disabled, see
+    /// [`OptionalSettings`](crate::services::settings::OptionalSettings).
+    const SERVICES_SETTINGS: &'static [(ServiceId, &'static str)] = &[];
+
+    /// Find the id of an attached service from its name, either as is or in any supported naming
+    /// convention (see [`find_service_id`](crate::services::ids::find_service_id))
+    /// Services of bundles run within the namespace of their bundle, like `plugins.indexer`, they
+    /// can be named without it as long as no other bundle has a service with the same name.
+    fn service_id_from_str(name: &str) -> Option<ServiceId> {
+        use crate::services::ids::{find_service_id, NAMESPACE_SEPARATOR};
+        if let Some(service_id) = find_service_id(Self::SERVICES_IDS, name) {
+            return Some(service_id);
+        }
+        let mut matches = Self::SERVICES_IDS.iter().filter(|service_id| {
+            service_id
+                .rsplit_once(NAMESPACE_SEPARATOR)
+                .and_then(|(_, id)| find_service_id(&[id], name))
+                .is_some()
+        });
+        match (matches.next(), matches.next()) {
+            (Some(service_id), None) => Some(service_id),
+            _ => None,
+        }
+    }
+
+    /// Whether the service is attached, pool members and bundles services included
+    fn has_service(&self, service_id: ServiceId) -> bool {
+        Self::SERVICES_IDS.contains(&service_id)
+    }
 
     /// Spawn a new instance of the Services object
     /// It returns a `(ServiceId, Runtime)` where Runtime is the `tokio::runtime::Runtime` attached for each
@@ -85,18 +245,68 @@ pub trait Services: Sized {
 
     // TODO: this probably will be removed once the services lifecycle is implemented
     /// Start all services attached to the trait implementer
-    fn start_all(&mut self) -> Result<ServicesLifeCycleHandle, Error>;
+    fn start_all(&mut self) -> Result<ServicesLifeCycleHandle, Error> {
+        self.start_all_except(&[])
+    }
+
+    /// Start all services attached to the trait implementer but the `stopped` ones, which are
+    /// reported stopped without being started, see
+    /// [`ServiceHandle::keep_stopped`](crate::services::handle::ServiceHandle::keep_stopped)
+    fn start_all_except(&mut self, stopped: &[ServiceId])
+        -> Result<ServicesLifeCycleHandle, Error>;
 
     /// Stop a service attached to the trait implementer
-    fn stop(&mut self, service_id: ServiceId) -> Result<(), Error>;
+    fn stop(&mut self, service_id: ServiceId, reason: StopReason) -> Result<(), Error>;
 
     /// Request communication relay to one of the services
     fn request_relay(&mut self, service_id: ServiceId) -> RelayResult;
 
     fn request_status_watcher(&self, service_id: ServiceId) -> ServiceStatusResult;
 
+    /// Whether an instance of one of the services is running, see
+    /// [`ServiceHandle::is_running`](crate::services::handle::ServiceHandle::is_running)
+    fn is_running(&self, service_id: ServiceId) -> Result<bool, Error>;
+
+    /// Request the broadcast relay of one of the services, see
+    /// [`ServiceBroadcast`](crate::services::broadcast::ServiceBroadcast)
+    fn request_broadcast(&self, service_id: ServiceId) -> BroadcastResult;
+
+    /// Check the settings of every service, see
+    /// [`ValidateSettings`](crate::services::settings::ValidateSettings)
+    fn validate_settings(_settings: &Self::Settings) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Update service settings
     fn update_settings(&mut self, settings: Self::Settings) -> Result<(), Error>;
+
+    /// Update a single service settings, `settings` should be of the type of the service
+    /// [`ServiceData::Settings`](crate::services::ServiceData::Settings).
+    /// Next time the service is started it uses the new settings and reloads its state from them.
+    fn update_service_settings(
+        &mut self,
+        service_id: ServiceId,
+        settings: AnySettings,
+    ) -> Result<(), Error>;
+
+    /// Promote the standby instance of a service, replacing the running one
+    /// See [`ServiceHandle::failover`](crate::services::handle::ServiceHandle::failover).
+    fn failover(&mut self, service_id: ServiceId) -> Result<(), Error>;
+
+    /// Start or stop members of a service pool until it has `members` of them
+    /// See [`ServicePool::scale`](crate::services::pool::ServicePool::scale).
+    fn scale(&mut self, service_id: ServiceId, members: usize) -> Result<Scaled, Error>;
+
+    /// Take the tasks of every running service instance, to wait for them to end on shutdown
+    fn teardown(&mut self) -> Vec<ServiceTask>;
+
+    /// Settings the services run with, as last updated, see [`OverwatchHandle::checkpoint`]
+    /// `settings` are the ones last given to the services, which disabled optional services keep.
+    fn current_settings(&self, settings: &Self::Settings) -> Self::Settings;
+
+    /// Run the state operators of the running services over their current states, see
+    /// [`ServiceHandle::request_snapshot`](crate::services::handle::ServiceHandle::request_snapshot)
+    fn request_snapshots(&self) -> Vec<SnapshotRequest>;
 }
 
 /// `OverwatchRunner` is the entity that handles a running overwatch
@@ -107,13 +317,21 @@ pub struct OverwatchRunner<S: Services> {
     services: S,
     #[allow(unused)]
     handle: OverwatchHandle,
-    finish_signal_sender: oneshot::Sender<()>,
+    finish_signal_sender: finished_signal::Sender,
+    /// Services not to start with the others, see [`OverwatchRunner::restore`]
+    stopped: Vec<ServiceId>,
+    /// Settings last given to the services, see [`Services::current_settings`]
+    settings: S::Settings,
 }
 
 /// Overwatch thread identifier
 /// it is used when creating the `tokio::runtime::Runtime` that Overwatch uses internally
 pub const OVERWATCH_THREAD_NAME: &str = "Overwatch";
 
+/// Lifecycle operations (reconfigure, failover...) that can wait for a service while another
+/// one is in progress
+pub const LIFECYCLE_QUEUE_CAPACITY: usize = 8;
+
 impl<S> OverwatchRunner<S>
 where
     S: Services + Send + 'static,
@@ -126,24 +344,104 @@ where
         settings: S::Settings,
         runtime: Option<Runtime>,
     ) -> std::result::Result<Overwatch, super::DynError> {
+        Self::run_with_context_config(settings, runtime, ContextConfig::default())
+    }
+
+    /// Same as [`OverwatchRunner::run`] but services contexts are built from `context_config`
+    /// (clock, metrics recorder, feature flags...) instead of the defaults.
+    pub fn run_with_context_config(
+        settings: S::Settings,
+        runtime: Option<Runtime>,
+        context_config: ContextConfig,
+    ) -> std::result::Result<Overwatch, super::DynError> {
+        let runtime = runtime.unwrap_or_else(default_multithread_runtime);
+        Self::run_with(
+            settings,
+            ServiceRuntime::Custom(runtime),
+            context_config,
+            Vec::new(),
+        )
+    }
+
+    /// Start the Overwatch runner process embedded on an already existing runtime
+    /// Same as [`OverwatchRunner::run`] but the host application keeps the ownership of the
+    /// runtime, see [`Overwatch::detach`] for waiting on it without blocking.
+    pub fn run_on(
+        settings: S::Settings,
+        runtime_handle: Handle,
+    ) -> std::result::Result<Overwatch, super::DynError> {
+        Self::run_with(
+            settings,
+            ServiceRuntime::FromParent(runtime_handle),
+            ContextConfig::default(),
+            Vec::new(),
+        )
+    }
+
+    /// Start the Overwatch runner process from the checkpoint at `path`, see
+    /// [`Overwatch::checkpoint`]
+    /// Services are started with the checkpoint settings, but the ones stopped when it was taken,
+    /// which are left stopped.
+    #[cfg(feature = "checkpoint")]
+    pub fn restore(
+        path: impl AsRef<Path>,
+        runtime: Option<Runtime>,
+    ) -> std::result::Result<Overwatch, super::DynError>
+    where
+        S::Settings: DeserializeOwned,
+    {
+        let checkpoint = Checkpoint::load(path)?;
+        let settings =
+            serde_json::from_value(checkpoint.settings).map_err(CheckpointError::Settings)?;
+        let stopped = checkpoint
+            .stopped
+            .iter()
+            .map(|name| {
+                S::service_id_from_str(name)
+                    .ok_or_else(|| CheckpointError::UnknownService(name.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
         let runtime = runtime.unwrap_or_else(default_multithread_runtime);
+        Self::run_with(
+            settings,
+            ServiceRuntime::Custom(runtime),
+            ContextConfig::default(),
+            stopped,
+        )
+    }
 
-        let (finish_signal_sender, finish_runner_signal) = tokio::sync::oneshot::channel();
+    fn run_with(
+        settings: S::Settings,
+        runtime: ServiceRuntime,
+        context_config: ContextConfig,
+        stopped: Vec<ServiceId>,
+    ) -> std::result::Result<Overwatch, super::DynError> {
+        let (finish_signal_sender, finish_runner_signal) = finished_signal::channel();
         let (commands_sender, commands_receiver) = tokio::sync::mpsc::channel(16);
-        let handle = OverwatchHandle::new(runtime.handle().clone(), commands_sender);
-        let services = S::new(settings, handle.clone())?;
+        if context_config.panic_backtraces {
+            crash::capture_panic_backtraces();
+        }
+        let handle =
+            OverwatchHandle::with_context_config(runtime.handle(), commands_sender, context_config)
+                .for_services::<S>();
+        let services = S::new(settings.clone(), handle.clone())?;
         let runner = OverwatchRunner {
             services,
             handle: handle.clone(),
             finish_signal_sender,
+            stopped,
+            settings,
         };
 
-        runtime.spawn(async move { runner.run_(commands_receiver).await });
+        let runner_task = spawn_checked(handle.runtime(), RUNNER_TASK, async move {
+            runner.run_(commands_receiver).await
+        })?;
 
         Ok(Overwatch {
             runtime,
             handle,
             finish_runner_signal,
+            runner_task,
         })
     }
 
@@ -154,84 +452,727 @@ where
     async fn run_(self, mut receiver: Receiver<OverwatchCommand>) {
         let Self {
             mut services,
-            handle: _,
+            handle,
             finish_signal_sender,
+            stopped,
+            mut settings,
         } = self;
-        let lifecycle_handlers = services.start_all().expect("Services to start running");
-        while let Some(command) = receiver.recv().await {
+        let relay_policy = handle.context_config().relay_policy.clone();
+        let clock = handle.context_config().clock.clone();
+        let metrics = handle.context_config().metrics.clone();
+        let started_at = clock.now();
+        let mut lifecycle_handlers = match services.start_all_except(&stopped) {
+            Ok(lifecycle_handlers) => {
+                Self::spawn_boot_report(
+                    &services,
+                    &lifecycle_handlers,
+                    &stopped,
+                    &handle,
+                    started_at,
+                );
+                Self::spawn_memory_monitor(&handle);
+                Self::spawn_watchdog(&handle);
+                Self::spawn_health_monitor(&handle);
+                Self::spawn_metrics_export(&handle);
+                #[cfg(feature = "persistent-metrics")]
+                Self::spawn_persistent_metrics_flush(&handle);
+                lifecycle_handlers
+            }
+            Err(e) => {
+                error!("Services couldn't be started: {e}");
+                handle.signal_shutdown();
+                finish_signal_sender.send();
+                return;
+            }
+        };
+        let mut operations = LifecycleQueues::new(
+            LIFECYCLE_QUEUE_CAPACITY,
+            handle.lifecycle_queue_depths().clone(),
+        );
+        // graceful reconfigurations come back here once their service is down
+        let (stopped_sender, mut stopped_receiver) =
+            tokio::sync::mpsc::unbounded_channel::<ReconfigureCommand>();
+        loop {
+            let command = tokio::select! {
+                command = receiver.recv() => match command {
+                    Some(command) => command,
+                    None => break,
+                },
+                Some(command) = stopped_receiver.recv() => {
+                    let span = command.span.clone();
+                    let service_id = span
+                        .instrument(Self::finish_reconfigure(&mut services, command))
+                        .await;
+                    let next = operations.finish(service_id);
+                    Self::run_operations(&mut services, &lifecycle_handlers, &mut operations, &stopped_sender, handle.context_config(), next).await;
+                    continue;
+                }
+            };
             info!(command = ?command, "Overwatch command received");
+            let received_at = clock.now();
+            let kind = CommandKind::of(&command);
             match command {
                 OverwatchCommand::Relay(relay_command) => {
-                    Self::handle_relay(&mut services, relay_command).await;
+                    Self::handle_relay(&mut services, relay_policy.as_ref(), relay_command).await;
                 }
                 OverwatchCommand::Status(status_command) => {
                     Self::handle_status(&mut services, status_command).await;
                 }
+                OverwatchCommand::Broadcast(broadcast_command) => {
+                    Self::handle_broadcast(&mut services, relay_policy.as_ref(), broadcast_command)
+                        .await;
+                }
                 OverwatchCommand::ServiceLifeCycle(msg) => match msg {
                     ServiceLifeCycleCommand {
                         service_id,
-                        msg: LifecycleMessage::Shutdown(channel),
+                        msg: LifecycleMessage::Shutdown(channel, reason),
                     } => {
-                        if let Err(e) = lifecycle_handlers.shutdown(service_id, channel) {
+                        let channel = channel.with_metrics(Arc::clone(&metrics), service_id);
+                        if let Err(e) = lifecycle_handlers.shutdown(service_id, channel, reason) {
                             error!(e);
                         }
                     }
                     ServiceLifeCycleCommand {
                         service_id,
-                        msg: LifecycleMessage::Kill,
+                        msg: LifecycleMessage::Kill(reason),
                     } => {
-                        if let Err(e) = lifecycle_handlers.kill(service_id) {
+                        if let Err(e) = lifecycle_handlers.kill(service_id, reason) {
                             error!(e);
                         }
                     }
                 },
-                OverwatchCommand::OverwatchLifeCycle(command) => {
-                    if matches!(
-                        command,
-                        OverwatchLifeCycleCommand::Kill | OverwatchLifeCycleCommand::Shutdown
-                    ) {
-                        if let Err(e) = lifecycle_handlers.kill_all() {
-                            error!(e);
+                OverwatchCommand::OverwatchLifeCycle(command) => match command {
+                    OverwatchLifeCycleCommand::StartAll => {
+                        Self::start_all(&mut services, &mut lifecycle_handlers);
+                    }
+                    OverwatchLifeCycleCommand::StopAll => {
+                        Self::stop_all(&mut services, &lifecycle_handlers);
+                    }
+                    OverwatchLifeCycleCommand::Kill | OverwatchLifeCycleCommand::Shutdown => {
+                        handle.signal_shutdown();
+                        let (order, grace) = if let OverwatchLifeCycleCommand::Shutdown = command {
+                            (
+                                &handle.context_config().shutdown_order,
+                                handle.context_config().shutdown_grace,
+                            )
+                        } else {
+                            (&ShutdownOrder::Parallel, Duration::ZERO)
+                        };
+                        let report =
+                            Self::teardown(&mut services, &lifecycle_handlers, order, grace).await;
+                        #[cfg(feature = "persistent-metrics")]
+                        if let Some(metrics) = &handle.context_config().persistent_metrics {
+                            if let Err(e) = metrics.flush() {
+                                error!("Persistent metrics couldn't be saved: {e}");
+                            }
                         }
+                        metrics::export(&handle);
+                        handle
+                            .events()
+                            .report_lifecycle(LifecycleEvent::OverwatchShutdown);
+                        handle.events().report_teardown(report);
                         break;
                     }
+                },
+                OverwatchCommand::Settings(command) => {
+                    Self::handle_settings_update(&mut services, &mut settings, command).await;
+                }
+                OverwatchCommand::Reconfigure(command) => {
+                    Self::submit_operation(
+                        &mut services,
+                        &lifecycle_handlers,
+                        &mut operations,
+                        &stopped_sender,
+                        handle.context_config(),
+                        LifecycleOperation::Reconfigure(command),
+                    )
+                    .await;
+                }
+                OverwatchCommand::Failover(command) => {
+                    Self::submit_operation(
+                        &mut services,
+                        &lifecycle_handlers,
+                        &mut operations,
+                        &stopped_sender,
+                        handle.context_config(),
+                        LifecycleOperation::Failover(command),
+                    )
+                    .await;
+                }
+                OverwatchCommand::Scale(command) => {
+                    let span = command.span.clone();
+                    span.instrument(Self::handle_scale(
+                        &mut services,
+                        &mut lifecycle_handlers,
+                        &handle,
+                        command,
+                    ))
+                    .await;
+                }
+                OverwatchCommand::Batch(command) => {
+                    Self::handle_batch(
+                        &mut services,
+                        &mut settings,
+                        &mut lifecycle_handlers,
+                        &mut operations,
+                        &stopped_sender,
+                        &handle,
+                        command,
+                    )
+                    .await;
                 }
-                OverwatchCommand::Settings(settings) => {
-                    Self::handle_settings_update(&mut services, settings).await;
+                OverwatchCommand::Custom(command) => {
+                    Self::handle_custom(&handle, command);
+                }
+                OverwatchCommand::Topology(command) => {
+                    Self::handle_topology(&mut services, &lifecycle_handlers, &handle, command)
+                        .await;
+                }
+                #[cfg(feature = "checkpoint")]
+                OverwatchCommand::Checkpoint(command) => {
+                    Self::handle_checkpoint(&services, &settings, &handle, command);
+                }
+                OverwatchCommand::Control(command) => {
+                    Self::submit_operation(
+                        &mut services,
+                        &lifecycle_handlers,
+                        &mut operations,
+                        &stopped_sender,
+                        handle.context_config(),
+                        LifecycleOperation::Control(command),
+                    )
+                    .await;
                 }
             }
+            handle.command_latencies().record(
+                kind,
+                clock.now().saturating_duration_since(received_at),
+                metrics.as_ref(),
+            );
         }
         // signal that we finished execution
-        finish_signal_sender
-            .send(())
-            .expect("Overwatch run finish signal to be sent properly");
+        handle.signal_shutdown();
+        finish_signal_sender.send();
+    }
+
+    /// Start every service that is not running, the lifecycle handles of the started pool members
+    /// included
+    fn start_all(services: &mut S, lifecycle_handlers: &mut ServicesLifeCycleHandle) {
+        match services.start_all() {
+            Ok(started) => *lifecycle_handlers = started,
+            Err(e) => error!("Services couldn't be started: {e}"),
+        }
+    }
+
+    /// Stop every service started so far, they can be started again with
+    /// [`OverwatchHandle::start_all`]
+    fn stop_all(services: &mut S, lifecycle_handlers: &ServicesLifeCycleHandle) {
+        for service_id in lifecycle_handlers.services_ids() {
+            if let Err(e) = services.stop(service_id, StopReason::Requested) {
+                error!("Service {service_id} couldn't be stopped: {e}");
+            }
+        }
+    }
+
+    /// Kill the services in `order`, waiting up to `grace` for each group of them to end and
+    /// aborting the remaining ones
+    async fn teardown(
+        services: &mut S,
+        lifecycle_handlers: &ServicesLifeCycleHandle,
+        order: &ShutdownOrder,
+        grace: Duration,
+    ) -> TeardownReport {
+        let mut tasks: HashMap<ServiceId, ServiceTask> = services
+            .teardown()
+            .into_iter()
+            .map(|task| (task.id(), task))
+            .collect();
+        let services_ids: Vec<_> = lifecycle_handlers.services_ids().collect();
+        let mut exits = Vec::with_capacity(tasks.len());
+        for stage in order.stages(&services_ids) {
+            for &service_id in &stage {
+                if let Err(e) = lifecycle_handlers.kill(service_id, StopReason::Shutdown) {
+                    error!(e);
+                }
+            }
+            let stage_tasks = stage
+                .iter()
+                .filter_map(|service_id| tasks.remove(service_id))
+                .map(|task| async move { (task.id(), task.join(grace).await) });
+            exits.extend(futures::future::join_all(stage_tasks).await);
+        }
+        // services without a lifecycle handle, if any, can't be killed but are given the grace too
+        exits.extend(
+            futures::future::join_all(
+                tasks
+                    .into_values()
+                    .map(|task| async move { (task.id(), task.join(grace).await) }),
+            )
+            .await,
+        );
+        exits.sort_unstable_by_key(|(service_id, _)| *service_id);
+        for (service_id, exit) in &exits {
+            match exit {
+                ServiceExit::Finished => {}
+                ServiceExit::Aborted => info!("Service {service_id} was aborted on shutdown"),
+                ServiceExit::Failed(e) => error!("Service {service_id} failed: {e}"),
+                ServiceExit::Panicked => error!("Service {service_id} panicked"),
+            }
+        }
+        TeardownReport { exits }
     }
 
-    async fn handle_relay(services: &mut S, command: RelayCommand) {
+    async fn handle_relay(services: &mut S, policy: &dyn RelayPolicy, command: RelayCommand) {
         let RelayCommand {
             service_id,
+            requester,
             reply_channel,
         } = command;
+        let relay = match requester {
+            Some(from) if !policy.allow(from, service_id) => {
+                info!("Relay from {from} to {service_id} denied by policy");
+                Err(RelayError::Unauthorized {
+                    from,
+                    to: service_id,
+                })
+            }
+            _ => services.request_relay(service_id),
+        };
         // send requested rely channel result to requesting service
-        if let Err(Err(e)) = reply_channel
-            .reply(services.request_relay(service_id))
-            .await
-        {
+        if let Err(Err(e)) = reply_channel.reply(relay).await {
             info!(error=?e, "Error requesting relay for service {}", service_id)
         }
     }
 
-    async fn handle_settings_update(services: &mut S, command: SettingsCommand) {
-        let SettingsCommand(settings) = command;
-        if let Ok(settings) = settings.downcast::<S::Settings>() {
-            if let Err(e) = services.update_settings(*settings) {
-                // TODO: add proper logging
-                error!("{e}");
+    /// Build the [`BootReport`](boot::BootReport) in the background, once the services are running
+    fn spawn_boot_report(
+        services: &S,
+        lifecycle_handlers: &ServicesLifeCycleHandle,
+        stopped: &[ServiceId],
+        handle: &OverwatchHandle,
+        started_at: Instant,
+    ) {
+        let mut started: Vec<ServiceId> = lifecycle_handlers
+            .services_ids()
+            .filter(|service_id| !stopped.contains(service_id))
+            .collect();
+        started.sort_unstable();
+        let watchers = started
+            .iter()
+            .filter_map(|&service_id| {
+                services
+                    .request_status_watcher(service_id)
+                    .ok()
+                    .map(|watcher| (service_id, watcher))
+            })
+            .collect();
+        let skipped = S::SERVICES_IDS
+            .iter()
+            .copied()
+            .filter(|&service_id| {
+                !started
+                    .iter()
+                    .any(|&id| id == service_id || is_member_of(id, service_id))
+            })
+            .collect();
+        let handle = handle.clone();
+        let task = spawn_checked(&handle.runtime().clone(), BOOT_REPORT_TASK, async move {
+            let report =
+                boot::collect(started_at, watchers, skipped, handle.context_config()).await;
+            handle.events().report_boot(report);
+        });
+        if let Err(e) = task {
+            error!("Boot report couldn't be built: {e}");
+        }
+    }
+
+    /// Start polling the services liveness probes
+    fn spawn_health_monitor(handle: &OverwatchHandle) {
+        let interval = handle.context_config().health_check_interval;
+        if let Err(e) = spawn_checked(
+            &handle.runtime().clone(),
+            HEALTH_MONITOR_TASK,
+            health::monitor(handle.clone(), interval),
+        ) {
+            error!("Health monitor couldn't be started: {e}");
+        }
+    }
+
+    /// Start handing the collected metrics to the configured exporters, if any
+    fn spawn_metrics_export(handle: &OverwatchHandle) {
+        if handle.context_config().metrics_exporters.is_empty() {
+            return;
+        }
+        let interval = handle.context_config().metrics_export_interval;
+        if let Err(e) = spawn_checked(
+            &handle.runtime().clone(),
+            METRICS_EXPORT_TASK,
+            metrics::publish(handle.clone(), interval),
+        ) {
+            error!("Metrics export couldn't be started: {e}");
+        }
+    }
+
+    /// Start writing the persistent metrics back periodically, if they are enabled
+    #[cfg(feature = "persistent-metrics")]
+    fn spawn_persistent_metrics_flush(handle: &OverwatchHandle) {
+        let Some(metrics) = handle.context_config().persistent_metrics.clone() else {
+            return;
+        };
+        if let Err(e) = spawn_checked(
+            &handle.runtime().clone(),
+            PERSISTENT_METRICS_TASK,
+            persistent_metrics::flush_periodically(handle.clone(), metrics),
+        ) {
+            error!("Persistent metrics flush couldn't be started: {e}");
+        }
+    }
+
+    /// Start sampling memory if a [`MemoryMonitor`](memory::MemoryMonitor) is configured
+    fn spawn_memory_monitor(handle: &OverwatchHandle) {
+        let Some(monitor) = handle.context_config().memory_monitor.clone() else {
+            return;
+        };
+        let low_priority = S::SERVICES_PRIORITIES
+            .iter()
+            .filter(|(_, priority)| *priority == ServicePriority::Low)
+            .map(|&(service_id, _)| service_id)
+            .collect();
+        if let Err(e) = spawn_checked(
+            &handle.runtime().clone(),
+            MEMORY_MONITOR_TASK,
+            monitor.run(handle.clone(), low_priority),
+        ) {
+            error!("Memory monitor couldn't be started: {e}");
+        }
+    }
+
+    /// Start checking the relays if a [`Watchdog`](watchdog::Watchdog) is configured
+    fn spawn_watchdog(handle: &OverwatchHandle) {
+        let Some(watchdog) = handle.context_config().watchdog else {
+            return;
+        };
+        if let Err(e) = spawn_checked(
+            &handle.runtime().clone(),
+            WATCHDOG_TASK,
+            watchdog.run(handle.clone()),
+        ) {
+            error!("Watchdog couldn't be started: {e}");
+        }
+    }
+
+    /// Update the settings of the services, keeping `given` up to date with them
+    async fn handle_settings_update(
+        services: &mut S,
+        given: &mut S::Settings,
+        command: SettingsCommand,
+    ) {
+        let SettingsCommand(settings, reply_channel) = command;
+        let result = match settings.downcast::<S::Settings>() {
+            Ok(settings) => S::validate_settings(&settings)
+                .and_then(|()| services.update_settings(S::Settings::clone(&settings)))
+                .map(|()| *given = *settings),
+            Err(_) => unreachable!("Statically should always be of the correct type"),
+        };
+        if let Err(e) = &result {
+            // TODO: add proper logging
+            error!("{e}");
+        }
+        if let Some(reply_channel) = reply_channel {
+            if reply_channel.reply(result).await.is_err() {
+                error!("Error reporting back settings update result");
+            }
+        }
+    }
+    /// Handle the commands of `batch` in order, as if each of them was received on its own
+    async fn handle_batch(
+        services: &mut S,
+        given: &mut S::Settings,
+        lifecycle_handlers: &mut ServicesLifeCycleHandle,
+        operations: &mut LifecycleQueues,
+        stopped_sender: &UnboundedSender<ReconfigureCommand>,
+        handle: &OverwatchHandle,
+        BatchCommand { commands, span }: BatchCommand,
+    ) {
+        span.instrument(async move {
+            for command in commands {
+                let operation = match command {
+                    BatchedCommand::Settings(settings) => {
+                        Self::handle_settings_update(services, given, settings).await;
+                        continue;
+                    }
+                    BatchedCommand::Scale(command) => {
+                        let span = command.span.clone();
+                        span.instrument(Self::handle_scale(
+                            services,
+                            lifecycle_handlers,
+                            handle,
+                            command,
+                        ))
+                        .await;
+                        continue;
+                    }
+                    BatchedCommand::Reconfigure(command) => {
+                        LifecycleOperation::Reconfigure(command)
+                    }
+                    BatchedCommand::Failover(command) => LifecycleOperation::Failover(command),
+                    BatchedCommand::Control(command) => LifecycleOperation::Control(command),
+                };
+                Self::submit_operation(
+                    services,
+                    lifecycle_handlers,
+                    operations,
+                    stopped_sender,
+                    handle.context_config(),
+                    operation,
+                )
+                .await;
+            }
+        })
+        .await;
+    }
+
+    async fn submit_operation(
+        services: &mut S,
+        lifecycle_handlers: &ServicesLifeCycleHandle,
+        operations: &mut LifecycleQueues,
+        stopped_sender: &UnboundedSender<ReconfigureCommand>,
+        context_config: &ContextConfig,
+        operation: LifecycleOperation,
+    ) {
+        match operations.submit(operation) {
+            Ok(next) => {
+                Self::run_operations(
+                    services,
+                    lifecycle_handlers,
+                    operations,
+                    stopped_sender,
+                    context_config,
+                    next,
+                )
+                .await
+            }
+            Err(operation) => {
+                let service_id = operation.service_id();
+                operation
+                    .reject(Error::LifecycleQueueFull { service_id })
+                    .await
             }
-        } else {
-            unreachable!("Statically should always be of the correct type");
         }
     }
+
+    /// Run `next` and the operations queued after it for the same service, until one of them
+    /// has to wait for its service to shut down
+    async fn run_operations(
+        services: &mut S,
+        lifecycle_handlers: &ServicesLifeCycleHandle,
+        operations: &mut LifecycleQueues,
+        stopped_sender: &UnboundedSender<ReconfigureCommand>,
+        context_config: &ContextConfig,
+        mut next: Option<LifecycleOperation>,
+    ) {
+        while let Some(operation) = next {
+            let service_id = operation.service_id();
+            match operation {
+                LifecycleOperation::Failover(command) => {
+                    let span = command.span.clone();
+                    span.instrument(Self::handle_failover(services, command))
+                        .await;
+                }
+                LifecycleOperation::Control(command) => {
+                    let span = command.span.clone();
+                    span.instrument(Self::handle_control(services, command))
+                        .await;
+                }
+                LifecycleOperation::Reconfigure(command) => {
+                    if let RestartMode::Graceful { timeout } = command.mode {
+                        Self::shutdown_then_reconfigure(
+                            lifecycle_handlers,
+                            stopped_sender,
+                            context_config,
+                            timeout,
+                            command,
+                        );
+                        return;
+                    }
+                    let span = command.span.clone();
+                    span.instrument(Self::finish_reconfigure(services, command))
+                        .await;
+                }
+            }
+            next = operations.finish(service_id);
+        }
+    }
+
+    /// Ask the service to shut down and hand the command back to the runner once it finished,
+    /// or `timeout` elapsed, without blocking other commands meanwhile
+    fn shutdown_then_reconfigure(
+        lifecycle_handlers: &ServicesLifeCycleHandle,
+        stopped_sender: &UnboundedSender<ReconfigureCommand>,
+        context_config: &ContextConfig,
+        timeout: Duration,
+        command: ReconfigureCommand,
+    ) {
+        let service_id = command.service_id;
+        let (sender, mut receiver) = finished_signal::channel();
+        let sender = sender.with_metrics(Arc::clone(&context_config.metrics), service_id);
+        let clock = Arc::clone(&context_config.clock);
+        let shutdown = lifecycle_handlers.shutdown(service_id, sender, StopReason::SettingsChange);
+        let stopped_sender = stopped_sender.clone();
+        let span = command.span.clone();
+        let name = format!("{service_id}:reconfigure");
+        spawn_named(
+            &Handle::current(),
+            &name,
+            span.instrument(async move {
+                match shutdown {
+                    Ok(()) => match receiver.recv_timeout(clock.as_ref(), timeout).await {
+                        Ok(()) => {}
+                        Err(RecvError::Elapsed(_)) => {
+                            info!("Service {service_id} didn't finish in {timeout:?}, aborting it");
+                        }
+                        Err(e) => info!("Service {service_id} didn't finish cleanly: {e}"),
+                    },
+                    Err(e) => {
+                        info!(error=?e, "Service {service_id} couldn't be shutdown gracefully")
+                    }
+                }
+                // the runner is gone if this fails, nothing left to reconfigure
+                let _ = stopped_sender.send(command);
+            }),
+        );
+    }
+
+    async fn finish_reconfigure(
+        services: &mut S,
+        ReconfigureCommand {
+            service_id,
+            settings,
+            reply_channel,
+            ..
+        }: ReconfigureCommand,
+    ) -> ServiceId {
+        let result = services
+            .stop(service_id, StopReason::SettingsChange)
+            .and_then(|_| services.update_service_settings(service_id, settings))
+            .and_then(|_| services.start(service_id));
+        if let Err(e) = &result {
+            error!("Error reconfiguring service {service_id}: {e}");
+        }
+        if reply_channel.reply(result).await.is_err() {
+            error!("Error reporting back reconfigure result for service: {service_id}")
+        }
+        service_id
+    }
+
+    async fn handle_failover(
+        services: &mut S,
+        FailoverCommand {
+            service_id,
+            reply_channel,
+            ..
+        }: FailoverCommand,
+    ) {
+        let result = services.failover(service_id);
+        if let Err(e) = &result {
+            error!("Error failing over service {service_id}: {e}");
+        }
+        if reply_channel.reply(result).await.is_err() {
+            error!("Error reporting back failover result for service: {service_id}")
+        }
+    }
+
+    /// Scale the pool, tracking the lifecycle of the new members and reporting each change to
+    /// [`OverwatchHandle::scaling_events`] subscribers
+    async fn handle_scale(
+        services: &mut S,
+        lifecycle_handlers: &mut ServicesLifeCycleHandle,
+        handle: &OverwatchHandle,
+        ScaleCommand {
+            service_id,
+            members,
+            reply_channel,
+            ..
+        }: ScaleCommand,
+    ) {
+        let result = services.scale(service_id, members).map(|scaled| {
+            let mut current = members + scaled.stopped.len() - scaled.started.len();
+            for member in scaled.stopped {
+                lifecycle_handlers.remove(member);
+                current -= 1;
+                handle.events().report_scaling(ScalingEvent {
+                    pool: service_id,
+                    member,
+                    change: ScalingChange::Stopped,
+                    members: current,
+                    target: members,
+                });
+            }
+            for (member, lifecycle_handle) in scaled.started {
+                lifecycle_handlers.insert(member, lifecycle_handle);
+                current += 1;
+                handle.events().report_scaling(ScalingEvent {
+                    pool: service_id,
+                    member,
+                    change: ScalingChange::Started,
+                    members: current,
+                    target: members,
+                });
+            }
+        });
+        if let Err(e) = &result {
+            error!("Error scaling pool {service_id} to {members} members: {e}");
+        }
+        if reply_channel.reply(result).await.is_err() {
+            error!("Error reporting back scaling result for pool: {service_id}")
+        }
+    }
+
+    fn handle_custom(handle: &OverwatchHandle, mut command: CustomCommand) {
+        for handler in &handle.context_config().command_handlers {
+            match handler.handle(command, handle) {
+                Ok(()) => return,
+                Err(unhandled) => command = unhandled,
+            }
+        }
+        error!("No handler took the custom command");
+    }
+
+    async fn handle_control(
+        services: &mut S,
+        ServiceControlCommand {
+            service_id,
+            action,
+            reply_channel,
+            ..
+        }: ServiceControlCommand,
+    ) {
+        let result = match action {
+            ServiceAction::Start => services.start(service_id),
+            ServiceAction::Stop => services.stop(service_id, StopReason::Requested),
+            ServiceAction::Restart => Self::restart_supervised(services, service_id),
+        };
+        if let Err(e) = &result {
+            error!("Error applying {action:?} to service {service_id}: {e}");
+        }
+        if reply_channel.reply(result).await.is_err() {
+            error!("Error reporting back {action:?} result for service: {service_id}")
+        }
+    }
+
+    /// Stop a service waiting to be restarted by its supervisor and start it again, unless it
+    /// was stopped or started meanwhile
+    fn restart_supervised(services: &mut S, service_id: ServiceId) -> Result<(), Error> {
+        let restarting = services
+            .request_status_watcher(service_id)
+            .is_ok_and(|watcher| watcher.current() == ServiceStatus::Restarting);
+        if !restarting {
+            return Ok(());
+        }
+        services.stop(service_id, StopReason::Supervision)?;
+        services.start(service_id)
+    }
+
     async fn handle_status(
         services: &mut S,
         StatusCommand {
@@ -240,15 +1181,139 @@ where
         }: StatusCommand,
     ) {
         let watcher_result = services.request_status_watcher(service_id);
-        match watcher_result {
-            Ok(watcher) => {
-                if reply_channel.reply(watcher).await.is_err() {
-                    error!("Error reporting back status watcher for service: {service_id}")
-                }
+        if let Err(e) = &watcher_result {
+            error!("{e}");
+        }
+        if reply_channel.reply(watcher_result).await.is_err() {
+            error!("Error reporting back status watcher for service: {service_id}")
+        }
+    }
+
+    async fn handle_topology(
+        services: &mut S,
+        lifecycle_handlers: &ServicesLifeCycleHandle,
+        handle: &OverwatchHandle,
+        TopologyCommand { reply_channel }: TopologyCommand,
+    ) {
+        let declared: Vec<_> = S::SERVICES_IDS
+            .iter()
+            .map(|&service_id| {
+                let priority = S::SERVICES_PRIORITIES
+                    .iter()
+                    .find(|(id, _)| *id == service_id)
+                    .map(|&(_, priority)| priority)
+                    .unwrap_or_default();
+                let relay_buffer_size = S::SERVICES_RELAY_BUFFER_SIZES
+                    .iter()
+                    .find(|(id, _)| *id == service_id)
+                    .map(|&(_, size)| size)
+                    .unwrap_or_default();
+                (service_id, priority, relay_buffer_size)
+            })
+            .collect();
+        let services_ids: Vec<_> = lifecycle_handlers.services_ids().collect();
+        let topology = Topology::new(
+            &declared,
+            S::SERVICES_POOLS,
+            &services_ids,
+            &handle.context_config().shutdown_order,
+            |service_id| {
+                services
+                    .request_status_watcher(service_id)
+                    .ok()
+                    .map(|watcher| watcher.current())
+            },
+        );
+        if reply_channel.reply(topology).await.is_err() {
+            error!("Error reporting back the services topology");
+        }
+    }
+
+    /// Take a checkpoint of the services, replying once their states are snapshotted from a task
+    /// of its own so slow state operators don't hold back other commands
+    #[cfg(feature = "checkpoint")]
+    fn handle_checkpoint(
+        services: &S,
+        given: &S::Settings,
+        handle: &OverwatchHandle,
+        CheckpointCommand {
+            serialize_settings,
+            reply_channel,
+        }: CheckpointCommand,
+    ) {
+        let checkpoint = serialize_settings(&services.current_settings(given)).map(|settings| {
+            let stopped = S::SERVICES_IDS
+                .iter()
+                .filter(|&&service_id| Self::is_stopped(services, service_id))
+                .map(|service_id| service_id.to_string())
+                .collect();
+            Checkpoint {
+                version: CHECKPOINT_VERSION,
+                settings,
+                stopped,
             }
-            Err(e) => {
-                error!("{e}");
+        });
+        let snapshots = if checkpoint.is_ok() {
+            services.request_snapshots()
+        } else {
+            Vec::new()
+        };
+        let task = spawn_checked(&handle.runtime().clone(), CHECKPOINT_TASK, async move {
+            // the checkpoint is only complete once the states made it through the operators
+            futures::future::join_all(snapshots.into_iter().map(SnapshotRequest::done)).await;
+            if let Err(e) = &checkpoint {
+                error!("Checkpoint couldn't be taken: {e}");
+            }
+            if reply_channel.reply(checkpoint).await.is_err() {
+                error!("Error reporting back the checkpoint");
+            }
+        });
+        if let Err(e) = task {
+            error!("Checkpoint couldn't be taken: {e}");
+        }
+    }
+
+    /// Whether a service is to be kept stopped when the checkpoint is restored: it was stopped, or
+    /// it was never started and runs no instance
+    #[cfg(feature = "checkpoint")]
+    fn is_stopped(services: &S, service_id: ServiceId) -> bool {
+        match services
+            .request_status_watcher(service_id)
+            .map(|watcher| watcher.current())
+        {
+            Ok(ServiceStatus::Stopped) => true,
+            // running services that don't report their status are left uninitialized too
+            Ok(ServiceStatus::Uninitialized) => {
+                matches!(services.is_running(service_id), Ok(false))
             }
+            _ => false,
+        }
+    }
+
+    async fn handle_broadcast(
+        services: &mut S,
+        policy: &dyn RelayPolicy,
+        BroadcastCommand {
+            service_id,
+            requester,
+            reply_channel,
+        }: BroadcastCommand,
+    ) {
+        let broadcast_result = match requester {
+            Some(from) if !policy.allow(from, service_id) => {
+                info!("Broadcast subscription from {from} to {service_id} denied by policy");
+                Err(RelayError::Unauthorized {
+                    from,
+                    to: service_id,
+                })
+            }
+            _ => services.request_broadcast(service_id),
+        };
+        if let Err(e) = &broadcast_result {
+            error!("{e}");
+        }
+        if reply_channel.reply(broadcast_result).await.is_err() {
+            error!("Error reporting back broadcast relay for service: {service_id}")
         }
     }
 }
@@ -256,9 +1321,10 @@ where
 /// Main Overwatch entity
 /// It manages the overwatch runtime and handle
 pub struct Overwatch {
-    runtime: Runtime,
+    runtime: ServiceRuntime,
     handle: OverwatchHandle,
-    finish_runner_signal: oneshot::Receiver<FinishOverwatchSignal>,
+    finish_runner_signal: finished_signal::Receiver,
+    runner_task: JoinHandle<()>,
 }
 
 impl Overwatch {
@@ -268,9 +1334,36 @@ impl Overwatch {
         &self.handle
     }
 
+    /// Token cancelled once Overwatch starts shutting down
+    /// See [`OverwatchHandle::shutdown_token`].
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.handle.shutdown_token()
+    }
+
     /// Get the underlaying tokio runtime handle
     pub fn runtime(&self) -> &Handle {
-        self.runtime.handle()
+        self.handle.runtime()
+    }
+
+    /// Run `future` on the Overwatch runtime, blocking until it completes
+    /// Unlike blocking on [`Overwatch::runtime`], it drives runtimes owned by Overwatch, current
+    /// thread ones included. It can't be called from within an async context.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        match &self.runtime {
+            ServiceRuntime::Custom(runtime) => runtime.block_on(future),
+            ServiceRuntime::FromParent(handle) => handle.block_on(future),
+        }
+    }
+
+    /// Save the settings the services `S` run with and the ones that are stopped to `path`,
+    /// as [`OverwatchHandle::checkpoint`] does, blocking until it is written
+    /// It can't be called from within an async context.
+    #[cfg(feature = "checkpoint")]
+    pub fn checkpoint<S: Services>(&self, path: impl AsRef<Path>) -> Result<(), CheckpointError>
+    where
+        S::Settings: Serialize,
+    {
+        self.block_on(self.handle.checkpoint::<S>(path))
     }
 
     /// Spawn a new task within the Overwatch runtime
@@ -279,38 +1372,104 @@ impl Overwatch {
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        self.runtime.spawn(future)
+        self.runtime().spawn(future)
     }
 
     /// Block until Overwatch finish its execution
     pub fn wait_finished(self) {
         let Self {
             runtime,
-            finish_runner_signal,
+            mut finish_runner_signal,
             ..
         } = self;
-        runtime.block_on(async move {
-            let signal_result = finish_runner_signal.await;
-            signal_result.expect("A finished signal arrived");
-        });
+        let finished = async move {
+            if finish_runner_signal.recv().await.is_err() {
+                error!("Overwatch runner finished without sending its finish signal");
+            }
+        };
+        match runtime {
+            ServiceRuntime::Custom(runtime) => runtime.block_on(finished),
+            ServiceRuntime::FromParent(handle) => handle.block_on(finished),
+        }
+    }
+
+    /// Get a future that resolves once Overwatch finish its execution
+    /// Unlike [`Overwatch::wait_finished`] it doesn't block, so hosts embedding Overwatch
+    /// (see [`OverwatchRunner::run_on`]) can await it on their own terms.
+    /// If Overwatch owns its runtime, it is shut down in the background once finished.
+    pub fn detach(self) -> impl Future<Output = ()> + Send + 'static {
+        let Self {
+            runtime,
+            mut finish_runner_signal,
+            ..
+        } = self;
+        async move {
+            if finish_runner_signal.recv().await.is_err() {
+                error!("Overwatch runner finished without sending its finish signal");
+            }
+            if let Some(runtime) = runtime.runtime() {
+                runtime.shutdown_background();
+            }
+        }
+    }
+
+    /// Forcefully tear down Overwatch
+    /// The service instances and their tasks are aborted, and so is the runner task, without
+    /// waiting for any of them. Services are not reported stopped and their last state is not
+    /// flushed.
+    pub fn abort(self) {
+        let Self {
+            runtime,
+            handle,
+            runner_task,
+            ..
+        } = self;
+        handle.signal_abort();
+        runner_task.abort();
+        if let Some(runtime) = runtime.runtime() {
+            runtime.shutdown_background();
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::overwatch::handle::OverwatchHandle;
-    use crate::overwatch::{Error, OverwatchRunner, Services, ServicesLifeCycleHandle};
+    use crate::overwatch::{
+        AnySettings, Error, OverwatchRunner, Services, ServicesLifeCycleHandle,
+    };
+    use crate::services::broadcast::BroadcastResult;
+    use crate::services::handle::{ServiceHandle, ServiceStateHandle, ServiceTask};
+    use crate::services::life_cycle::StopReason;
+    use crate::services::pool::Scaled;
+    use crate::services::relay::NoMessage;
     use crate::services::relay::{RelayError, RelayResult};
+    use crate::services::state::{NoOperator, NoState, SnapshotRequest};
     use crate::services::status::{ServiceStatusError, ServiceStatusResult};
-    use crate::services::ServiceId;
+    use crate::services::{ServiceCore, ServiceData, ServiceId};
+    use crate::DynError;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::time::Duration;
     use tokio::time::sleep;
 
+    struct EmptyService;
+
+    impl ServiceData for EmptyService {
+        const SERVICE_ID: ServiceId = "empty";
+        type Settings = ();
+        type State = NoState<Self::Settings>;
+        type StateOperator = NoOperator<Self::State>;
+        type Message = NoMessage;
+    }
+
     struct EmptyServices;
 
     impl Services for EmptyServices {
         type Settings = ();
 
+        const SERVICES_IDS: &'static [ServiceId] = &[];
+
         fn new(
             _settings: Self::Settings,
             _overwatch_handle: OverwatchHandle,
@@ -322,11 +1481,14 @@ mod test {
             Err(Error::Unavailable { service_id })
         }
 
-        fn start_all(&mut self) -> Result<ServicesLifeCycleHandle, Error> {
+        fn start_all_except(
+            &mut self,
+            _stopped: &[ServiceId],
+        ) -> Result<ServicesLifeCycleHandle, Error> {
             Ok(ServicesLifeCycleHandle::empty())
         }
 
-        fn stop(&mut self, service_id: ServiceId) -> Result<(), Error> {
+        fn stop(&mut self, service_id: ServiceId, _reason: StopReason) -> Result<(), Error> {
             Err(Error::Unavailable { service_id })
         }
 
@@ -338,9 +1500,43 @@ mod test {
             Err(ServiceStatusError::Unavailable { service_id })
         }
 
+        fn is_running(&self, service_id: ServiceId) -> Result<bool, Error> {
+            Err(Error::Unavailable { service_id })
+        }
+
+        fn request_broadcast(&self, service_id: ServiceId) -> BroadcastResult {
+            Err(RelayError::Unavailable { service_id })
+        }
+
         fn update_settings(&mut self, _settings: Self::Settings) -> Result<(), Error> {
             Ok(())
         }
+
+        fn update_service_settings(
+            &mut self,
+            service_id: ServiceId,
+            _settings: AnySettings,
+        ) -> Result<(), Error> {
+            Err(Error::Unavailable { service_id })
+        }
+
+        fn failover(&mut self, service_id: ServiceId) -> Result<(), Error> {
+            Err(Error::Unavailable { service_id })
+        }
+
+        fn scale(&mut self, service_id: ServiceId, _members: usize) -> Result<Scaled, Error> {
+            Err(Error::Unavailable { service_id })
+        }
+
+        fn teardown(&mut self) -> Vec<ServiceTask> {
+            Vec::new()
+        }
+
+        fn current_settings(&self, _settings: &Self::Settings) -> Self::Settings {}
+
+        fn request_snapshots(&self) -> Vec<SnapshotRequest> {
+            Vec::new()
+        }
     }
 
     #[test]
@@ -368,4 +1564,169 @@ mod test {
 
         overwatch.wait_finished();
     }
+
+    #[test]
+    fn shutdown_token_is_cancelled_on_shutdown() {
+        let runtime = crate::utils::runtime::default_multithread_runtime();
+        let overwatch =
+            OverwatchRunner::<EmptyServices>::run_on((), runtime.handle().clone()).unwrap();
+        let handle = overwatch.handle().clone();
+        let token = overwatch.shutdown_token();
+        // cancelling a token given away doesn't shut Overwatch down
+        overwatch.shutdown_token().cancel();
+
+        runtime.block_on(async move {
+            let finished = overwatch.detach();
+            sleep(Duration::from_millis(50)).await;
+            assert!(!token.is_cancelled());
+            handle.shutdown().await;
+            tokio::time::timeout(Duration::from_secs(1), token.cancelled())
+                .await
+                .expect("Shutdown token to be cancelled");
+            finished.await;
+        });
+    }
+
+    #[test]
+    fn status_watcher_reports_unavailable_service() {
+        let overwatch = OverwatchRunner::<EmptyServices>::run((), None).unwrap();
+        let handle = overwatch.handle().clone();
+
+        overwatch.spawn(async move {
+            let result = handle.status_watcher::<EmptyService>().await;
+            assert!(matches!(
+                result,
+                Err(ServiceStatusError::Unavailable {
+                    service_id: "empty"
+                })
+            ));
+            handle.shutdown().await;
+        });
+
+        overwatch.wait_finished();
+    }
+
+    #[test]
+    fn requests_time_out_when_runner_is_unresponsive() {
+        let runtime = crate::utils::runtime::default_multithread_runtime();
+        // keep the receiver alive but never poll it, as a wedged runner would
+        let (sender, _receiver) = tokio::sync::mpsc::channel(16);
+        let handle = OverwatchHandle::new(runtime.handle().clone(), sender);
+
+        runtime.block_on(async move {
+            let status = handle
+                .status_watcher_with_timeout::<EmptyService>(Duration::from_millis(50))
+                .await;
+            assert!(matches!(status, Err(ServiceStatusError::Timeout { .. })));
+
+            let relay = handle
+                .relay::<EmptyService>()
+                .connect_with_timeout(Duration::from_millis(50))
+                .await;
+            assert!(matches!(relay, Err(RelayError::Timeout { .. })));
+        });
+    }
+
+    #[test]
+    fn run_overwatch_on_host_runtime_then_detach() {
+        let runtime = crate::utils::runtime::default_multithread_runtime();
+        let overwatch =
+            OverwatchRunner::<EmptyServices>::run_on((), runtime.handle().clone()).unwrap();
+        let handle = overwatch.handle().clone();
+
+        runtime.block_on(async move {
+            let finished = overwatch.detach();
+            handle.shutdown().await;
+            tokio::time::timeout(Duration::from_secs(1), finished)
+                .await
+                .expect("Overwatch to finish after shutdown");
+        });
+    }
+
+    static SPINNING: AtomicBool = AtomicBool::new(false);
+
+    /// Runs until its task is dropped
+    struct SpinningService;
+
+    impl ServiceData for SpinningService {
+        const SERVICE_ID: ServiceId = "spinning";
+        type Settings = ();
+        type State = NoState<Self::Settings>;
+        type StateOperator = NoOperator<Self::State>;
+        type Message = NoMessage;
+    }
+
+    struct Spinning;
+
+    impl Drop for Spinning {
+        fn drop(&mut self) {
+            SPINNING.store(false, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl ServiceCore for SpinningService {
+        fn init(
+            _service_state: ServiceStateHandle<Self>,
+            _initial_state: Self::State,
+        ) -> Result<Self, DynError> {
+            Ok(Self)
+        }
+
+        async fn run(self) -> Result<(), DynError> {
+            SPINNING.store(true, Ordering::SeqCst);
+            let _spinning = Spinning;
+            std::future::pending().await
+        }
+    }
+
+    #[test]
+    fn abort_embedded_overwatch() {
+        let runtime = crate::utils::runtime::default_multithread_runtime();
+        let overwatch =
+            OverwatchRunner::<EmptyServices>::run_on((), runtime.handle().clone()).unwrap();
+        let handle = overwatch.handle().clone();
+        // started on the host runtime, which outlives Overwatch
+        let mut spinning = ServiceHandle::<SpinningService>::new((), handle.clone()).unwrap();
+        spinning.start().unwrap();
+
+        runtime.block_on(async move {
+            sleep(Duration::from_millis(50)).await;
+            assert!(SPINNING.load(Ordering::SeqCst));
+            overwatch.abort();
+            sleep(Duration::from_millis(50)).await;
+            assert!(!SPINNING.load(Ordering::SeqCst));
+            assert!(handle.status_watcher::<EmptyService>().await.is_err());
+        });
+    }
+
+    #[test]
+    fn try_update_settings_reports_full_channel() {
+        let runtime = crate::utils::runtime::default_multithread_runtime();
+        let (sender, _receiver) = tokio::sync::mpsc::channel(1);
+        let handle = OverwatchHandle::new(runtime.handle().clone(), sender);
+
+        assert!(handle.try_update_settings::<EmptyServices>(()).is_ok());
+        assert!(matches!(
+            handle.try_update_settings::<EmptyServices>(()),
+            Err(Error::Busy)
+        ));
+        let stats = handle.clone().settings_stats();
+        assert_eq!((stats.queued, stats.dropped), (1, 1));
+    }
+
+    #[test]
+    fn run_on_shut_down_runtime_fails() {
+        let runtime = crate::utils::runtime::default_multithread_runtime();
+        let runtime_handle = runtime.handle().clone();
+        runtime.shutdown_timeout(Duration::from_secs(1));
+
+        let error = OverwatchRunner::<EmptyServices>::run_on((), runtime_handle)
+            .err()
+            .expect("Overwatch not to run without a runtime");
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::RuntimeUnavailable)
+        ));
+    }
 }
@@ -0,0 +1,175 @@
+//! Collected metrics rendered in the Prometheus text format
+//! Serve [`PrometheusExporter::render`] from the application `/metrics` endpoint, it renders
+//! the metrics as they are when scraped.
+// std
+use std::fmt::Write;
+// internal
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::metrics::{MetricsSnapshot, ServiceMetrics};
+
+/// Media type of [`PrometheusExporter::render`], for the `Content-Type` header
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Renders the metrics of the application it is created for
+#[derive(Clone)]
+pub struct PrometheusExporter {
+    handle: OverwatchHandle,
+}
+
+impl PrometheusExporter {
+    pub fn new(handle: OverwatchHandle) -> Self {
+        Self { handle }
+    }
+
+    /// Current metrics, see [`OverwatchHandle::metrics`]
+    pub fn render(&self) -> String {
+        render(&self.handle.metrics())
+    }
+}
+
+type Sample = fn(&ServiceMetrics) -> Option<f64>;
+
+/// Name, type, help and value of each exported metric
+const FAMILIES: &[(&str, &str, &str, Sample)] = &[
+    (
+        "overwatch_relay_messages_sent_total",
+        "counter",
+        "Messages sent to the service relay",
+        |service| Some(service.relay.sent as f64),
+    ),
+    (
+        "overwatch_relay_messages_received_total",
+        "counter",
+        "Messages the service received from its relay",
+        |service| Some(service.relay.received as f64),
+    ),
+    (
+        "overwatch_relay_messages_dropped_total",
+        "counter",
+        "Messages that couldn't be sent because the service relay was closed",
+        |service| Some(service.relay.dropped as f64),
+    ),
+    (
+        "overwatch_relay_messages_overflowed_total",
+        "counter",
+        "Messages discarded or rejected because the service relay buffer was full",
+        |service| Some(service.relay.overflowed as f64),
+    ),
+    (
+        "overwatch_relay_messages_throttled_total",
+        "counter",
+        "Messages held back by the service rate limit",
+        |service| Some(service.relay.throttled as f64),
+    ),
+    (
+        "overwatch_relay_messages_rate_limited_total",
+        "counter",
+        "Messages dropped by the service rate limit",
+        |service| Some(service.relay.rate_limited as f64),
+    ),
+    (
+        "overwatch_relay_queue_depth",
+        "gauge",
+        "Messages waiting in the service relay buffer",
+        |service| Some(service.relay.queue_depth as f64),
+    ),
+    (
+        "overwatch_relay_send_latency_mean_seconds",
+        "gauge",
+        "Mean time taken by sends to the service relay",
+        |service| Some(service.relay.mean_send_latency.as_secs_f64()),
+    ),
+    (
+        "overwatch_relay_send_latency_max_seconds",
+        "gauge",
+        "Longest time taken by a send to the service relay",
+        |service| Some(service.relay.max_send_latency.as_secs_f64()),
+    ),
+    (
+        "overwatch_service_starts_total",
+        "counter",
+        "Times the service was started",
+        |service| Some(service.lifecycle.starts as f64),
+    ),
+    (
+        "overwatch_service_stops_total",
+        "counter",
+        "Times a service instance stopped",
+        |service| Some(service.lifecycle.stops as f64),
+    ),
+    (
+        "overwatch_service_uptime_seconds",
+        "gauge",
+        "Time the running service instance has been running for",
+        |service| service.lifecycle.uptime.map(|uptime| uptime.as_secs_f64()),
+    ),
+    (
+        "overwatch_service_tasks_spawned_total",
+        "counter",
+        "Tasks spawned by the service instances",
+        |service| Some(service.tasks.spawned as f64),
+    ),
+    (
+        "overwatch_service_tasks_running",
+        "gauge",
+        "Tasks spawned by the service not finished yet",
+        |service| Some(service.tasks.running as f64),
+    ),
+];
+
+fn render(snapshot: &MetricsSnapshot) -> String {
+    let mut rendered = String::new();
+    for (name, kind, help, sample) in FAMILIES {
+        // writing to a string can't fail
+        let _ = writeln!(rendered, "# HELP {name} {help}");
+        let _ = writeln!(rendered, "# TYPE {name} {kind}");
+        for service in &snapshot.services {
+            if let Some(value) = sample(service) {
+                let service_id = escape(service.service_id);
+                let _ = writeln!(rendered, "{name}{{service=\"{service_id}\"}} {value}");
+            }
+        }
+    }
+    rendered
+}
+
+/// Escape a label value as the text format requires
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::metrics::{
+        LifecycleMetrics, MetricsSnapshot, RelayMetrics, ServiceMetrics, TaskMetrics,
+    };
+    use crate::overwatch::prometheus::render;
+    use std::time::Duration;
+
+    #[test]
+    fn snapshots_are_rendered_per_service() {
+        let rendered = render(&MetricsSnapshot {
+            services: vec![ServiceMetrics {
+                service_id: "sink",
+                relay: RelayMetrics {
+                    sent: 3,
+                    ..RelayMetrics::default()
+                },
+                lifecycle: LifecycleMetrics {
+                    starts: 1,
+                    stops: 0,
+                    uptime: Some(Duration::from_millis(1500)),
+                },
+                tasks: TaskMetrics::default(),
+            }],
+        });
+        assert!(rendered.contains(
+            "# TYPE overwatch_relay_messages_sent_total counter\n\
+             overwatch_relay_messages_sent_total{service=\"sink\"} 3\n"
+        ));
+        assert!(rendered.contains("overwatch_service_uptime_seconds{service=\"sink\"} 1.5\n"));
+    }
+}
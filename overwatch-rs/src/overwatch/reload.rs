@@ -0,0 +1,30 @@
+// std
+use std::sync::Arc;
+// crates
+// internal
+use crate::overwatch::{Error, Services};
+use crate::DynError;
+
+/// Source of the whole application settings, typically a configuration file
+/// Validation belongs to [`SettingsLoader::load`] as well, invalid settings are never applied.
+pub trait SettingsLoader<S: Services>: Send + Sync + 'static {
+    fn load(&self) -> Result<S::Settings, DynError>;
+}
+
+impl<S, F> SettingsLoader<S> for F
+where
+    S: Services,
+    F: Fn() -> Result<S::Settings, DynError> + Send + Sync + 'static,
+{
+    fn load(&self) -> Result<S::Settings, DynError> {
+        self()
+    }
+}
+
+/// Outcome of a settings reload, see
+/// [`OverwatchHandle::reload_settings`](crate::overwatch::handle::OverwatchHandle::reload_settings)
+#[derive(Clone, Debug)]
+pub enum ReloadEvent {
+    Applied,
+    Failed(Arc<Error>),
+}
@@ -0,0 +1,234 @@
+// std
+use std::time::Duration;
+// crates
+use futures::future::try_join_all;
+use thiserror::Error;
+use tracing::info;
+// internal
+use crate::overwatch::commands::ServiceAction;
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::Error as OverwatchError;
+use crate::services::status::{ServiceStatus, ServiceStatusError};
+use crate::services::{ServiceData, ServiceId};
+
+const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Single step of a [`Sequence`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Start a service, a no-op if it is already running
+    Start(ServiceId),
+    /// Wait until a service is [`ServiceStatus::Running`]
+    WaitReady(ServiceId),
+    /// Start several services at once, without waiting for any of them
+    StartGroup(Vec<ServiceId>),
+    /// Stop a running service
+    Stop(ServiceId),
+}
+
+/// How many times a failed step is run again, and how long to wait before each attempt
+/// A [`Step::WaitReady`] step restarts its service before waiting for it again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepRetry {
+    pub retries: usize,
+    pub delay: Duration,
+}
+
+#[derive(Clone, Debug)]
+struct PlannedStep {
+    step: Step,
+    retry: Option<StepRetry>,
+}
+
+#[derive(Error, Debug)]
+pub enum SequenceError {
+    #[error("Step {step} of the sequence failed: {source}")]
+    Lifecycle {
+        step: usize,
+        #[source]
+        source: OverwatchError,
+    },
+
+    #[error("Status of service {service_id} couldn't be watched at step {step}: {source}")]
+    Status {
+        step: usize,
+        service_id: ServiceId,
+        #[source]
+        source: ServiceStatusError,
+    },
+
+    #[error(
+        "Service {service_id} was not ready within {timeout:?} at step {step}, it is {status:?}"
+    )]
+    NotReady {
+        step: usize,
+        service_id: ServiceId,
+        timeout: Duration,
+        status: ServiceStatus,
+    },
+}
+
+/// Ordered list of services starts, stops and readiness waits, run with
+/// [`OverwatchHandle::run_sequence`]
+///
+/// ```ignore
+/// let sequence = Sequence::new()
+///     .start::<A>()
+///     .wait_ready::<A>()
+///     .retry(3, Duration::from_secs(1))
+///     .start_group([B::SERVICE_ID, C::SERVICE_ID])
+///     .stop::<D>();
+/// handle.run_sequence(&sequence).await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sequence {
+    steps: Vec<PlannedStep>,
+    ready_timeout: Duration,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            ready_timeout: DEFAULT_READY_TIMEOUT,
+        }
+    }
+}
+
+impl Sequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start<S: ServiceData>(self) -> Self {
+        self.step(Step::Start(S::SERVICE_ID))
+    }
+
+    pub fn wait_ready<S: ServiceData>(self) -> Self {
+        self.step(Step::WaitReady(S::SERVICE_ID))
+    }
+
+    pub fn start_group(self, services_ids: impl IntoIterator<Item = ServiceId>) -> Self {
+        self.step(Step::StartGroup(services_ids.into_iter().collect()))
+    }
+
+    pub fn stop<S: ServiceData>(self) -> Self {
+        self.step(Step::Stop(S::SERVICE_ID))
+    }
+
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(PlannedStep { step, retry: None });
+        self
+    }
+
+    /// Run the step added last up to `retries` more times if it fails, waiting `delay`
+    /// before each attempt
+    /// It has no effect on an empty sequence.
+    pub fn retry(mut self, retries: usize, delay: Duration) -> Self {
+        if let Some(last) = self.steps.last_mut() {
+            last.retry = Some(StepRetry { retries, delay });
+        }
+        self
+    }
+
+    /// How long [`Step::WaitReady`] steps wait for their service, 30 seconds by default
+    pub fn with_ready_timeout(mut self, ready_timeout: Duration) -> Self {
+        self.ready_timeout = ready_timeout;
+        self
+    }
+
+    pub fn steps(&self) -> impl ExactSizeIterator<Item = &Step> {
+        self.steps.iter().map(|planned| &planned.step)
+    }
+
+    pub(crate) async fn run(&self, handle: &OverwatchHandle) -> Result<(), SequenceError> {
+        for (step, planned) in self.steps.iter().enumerate() {
+            let mut attempt = 0;
+            while let Err(e) = self.run_step(handle, step, &planned.step).await {
+                let Some(StepRetry { retries, delay }) = planned.retry else {
+                    return Err(e);
+                };
+                if attempt == retries {
+                    return Err(e);
+                }
+                attempt += 1;
+                info!("Step {step} of the sequence failed, retrying it ({attempt}/{retries}): {e}");
+                handle.context_config().clock.sleep(delay).await;
+                if let Step::WaitReady(service_id) = planned.step {
+                    Self::restart(handle, step, service_id).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn restart(
+        handle: &OverwatchHandle,
+        step: usize,
+        service_id: ServiceId,
+    ) -> Result<(), SequenceError> {
+        handle
+            .control_service(service_id, ServiceAction::Stop)
+            .await
+            .map_err(|source| SequenceError::Lifecycle { step, source })?;
+        handle
+            .control_service(service_id, ServiceAction::Start)
+            .await
+            .map_err(|source| SequenceError::Lifecycle { step, source })
+    }
+
+    async fn run_step(
+        &self,
+        handle: &OverwatchHandle,
+        step: usize,
+        action: &Step,
+    ) -> Result<(), SequenceError> {
+        match action {
+            Step::Start(service_id) => handle
+                .control_service(service_id, ServiceAction::Start)
+                .await
+                .map_err(|source| SequenceError::Lifecycle { step, source })?,
+            Step::WaitReady(service_id) => self.wait_ready_for(handle, step, service_id).await?,
+            Step::StartGroup(services_ids) => {
+                try_join_all(
+                    services_ids
+                        .iter()
+                        .map(|service_id| handle.control_service(service_id, ServiceAction::Start)),
+                )
+                .await
+                .map_err(|source| SequenceError::Lifecycle { step, source })?;
+            }
+            Step::Stop(service_id) => handle
+                .control_service(service_id, ServiceAction::Stop)
+                .await
+                .map_err(|source| SequenceError::Lifecycle { step, source })?,
+        }
+        Ok(())
+    }
+
+    async fn wait_ready_for(
+        &self,
+        handle: &OverwatchHandle,
+        step: usize,
+        service_id: ServiceId,
+    ) -> Result<(), SequenceError> {
+        let mut watcher = handle
+            .status_watcher_for(service_id)
+            .await
+            .map_err(|source| SequenceError::Status {
+                step,
+                service_id,
+                source,
+            })?;
+        watcher
+            .wait_until(ServiceStatus::is_up, Some(self.ready_timeout))
+            .await
+            .map(|_| ())
+            .map_err(|status| SequenceError::NotReady {
+                step,
+                service_id,
+                timeout: self.ready_timeout,
+                status,
+            })
+    }
+}
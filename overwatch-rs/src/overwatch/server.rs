@@ -0,0 +1,41 @@
+//! Servers taking requests from other processes: the control plane, the admin API and remote
+//! relays
+
+// crates
+use tokio_util::sync::CancellationToken;
+// internal
+use crate::overwatch::handle::OverwatchHandle;
+
+/// Listening server, stopped along with Overwatch or by [`ServerHandle::abort`]
+#[derive(Debug)]
+pub struct ServerHandle<A> {
+    address: A,
+    cancellation_token: CancellationToken,
+}
+
+impl<A> ServerHandle<A> {
+    /// Handle of a server listening on `address`, whose tasks have to stop once
+    /// [`ServerHandle::cancellation_token`] is cancelled
+    pub(crate) fn new(address: A, handle: &OverwatchHandle) -> Self {
+        Self {
+            address,
+            cancellation_token: handle.shutdown_token(),
+        }
+    }
+
+    /// Cancelled when the server has to stop, both listening and serving the connections it
+    /// accepted
+    pub(crate) fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    /// Address listened on, with the actual port when asked to listen on port 0
+    pub fn address(&self) -> &A {
+        &self.address
+    }
+
+    /// Stop listening and close the connections in progress
+    pub fn abort(&self) {
+        self.cancellation_token.cancel();
+    }
+}
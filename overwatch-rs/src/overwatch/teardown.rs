@@ -0,0 +1,151 @@
+// std
+use std::collections::HashMap;
+// internal
+use crate::services::pool::is_member_of;
+use crate::services::ServiceId;
+
+/// In which order services are stopped when Overwatch is shut down
+/// Services stopped together are given the whole
+/// [`shutdown_grace`](crate::services::context::ContextConfig::shutdown_grace) to end before the
+/// next ones are stopped. Pool members are stopped along with their pool.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ShutdownOrder {
+    /// Every service is stopped at once
+    #[default]
+    Parallel,
+    /// Services are stopped one after another in this order, the ones not listed are stopped
+    /// afterwards, all at once
+    Declared(Vec<ServiceId>),
+    /// Services are stopped once every service depending on them has ended, so consumers stop
+    /// before their providers
+    /// Maps each service to the services it depends on. Services depending on each other, and
+    /// the services they depend on, are stopped last, all at once.
+    ReverseDependencies(HashMap<ServiceId, Vec<ServiceId>>),
+}
+
+impl ShutdownOrder {
+    /// Groups of `services_ids` stopped together, in stopping order
+    pub(crate) fn stages(&self, services_ids: &[ServiceId]) -> Vec<Vec<ServiceId>> {
+        let matching = |declared: ServiceId| {
+            services_ids
+                .iter()
+                .copied()
+                .filter(move |&id| id == declared || is_member_of(id, declared))
+        };
+        let mut remaining = services_ids.to_vec();
+        let mut stages = Vec::new();
+        match self {
+            Self::Parallel => {}
+            Self::Declared(order) => {
+                for &declared in order {
+                    let stage: Vec<_> = matching(declared)
+                        .filter(|id| remaining.contains(id))
+                        .collect();
+                    remaining.retain(|id| !stage.contains(id));
+                    if !stage.is_empty() {
+                        stages.push(stage);
+                    }
+                }
+            }
+            Self::ReverseDependencies(dependencies) => {
+                let dependencies_of = |id: ServiceId| -> Vec<ServiceId> {
+                    dependencies
+                        .iter()
+                        .filter(|(&service, _)| id == service || is_member_of(id, service))
+                        .flat_map(|(_, providers)| providers.iter().copied().flat_map(matching))
+                        .filter(|&provider| provider != id)
+                        .collect()
+                };
+                loop {
+                    let providers: Vec<_> = remaining
+                        .iter()
+                        .flat_map(|&id| dependencies_of(id))
+                        .collect();
+                    let stage: Vec<_> = remaining
+                        .iter()
+                        .copied()
+                        .filter(|id| !providers.contains(id))
+                        .collect();
+                    if stage.is_empty() {
+                        break;
+                    }
+                    remaining.retain(|id| !stage.contains(id));
+                    stages.push(stage);
+                }
+            }
+        }
+        if !remaining.is_empty() {
+            stages.push(remaining);
+        }
+        stages
+    }
+}
+
+/// How a service instance ended when Overwatch was shut down
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServiceExit {
+    /// Its main loop returned successfully
+    Finished,
+    /// Its main loop returned an error
+    Failed(String),
+    /// It was still running once the shutdown grace period elapsed, so it was aborted
+    Aborted,
+    Panicked,
+}
+
+/// How every running service ended when Overwatch was shut down, see
+/// [`OverwatchHandle::shutdown_and_wait`](crate::overwatch::handle::OverwatchHandle::shutdown_and_wait)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TeardownReport {
+    /// Exit of each service, sorted by service id
+    pub exits: Vec<(ServiceId, ServiceExit)>,
+}
+
+impl TeardownReport {
+    /// Check if every service finished on its own
+    pub fn is_clean(&self) -> bool {
+        self.exits
+            .iter()
+            .all(|(_, exit)| *exit == ServiceExit::Finished)
+    }
+
+    pub fn exit(&self, service_id: ServiceId) -> Option<&ServiceExit> {
+        self.exits
+            .iter()
+            .find(|(id, _)| *id == service_id)
+            .map(|(_, exit)| exit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::teardown::ShutdownOrder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn declared_order_stops_unlisted_services_last() {
+        let order = ShutdownOrder::Declared(vec!["api", "pool", "missing"]);
+        assert_eq!(
+            order.stages(&["db", "pool#0", "api", "pool#1"]),
+            [vec!["api"], vec!["pool#0", "pool#1"], vec!["db"]]
+        );
+        assert_eq!(
+            ShutdownOrder::Parallel.stages(&["db", "api"]),
+            [vec!["db", "api"]]
+        );
+    }
+
+    #[test]
+    fn consumers_stop_before_their_providers() {
+        let order = ShutdownOrder::ReverseDependencies(HashMap::from([
+            ("api", vec!["cache", "db"]),
+            ("cache", vec!["db"]),
+            ("a", vec!["b"]),
+            ("b", vec!["a"]),
+        ]));
+        assert_eq!(
+            order.stages(&["db", "cache", "api", "a", "b"]),
+            [vec!["api"], vec!["cache"], vec!["db"], vec!["a", "b"]]
+        );
+    }
+}
@@ -0,0 +1,446 @@
+//! Helpers to run a whole application in integration tests
+
+// std
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "test-util")]
+use std::time::Instant;
+// crates
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing::error;
+// internal
+use crate::overwatch::boot::BootReport;
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::teardown::TeardownReport;
+use crate::overwatch::{Overwatch, OverwatchRunner, Services};
+#[cfg(feature = "test-util")]
+use crate::services::clock::{Clock, VirtualClock};
+use crate::services::context::ContextConfig;
+use crate::services::handle::ServiceStateHandle;
+use crate::services::rate_limit::RateLimit;
+use crate::services::relay::BackpressurePolicy;
+use crate::services::state::{NoOperator, NoState};
+use crate::services::status::{ServiceStatus, ServiceStatusError};
+use crate::services::{ServiceCore, ServiceData, ServiceId};
+use crate::DynError;
+
+#[derive(Error, Debug)]
+pub enum HarnessError {
+    #[error(transparent)]
+    Status(#[from] ServiceStatusError),
+
+    #[error("service {service_id} wasn't running within {timeout:?}, it is {status:?}")]
+    NotReady {
+        service_id: ServiceId,
+        status: ServiceStatus,
+        timeout: Duration,
+    },
+
+    #[error("services {services:?} weren't running once booted")]
+    NotBooted { services: Vec<ServiceId> },
+}
+
+/// Directory created for a single test, removed with [`EphemeralDir::remove`]
+/// Those of an [`AppHarness`] are removed along with it, and provided to the services as a
+/// shared resource, see
+/// [`ServiceStateHandle::resources`](crate::services::handle::ServiceStateHandle::resources).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EphemeralDir(PathBuf);
+
+impl EphemeralDir {
+    /// New empty directory under the system temporary one
+    pub fn create() -> std::io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "overwatch-harness-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn remove(&self) -> std::io::Result<()> {
+        std::fs::remove_dir_all(&self.0)
+    }
+}
+
+/// Full `S` application run for a test, on its own runtime and [`EphemeralDir`]
+///
+/// Settings are built from the ephemeral directory, so file based state operators and services
+/// keep their files apart from other tests. Dropping the harness aborts the application and
+/// removes the directory, use [`AppHarness::shutdown`] to stop it gracefully instead.
+///
+/// ```ignore
+/// let harness = AppHarness::<App>::run(|dir| AppServiceSettings {
+///     store: StoreSettings { path: dir.join("store.db") },
+/// })?;
+/// harness.block_on(async {
+///     harness.wait_booted().await?;
+///     let store = harness.handle().relay::<Store>().connect().await?;
+///     // ...
+/// });
+/// ```
+pub struct AppHarness<S> {
+    overwatch: Option<Overwatch>,
+    dir: EphemeralDir,
+    _services: PhantomData<fn() -> S>,
+}
+
+impl<S> AppHarness<S>
+where
+    S: Services + Send + 'static,
+{
+    pub fn run(settings: impl FnOnce(&Path) -> S::Settings) -> Result<Self, DynError> {
+        Self::run_with_context_config(settings, ContextConfig::default())
+    }
+
+    /// Same as [`AppHarness::run`] but services contexts are built from `context_config`
+    pub fn run_with_context_config(
+        settings: impl FnOnce(&Path) -> S::Settings,
+        context_config: ContextConfig,
+    ) -> Result<Self, DynError> {
+        let dir = EphemeralDir::create()?;
+        let settings = settings(dir.path());
+        let context_config = context_config.with_resource(dir.clone());
+        let overwatch =
+            match OverwatchRunner::<S>::run_with_context_config(settings, None, context_config) {
+                Ok(overwatch) => overwatch,
+                Err(e) => {
+                    let _ = dir.remove();
+                    return Err(e);
+                }
+            };
+        Ok(Self {
+            overwatch: Some(overwatch),
+            dir,
+            _services: PhantomData,
+        })
+    }
+
+    pub fn handle(&self) -> &OverwatchHandle {
+        self.overwatch().handle()
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Run `future` on the application runtime, blocking until it completes
+    /// It can't be called from within an async context.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.overwatch().runtime().block_on(future)
+    }
+
+    /// Wait up to `timeout` for the `T` service to be running
+    pub async fn wait_ready<T: ServiceData>(&self, timeout: Duration) -> Result<(), HarnessError> {
+        let mut watcher = self.handle().status_watcher::<T>().await?;
+        watcher
+            .wait_until(ServiceStatus::is_up, Some(timeout))
+            .await
+            .map(|_| ())
+            .map_err(|status| HarnessError::NotReady {
+                service_id: self.handle().resolve(T::SERVICE_ID),
+                status,
+                timeout,
+            })
+    }
+
+    /// Wait for the [`BootReport`], failing if any started service wasn't running by then
+    /// How long services are waited for is set with
+    /// [`ContextConfig::with_boot_report_timeout`].
+    pub async fn wait_booted(&self) -> Result<BootReport, HarnessError> {
+        let report = self.handle().wait_for_boot_report().await;
+        let services: Vec<_> = report
+            .started
+            .iter()
+            .filter(|boot| boot.time_to_ready.is_none())
+            .map(|boot| boot.service_id)
+            .collect();
+        if services.is_empty() {
+            Ok(report)
+        } else {
+            Err(HarnessError::NotBooted { services })
+        }
+    }
+
+    /// Shut the application down gracefully and wait for it to finish
+    pub fn shutdown(mut self) -> Option<TeardownReport> {
+        let overwatch = self.overwatch.take()?;
+        let report = overwatch
+            .runtime()
+            .block_on(overwatch.handle().shutdown_and_wait());
+        overwatch.wait_finished();
+        report
+    }
+
+    fn overwatch(&self) -> &Overwatch {
+        self.overwatch
+            .as_ref()
+            .expect("the application only stops once the harness is consumed")
+    }
+}
+
+impl<S> Drop for AppHarness<S> {
+    fn drop(&mut self) {
+        if let Some(overwatch) = self.overwatch.take() {
+            overwatch.abort();
+        }
+        if let Err(e) = self.dir.remove() {
+            error!(
+                "Harness directory {:?} couldn't be removed: {e}",
+                self.dir.path()
+            );
+        }
+    }
+}
+
+/// Application run for a test on a paused, current thread, runtime, where time is virtual
+///
+/// Nothing runs until the test drives the runtime with [`OverwatchTestRunner::block_on`] or
+/// [`OverwatchTestRunner::advance`]. Timers fire in order as virtual time goes by, and the
+/// services are given a [`VirtualClock`], so lifecycle timeouts, debounced state saves or retry
+/// backoffs can be tested deterministically, without waiting for them. Requires the
+/// `test-util` feature.
+///
+/// ```ignore
+/// let runner = OverwatchTestRunner::<App>::run(settings)?;
+/// runner.advance(Duration::from_secs(30));
+/// assert_eq!(runner.block_on(store_writes()), 1);
+/// ```
+#[cfg(feature = "test-util")]
+pub struct OverwatchTestRunner<S> {
+    overwatch: Option<Overwatch>,
+    _services: PhantomData<fn() -> S>,
+}
+
+#[cfg(feature = "test-util")]
+impl<S> OverwatchTestRunner<S>
+where
+    S: Services + Send + 'static,
+{
+    pub fn run(settings: S::Settings) -> Result<Self, DynError> {
+        Self::run_with_context_config(settings, ContextConfig::default())
+    }
+
+    /// Same as [`OverwatchTestRunner::run`] but services contexts are built from
+    /// `context_config`, its clock is replaced with a [`VirtualClock`]
+    pub fn run_with_context_config(
+        settings: S::Settings,
+        context_config: ContextConfig,
+    ) -> Result<Self, DynError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()?;
+        let overwatch = OverwatchRunner::<S>::run_with_context_config(
+            settings,
+            Some(runtime),
+            context_config.with_clock(VirtualClock),
+        )?;
+        Ok(Self {
+            overwatch: Some(overwatch),
+            _services: PhantomData,
+        })
+    }
+
+    pub fn handle(&self) -> &OverwatchHandle {
+        self.overwatch().handle()
+    }
+
+    /// Run `future` until it completes, virtual time jumps to the next timer whenever the
+    /// application is idle meanwhile
+    /// It can't be called from within an async context.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.overwatch().block_on(future)
+    }
+
+    /// Run the application until `duration` went by in virtual time
+    pub fn advance(&self, duration: Duration) {
+        self.block_on(async {
+            tokio::time::sleep(duration).await;
+            // let the tasks woken at the same instant run before returning
+            tokio::task::yield_now().await;
+        });
+    }
+
+    /// Current virtual time
+    pub fn now(&self) -> Instant {
+        self.block_on(async { VirtualClock.now() })
+    }
+
+    /// Shut the application down gracefully and wait for it to finish
+    pub fn shutdown(mut self) -> Option<TeardownReport> {
+        let overwatch = self.overwatch.take()?;
+        let report = overwatch.block_on(overwatch.handle().shutdown_and_wait());
+        overwatch.wait_finished();
+        report
+    }
+
+    fn overwatch(&self) -> &Overwatch {
+        self.overwatch
+            .as_ref()
+            .expect("the application only stops once the runner is consumed")
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl<S> Drop for OverwatchTestRunner<S> {
+    fn drop(&mut self) {
+        if let Some(overwatch) = self.overwatch.take() {
+            overwatch.abort();
+        }
+    }
+}
+
+type Script<M> = Arc<Mutex<dyn FnMut(M) + Send>>;
+
+/// Settings of a [`MockService`], the messages it receives are recorded here
+/// Clones share the recorded messages, keep one around to check them once the mock is running.
+pub struct MockSettings<M> {
+    received: Arc<watch::Sender<Vec<String>>>,
+    script: Option<Script<M>>,
+}
+
+// auto derive introduces an unnecessary Clone bound on M
+impl<M> Clone for MockSettings<M> {
+    fn clone(&self) -> Self {
+        Self {
+            received: Arc::clone(&self.received),
+            script: self.script.clone(),
+        }
+    }
+}
+
+impl<M> Debug for MockSettings<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockSettings")
+            .field("received", &*self.received.borrow())
+            .field("scripted", &self.script.is_some())
+            .finish()
+    }
+}
+
+impl<M> Default for MockSettings<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> MockSettings<M> {
+    pub fn new() -> Self {
+        Self {
+            received: Arc::new(watch::Sender::new(Vec::new())),
+            script: None,
+        }
+    }
+
+    /// Hand every received message to `script`, once recorded, e.g. to answer requests
+    pub fn reply_with(mut self, script: impl FnMut(M) + Send + 'static) -> Self {
+        self.script = Some(Arc::new(Mutex::new(script)));
+        self
+    }
+
+    /// Debug representation of the messages received so far, oldest first
+    pub fn received(&self) -> Vec<String> {
+        self.received.borrow().clone()
+    }
+
+    /// Wait until at least `count` messages were received, returning them all
+    pub async fn wait_received(&self, count: usize) -> Vec<String> {
+        let mut receiver = self.received.subscribe();
+        let received = receiver
+            .wait_for(|received| received.len() >= count)
+            .await
+            .expect("Received messages sender is owned by self");
+        received.clone()
+    }
+}
+
+/// Stand-in for the `S` service in tests, running under its id and taking its messages
+///
+/// The rest of the application reaches the mock as if it was `S`, through
+/// `relay::<S>()` or `status_watcher::<S>()`. Swap it for `S` in the services struct with a
+/// `cfg(test)` type alias. The mock reports it is running right away and records every message
+/// it receives in its [`MockSettings`].
+///
+/// ```ignore
+/// #[cfg(not(test))]
+/// type Store = StoreService;
+/// #[cfg(test)]
+/// type Store = MockService<StoreService>;
+///
+/// #[derive(Services)]
+/// struct App {
+///     store: ServiceHandle<Store>,
+///     api: ServiceHandle<Api>,
+/// }
+///
+/// let store = MockSettings::new().reply_with(|message| {
+///     if let StoreMessage::Get { reply, .. } = message {
+///         let _ = reply.send(None);
+///     }
+/// });
+/// let settings = AppServiceSettings {
+///     store: store.clone(),
+///     api: ApiSettings::default(),
+/// };
+/// // ...
+/// assert_eq!(store.wait_received(1).await.len(), 1);
+/// ```
+pub struct MockService<S: ServiceData> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<S: ServiceData> ServiceData for MockService<S> {
+    const SERVICE_ID: ServiceId = S::SERVICE_ID;
+    // senders see the same relay as with the real service
+    const SERVICE_RELAY_BUFFER_SIZE: usize = S::SERVICE_RELAY_BUFFER_SIZE;
+    const PRIORITY_RELAY_BUFFER_SIZE: usize = S::PRIORITY_RELAY_BUFFER_SIZE;
+    const BACKPRESSURE_THRESHOLD: usize = S::BACKPRESSURE_THRESHOLD;
+    const BACKPRESSURE_POLICY: BackpressurePolicy = S::BACKPRESSURE_POLICY;
+    const RATE_LIMIT: Option<RateLimit> = S::RATE_LIMIT;
+    type Settings = MockSettings<S::Message>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = S::Message;
+}
+
+#[async_trait]
+impl<S> ServiceCore for MockService<S>
+where
+    S: ServiceData + Send + 'static,
+    S::Message: Send,
+{
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let settings = self.service_state.settings_reader.get_updated_settings();
+        self.service_state.status_handle.updater().running();
+        while let Some(message) = self.service_state.inbound_relay.recv().await {
+            settings
+                .received
+                .send_modify(|received| received.push(format!("{message:?}")));
+            if let Some(script) = &settings.script {
+                (script.lock().expect("Mock script lock not poisoned"))(message);
+            }
+        }
+        Ok(())
+    }
+}
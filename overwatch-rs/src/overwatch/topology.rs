@@ -0,0 +1,215 @@
+//! Machine readable description of the running application, for tooling that can't link against it
+//! The JSON document of [`Topology::to_json`] is versioned with [`TOPOLOGY_VERSION`], fields are
+//! only added within a version, so consumers can ignore the ones they don't know, it requires the
+//! `topology-json` feature.
+// crates
+#[cfg(feature = "topology-json")]
+use serde_json::{json, Value};
+// internal
+use crate::overwatch::teardown::ShutdownOrder;
+use crate::services::pool::is_member_of;
+use crate::services::status::ServiceStatus;
+use crate::services::{ServiceId, ServicePriority};
+
+/// Version of the [`Topology::to_json`] document, bumped on breaking changes
+#[cfg(feature = "topology-json")]
+pub const TOPOLOGY_VERSION: u32 = 1;
+
+/// A running, or declared, service instance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceNode {
+    pub service_id: ServiceId,
+    /// Pool the service is a member of, if any
+    pub group: Option<ServiceId>,
+    pub priority: ServicePriority,
+    pub relay_buffer_size: usize,
+    /// `None` if the runner couldn't watch the service status
+    pub status: Option<ServiceStatus>,
+}
+
+/// A [`ServicePool`](crate::services::pool::ServicePool) and its current number of members
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceGroup {
+    pub group_id: ServiceId,
+    pub size: usize,
+}
+
+/// `service_id` relies on `depends_on`, as declared with
+/// [`ShutdownOrder::ReverseDependencies`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    pub service_id: ServiceId,
+    pub depends_on: ServiceId,
+}
+
+/// Services, pools and dependencies of the application, see [`OverwatchHandle::topology`](crate::overwatch::handle::OverwatchHandle::topology)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Topology {
+    /// Sorted by service id, pool members are listed instead of their pool
+    pub services: Vec<ServiceNode>,
+    pub groups: Vec<ServiceGroup>,
+    pub dependencies: Vec<Dependency>,
+}
+
+impl Topology {
+    /// Topology of the `declared` services, with their priority and relay buffer size
+    /// Pools are expanded to their members found in `services_ids`, the ids of the services known
+    /// to the runner.
+    pub(crate) fn new(
+        declared: &[(ServiceId, ServicePriority, usize)],
+        pools: &[ServiceId],
+        services_ids: &[ServiceId],
+        shutdown_order: &ShutdownOrder,
+        status: impl Fn(ServiceId) -> Option<ServiceStatus>,
+    ) -> Self {
+        let mut services = Vec::new();
+        let mut groups = Vec::new();
+        for &(service_id, priority, relay_buffer_size) in declared {
+            let node = |service_id, group| ServiceNode {
+                service_id,
+                group,
+                priority,
+                relay_buffer_size,
+                status: status(service_id),
+            };
+            if pools.contains(&service_id) {
+                let members: Vec<_> = services_ids
+                    .iter()
+                    .copied()
+                    .filter(|&id| is_member_of(id, service_id))
+                    .collect();
+                groups.push(ServiceGroup {
+                    group_id: service_id,
+                    size: members.len(),
+                });
+                services.extend(members.into_iter().map(|id| node(id, Some(service_id))));
+            } else {
+                services.push(node(service_id, None));
+            }
+        }
+        services.sort_unstable_by_key(|service| service.service_id);
+        groups.sort_unstable_by_key(|group| group.group_id);
+        let mut dependencies: Vec<_> = match shutdown_order {
+            ShutdownOrder::ReverseDependencies(dependencies) => dependencies
+                .iter()
+                .flat_map(|(&service_id, providers)| {
+                    providers.iter().map(move |&depends_on| Dependency {
+                        service_id,
+                        depends_on,
+                    })
+                })
+                .collect(),
+            ShutdownOrder::Parallel | ShutdownOrder::Declared(_) => Vec::new(),
+        };
+        dependencies
+            .sort_unstable_by_key(|dependency| (dependency.service_id, dependency.depends_on));
+        Self {
+            services,
+            groups,
+            dependencies,
+        }
+    }
+
+    pub fn service(&self, service_id: ServiceId) -> Option<&ServiceNode> {
+        self.services
+            .iter()
+            .find(|service| service.service_id == service_id)
+    }
+
+    /// Versioned JSON document of the topology
+    #[cfg(feature = "topology-json")]
+    pub fn to_json(&self) -> Value {
+        let services: Vec<_> = self
+            .services
+            .iter()
+            .map(|service| {
+                json!({
+                    "id": service.service_id,
+                    "group": service.group,
+                    "priority": priority_name(service.priority),
+                    "relay_buffer_size": service.relay_buffer_size,
+                    "status": service.status.as_ref().map(status_name),
+                })
+            })
+            .collect();
+        let groups: Vec<_> = self
+            .groups
+            .iter()
+            .map(|group| json!({ "id": group.group_id, "size": group.size }))
+            .collect();
+        let dependencies: Vec<_> = self
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                json!({ "service": dependency.service_id, "depends_on": dependency.depends_on })
+            })
+            .collect();
+        json!({
+            "version": TOPOLOGY_VERSION,
+            "services": services,
+            "groups": groups,
+            "dependencies": dependencies,
+        })
+    }
+}
+
+/// Names are part of the document format, they don't follow the `Debug` output
+#[cfg(feature = "topology-json")]
+fn priority_name(priority: ServicePriority) -> &'static str {
+    match priority {
+        ServicePriority::Low => "low",
+        ServicePriority::Normal => "normal",
+        ServicePriority::High => "high",
+    }
+}
+
+#[cfg(any(feature = "admin-http", feature = "cli", feature = "topology-json"))]
+pub(crate) fn status_name(status: &ServiceStatus) -> &'static str {
+    match status {
+        ServiceStatus::Uninitialized => "uninitialized",
+        ServiceStatus::Running => "running",
+        ServiceStatus::Degraded => "degraded",
+        ServiceStatus::Detached => "detached",
+        ServiceStatus::Stopped => "stopped",
+        ServiceStatus::Restarting => "restarting",
+        ServiceStatus::Failed(_) => "failed",
+    }
+}
+
+#[cfg(all(test, feature = "topology-json"))]
+mod test {
+    use crate::overwatch::teardown::ShutdownOrder;
+    use crate::overwatch::topology::{Topology, TOPOLOGY_VERSION};
+    use crate::services::status::ServiceStatus;
+    use crate::services::ServicePriority;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn pools_are_listed_through_their_members() {
+        let order = ShutdownOrder::ReverseDependencies(HashMap::from([("api", vec!["workers"])]));
+        let topology = Topology::new(
+            &[
+                ("api", ServicePriority::High, 8),
+                ("workers", ServicePriority::Normal, 16),
+            ],
+            &["workers"],
+            &["api", "workers#1", "workers#0"],
+            &order,
+            |service_id| (service_id != "workers#1").then_some(ServiceStatus::Running),
+        );
+        assert_eq!(
+            topology.to_json(),
+            json!({
+                "version": TOPOLOGY_VERSION,
+                "services": [
+                    {"id": "api", "group": null, "priority": "high", "relay_buffer_size": 8, "status": "running"},
+                    {"id": "workers#0", "group": "workers", "priority": "normal", "relay_buffer_size": 16, "status": "running"},
+                    {"id": "workers#1", "group": "workers", "priority": "normal", "relay_buffer_size": 16, "status": null},
+                ],
+                "groups": [{"id": "workers", "size": 2}],
+                "dependencies": [{"service": "api", "depends_on": "workers"}],
+            })
+        );
+    }
+}
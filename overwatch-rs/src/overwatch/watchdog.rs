@@ -0,0 +1,118 @@
+//! Detection of stuck relays, to find deadlocks between services relaying to each other
+//!
+//! Two services sending to each other with [`send`](crate::services::relay::OutboundRelay::send)
+//! while handling their messages end up waiting on each other once both buffers are full. The
+//! [`Watchdog`], set with
+//! [`ContextConfig::with_watchdog`](crate::services::context::ContextConfig::with_watchdog),
+//! warns about relays full for too long and services leaving messages in their relay without
+//! polling it, and publishes a [`WatchdogEvent`] to
+//! [`OverwatchHandle::watchdog_events`] subscribers for each of them.
+
+// std
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+// crates
+use tracing::warn;
+// internal
+use crate::overwatch::events::WatchdogEvent;
+use crate::overwatch::handle::OverwatchHandle;
+use crate::services::ServiceId;
+
+/// Watches the relays of all services for stalls, see [`crate::overwatch::watchdog`]
+#[derive(Clone, Copy, Debug)]
+pub struct Watchdog {
+    interval: Duration,
+    full_relay_threshold: Duration,
+    heartbeat: Duration,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            full_relay_threshold: Duration::from_secs(5),
+            heartbeat: Duration::from_secs(5),
+        }
+    }
+
+    /// How often relays are checked, 1 second by default
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// How long a relay buffer can stay full before it is reported, 5 seconds by default
+    pub fn with_full_relay_threshold(mut self, threshold: Duration) -> Self {
+        self.full_relay_threshold = threshold;
+        self
+    }
+
+    /// How long a service can leave messages in its relay without polling it before it is
+    /// reported, 5 seconds by default
+    pub fn with_heartbeat(mut self, heartbeat: Duration) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    /// Check the relays until Overwatch shuts down
+    /// Each stall is reported once, and again if it happens after clearing.
+    pub(crate) async fn run(self, handle: OverwatchHandle) {
+        let shutdown = handle.shutdown_token();
+        let clock = Arc::clone(&handle.context_config().clock);
+        let mut full = HashSet::new();
+        let mut unresponsive = HashSet::new();
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = clock.sleep(self.interval) => {}
+            }
+            for (service_id, stats) in handle.metrics_registry().relays() {
+                if let Some(since) = Self::stalled(
+                    &mut full,
+                    service_id,
+                    stats.full_for(),
+                    self.full_relay_threshold,
+                ) {
+                    warn!("Relay of service {service_id} has been full for {since:?}");
+                    handle
+                        .events()
+                        .report_watchdog(WatchdogEvent::RelayFull { service_id, since });
+                }
+                if let Some(since) = Self::stalled(
+                    &mut unresponsive,
+                    service_id,
+                    stats.unpolled_for(),
+                    self.heartbeat,
+                ) {
+                    warn!("Service {service_id} hasn't polled its relay for {since:?}");
+                    handle
+                        .events()
+                        .report_watchdog(WatchdogEvent::Unresponsive { service_id, since });
+                }
+            }
+        }
+    }
+
+    /// How long `service_id` is stalled for, if over `threshold` and not reported yet
+    fn stalled(
+        reported: &mut HashSet<ServiceId>,
+        service_id: ServiceId,
+        stalled_for: Option<Duration>,
+        threshold: Duration,
+    ) -> Option<Duration> {
+        match stalled_for {
+            Some(since) if since >= threshold => reported.insert(service_id).then_some(since),
+            _ => {
+                reported.remove(service_id);
+                None
+            }
+        }
+    }
+}
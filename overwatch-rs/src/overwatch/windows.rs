@@ -0,0 +1,144 @@
+//! Run an Overwatch application as a Windows Service
+//! Requests of the service control manager are mapped to the Overwatch lifecycle: pausing the
+//! service stops all of its services, see [`OverwatchHandle::stop_all`], continuing starts them
+//! again with [`OverwatchHandle::start_all`], and stopping it, or shutting the system down, shuts
+//! Overwatch down gracefully.
+//!
+//! Overwatch is run from the service entry point, registered with the re-exported
+//! [`windows_service`] crate:
+//!
+//! ```ignore
+//! use overwatch_rs::overwatch::windows::{run_windows_service, windows_service};
+//!
+//! windows_service::define_windows_service!(ffi_service_main, service_main);
+//!
+//! fn service_main(_arguments: Vec<std::ffi::OsString>) {
+//!     let overwatch = OverwatchRunner::<App>::run(settings(), None).unwrap();
+//!     run_windows_service("app", overwatch).unwrap();
+//! }
+//!
+//! fn main() -> windows_service::Result<()> {
+//!     windows_service::service_dispatcher::start("app", ffi_service_main)
+//! }
+//! ```
+// std
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+// crates
+use tracing::{error, info};
+pub use windows_service;
+use windows_service::service::{
+    ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{
+    self, ServiceControlHandlerResult, ServiceStatusHandle,
+};
+// internal
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::Overwatch;
+
+/// Exit code the service reports if its services weren't torn down cleanly
+pub const UNCLEAN_TEARDOWN_EXIT_CODE: u32 = 1;
+
+/// Time the service control manager is told a pending stop may take
+const STOP_WAIT_HINT: Duration = Duration::from_secs(30);
+
+/// Report `overwatch` running as the Windows Service `name` and handle the service control
+/// requests until it finishes, blocking the calling thread
+/// The service is reported stopped with the [`UNCLEAN_TEARDOWN_EXIT_CODE`] service specific exit
+/// code if any of its services failed or had to be aborted on shutdown.
+pub fn run_windows_service(name: &str, overwatch: Overwatch) -> windows_service::Result<()> {
+    let handle = overwatch.handle().clone();
+    // set once registered, the service control manager sends no request before that
+    let status_handle: Arc<OnceLock<ServiceStatusHandle>> = Arc::default();
+    let event_handler = {
+        let status_handle = Arc::clone(&status_handle);
+        move |control| {
+            let state = match control {
+                ServiceControl::Interrogate => return ServiceControlHandlerResult::NoError,
+                ServiceControl::Pause => {
+                    info!("Windows Service paused, stopping all services");
+                    spawn_command(&handle, |handle| async move { handle.stop_all().await });
+                    ServiceState::Paused
+                }
+                ServiceControl::Continue => {
+                    info!("Windows Service continued, starting all services");
+                    spawn_command(&handle, |handle| async move { handle.start_all().await });
+                    ServiceState::Running
+                }
+                ServiceControl::Stop | ServiceControl::Shutdown | ServiceControl::Preshutdown => {
+                    info!("Windows Service stopped, shutting down");
+                    spawn_command(&handle, |handle| async move {
+                        handle.shutdown().await;
+                        Ok(())
+                    });
+                    ServiceState::StopPending
+                }
+                _ => return ServiceControlHandlerResult::NotImplemented,
+            };
+            if let Some(status_handle) = status_handle.get() {
+                report(status_handle, state, ServiceExitCode::NO_ERROR);
+            }
+            ServiceControlHandlerResult::NoError
+        }
+    };
+    let registered = service_control_handler::register(name, event_handler)?;
+    let _ = status_handle.set(registered);
+    report(
+        &registered,
+        ServiceState::Running,
+        ServiceExitCode::NO_ERROR,
+    );
+
+    let handle = overwatch.handle().clone();
+    overwatch.wait_finished();
+    let exit_code = match handle.events().teardown_report() {
+        Some(report) if report.is_clean() => ServiceExitCode::NO_ERROR,
+        _ => ServiceExitCode::ServiceSpecific(UNCLEAN_TEARDOWN_EXIT_CODE),
+    };
+    registered.set_service_status(status(ServiceState::Stopped, exit_code))
+}
+
+/// Send a lifecycle command from the service control handler thread, which is not one of the
+/// Overwatch runtime
+fn spawn_command<F, Fut>(handle: &OverwatchHandle, command: F)
+where
+    F: FnOnce(OverwatchHandle) -> Fut,
+    Fut: std::future::Future<Output = Result<(), crate::overwatch::Error>> + Send + 'static,
+{
+    let sent = command(handle.clone());
+    handle.runtime().spawn(async move {
+        if let Err(e) = sent.await {
+            error!("Windows Service request couldn't be handled: {e}");
+        }
+    });
+}
+
+fn report(status_handle: &ServiceStatusHandle, state: ServiceState, exit_code: ServiceExitCode) {
+    if let Err(e) = status_handle.set_service_status(status(state, exit_code)) {
+        error!("Windows Service status couldn't be reported: {e}");
+    }
+}
+
+fn status(state: ServiceState, exit_code: ServiceExitCode) -> ServiceStatus {
+    let stopping = matches!(state, ServiceState::StopPending | ServiceState::Stopped);
+    ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted: if stopping {
+            ServiceControlAccept::empty()
+        } else {
+            ServiceControlAccept::STOP
+                | ServiceControlAccept::SHUTDOWN
+                | ServiceControlAccept::PAUSE_CONTINUE
+        },
+        exit_code,
+        checkpoint: 0,
+        wait_hint: if state == ServiceState::StopPending {
+            STOP_WAIT_HINT
+        } else {
+            Duration::ZERO
+        },
+        process_id: None,
+    }
+}
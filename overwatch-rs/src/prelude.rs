@@ -0,0 +1,27 @@
+//! Blessed user facing surface of Overwatch
+//!
+//! Importing `overwatch_rs::prelude::*` is enough to define services and run an application.
+//! Items here are kept stable across internal refactors, prefer them over deep module paths.
+
+pub use crate::overwatch::commands::RestartMode;
+pub use crate::overwatch::handle::OverwatchHandle;
+pub use crate::overwatch::{Overwatch, OverwatchRunner, Services};
+pub use crate::services::context::ServiceContext;
+pub use crate::services::handle::{ServiceHandle, ServiceStateHandle};
+pub use crate::services::life_cycle::LifecycleMessage;
+pub use crate::services::output::ServiceOutput;
+pub use crate::services::relay::{
+    InboundRelay, NoMessage, NoRelay, OutboundRelay, Relay, RelayError, RelayEvent, RelayMessage,
+    RetryPolicy,
+};
+pub use crate::services::settings::SettingsNotifier;
+pub use crate::services::state::{
+    NoOperator, NoState, PersistContext, ServiceState, StateOperator, StateUpdater,
+};
+pub use crate::services::status::{ServiceStatus, StatusWatcher};
+pub use crate::services::supervisor::{Backoff, RestartPolicy};
+pub use crate::services::{ServiceCore, ServiceData, ServiceId, ServicePriority, StartBudget};
+pub use crate::DynError;
+pub use crate::{service_eprintln, service_println};
+#[cfg(feature = "derive")]
+pub use overwatch_derive::Services;
@@ -0,0 +1,232 @@
+// std
+use std::any::{Any, TypeId};
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+// crates
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+// internal
+use crate::services::relay::RelayError;
+use crate::services::{ServiceData, ServiceId};
+
+struct Replay<M> {
+    sender: broadcast::Sender<M>,
+    last: VecDeque<M>,
+    capacity: usize,
+}
+
+/// Relay delivering every message to all its subscribers
+/// The last `replay` messages are kept and handed to new subscribers first, so a service that
+/// starts late, or restarts, catches up with what it missed without asking senders to resend
+/// their current state. Clones send to the same subscribers.
+pub struct BroadcastRelay<M> {
+    inner: Arc<Mutex<Replay<M>>>,
+}
+
+impl<M> Clone for BroadcastRelay<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<M: Clone> BroadcastRelay<M> {
+    /// Relay buffering up to `buffer_size` messages per subscriber, and replaying the last
+    /// `replay` messages to new subscribers
+    pub fn new(buffer_size: usize, replay: usize) -> Self {
+        let (sender, _) = broadcast::channel(buffer_size);
+        Self {
+            inner: Arc::new(Mutex::new(Replay {
+                sender,
+                last: VecDeque::with_capacity(replay),
+                capacity: replay,
+            })),
+        }
+    }
+
+    /// Send a message to the current subscribers, returning how many of them there are
+    /// Messages sent without subscribers are still kept for replay.
+    pub fn send(&self, message: M) -> usize {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Broadcast relay lock not poisoned");
+        if inner.capacity > 0 {
+            if inner.last.len() == inner.capacity {
+                inner.last.pop_front();
+            }
+            inner.last.push_back(message.clone());
+        }
+        inner.sender.send(message).unwrap_or(0)
+    }
+
+    /// Subscribe to messages sent from now on, after the replayed ones
+    pub fn subscribe(&self) -> BroadcastSubscription<M> {
+        // subscribing while holding the lock so no message is missed or received twice
+        let inner = self
+            .inner
+            .lock()
+            .expect("Broadcast relay lock not poisoned");
+        BroadcastSubscription {
+            replay: inner.last.clone(),
+            receiver: inner.sender.subscribe(),
+        }
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.inner
+            .lock()
+            .expect("Broadcast relay lock not poisoned")
+            .sender
+            .receiver_count()
+    }
+}
+
+/// Receiving side of a [`BroadcastRelay`]
+pub struct BroadcastSubscription<M> {
+    replay: VecDeque<M>,
+    receiver: broadcast::Receiver<M>,
+}
+
+impl<M: Clone> BroadcastSubscription<M> {
+    /// Next replayed message, or next message sent since subscribing
+    /// Messages overwritten because this subscriber fell behind are skipped. It returns `None`
+    /// once every [`BroadcastRelay`] clone is dropped.
+    pub async fn recv(&mut self) -> Option<M> {
+        if let Some(message) = self.replay.pop_front() {
+            return Some(message);
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok(message) => return Some(message),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Broadcast subscriber lagged behind, {skipped} messages skipped");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Service publishing typed messages to its subscribers, see
+/// [`ServiceStateHandle::broadcast`](crate::services::handle::ServiceStateHandle::broadcast)
+/// and [`OverwatchHandle::subscribe`](crate::overwatch::handle::OverwatchHandle::subscribe)
+/// Both sides use the same message type, so publishers and subscribers can't disagree on it.
+pub trait BroadcastingService: ServiceData {
+    type Broadcast: Clone + Send + 'static;
+}
+
+pub type BroadcastResult = Result<ServiceBroadcast, RelayError>;
+
+/// Broadcast relay of a service, see [`ServiceData::BROADCAST_BUFFER_SIZE`](crate::services::ServiceData::BROADCAST_BUFFER_SIZE)
+/// The relay is created for the message type it is first asked for, either by the service
+/// publishing through
+/// [`ServiceStateHandle::broadcast`](crate::services::handle::ServiceStateHandle::broadcast)
+/// or by a subscriber from [`OverwatchHandle::subscribe`](crate::overwatch::handle::OverwatchHandle::subscribe),
+/// so subscribers don't depend on the service being started first. It outlives service
+/// restarts.
+#[derive(Clone)]
+pub struct ServiceBroadcast {
+    service_id: ServiceId,
+    buffer_size: usize,
+    replay: usize,
+    relay: Arc<Mutex<Option<Box<dyn Any + Send>>>>,
+}
+
+impl Debug for ServiceBroadcast {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceBroadcast")
+            .field("service_id", &self.service_id)
+            .field("buffer_size", &self.buffer_size)
+            .field("replay", &self.replay)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ServiceBroadcast {
+    pub(crate) fn new(service_id: ServiceId, buffer_size: usize, replay: usize) -> Self {
+        Self {
+            service_id,
+            buffer_size,
+            replay,
+            relay: Arc::default(),
+        }
+    }
+
+    pub fn service_id(&self) -> ServiceId {
+        self.service_id
+    }
+
+    /// Relay of the service, failing with [`RelayError::InvalidMessage`] if it was created for
+    /// another message type than `M`
+    pub fn relay<M: Clone + Send + 'static>(&self) -> Result<BroadcastRelay<M>, RelayError> {
+        let mut relay = self
+            .relay
+            .lock()
+            .expect("Service broadcast lock not poisoned");
+        relay
+            .get_or_insert_with(|| {
+                Box::new(BroadcastRelay::<M>::new(self.buffer_size, self.replay))
+            })
+            .downcast_ref::<BroadcastRelay<M>>()
+            .cloned()
+            .ok_or_else(|| RelayError::InvalidMessage {
+                type_id: format!("{:?}", TypeId::of::<M>()),
+                service_id: self.service_id,
+            })
+    }
+
+    pub fn subscribe<M: Clone + Send + 'static>(
+        &self,
+    ) -> Result<BroadcastSubscription<M>, RelayError> {
+        self.relay().map(|relay| relay.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::broadcast::{BroadcastRelay, ServiceBroadcast};
+    use crate::services::relay::RelayError;
+
+    #[tokio::test]
+    async fn late_subscribers_get_the_last_messages() {
+        let relay = BroadcastRelay::new(8, 2);
+        let mut early = relay.subscribe();
+        for i in 0..3 {
+            relay.send(i);
+        }
+        let mut late = relay.subscribe();
+        assert_eq!(relay.send(3), 2);
+        drop(relay);
+
+        let mut received = Vec::new();
+        while let Some(message) = early.recv().await {
+            received.push(message);
+        }
+        assert_eq!(received, [0, 1, 2, 3]);
+        received.clear();
+        while let Some(message) = late.recv().await {
+            received.push(message);
+        }
+        assert_eq!(received, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn service_broadcast_keeps_its_first_message_type() {
+        let broadcast = ServiceBroadcast::new("publisher", 8, 1);
+        let mut subscription = broadcast.subscribe::<u32>().unwrap();
+        broadcast.relay::<u32>().unwrap().send(1);
+        assert_eq!(subscription.recv().await, Some(1));
+        assert_eq!(broadcast.subscribe::<u32>().unwrap().recv().await, Some(1));
+        assert!(matches!(
+            broadcast.relay::<String>(),
+            Err(RelayError::InvalidMessage {
+                service_id: "publisher",
+                ..
+            })
+        ));
+    }
+}
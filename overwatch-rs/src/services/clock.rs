@@ -0,0 +1,231 @@
+// std
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+// crates
+use futures::future::{BoxFuture, Either};
+use futures::FutureExt;
+use thiserror::Error;
+use tokio::sync::watch;
+
+/// Source of time for services and framework time operations
+///
+/// Every timeout, grace period and delay the framework waits for goes through the clock set in
+/// the [`ContextConfig`](crate::services::context::ContextConfig), so simulations can drive time
+/// for the whole application, e.g. with a [`ManualClock`].
+pub trait Clock: Send + Sync + 'static {
+    fn now(&self) -> Instant;
+
+    /// Complete once the clock reaches `deadline`
+    /// Defaults to waiting in real time, which is only right for clocks following it.
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        tokio::time::sleep_until(deadline.into()).boxed()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.sleep_until(self.now() + duration)
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("deadline elapsed after {0:?}")]
+pub struct Elapsed(pub Duration);
+
+impl dyn Clock {
+    /// Run `future` until it completes or `duration`, counted from now, elapses on this clock
+    pub fn timeout<F: Future>(
+        &self,
+        duration: Duration,
+        future: F,
+    ) -> impl Future<Output = Result<F::Output, Elapsed>> {
+        let sleep = self.sleep(duration);
+        async move {
+            let future = std::pin::pin!(future);
+            match futures::future::select(future, sleep).await {
+                Either::Left((output, _)) => Ok(output),
+                Either::Right(_) => Err(Elapsed(duration)),
+            }
+        }
+    }
+}
+
+/// [`Clock`] backed by the system monotonic clock
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// [`Clock`] following the time of the tokio runtime
+/// On runtimes started paused time is virtual, it jumps to the next timer whenever every task is
+/// idle, so waiting costs no real time. Otherwise it is the same as [`SystemClock`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VirtualClock;
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
+
+/// [`Clock`] standing still until it is advanced, for tests
+/// Clones share the same time, so a clone kept by the test moves the clock of the application.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<watch::Sender<Instant>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::starting_at(Instant::now())
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn starting_at(now: Instant) -> Self {
+        Self {
+            now: Arc::new(watch::Sender::new(now)),
+        }
+    }
+
+    /// Move the clock `duration` forward, waking up everything sleeping until then
+    pub fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| *now += duration);
+    }
+
+    /// Move the clock forward to `instant`, it is a no-op if the clock is already past it
+    pub fn advance_to(&self, instant: Instant) {
+        self.now.send_if_modified(|now| {
+            let moved = instant > *now;
+            *now = (*now).max(instant);
+            moved
+        });
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.borrow()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        let mut now = self.now.subscribe();
+        async move {
+            // the sender lives as long as the clock, which can't move anymore once dropped
+            if now.wait_for(|now| *now >= deadline).await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+        .boxed()
+    }
+}
+
+/// [`Clock`] running `speed` times as fast as real time, for simulations
+#[derive(Clone, Copy, Debug)]
+pub struct SimulatedClock {
+    origin: Instant,
+    started_at: Instant,
+    speed: f64,
+}
+
+impl SimulatedClock {
+    /// Start the clock at the current time
+    ///
+    /// # Panics
+    /// If `speed` isn't a finite positive number.
+    pub fn new(speed: f64) -> Self {
+        Self::starting_at(Instant::now(), speed)
+    }
+
+    /// Start the clock at `origin`, it moves from there on at `speed`
+    ///
+    /// # Panics
+    /// If `speed` isn't a finite positive number.
+    pub fn starting_at(origin: Instant, speed: f64) -> Self {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "simulated clock speed must be positive, got {speed}"
+        );
+        Self {
+            origin,
+            started_at: Instant::now(),
+            speed,
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        self.origin + self.started_at.elapsed().mul_f64(self.speed)
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        let remaining = deadline.saturating_duration_since(self.now());
+        tokio::time::sleep(remaining.div_f64(self.speed)).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::clock::{Clock, Elapsed, ManualClock, SimulatedClock};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn manual_clock_wakes_sleepers_when_advanced() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let sleeper = tokio::spawn(clock.sleep(Duration::from_secs(10)));
+
+        clock.advance(Duration::from_secs(5));
+        tokio::task::yield_now().await;
+        assert!(!sleeper.is_finished());
+        assert_eq!(clock.now(), start + Duration::from_secs(5));
+
+        clock.advance_to(start + Duration::from_secs(10));
+        // a clock doesn't go back
+        clock.advance_to(start);
+        sleeper.await.unwrap();
+        assert_eq!(clock.now(), start + Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn timeout_elapses_on_the_clock() {
+        let manual = ManualClock::new();
+        let clock: Arc<dyn Clock> = Arc::new(manual.clone());
+        let timeout =
+            tokio::spawn(clock.timeout(Duration::from_secs(3600), std::future::pending::<()>()));
+        manual.advance(Duration::from_secs(3600));
+        assert_eq!(
+            timeout.await.unwrap(),
+            Err(Elapsed(Duration::from_secs(3600)))
+        );
+
+        let clock: &dyn Clock = &manual;
+        assert_eq!(
+            clock.timeout(Duration::from_secs(1), async { 1 }).await,
+            Ok(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn simulated_clock_runs_faster_than_real_time() {
+        let origin = Instant::now();
+        let clock = SimulatedClock::starting_at(origin, 1000.0);
+        let started = Instant::now();
+        clock.sleep(Duration::from_secs(10)).await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(clock.now() >= origin + Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,630 @@
+// std
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+// crates
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::{JoinHandle, LocalSet};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::warn;
+// internal
+use crate::overwatch::commands::CustomCommandHandler;
+use crate::overwatch::health::{HealthRegistry, LivenessProbe};
+use crate::overwatch::memory::MemoryMonitor;
+use crate::overwatch::metrics::{MetricsExporter, TaskStats};
+use crate::overwatch::teardown::ShutdownOrder;
+use crate::overwatch::watchdog::Watchdog;
+use crate::services::clock::VirtualClock;
+pub use crate::services::clock::{Clock, SystemClock};
+#[cfg(feature = "persistent-metrics")]
+use crate::services::persistent_metrics::PersistentMetrics;
+use crate::services::persistent_metrics::ServiceCounters;
+use crate::services::recording::MessageRecorder;
+use crate::services::relay::{AllowAll, RelayPolicy};
+use crate::services::resources::SharedResources;
+use crate::services::simulation::LatencyModel;
+use crate::services::yielding::{YieldBudget, DEFAULT_YIELD_BUDGET};
+use crate::services::ServiceId;
+use crate::utils::runtime::spawn_named;
+
+/// Default [`ContextConfig::shutdown_grace`]
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+/// Sink for metrics produced by services
+/// Metrics are identified by name and attributed to the service reporting them.
+pub trait MetricsRecorder: Send + Sync + 'static {
+    fn increment_counter(&self, service_id: ServiceId, name: &'static str, value: u64);
+    fn record_gauge(&self, service_id: ServiceId, name: &'static str, value: f64);
+}
+
+/// [`MetricsRecorder`] that discards everything
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoMetrics;
+
+impl MetricsRecorder for NoMetrics {
+    fn increment_counter(&self, _service_id: ServiceId, _name: &'static str, _value: u64) {}
+
+    fn record_gauge(&self, _service_id: ServiceId, _name: &'static str, _value: f64) {}
+}
+
+/// Set of enabled feature flags, shared by all services
+#[derive(Clone, Debug, Default)]
+pub struct FeatureFlags(Arc<HashSet<String>>);
+
+impl FeatureFlags {
+    pub fn new<I: IntoIterator<Item = impl Into<String>>>(flags: I) -> Self {
+        Self(Arc::new(flags.into_iter().map(Into::into).collect()))
+    }
+
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.0.contains(flag)
+    }
+}
+
+/// What happens when a service panics while its initial state is loaded or it is initialized
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PanicPolicy {
+    /// Abort the whole process, for applications that can't run with a service missing
+    AbortProcess,
+    /// Leave the service [`Failed`](crate::services::status::ServiceStatus::Failed) and report
+    /// the panic to the errors stream, the other services keep running
+    #[default]
+    MarkFailed,
+    /// Try again up to `retries` times before marking the service failed
+    Restart { retries: usize },
+}
+
+/// Framework wide utilities every [`ServiceContext`] is built from
+#[derive(Clone)]
+pub struct ContextConfig {
+    pub clock: Arc<dyn Clock>,
+    pub metrics: Arc<dyn MetricsRecorder>,
+    pub features: FeatureFlags,
+    /// Objects shared by the services, see [`SharedResources`]
+    pub resources: SharedResources,
+    /// Checked by the overwatch runner when a service requests a relay to another one
+    pub relay_policy: Arc<dyn RelayPolicy>,
+    /// Number of status transitions and state snapshots kept for debugging, 0 disables it
+    /// See [`OverwatchHandle::history_since`](crate::overwatch::handle::OverwatchHandle::history_since).
+    pub history_capacity: usize,
+    /// Where the application settings came from (files, environment...), for the boot report
+    pub config_sources: Vec<String>,
+    /// How long the boot report waits for the started services to report they are running
+    pub boot_report_timeout: Duration,
+    /// Handlers of application defined commands, tried in order
+    pub command_handlers: Vec<Arc<dyn CustomCommandHandler>>,
+    pub panic_policy: PanicPolicy,
+    /// Install a panic hook capturing the backtrace of service panics, see
+    /// [`ServiceCrash::backtrace`](crate::overwatch::crash::ServiceCrash::backtrace)
+    /// The hook is process wide and kept once installed, panics raised outside of services are
+    /// passed on to the previous hook untouched.
+    pub panic_backtraces: bool,
+    /// How long services are given to finish on their own on shutdown before being aborted,
+    /// [`DEFAULT_SHUTDOWN_GRACE`] by default
+    pub shutdown_grace: Duration,
+    pub shutdown_order: ShutdownOrder,
+    /// Virtual latency of the messages sent through relays, `None` outside simulations
+    pub relay_latency: Option<Arc<dyn LatencyModel>>,
+    /// Where the messages delivered through relays are recorded, see
+    /// [`crate::services::recording`]
+    pub recorder: Option<Arc<MessageRecorder>>,
+    /// Counters kept on disk between runs, see [`crate::services::persistent_metrics`]
+    #[cfg(feature = "persistent-metrics")]
+    pub persistent_metrics: Option<Arc<PersistentMetrics>>,
+    pub memory_monitor: Option<MemoryMonitor>,
+    pub watchdog: Option<Watchdog>,
+    /// Messages a service relay hands over before yielding to the executor once, also used by
+    /// [`ServiceContext::maybe_yield`], 0 never yields
+    pub yield_budget: usize,
+    /// How often the services liveness probes are polled, also how long each probe can take
+    pub health_check_interval: Duration,
+    /// Where the collected [`MetricsSnapshot`](crate::overwatch::metrics::MetricsSnapshot)s are
+    /// sent, see [`MetricsExporter`]
+    pub metrics_exporters: Vec<Arc<dyn MetricsExporter>>,
+    pub metrics_export_interval: Duration,
+    /// Worker threads of the named thread pools services run on, see
+    /// [`RuntimePolicy::Pool`](crate::services::runtime::RuntimePolicy::Pool), pools left out
+    /// get one per core
+    /// Services can't start on a pool without worker threads, they fail with
+    /// [`Error::NoWorkerThreads`](crate::overwatch::Error::NoWorkerThreads).
+    pub thread_pools: HashMap<&'static str, usize>,
+}
+
+impl ContextConfig {
+    /// Persisted counters of `service_id`, if enabled
+    #[cfg(feature = "persistent-metrics")]
+    pub(crate) fn persisted_counters(&self, service_id: ServiceId) -> Option<Arc<ServiceCounters>> {
+        self.persistent_metrics
+            .as_ref()
+            .map(|metrics| metrics.counters(service_id))
+    }
+
+    #[cfg(not(feature = "persistent-metrics"))]
+    pub(crate) fn persisted_counters(
+        &self,
+        _service_id: ServiceId,
+    ) -> Option<Arc<ServiceCounters>> {
+        None
+    }
+
+    /// Config for simulations, see [`crate::services::simulation`]: time is virtual and relay
+    /// messages are delayed according to `latency`
+    pub fn simulation<L: LatencyModel>(latency: L) -> Self {
+        Self::default()
+            .with_clock(VirtualClock)
+            .with_relay_latency(latency)
+    }
+
+    pub fn with_clock<C: Clock>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_relay_latency<L: LatencyModel>(mut self, latency: L) -> Self {
+        self.relay_latency = Some(Arc::new(latency));
+        self
+    }
+
+    pub fn with_recorder(mut self, recorder: MessageRecorder) -> Self {
+        self.recorder = Some(Arc::new(recorder));
+        self
+    }
+
+    pub fn with_metrics<M: MetricsRecorder>(mut self, metrics: M) -> Self {
+        self.metrics = Arc::new(metrics);
+        self
+    }
+
+    #[cfg(feature = "persistent-metrics")]
+    pub fn with_persistent_metrics(mut self, metrics: PersistentMetrics) -> Self {
+        self.persistent_metrics = Some(Arc::new(metrics));
+        self
+    }
+
+    pub fn with_memory_monitor(mut self, monitor: MemoryMonitor) -> Self {
+        self.memory_monitor = Some(monitor);
+        self
+    }
+
+    pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.watchdog = Some(watchdog);
+        self
+    }
+
+    pub fn with_thread_pool(mut self, name: &'static str, worker_threads: usize) -> Self {
+        self.thread_pools.insert(name, worker_threads);
+        self
+    }
+
+    pub fn with_features(mut self, features: FeatureFlags) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn with_resources(mut self, resources: SharedResources) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    /// Share `resource` with all services, see [`SharedResources::with`]
+    pub fn with_resource<T: Send + Sync + 'static>(mut self, resource: T) -> Self {
+        self.resources = self.resources.with(resource);
+        self
+    }
+
+    pub fn with_relay_policy<P: RelayPolicy>(mut self, policy: P) -> Self {
+        self.relay_policy = Arc::new(policy);
+        self
+    }
+
+    pub fn with_history_capacity(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity;
+        self
+    }
+
+    pub fn with_config_source(mut self, source: impl Into<String>) -> Self {
+        self.config_sources.push(source.into());
+        self
+    }
+
+    pub fn with_boot_report_timeout(mut self, timeout: Duration) -> Self {
+        self.boot_report_timeout = timeout;
+        self
+    }
+
+    pub fn with_shutdown_grace(mut self, grace: Duration) -> Self {
+        self.shutdown_grace = grace;
+        self
+    }
+
+    pub fn with_shutdown_order(mut self, order: ShutdownOrder) -> Self {
+        self.shutdown_order = order;
+        self
+    }
+
+    pub fn with_yield_budget(mut self, budget: usize) -> Self {
+        self.yield_budget = budget;
+        self
+    }
+
+    pub fn with_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    pub fn with_metrics_exporter<E: MetricsExporter>(mut self, exporter: E) -> Self {
+        self.metrics_exporters.push(Arc::new(exporter));
+        self
+    }
+
+    pub fn with_metrics_export_interval(mut self, interval: Duration) -> Self {
+        self.metrics_export_interval = interval;
+        self
+    }
+
+    pub fn with_panic_policy(mut self, policy: PanicPolicy) -> Self {
+        self.panic_policy = policy;
+        self
+    }
+
+    pub fn with_panic_backtraces(mut self, enabled: bool) -> Self {
+        self.panic_backtraces = enabled;
+        self
+    }
+
+    /// Register a handler for commands sent with
+    /// [`OverwatchHandle::send_custom`](crate::overwatch::handle::OverwatchHandle::send_custom)
+    pub fn with_command_handler<H: CustomCommandHandler>(mut self, handler: H) -> Self {
+        self.command_handlers.push(Arc::new(handler));
+        self
+    }
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            metrics: Arc::new(NoMetrics),
+            features: FeatureFlags::default(),
+            resources: SharedResources::default(),
+            relay_policy: Arc::new(AllowAll),
+            history_capacity: 0,
+            config_sources: Vec::new(),
+            boot_report_timeout: Duration::from_secs(5),
+            command_handlers: Vec::new(),
+            panic_policy: PanicPolicy::default(),
+            panic_backtraces: false,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            shutdown_order: ShutdownOrder::default(),
+            relay_latency: None,
+            recorder: None,
+            #[cfg(feature = "persistent-metrics")]
+            persistent_metrics: None,
+            memory_monitor: None,
+            watchdog: None,
+            yield_budget: DEFAULT_YIELD_BUDGET,
+            health_check_interval: Duration::from_secs(10),
+            metrics_exporters: Vec::new(),
+            metrics_export_interval: Duration::from_secs(10),
+            thread_pools: HashMap::new(),
+        }
+    }
+}
+
+impl Debug for ContextConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextConfig")
+            .field("features", &self.features)
+            .field("resources", &self.resources)
+            .field("history_capacity", &self.history_capacity)
+            .field("config_sources", &self.config_sources)
+            .field("boot_report_timeout", &self.boot_report_timeout)
+            .field("panic_policy", &self.panic_policy)
+            .field("panic_backtraces", &self.panic_backtraces)
+            .field("shutdown_grace", &self.shutdown_grace)
+            .field("shutdown_order", &self.shutdown_order)
+            .field("memory_monitor", &self.memory_monitor)
+            .field("watchdog", &self.watchdog)
+            .field("yield_budget", &self.yield_budget)
+            .field("health_check_interval", &self.health_check_interval)
+            .field("metrics_export_interval", &self.metrics_export_interval)
+            .field("thread_pools", &self.thread_pools)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Spawns a `!Send` task on the local thread of a service, see [`ScopedSpawner::spawn_local`]
+type LocalJob = Box<dyn FnOnce() + Send>;
+
+/// Spawner whose tasks do not outlive the service instance that spawned them
+/// Its tasks are counted in the service [`TaskMetrics`](crate::overwatch::metrics::TaskMetrics),
+/// and awaited before the service is reported stopped.
+#[derive(Clone, Debug)]
+pub struct ScopedSpawner {
+    /// Tasks are named after the service in tokio-console
+    service_id: ServiceId,
+    runtime: Handle,
+    cancellation_token: CancellationToken,
+    tasks: TaskTracker,
+    stats: Arc<TaskStats>,
+    /// Jobs queue of the thread running the `!Send` tasks, started by the first of them
+    local: Arc<Mutex<Option<mpsc::UnboundedSender<LocalJob>>>>,
+}
+
+impl ScopedSpawner {
+    /// Spawn a task that is cancelled once the service instance is stopped
+    /// The task output is `None` if it was cancelled before finishing.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<Option<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let running = self.stats.spawned();
+        let future = self
+            .cancellation_token
+            .clone()
+            .run_until_cancelled_owned(future);
+        spawn_named(
+            &self.runtime,
+            self.service_id,
+            self.tasks.track_future(async move {
+                let _running = running;
+                future.await
+            }),
+        )
+    }
+
+    /// Run blocking code on the blocking thread pool
+    /// It can't be cancelled, so it should check the service
+    /// [`cancellation_token`](ServiceContext::cancellation_token) if it runs for long, the
+    /// service isn't reported stopped until it returns.
+    pub fn spawn_blocking<F, R>(&self, f: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let running = self.stats.spawned();
+        self.tasks.spawn_blocking_on(
+            move || {
+                let _running = running;
+                f()
+            },
+            &self.runtime,
+        )
+    }
+
+    /// Run a `!Send` future built by `f` on the local thread of the service
+    /// All the `!Send` tasks of a service instance share a single thread, started along with the
+    /// first of them. The future is cancelled once the service instance is stopped, the task
+    /// output is `None` if it was cancelled before finishing.
+    pub fn spawn_local<F, Fut>(&self, f: F) -> JoinHandle<Option<Fut::Output>>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let running = self.stats.spawned();
+        // taken right away, so the task is waited for even if it is not spawned yet
+        let tracked = self.tasks.token();
+        let cancellation_token = self.cancellation_token.clone();
+        let (output_sender, output) = oneshot::channel();
+        let job: LocalJob = Box::new(move || {
+            let task = tokio::task::spawn_local(async move {
+                let _running = running;
+                let _tracked = tracked;
+                cancellation_token.run_until_cancelled_owned(f()).await
+            });
+            tokio::task::spawn_local(async move {
+                let _ = output_sender.send(task.await);
+            });
+        });
+        // a job that can't be queued is dropped, and its task reported cancelled
+        let _ = self.local_jobs().send(job);
+        spawn_named(&self.runtime, self.service_id, async move {
+            match output.await {
+                Ok(Ok(output)) => output,
+                Ok(Err(e)) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                _ => None,
+            }
+        })
+    }
+
+    /// Jobs queue of the local thread, started if it isn't running
+    fn local_jobs(&self) -> mpsc::UnboundedSender<LocalJob> {
+        let mut local = self.local.lock().expect("Local thread lock not poisoned");
+        local
+            .get_or_insert_with(|| start_local_thread(self.service_id))
+            .clone()
+    }
+
+    /// Wait up to the configured [`ContextConfig::shutdown_grace`] for the tasks spawned so far
+    /// to finish
+    /// The local thread ends once its tasks are done. Tasks spawned afterwards are not waited
+    /// for, those still running once the grace elapsed are logged and left behind.
+    pub(crate) async fn join_all(&self, config: &ContextConfig) {
+        self.tasks.close();
+        // the local thread runs its remaining tasks to completion once its queue is closed
+        self.local
+            .lock()
+            .expect("Local thread lock not poisoned")
+            .take();
+        let grace = config.shutdown_grace;
+        if config
+            .clock
+            .timeout(grace, self.tasks.wait())
+            .await
+            .is_err()
+        {
+            warn!(
+                "Service {} left {} tasks running after {grace:?}",
+                self.service_id,
+                self.tasks.len()
+            );
+        }
+    }
+}
+
+/// Start the thread running the `!Send` tasks of the `service_id` service, on a [`LocalSet`]
+/// It runs the jobs it is sent until its queue is closed, then until their tasks are done.
+fn start_local_thread(service_id: ServiceId) -> mpsc::UnboundedSender<LocalJob> {
+    let (jobs_sender, mut jobs) = mpsc::unbounded_channel::<LocalJob>();
+    std::thread::Builder::new()
+        .name(format!("{service_id}-local"))
+        .spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Local runtime to build properly");
+            let local = LocalSet::new();
+            local.block_on(&runtime, async move {
+                while let Some(job) = jobs.recv().await {
+                    job();
+                }
+            });
+            runtime.block_on(local);
+        })
+        .expect("Local thread to start");
+    jobs_sender
+}
+
+/// Runtime utilities available to a service instance
+/// New cross-cutting utilities are added here instead of to the service state handle, so
+/// services `init` signatures are not affected.
+#[derive(Clone, Debug)]
+pub struct ServiceContext {
+    service_id: ServiceId,
+    config: ContextConfig,
+    spawner: ScopedSpawner,
+    cancellation_token: CancellationToken,
+    yield_budget: Arc<YieldBudget>,
+    /// Where liveness probes are registered, not polled unless set by the overwatch runner
+    health: HealthRegistry,
+}
+
+impl ServiceContext {
+    pub fn new(service_id: ServiceId, config: ContextConfig, runtime: Handle) -> Self {
+        let cancellation_token = CancellationToken::new();
+        let yield_budget = Arc::new(YieldBudget::new(config.yield_budget));
+        Self {
+            service_id,
+            config,
+            spawner: ScopedSpawner {
+                service_id,
+                runtime,
+                cancellation_token: cancellation_token.clone(),
+                tasks: TaskTracker::new(),
+                stats: Arc::default(),
+                local: Arc::default(),
+            },
+            cancellation_token,
+            yield_budget,
+            health: HealthRegistry::default(),
+        }
+    }
+
+    pub(crate) fn with_health(mut self, health: HealthRegistry) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub(crate) fn with_task_stats(mut self, stats: Arc<TaskStats>) -> Self {
+        self.spawner.stats = stats;
+        self
+    }
+
+    /// Cancel the service tasks once `parent` is, as well as when the service stops
+    pub(crate) fn cancelled_with(mut self, parent: &CancellationToken) -> Self {
+        self.cancellation_token = parent.child_token();
+        self.spawner.cancellation_token = self.cancellation_token.clone();
+        self
+    }
+
+    pub fn service_id(&self) -> ServiceId {
+        self.service_id
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.config.clock.as_ref()
+    }
+
+    pub(crate) fn shared_clock(&self) -> Arc<dyn Clock> {
+        Arc::clone(&self.config.clock)
+    }
+
+    pub fn spawner(&self) -> &ScopedSpawner {
+        &self.spawner
+    }
+
+    /// Token cancelled once the service instance is stopped
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    pub fn features(&self) -> &FeatureFlags {
+        &self.config.features
+    }
+
+    pub fn resources(&self) -> &SharedResources {
+        &self.config.resources
+    }
+
+    /// Count a unit of work, yielding to the executor once every
+    /// [`yield_budget`](ContextConfig::yield_budget) of them
+    /// Call it in loops going through many items without awaiting, so other services keep
+    /// running meanwhile. The budget is shared by the clones of this context.
+    pub async fn maybe_yield(&self) {
+        self.yield_budget.maybe_yield().await;
+    }
+
+    /// Register the liveness probe of this service instance, replacing the previous one
+    /// The probe is dropped once the instance is stopped, a restarted service registers it again.
+    pub fn register_liveness_probe<P: LivenessProbe>(&self, probe: P) {
+        self.health.register(
+            self.service_id,
+            Arc::new(probe),
+            self.cancellation_token.clone(),
+        );
+    }
+
+    pub fn increment_counter(&self, name: &'static str, value: u64) {
+        self.config
+            .metrics
+            .increment_counter(self.service_id, name, value);
+    }
+
+    pub fn record_gauge(&self, name: &'static str, value: f64) {
+        self.config
+            .metrics
+            .record_gauge(self.service_id, name, value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::context::{ContextConfig, FeatureFlags, ServiceContext};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn scoped_tasks_are_cancelled_with_the_service() {
+        let context = ServiceContext::new(
+            "context",
+            ContextConfig::default().with_features(FeatureFlags::new(["fast-path"])),
+            tokio::runtime::Handle::current(),
+        );
+        assert!(context.features().is_enabled("fast-path"));
+        assert!(!context.features().is_enabled("slow-path"));
+
+        let finished = context.spawner().spawn(async { 1 });
+        assert_eq!(finished.await.unwrap(), Some(1));
+
+        let pending = context
+            .spawner()
+            .spawn(tokio::time::sleep(Duration::from_secs(60)));
+        context.cancellation_token().cancel();
+        assert_eq!(pending.await.unwrap(), None);
+    }
+}
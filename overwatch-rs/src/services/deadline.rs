@@ -0,0 +1,189 @@
+// std
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+// crates
+use thiserror::Error;
+use tracing::debug;
+// internal
+use crate::services::clock::Clock;
+use crate::services::context::ServiceContext;
+use crate::services::relay::{InboundRelay, OutboundRelay, RelayError, RelayMessage};
+
+/// Counter of messages whose deadline passed before they were handled
+pub const DEADLINE_EXPIRED_METRIC: &str = "deadline_expired";
+/// Counter of messages whose handling was cancelled as it didn't finish by their deadline
+pub const DEADLINE_MISSED_METRIC: &str = "deadline_missed";
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadlineError {
+    #[error("message deadline passed {late:?} before it was handled")]
+    Expired { late: Duration },
+
+    #[error("message handling was cancelled after {budget:?}, when its deadline passed")]
+    Missed { budget: Duration },
+}
+
+/// Message that must be handled before `deadline`, measured with the [`Clock`] of the
+/// application
+#[derive(Debug)]
+pub struct Deadline<M> {
+    pub message: M,
+    pub deadline: Instant,
+}
+
+impl<M: 'static> RelayMessage for Deadline<M> {}
+
+impl<M> Deadline<M> {
+    /// Run `handler` on the message, cancelling it if it is still running at the deadline
+    /// The handler isn't run at all if the deadline already passed. Both cases are counted in
+    /// the service metrics, as [`DEADLINE_EXPIRED_METRIC`] and [`DEADLINE_MISSED_METRIC`].
+    pub async fn handle<F, Fut>(
+        self,
+        context: &ServiceContext,
+        handler: F,
+    ) -> Result<Fut::Output, DeadlineError>
+    where
+        F: FnOnce(M) -> Fut,
+        Fut: Future,
+    {
+        let now = context.clock().now();
+        let Some(budget) = self.deadline.checked_duration_since(now) else {
+            let late = now.saturating_duration_since(self.deadline);
+            debug!("Dropping message {late:?} past its deadline");
+            context.increment_counter(DEADLINE_EXPIRED_METRIC, 1);
+            return Err(DeadlineError::Expired { late });
+        };
+        context
+            .clock()
+            .timeout(budget, handler(self.message))
+            .await
+            .map_err(|_| {
+                debug!("Message handling cancelled after {budget:?}, at its deadline");
+                context.increment_counter(DEADLINE_MISSED_METRIC, 1);
+                DeadlineError::Missed { budget }
+            })
+    }
+}
+
+/// Relay attaching a deadline to every message sent through it
+pub struct DeadlineRelay<M> {
+    relay: OutboundRelay<Deadline<M>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<M> Clone for DeadlineRelay<M> {
+    fn clone(&self) -> Self {
+        Self {
+            relay: self.relay.clone(),
+            clock: Arc::clone(&self.clock),
+        }
+    }
+}
+
+impl<M> DeadlineRelay<M> {
+    /// Wrap `relay`, computing deadlines with the clock of the sending service `context`
+    pub fn new(relay: OutboundRelay<Deadline<M>>, context: &ServiceContext) -> Self {
+        Self {
+            relay,
+            clock: context.shared_clock(),
+        }
+    }
+
+    /// Send a message that must be handled within `timeout` from now
+    pub async fn send(&self, message: M, timeout: Duration) -> Result<(), (RelayError, M)> {
+        self.send_by(message, self.clock.now() + timeout).await
+    }
+
+    /// Send a message that must be handled before `deadline`
+    pub async fn send_by(&self, message: M, deadline: Instant) -> Result<(), (RelayError, M)> {
+        self.relay
+            .send(Deadline { message, deadline })
+            .await
+            .map_err(|(e, Deadline { message, .. })| (e, message))
+    }
+
+    pub fn into_inner(self) -> OutboundRelay<Deadline<M>> {
+        self.relay
+    }
+}
+
+/// Handle every message of `inbound_relay` with `handler`, within their deadlines, until the
+/// relay is closed
+/// Messages that miss their deadline are reported through the service metrics and skipped,
+/// see [`Deadline::handle`].
+pub async fn dispatch_with_deadlines<M, F, Fut>(
+    inbound_relay: &mut InboundRelay<Deadline<M>>,
+    context: &ServiceContext,
+    mut handler: F,
+) where
+    F: FnMut(M) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    while let Some(message) = inbound_relay.recv().await {
+        let _ = message.handle(context, &mut handler).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::context::{ContextConfig, MetricsRecorder, ServiceContext};
+    use crate::services::deadline::{
+        dispatch_with_deadlines, Deadline, DeadlineError, DeadlineRelay, DEADLINE_EXPIRED_METRIC,
+        DEADLINE_MISSED_METRIC,
+    };
+    use crate::services::relay::relay;
+    use crate::services::ServiceId;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    #[derive(Clone, Default)]
+    struct Counters(Arc<Mutex<Vec<&'static str>>>);
+
+    impl MetricsRecorder for Counters {
+        fn increment_counter(&self, _service_id: ServiceId, name: &'static str, _value: u64) {
+            self.0.lock().unwrap().push(name);
+        }
+
+        fn record_gauge(&self, _service_id: ServiceId, _name: &'static str, _value: f64) {}
+    }
+
+    #[tokio::test]
+    async fn late_messages_are_skipped_and_counted() {
+        let counters = Counters::default();
+        let context = ServiceContext::new(
+            "deadline",
+            ContextConfig::default().with_metrics(counters.clone()),
+            tokio::runtime::Handle::current(),
+        );
+        let expired = Deadline {
+            message: 1,
+            deadline: Instant::now() - Duration::from_millis(10),
+        };
+        assert!(matches!(
+            expired.handle(&context, |_| async {}).await,
+            Err(DeadlineError::Expired { .. })
+        ));
+
+        let (mut inbound, outbound) = relay(4);
+        let relay = DeadlineRelay::new(outbound, &context);
+        relay.send(10, Duration::from_secs(5)).await.unwrap();
+        relay.send(1000, Duration::from_millis(20)).await.unwrap();
+        relay.send(20, Duration::from_secs(5)).await.unwrap();
+        drop(relay);
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        dispatch_with_deadlines(&mut inbound, &context, |millis| {
+            let handled = Arc::clone(&handled);
+            async move {
+                tokio::time::sleep(Duration::from_millis(millis)).await;
+                handled.lock().unwrap().push(millis);
+            }
+        })
+        .await;
+        assert_eq!(*handled.lock().unwrap(), [10, 20]);
+        assert_eq!(
+            *counters.0.lock().unwrap(),
+            [DEADLINE_EXPIRED_METRIC, DEADLINE_MISSED_METRIC]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Relay messages stamped with a correlation id, to follow a logical request across services
+//! A service taking [`Envelope`]s handles them with [`Envelope::handle`], messages it sends
+//! meanwhile through an [`EnvelopeRelay`] carry the same [`CorrelationId`], and the tracing spans
+//! of every hop are nested under the span the request was first sent from.
+// std
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+// crates
+use tracing::{info_span, Instrument, Span};
+// internal
+use crate::services::context::ServiceContext;
+use crate::services::relay::{InboundRelay, OutboundRelay, RelayError, RelayMessage};
+use crate::services::ServiceId;
+
+tokio::task_local! {
+    static CURRENT: CorrelationId;
+}
+
+/// Id shared by the messages of one logical request, unique within the process
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    pub fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Id of the envelope being handled by the current task, see [`Envelope::handle`]
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|current| *current).ok()
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for CorrelationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for CorrelationId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Message along with the request it belongs to and the service it comes from
+#[derive(Debug)]
+pub struct Envelope<M> {
+    pub message: M,
+    pub correlation_id: CorrelationId,
+    /// `None` if sent from outside any service
+    pub origin: Option<ServiceId>,
+    /// Child of the span the message was sent from
+    span: Span,
+}
+
+impl<M: 'static> RelayMessage for Envelope<M> {}
+
+impl<M> Envelope<M> {
+    /// Stamp `message` with the correlation id of the envelope being handled, or a new one
+    pub fn new(message: M, origin: Option<ServiceId>) -> Self {
+        let correlation_id = CorrelationId::current().unwrap_or_default();
+        let span = info_span!(
+            "envelope",
+            correlation_id = %correlation_id,
+            origin = origin.unwrap_or("external"),
+        );
+        Self {
+            message,
+            correlation_id,
+            origin,
+            span,
+        }
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Run `handler` on the message within the envelope span
+    /// Envelopes created meanwhile by the same task keep the correlation id.
+    pub async fn handle<F, Fut>(self, handler: F) -> Fut::Output
+    where
+        F: FnOnce(M) -> Fut,
+        Fut: Future,
+    {
+        let Self {
+            message,
+            correlation_id,
+            span,
+            ..
+        } = self;
+        CURRENT
+            .scope(correlation_id, handler(message).instrument(span))
+            .await
+    }
+}
+
+/// Relay stamping every message sent through it, see [`Envelope::new`]
+pub struct EnvelopeRelay<M> {
+    relay: OutboundRelay<Envelope<M>>,
+    origin: Option<ServiceId>,
+}
+
+impl<M> Clone for EnvelopeRelay<M> {
+    fn clone(&self) -> Self {
+        Self {
+            relay: self.relay.clone(),
+            origin: self.origin,
+        }
+    }
+}
+
+impl<M> EnvelopeRelay<M> {
+    /// Wrap `relay`, attributing the messages to the sending service `context`
+    pub fn new(relay: OutboundRelay<Envelope<M>>, context: &ServiceContext) -> Self {
+        Self {
+            relay,
+            origin: Some(context.service_id()),
+        }
+    }
+
+    /// Wrap `relay` for sending from outside any service
+    pub fn external(relay: OutboundRelay<Envelope<M>>) -> Self {
+        Self {
+            relay,
+            origin: None,
+        }
+    }
+
+    /// Send `message`, returning the correlation id it was stamped with
+    pub async fn send(&self, message: M) -> Result<CorrelationId, (RelayError, M)> {
+        let envelope = Envelope::new(message, self.origin);
+        let correlation_id = envelope.correlation_id;
+        self.relay
+            .send(envelope)
+            .await
+            .map_err(|(e, Envelope { message, .. })| (e, message))?;
+        Ok(correlation_id)
+    }
+
+    pub fn into_inner(self) -> OutboundRelay<Envelope<M>> {
+        self.relay
+    }
+}
+
+/// Handle every message of `inbound_relay` with `handler` until the relay is closed, see
+/// [`Envelope::handle`]
+pub async fn dispatch_enveloped<M, F, Fut>(
+    inbound_relay: &mut InboundRelay<Envelope<M>>,
+    mut handler: F,
+) where
+    F: FnMut(M) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    while let Some(envelope) = inbound_relay.recv().await {
+        envelope.handle(&mut handler).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::context::{ContextConfig, ServiceContext};
+    use crate::services::envelope::{dispatch_enveloped, CorrelationId, EnvelopeRelay};
+    use crate::services::relay::relay;
+
+    #[tokio::test]
+    async fn correlation_id_follows_the_request() {
+        let context = ServiceContext::new(
+            "frontend",
+            ContextConfig::default(),
+            tokio::runtime::Handle::current(),
+        );
+        let (mut frontend_inbound, frontend) = relay(4);
+        let (mut backend_inbound, backend) = relay(4);
+        let frontend = EnvelopeRelay::external(frontend);
+        let backend = EnvelopeRelay::new(backend, &context);
+
+        let first = frontend.send("first").await.unwrap();
+        let second = frontend.send("second").await.unwrap();
+        assert_ne!(first, second);
+        drop(frontend);
+        dispatch_enveloped(&mut frontend_inbound, |message| {
+            let backend = backend.clone();
+            async move {
+                assert!(CorrelationId::current().is_some());
+                backend.send(message).await.unwrap();
+            }
+        })
+        .await;
+        drop(backend);
+
+        let mut forwarded = Vec::new();
+        while let Some(envelope) = backend_inbound.recv().await {
+            assert_eq!(envelope.origin, Some("frontend"));
+            forwarded.push((envelope.message, envelope.correlation_id));
+        }
+        assert_eq!(forwarded, [("first", first), ("second", second)]);
+        assert_eq!(CorrelationId::current(), None);
+    }
+}
@@ -0,0 +1,159 @@
+// std
+use std::pin::Pin;
+use std::task::{Context, Poll};
+// crates
+use futures::Stream;
+// internal
+
+/// Identifier of a source within a [`FanIn`], as returned by [`FanIn::add`]
+pub type SourceId = usize;
+
+/// How a [`FanIn`] chooses which source to pull the next item from
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PollStrategy {
+    /// Take one item from each source in turn
+    #[default]
+    RoundRobin,
+    /// Take up to `weight` consecutive items from each source before moving to the next one
+    WeightedFair,
+}
+
+/// Consumption counters for a single [`FanIn`] source
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SourceMetrics {
+    pub weight: usize,
+    pub consumed: u64,
+    pub finished: bool,
+}
+
+struct Source<T> {
+    stream: Pin<Box<dyn Stream<Item = T> + Send>>,
+    metrics: SourceMetrics,
+}
+
+/// Merge several message streams (inbound relays, subscriptions...) into a single one
+/// Sources are polled in turns so a chatty one cannot starve the others.
+/// The merged stream finishes once every source has finished.
+pub struct FanIn<T> {
+    sources: Vec<Source<T>>,
+    strategy: PollStrategy,
+    /// Source to be polled next
+    cursor: usize,
+    /// Items taken from the current source in its turn
+    taken: usize,
+}
+
+impl<T> FanIn<T> {
+    pub fn new(strategy: PollStrategy) -> Self {
+        Self {
+            sources: Vec::new(),
+            strategy,
+            cursor: 0,
+            taken: 0,
+        }
+    }
+
+    /// Add a new source, `weight` is only relevant for [`PollStrategy::WeightedFair`] and
+    /// is bumped to 1 if 0.
+    pub fn add<St>(&mut self, stream: St, weight: usize) -> SourceId
+    where
+        St: Stream<Item = T> + Send + 'static,
+    {
+        self.sources.push(Source {
+            stream: Box::pin(stream),
+            metrics: SourceMetrics {
+                weight: weight.max(1),
+                ..Default::default()
+            },
+        });
+        self.sources.len() - 1
+    }
+
+    pub fn strategy(&self) -> PollStrategy {
+        self.strategy
+    }
+
+    /// Consumption counters of a given source
+    pub fn metrics(&self, source: SourceId) -> Option<SourceMetrics> {
+        self.sources.get(source).map(|source| source.metrics)
+    }
+
+    /// Consumption counters of all sources, indexed by [`SourceId`]
+    pub fn all_metrics(&self) -> impl Iterator<Item = SourceMetrics> + '_ {
+        self.sources.iter().map(|source| source.metrics)
+    }
+
+    fn advance(&mut self) {
+        self.cursor = (self.cursor + 1) % self.sources.len();
+        self.taken = 0;
+    }
+}
+
+impl<T> Stream for FanIn<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        for _ in 0..this.sources.len() {
+            let source = &mut this.sources[this.cursor];
+            if source.metrics.finished {
+                this.advance();
+                continue;
+            }
+            match source.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    source.metrics.consumed += 1;
+                    let weight = source.metrics.weight;
+                    this.taken += 1;
+                    let turn_over = match this.strategy {
+                        PollStrategy::RoundRobin => true,
+                        PollStrategy::WeightedFair => this.taken >= weight,
+                    };
+                    if turn_over {
+                        this.advance();
+                    }
+                    return Poll::Ready(Some(item));
+                }
+                Poll::Ready(None) => {
+                    source.metrics.finished = true;
+                    this.advance();
+                }
+                Poll::Pending => this.advance(),
+            }
+        }
+        if this.sources.iter().all(|source| source.metrics.finished) {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::fan_in::{FanIn, PollStrategy};
+    use futures::{stream, StreamExt};
+
+    fn sources(strategy: PollStrategy) -> FanIn<&'static str> {
+        let mut fan_in = FanIn::new(strategy);
+        fan_in.add(stream::iter(["a"; 6]), 2);
+        fan_in.add(stream::iter(["b"; 3]), 1);
+        fan_in
+    }
+
+    #[tokio::test]
+    async fn round_robin_alternates_sources() {
+        let items: Vec<_> = sources(PollStrategy::RoundRobin).collect().await;
+        assert_eq!(items, ["a", "b", "a", "b", "a", "b", "a", "a", "a"]);
+    }
+
+    #[tokio::test]
+    async fn weighted_fair_follows_weights() {
+        let mut fan_in = sources(PollStrategy::WeightedFair);
+        let items: Vec<_> = fan_in.by_ref().collect().await;
+        assert_eq!(items, ["a", "a", "b", "a", "a", "b", "a", "a", "b"]);
+        assert_eq!(fan_in.metrics(0).unwrap().consumed, 6);
+        assert_eq!(fan_in.metrics(1).unwrap().consumed, 3);
+        assert!(fan_in.all_metrics().all(|metrics| metrics.finished));
+    }
+}
@@ -1,14 +1,47 @@
+// std
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 // crates
+use futures::future::{AbortHandle, Abortable, BoxFuture};
+use futures::FutureExt;
 use tokio::runtime::Handle;
-use tracing::info;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, Instrument};
 // internal
+use crate::overwatch::crash::{in_service, in_service_task, take_panic_backtrace, ServiceCrash};
+use crate::overwatch::events::LifecycleEvent;
 use crate::overwatch::handle::OverwatchHandle;
-use crate::services::life_cycle::LifecycleHandle;
-use crate::services::relay::{relay, InboundRelay, OutboundRelay};
+use crate::overwatch::teardown::ServiceExit;
+use crate::overwatch::Error;
+use crate::services::broadcast::{BroadcastRelay, BroadcastingService, ServiceBroadcast};
+use crate::services::clock::Clock;
+use crate::services::context::{PanicPolicy, ServiceContext};
+use crate::services::ids::namespaced;
+use crate::services::life_cycle::{LifecycleHandle, LifecycleMessage, StopReason};
+use crate::services::output::ServiceOutput;
+use crate::services::pipeline::{Downstream, PipelineStage};
+use crate::services::rate_limit::RateLimiter;
+use crate::services::relay::{
+    monitored_relay, BackpressureMonitor, InboundRelay, OutboundRelay, RelayError, RelayMessage,
+};
+use crate::services::resources::SharedResources;
 use crate::services::settings::{SettingsNotifier, SettingsUpdater};
-use crate::services::state::{StateHandle, StateOperator, StateUpdater};
-use crate::services::status::{StatusHandle, StatusWatcher};
-use crate::services::{ServiceCore, ServiceData, ServiceId, ServiceState};
+use crate::services::state::{
+    PersistContext, SnapshotRequest, StateHandle, StateOperator, StateUpdater,
+};
+use crate::services::status::{ServiceStatus, StatusHandle, StatusWatcher};
+use crate::services::supervisor::Supervisor;
+use crate::services::{
+    service_span, ServiceCore, ServiceData, ServiceId, ServiceState, TRACING_TARGET,
+};
+use crate::utils::runtime::spawn_checked;
+use crate::DynError;
 
 // TODO: Abstract handle over state, to differentiate when the service is running and when it is not
 // that way we can expose a better API depending on what is happenning. Would get rid of the probably
@@ -16,6 +49,9 @@ use crate::services::{ServiceCore, ServiceData, ServiceId, ServiceState};
 /// Service handle
 /// This is used to access different parts of the service
 pub struct ServiceHandle<S: ServiceData> {
+    /// Service id, [`ServiceData::SERVICE_ID`] within the namespace of its bundle unless the
+    /// service is a pool member
+    id: ServiceId,
     /// Message channel relay
     /// Would be None if service is not running
     /// Will contain the channel if service is running
@@ -25,6 +61,38 @@ pub struct ServiceHandle<S: ServiceData> {
     settings: SettingsUpdater<S::Settings>,
     status: StatusHandle<S>,
     initial_state: S::State,
+    /// Lifecycle handle shared by every instance this handle spawns
+    lifecycle_handle: LifecycleHandle,
+    /// Running service instance, if any
+    instance: Option<ServiceInstance<S::State>>,
+    /// Initialized instance waiting to be promoted by [`ServiceHandle::failover`], if any
+    standby: Option<StandbyInstance<S>>,
+    /// Consecutive restarts made by the [`RestartPolicy`](crate::services::supervisor::RestartPolicy)
+    restarts: Arc<AtomicUsize>,
+    broadcast: ServiceBroadcast,
+}
+
+/// Resources needed to stop a running service instance
+struct ServiceInstance<State> {
+    abort_handle: AbortHandle,
+    state_updater: StateUpdater<State>,
+    cancellation_token: CancellationToken,
+    task: JoinHandle<ServiceExit>,
+}
+
+/// Running service instance taken out of its [`ServiceHandle`] to wait for it to end, see
+/// [`ServiceHandle::take_task`]
+pub struct ServiceTask {
+    id: ServiceId,
+    join: Box<dyn FnOnce(Duration) -> BoxFuture<'static, ServiceExit> + Send>,
+}
+
+/// Initialized service instance whose main loop does not run until promoted
+struct StandbyInstance<S: ServiceData> {
+    instance: ServiceInstance<S::State>,
+    outbound_relay: Option<OutboundRelay<S::Message>>,
+    /// Dropping it discards the standby instance
+    promote: oneshot::Sender<()>,
 }
 
 /// Service core resources
@@ -38,6 +106,9 @@ pub struct ServiceStateHandle<S: ServiceData> {
     pub settings_reader: SettingsNotifier<S::Settings>,
     pub state_updater: StateUpdater<S::State>,
     pub lifecycle_handle: LifecycleHandle,
+    /// Runtime utilities for this service instance
+    pub context: ServiceContext,
+    broadcast: ServiceBroadcast,
 }
 
 /// Main service executor
@@ -47,32 +118,70 @@ pub struct ServiceRunner<S: ServiceData> {
     state_handle: StateHandle<S::State, S::StateOperator>,
     lifecycle_handle: LifecycleHandle,
     initial_state: S::State,
+    /// Completes when the service drops `service_state.inbound_relay`
+    /// `None` if the service takes no messages
+    relay_dropped: Option<oneshot::Receiver<()>>,
+    supervisor: Supervisor,
 }
 
 impl<S: ServiceData> ServiceHandle<S> {
+    /// Handle for a service running under [`ServiceData::SERVICE_ID`], within the namespace of
+    /// the bundle `overwatch_handle` belongs to if any
     pub fn new(
         settings: S::Settings,
         overwatch_handle: OverwatchHandle,
     ) -> Result<Self, <S::State as ServiceState>::Error> {
-        let initial_state = if let Ok(Some(loaded_state)) = S::StateOperator::try_load(&settings) {
-            info!("Loaded state from Operator");
-            loaded_state
-        } else {
-            info!("Couldn't load state from Operator. Creating from settings.");
-            S::State::from_settings(&settings)?
-        };
+        let id = namespaced(overwatch_handle.namespace(), S::SERVICE_ID);
+        Self::with_id(id, settings, overwatch_handle)
+    }
+
+    /// Handle for a service running under a different id than [`ServiceData::SERVICE_ID`],
+    /// as members of a [`ServicePool`](crate::services::pool::ServicePool) do
+    pub(crate) fn with_id(
+        id: ServiceId,
+        settings: S::Settings,
+        overwatch_handle: OverwatchHandle,
+    ) -> Result<Self, <S::State as ServiceState>::Error> {
+        let initial_state = service_span(id).in_scope(|| Self::load_initial_state(&settings))?;
+        let status = StatusHandle::recorded(
+            id,
+            overwatch_handle.history(),
+            overwatch_handle.context_config().persisted_counters(id),
+            overwatch_handle.events(),
+        );
 
         Ok(Self {
+            id,
             outbound_relay: None,
             overwatch_handle,
             settings: SettingsUpdater::new(settings),
-            status: StatusHandle::new(),
+            status,
             initial_state,
+            lifecycle_handle: LifecycleHandle::new(),
+            instance: None,
+            standby: None,
+            restarts: Arc::default(),
+            broadcast: ServiceBroadcast::new(id, S::BROADCAST_BUFFER_SIZE, S::BROADCAST_REPLAY),
         })
     }
 
+    fn load_initial_state(
+        settings: &S::Settings,
+    ) -> Result<S::State, <S::State as ServiceState>::Error> {
+        if let Ok(Some(loaded_state)) = S::StateOperator::try_load(settings) {
+            info!(target: TRACING_TARGET, "Loaded state from Operator");
+            Ok(loaded_state)
+        } else {
+            info!(
+                target: TRACING_TARGET,
+                "Couldn't load state from Operator. Creating from settings."
+            );
+            S::State::from_settings(settings)
+        }
+    }
+
     pub fn id(&self) -> ServiceId {
-        S::SERVICE_ID
+        self.id
     }
 
     /// Service runtime getter
@@ -92,50 +201,485 @@ impl<S: ServiceData> ServiceHandle<S> {
         self.outbound_relay.clone()
     }
 
+    /// Request a relay with this service, failing with [`RelayError::NoRelay`] if it takes no
+    /// messages
+    pub fn request_relay(&self) -> Result<OutboundRelay<S::Message>, RelayError> {
+        if <S::Message as RelayMessage>::NO_RELAY {
+            return Err(RelayError::NoRelay {
+                service_id: self.id,
+            });
+        }
+        self.relay_with().ok_or(RelayError::AlreadyConnected)
+    }
+
     pub fn status_watcher(&self) -> StatusWatcher {
         self.status.watcher()
     }
 
+    /// Broadcast relay other services subscribe to
+    pub fn broadcast(&self) -> ServiceBroadcast {
+        self.broadcast.clone()
+    }
+
     /// Update settings
     pub fn update_settings(&self, settings: S::Settings) {
         self.settings.update(settings)
     }
 
+    /// Settings the service runs with, as last updated
+    pub fn settings(&self) -> S::Settings {
+        self.settings.notifier().get_updated_settings()
+    }
+
+    /// Run the state operator of the running instance, if any, over its current state with
+    /// [`PersistContext::Snapshot`]
+    /// `None` if there is no instance to snapshot.
+    pub fn request_snapshot(&self) -> Option<SnapshotRequest> {
+        // the state handle is gone if this fails, there is nothing to snapshot
+        self.instance
+            .as_ref()
+            .and_then(|instance| instance.state_updater.snapshot().ok())
+    }
+
+    /// Update settings and reload the initial state from them
+    /// Next instance built with [`ServiceHandle::service_runner`] starts from the new settings
+    /// only, the running instance (if any) is not affected.
+    /// Panics while loading the state are handled as the configured [`PanicPolicy`] says.
+    pub fn reconfigure(&mut self, settings: S::Settings) -> Result<(), Error>
+    where
+        <S::State as ServiceState>::Error: Into<DynError>,
+    {
+        let service_id = self.id;
+        self.initial_state = self.retry_panics(|_| {
+            let loaded = service_span(service_id).in_scope(|| {
+                std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    in_service(|| Self::load_initial_state(&settings))
+                }))
+            });
+            loaded
+                .map_err(|panic| Error::Panicked {
+                    service_id,
+                    message: panic_message(panic.as_ref()),
+                })?
+                .map_err(|e| Error::from(e.into()))
+        })?;
+        self.settings.update(settings);
+        Ok(())
+    }
+
+    /// Run `f` again while it panics, as far as the configured [`PanicPolicy`] allows
+    /// Once the policy gives up the service is marked [`ServiceStatus::Failed`], the panic is
+    /// reported to the errors stream and returned as [`Error::Panicked`].
+    fn retry_panics<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let policy = self.overwatch_handle.context_config().panic_policy;
+        let mut attempt = 0;
+        loop {
+            let message = match f(self) {
+                Err(Error::Panicked { message, .. }) => message,
+                result => return result,
+            };
+            error!(
+                target: TRACING_TARGET,
+                "Service {} panicked while being initialized: {message}", self.id
+            );
+            match policy {
+                PanicPolicy::AbortProcess => std::process::abort(),
+                PanicPolicy::Restart { retries } if attempt < retries => {
+                    attempt += 1;
+                    info!(
+                        target: TRACING_TARGET,
+                        "Initializing service {} again ({attempt}/{retries})", self.id
+                    );
+                }
+                PanicPolicy::MarkFailed | PanicPolicy::Restart { .. } => {
+                    self.status.updater().failed(message.clone());
+                    report_panic(&self.overwatch_handle, self.id, message.clone());
+                    self.overwatch_handle.report_error(
+                        self.id,
+                        Box::new(Error::Panicked {
+                            service_id: self.id,
+                            message: message.clone(),
+                        }),
+                    );
+                    return Err(Error::Panicked {
+                        service_id: self.id,
+                        message,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Stop the running service instance, if any, and discard the standby one
+    /// The service is sent a `Kill` lifecycle message with `reason` and its task is aborted
+    /// right after.
+    /// Any further state update from that instance is rejected.
+    /// Unless stopped to be restarted by its supervisor, the service restarts count is reset.
+    pub fn stop(&mut self, reason: StopReason) {
+        if reason != StopReason::Supervision {
+            self.restarts.store(0, Ordering::Relaxed);
+        }
+        self.discard_standby();
+        self.stop_instance(reason);
+    }
+
+    /// Report the service stopped without starting it, for it to be started later like any
+    /// stopped service, see [`OverwatchRunner::restore`](crate::overwatch::OverwatchRunner::restore)
+    pub fn keep_stopped(&mut self) -> (ServiceId, LifecycleHandle) {
+        self.status.updater().update(ServiceStatus::Stopped);
+        (self.id, self.lifecycle_handle.clone())
+    }
+
+    fn stop_instance(&mut self, reason: StopReason) {
+        if let Some(instance) = self.instance.take() {
+            // the service could not be listening to lifecycle messages, so the error is irrelevant
+            let _ = self.lifecycle_handle.send(LifecycleMessage::Kill(reason));
+            instance.stop();
+            self.replace_relay(None);
+            self.status.updater().update(ServiceStatus::Stopped);
+        }
+    }
+
+    /// Set the relay to the current instance, relays to the previous one cached by overwatch
+    /// handles are dropped
+    fn replace_relay(&mut self, outbound_relay: Option<OutboundRelay<S::Message>>) {
+        self.outbound_relay = outbound_relay;
+        self.overwatch_handle.relay_cache().invalidate(self.id);
+    }
+
+    fn discard_standby(&mut self) {
+        if let Some(standby) = self.standby.take() {
+            standby.instance.discard();
+        }
+    }
+
+    /// Check if an instance of this service was started and not stopped through this handle
+    pub fn is_running(&self) -> bool {
+        self.instance.is_some()
+    }
+
+    /// Check if there is a standby instance ready to be promoted
+    pub fn has_standby(&self) -> bool {
+        self.standby.is_some()
+    }
+
     /// Build a runner for this service
     pub fn service_runner(&mut self) -> ServiceRunner<S> {
         // TODO: add proper status handling here, a service should be able to produce a runner if it is already running.
-        let (inbound_relay, outbound_relay) = relay::<S::Message>(S::SERVICE_RELAY_BUFFER_SIZE);
-        let settings_reader = self.settings.notifier();
+        let (runner, outbound_relay) = self.build_runner();
         // add relay channel to handle
-        self.outbound_relay = Some(outbound_relay);
+        self.replace_relay(outbound_relay);
+        runner
+    }
+
+    /// Build a runner along with the relay to reach it, unless the service takes no messages
+    fn build_runner(&self) -> (ServiceRunner<S>, Option<OutboundRelay<S::Message>>) {
+        let (inbound_relay, outbound_relay, relay_dropped) =
+            if <S::Message as RelayMessage>::NO_RELAY {
+                (InboundRelay::closed(), None, None)
+            } else {
+                let (mut inbound_relay, outbound_relay) = monitored_relay::<S::Message>(
+                    S::SERVICE_RELAY_BUFFER_SIZE,
+                    S::PRIORITY_RELAY_BUFFER_SIZE,
+                    S::BACKPRESSURE_POLICY,
+                    BackpressureMonitor::new(
+                        self.id,
+                        S::BACKPRESSURE_THRESHOLD,
+                        self.overwatch_handle.events().clone(),
+                    ),
+                );
+                let relay_dropped = inbound_relay.dropped_signal();
+                let config = self.overwatch_handle.context_config();
+                let stats = self.overwatch_handle.metrics_registry().service(self.id);
+                let mut inbound_relay = inbound_relay
+                    .paused_by(self.overwatch_handle.relay_pause(self.id))
+                    .counted(config.persisted_counters(self.id))
+                    .yielding(config.yield_budget)
+                    .measured(Arc::clone(stats.relay()));
+                if let Some(limit) = S::RATE_LIMIT {
+                    let limiter = RateLimiter::new(limit, Arc::clone(&config.clock))
+                        .measured(Arc::clone(stats.relay()));
+                    inbound_relay = inbound_relay.rate_limited(limiter);
+                }
+                let outbound_relay = outbound_relay.measured(Arc::clone(stats.relay()));
+                (inbound_relay, Some(outbound_relay), Some(relay_dropped))
+            };
+        let settings_reader = self.settings.notifier();
         let settings = self.settings.notifier().get_updated_settings();
         let operator = S::StateOperator::from_settings(settings);
         let (state_handle, state_updater) =
             StateHandle::<S::State, S::StateOperator>::new(self.initial_state.clone(), operator);
+        let state_handle = state_handle
+            .recorded(self.id, self.overwatch_handle.history())
+            .with_persistence(S::STATE_PERSISTENCE);
 
-        let lifecycle_handle = LifecycleHandle::new();
+        let lifecycle_handle = self.lifecycle_handle.clone();
 
         let service_state = ServiceStateHandle {
             inbound_relay,
             status_handle: self.status.clone(),
-            overwatch_handle: self.overwatch_handle.clone(),
+            overwatch_handle: self.overwatch_handle.for_service(self.id),
             state_updater,
             settings_reader,
             lifecycle_handle: lifecycle_handle.clone(),
+            context: ServiceContext::new(
+                self.id,
+                self.overwatch_handle.context_config().clone(),
+                self.overwatch_handle.runtime().clone(),
+            )
+            .with_health(self.overwatch_handle.health().clone())
+            .with_task_stats(Arc::clone(
+                self.overwatch_handle
+                    .metrics_registry()
+                    .service(self.id)
+                    .tasks(),
+            ))
+            .cancelled_with(&self.overwatch_handle.abort_token()),
+            broadcast: self.broadcast.clone(),
         };
 
-        ServiceRunner {
+        let runner = ServiceRunner {
             service_state,
             state_handle,
             lifecycle_handle,
             initial_state: self.initial_state.clone(),
+            relay_dropped,
+            supervisor: Supervisor::new(
+                self.id,
+                S::RESTART_POLICY,
+                Arc::clone(&self.restarts),
+                self.overwatch_handle.clone(),
+            ),
+        };
+        (runner, outbound_relay)
+    }
+}
+
+impl<State> ServiceInstance<State> {
+    /// Stop the instance, flushing its last state with [`PersistContext::StopFlush`]
+    fn stop(&self) {
+        // the state handle is gone if this fails, there is nothing to flush to
+        let _ = self.state_updater.flush(PersistContext::StopFlush);
+        self.discard();
+    }
+
+    /// Check if the main loop of the instance ended, on its own or stopped
+    fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stop an instance that never ran, without flushing its state
+    fn discard(&self) {
+        self.cancellation_token.cancel();
+        self.state_updater.stop();
+        self.abort_handle.abort();
+    }
+
+    /// Wait up to `grace` for the instance to end on its own, and stop it afterwards
+    async fn join(mut self, grace: Duration, clock: Arc<dyn Clock>) -> ServiceExit {
+        let joined = match clock.timeout(grace, &mut self.task).await {
+            Ok(joined) => joined,
+            Err(_) => {
+                self.stop();
+                (&mut self.task).await
+            }
+        };
+        match joined {
+            Ok(exit) => exit,
+            Err(e) if e.is_panic() => ServiceExit::Panicked,
+            Err(_) => ServiceExit::Aborted,
+        }
+    }
+}
+
+impl<S> ServiceHandle<S>
+where
+    S::State: Send + Sync + 'static,
+    S::StateOperator: Send + 'static,
+    S: ServiceCore + 'static,
+{
+    /// Build a runner for this service and spawn it, keeping track of the running instance
+    /// so it can be later stopped with [`ServiceHandle::stop`].
+    /// If the service is configured with [`ServiceData::WARM_STANDBY`] a standby instance is
+    /// prepared as well, failing to do so is only logged.
+    /// It is a no-op if the service is already running. An instance whose main loop ended on its
+    /// own is not running anymore, it is cleaned up and a new one is started.
+    /// A service panicking in [`ServiceCore::init`] is handled as the configured [`PanicPolicy`]
+    /// says. If it ends up [`ServiceStatus::Failed`] it is left not running, but this still
+    /// succeeds so the other services can be started.
+    /// Any other failure marks the service [`ServiceStatus::Failed`] as well, and is returned.
+    pub fn start(&mut self) -> Result<(ServiceId, LifecycleHandle), Error> {
+        if self
+            .instance
+            .as_ref()
+            .is_some_and(ServiceInstance::is_finished)
+        {
+            if let Some(instance) = self.instance.take() {
+                instance.stop();
+            }
+            self.replace_relay(None);
+        }
+        if self.instance.is_some() {
+            return Ok((self.id, self.lifecycle_handle.clone()));
+        }
+        self.overwatch_handle
+            .events()
+            .report_lifecycle(LifecycleEvent::ServiceStarting {
+                service_id: self.id,
+            });
+        let spawned = self.retry_panics(|handle| handle.service_runner().spawn());
+        let (instance, lifecycle_handle) = match spawned {
+            Ok(spawned) => spawned,
+            Err(Error::Panicked { .. }) => {
+                self.replace_relay(None);
+                return Ok((self.id, self.lifecycle_handle.clone()));
+            }
+            Err(e) => {
+                self.replace_relay(None);
+                self.status.updater().failed(e.to_string());
+                report_crash(&self.overwatch_handle, self.id, e.to_string());
+                return Err(e);
+            }
+        };
+        self.instance = Some(instance);
+        // the service runs either way, it just can't fail over until a standby is prepared
+        if S::WARM_STANDBY && self.standby.is_none() {
+            if let Err(e) = self.prepare_standby() {
+                error!(
+                    target: TRACING_TARGET,
+                    "Couldn't prepare a standby instance for {}: {e}", self.id
+                );
+            }
+        }
+        Ok((self.id, lifecycle_handle))
+    }
+
+    /// Take the running instance, if any, to wait for it to end with [`ServiceTask::join`]
+    /// The standby instance is discarded. The service is considered not running afterwards.
+    pub fn take_task(&mut self) -> Option<ServiceTask> {
+        self.discard_standby();
+        let instance = self.instance.take()?;
+        self.replace_relay(None);
+        let clock = Arc::clone(&self.overwatch_handle.context_config().clock);
+        Some(ServiceTask {
+            id: self.id,
+            join: Box::new(move |grace| instance.join(grace, clock).boxed()),
+        })
+    }
+
+    /// Initialize a standby instance: its state is loaded and [`ServiceCore::init`] is called,
+    /// but its main loop does not run until promoted with [`ServiceHandle::failover`].
+    /// A previously prepared standby instance is discarded.
+    /// Lifecycle messages are only seen by standby instances subscribing to them once running.
+    pub fn prepare_standby(&mut self) -> Result<(), Error> {
+        self.discard_standby();
+        let (runner, outbound_relay) = self.build_runner();
+        let (promote, promoted) = oneshot::channel();
+        let (instance, _) = runner.spawn_after(async move { promoted.await.is_ok() })?;
+        self.standby = Some(StandbyInstance {
+            instance,
+            outbound_relay,
+            promote,
+        });
+        Ok(())
+    }
+
+    /// Replace the running instance with the standby one in a single step
+    /// The running instance is stopped and relays requested from now on reach the promoted one,
+    /// relays obtained before have to be requested again.
+    /// If the service is configured with [`ServiceData::WARM_STANDBY`] a new standby instance
+    /// is prepared right away.
+    pub fn failover(&mut self) -> Result<(), Error> {
+        let StandbyInstance {
+            instance,
+            outbound_relay,
+            promote,
+        } = self.standby.take().ok_or(Error::NoStandby {
+            service_id: self.id,
+        })?;
+        self.stop_instance(StopReason::Failover);
+        if promote.send(()).is_err() {
+            // the standby task is gone already, so there is nothing to promote
+            instance.discard();
+            return Err(Error::Unavailable {
+                service_id: self.id,
+            });
         }
+        self.instance = Some(instance);
+        self.replace_relay(outbound_relay);
+        if S::WARM_STANDBY {
+            if let Err(e) = self.prepare_standby() {
+                error!(
+                    target: TRACING_TARGET,
+                    "Couldn't prepare a new standby instance for {}: {e}",
+                    self.id
+                );
+            }
+        }
+        Ok(())
     }
 }
 
 impl<S: ServiceData> ServiceStateHandle<S> {
     pub fn id(&self) -> ServiceId {
-        S::SERVICE_ID
+        self.context.service_id()
+    }
+
+    /// Report a non fatal error, it is forwarded to
+    /// [`OverwatchHandle::error_events`] subscribers
+    pub fn report_error(&self, error: impl Into<crate::DynError>) {
+        self.overwatch_handle.report_error(self.id(), error.into());
+    }
+
+    /// Output tagged with the service id, to be used instead of printing to stdout/stderr
+    pub fn output(&self) -> ServiceOutput {
+        ServiceOutput::new(self.id())
+    }
+
+    /// Objects shared by all services, provided when running the application
+    pub fn resources(&self) -> &SharedResources {
+        self.context.resources()
+    }
+
+    /// Run blocking code attributed to this service instance, see
+    /// [`ScopedSpawner::spawn_blocking`](crate::services::context::ScopedSpawner::spawn_blocking)
+    pub fn spawn_blocking<F, R>(&self, f: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.context.spawner().spawn_blocking(f)
+    }
+
+    /// Run a `!Send` future attributed to this service instance, see
+    /// [`ScopedSpawner::spawn_local`](crate::services::context::ScopedSpawner::spawn_local)
+    pub fn spawn_local<F, Fut>(&self, f: F) -> JoinHandle<Option<Fut::Output>>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.context.spawner().spawn_local(f)
+    }
+}
+
+impl<S: BroadcastingService> ServiceStateHandle<S> {
+    /// Relay publishing the [`BroadcastingService::Broadcast`] messages of the service to every
+    /// subscribed service, see [`OverwatchHandle::subscribe`]
+    /// It fails with [`RelayError::InvalidMessage`] if the relay was already asked for another
+    /// message type through [`OverwatchHandle::broadcast_for`].
+    pub fn broadcast(&self) -> Result<BroadcastRelay<S::Broadcast>, RelayError> {
+        self.broadcast.relay()
+    }
+}
+
+impl<S: PipelineStage> ServiceStateHandle<S> {
+    /// Connect to the next stage of the [`Pipeline`](crate::services::pipeline::Pipeline)
+    pub async fn downstream(&self) -> Result<Downstream<S::Next>, RelayError> {
+        Downstream::connect(&self.overwatch_handle).await
     }
 }
 
@@ -148,19 +692,254 @@ where
     /// Spawn the service main loop and handle it lifecycle
     /// Return a handle to abort execution manually
     pub fn run(self) -> Result<(ServiceId, LifecycleHandle), crate::DynError> {
+        let service_id = self.service_state.id();
+        let (_, lifecycle_handle) = self.spawn()?;
+        Ok((service_id, lifecycle_handle))
+    }
+
+    fn spawn(self) -> Result<(ServiceInstance<S::State>, LifecycleHandle), Error> {
+        self.spawn_after(async { true })
+    }
+
+    /// Initialize the service and spawn its main loop, which only runs once `gate` resolves
+    /// to `true`
+    /// Fails with [`Error::RuntimeUnavailable`] if the runtime is shutting down.
+    fn spawn_after<G>(self, gate: G) -> Result<(ServiceInstance<S::State>, LifecycleHandle), Error>
+    where
+        G: Future<Output = bool> + Send + 'static,
+    {
         let ServiceRunner {
             service_state,
             state_handle,
             lifecycle_handle,
             initial_state,
+            relay_dropped,
+            supervisor,
         } = self;
 
-        let runtime = service_state.overwatch_handle.runtime().clone();
-        let service = S::init(service_state, initial_state)?;
+        let service_id = service_state.id();
+        let overwatch_handle = service_state.overwatch_handle.clone();
+        let runtime = overwatch_handle.service_runtime::<S>(service_id)?;
+        let state_updater = service_state.state_updater.clone();
+        let status_handle = service_state.status_handle.clone();
+        let cancellation_token = service_state.context.cancellation_token().clone();
+        let spawner = service_state.context.spawner().clone();
+        let counters = overwatch_handle
+            .context_config()
+            .persisted_counters(service_id);
+        let lifecycle_stats = Arc::clone(
+            overwatch_handle
+                .metrics_registry()
+                .service(service_id)
+                .lifecycle(),
+        );
+        let span = service_span(service_id);
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let service = span
+            .in_scope(|| {
+                std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    in_service(|| S::init(service_state, initial_state))
+                }))
+            })
+            .map_err(|panic| Error::Panicked {
+                service_id,
+                message: panic_message(panic.as_ref()),
+            })??;
+        let service = Abortable::new(
+            AssertUnwindSafe(in_service_task(service.run())).catch_unwind(),
+            abort_registration,
+        );
 
-        runtime.spawn(service.run());
-        runtime.spawn(state_handle.run());
+        let instance_state_updater = state_updater.clone();
+        let aborted_state_updater = state_updater.clone();
+        let aborted = overwatch_handle.abort_token();
+        let instance_cancellation_token = cancellation_token.clone();
+        let start_budget =
+            enforce_start_budget::<S>(service_id, status_handle.clone(), overwatch_handle.clone());
+        let service_task = async move {
+            if !gate.await {
+                state_updater.stop();
+                cancellation_token.cancel();
+                return ServiceExit::Aborted;
+            }
+            debug!(target: TRACING_TARGET, "Service started");
+            supervisor.watch_stability(status_handle.watcher(), cancellation_token.clone());
+            if let Some(counters) = counters {
+                counters.record_start();
+            }
+            // the stop is recorded whichever way the task ends
+            let _running = lifecycle_stats.started();
+            let relay_dropped = async move {
+                match relay_dropped {
+                    Some(relay_dropped) => {
+                        let _ = relay_dropped.await;
+                    }
+                    // services taking no messages have no relay to drop
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::pin!(service, relay_dropped, start_budget);
+            let result = tokio::select! {
+                // a finished or aborted service drops its relay too, that's not worth reporting
+                biased;
+                result = &mut service => result,
+                _ = &mut relay_dropped => {
+                    status_handle.updater().update(ServiceStatus::Detached);
+                    overwatch_handle.report_error(
+                        service_id,
+                        Box::new(RelayError::InboundDropped { service_id }),
+                    );
+                    service.await
+                }
+                message = &mut start_budget => {
+                    // dropping the service future aborts it, it is left failed instead of stopped
+                    state_updater.stop();
+                    cancellation_token.cancel();
+                    spawner.join_all(overwatch_handle.context_config()).await;
+                    report_crash(&overwatch_handle, service_id, message.clone());
+                    return ServiceExit::Failed(message);
+                }
+            };
+            // an aborted service is reported stopped by whoever aborted it
+            let Ok(result) = result else {
+                debug!(target: TRACING_TARGET, "Service aborted");
+                return ServiceExit::Aborted;
+            };
+            let exit = match result {
+                Ok(Ok(())) => ServiceExit::Finished,
+                Ok(Err(e)) => ServiceExit::Failed(e.to_string()),
+                Err(panic) => {
+                    let message = panic_message(panic.as_ref());
+                    error!(target: TRACING_TARGET, "Service panicked: {message}");
+                    report_panic(&overwatch_handle, service_id, message.clone());
+                    // the state handle is gone if this fails, there is nothing to flush to
+                    let _ = state_updater.flush(PersistContext::PanicFlush);
+                    state_updater.stop();
+                    cancellation_token.cancel();
+                    spawner.join_all(overwatch_handle.context_config()).await;
+                    match supervisor.restart_delay(true) {
+                        Some(delay) => {
+                            status_handle.updater().update(ServiceStatus::Restarting);
+                            supervisor.schedule(delay);
+                        }
+                        None => status_handle.updater().failed(message),
+                    }
+                    std::panic::resume_unwind(panic);
+                }
+            };
+            if let ServiceExit::Failed(e) = &exit {
+                report_crash(&overwatch_handle, service_id, e.clone());
+            }
+            // the state handle is gone if this fails, there is nothing to flush to
+            let _ = state_updater.flush(PersistContext::StopFlush);
+            // stop accepting state updates from leftover updater clones before reporting stopped
+            state_updater.stop();
+            cancellation_token.cancel();
+            // the service tasks are done once it is reported stopped
+            spawner.join_all(overwatch_handle.context_config()).await;
+            match supervisor.restart_delay(matches!(exit, ServiceExit::Failed(_))) {
+                Some(delay) => {
+                    status_handle.updater().update(ServiceStatus::Restarting);
+                    supervisor.schedule(delay);
+                }
+                None => status_handle.updater().update(ServiceStatus::Stopped),
+            }
+            debug!(target: TRACING_TARGET, "Service stopped");
+            exit
+        };
+        let service_task = async move {
+            tokio::select! {
+                exit = service_task => exit,
+                // the runner is aborted along with Overwatch, it won't stop the instance
+                () = aborted.cancelled() => {
+                    aborted_state_updater.stop();
+                    ServiceExit::Aborted
+                }
+            }
+        };
+        let task = spawn_checked(&runtime, service_id, service_task.instrument(span.clone()))?;
+        spawn_checked(
+            &runtime,
+            &format!("{service_id}:state"),
+            state_handle.run().instrument(span),
+        )?;
 
-        Ok((S::SERVICE_ID, lifecycle_handle))
+        let instance = ServiceInstance {
+            abort_handle,
+            state_updater: instance_state_updater,
+            cancellation_token: instance_cancellation_token,
+            task,
+        };
+        Ok((instance, lifecycle_handle))
     }
 }
+
+impl ServiceTask {
+    pub fn id(&self) -> ServiceId {
+        self.id
+    }
+
+    /// Wait up to `grace` for the instance to end on its own, and stop it afterwards
+    pub async fn join(self, grace: Duration) -> ServiceExit {
+        (self.join)(grace).await
+    }
+}
+
+/// Completes if the service isn't running within its [`ServiceData::START_BUDGET`] and has to be
+/// aborted, with the reason why
+/// The service is marked [`ServiceStatus::Failed`] and [`Error::StartTimeout`] is reported once
+/// the budget is exceeded, whether it is aborted or not.
+async fn enforce_start_budget<S: ServiceData>(
+    service_id: ServiceId,
+    status_handle: StatusHandle<S>,
+    overwatch_handle: OverwatchHandle,
+) -> String {
+    let Some(budget) = S::START_BUDGET else {
+        return std::future::pending().await;
+    };
+    let clock = Arc::clone(&overwatch_handle.context_config().clock);
+    let mut watcher = status_handle.watcher();
+    let running = watcher.wait_until(ServiceStatus::is_up, None);
+    if clock.timeout(budget.limit, running).await.is_ok() {
+        return std::future::pending().await;
+    }
+    let timeout = || Error::StartTimeout {
+        service_id,
+        budget: budget.limit,
+    };
+    error!(target: TRACING_TARGET, "{}", timeout());
+    status_handle.updater().failed(timeout().to_string());
+    overwatch_handle.report_error(service_id, Box::new(timeout()));
+    if !budget.abort {
+        return std::future::pending().await;
+    }
+    timeout().to_string()
+}
+
+fn report_crash(overwatch_handle: &OverwatchHandle, service_id: ServiceId, error: String) {
+    overwatch_handle.report_crash(ServiceCrash {
+        service_id,
+        error,
+        panicked: false,
+        backtrace: None,
+    });
+}
+
+/// Report a panic caught on this thread, with its backtrace if it was captured
+fn report_panic(overwatch_handle: &OverwatchHandle, service_id: ServiceId, message: String) {
+    overwatch_handle.report_crash(ServiceCrash {
+        service_id,
+        error: message,
+        panicked: true,
+        backtrace: take_panic_backtrace().map(Arc::new),
+    });
+}
+
+/// Message a panic was raised with, if it was raised with a string
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non string panic payload".to_string())
+}
@@ -0,0 +1,215 @@
+// std
+use std::collections::{BTreeSet, HashSet};
+use std::sync::{Mutex, OnceLock};
+// crates
+// internal
+use crate::services::pool::is_member_of;
+use crate::services::ServiceId;
+
+/// Separator between the namespace of a bundled service and its own id, `<bundle>.<service>`
+pub const NAMESPACE_SEPARATOR: char = '.';
+
+/// Separator between the id of a service pool and the index of its members, `<pool>#<index>`
+pub const POOL_MEMBER_SEPARATOR: char = '#';
+
+/// Naming conventions a [`ServiceId`] can be rendered in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IdCase {
+    /// `foo-service`
+    Kebab,
+    /// `foo_service`
+    Snake,
+}
+
+/// Split a service id into lowercase words, using `-`, `_`, spaces and case changes as boundaries
+fn words(id: &str) -> Vec<String> {
+    let chars: Vec<char> = id.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if matches!(c, '-' | '_' | ' ') {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            // `fooBar` or the `S` in `HTTPServer`
+            if !previous.is_uppercase() || next_is_lower {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Render a service id in the given naming convention
+pub fn to_case(id: ServiceId, case: IdCase) -> String {
+    let separator = match case {
+        IdCase::Kebab => "-",
+        IdCase::Snake => "_",
+    };
+    words(id).join(separator)
+}
+
+/// Whether `a` and `b` are the same name written in the same or different naming conventions
+#[cfg(feature = "config")]
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    a == b || words(a) == words(b)
+}
+
+/// Find the service id `name` refers to, written either as is or in any of the supported naming
+/// conventions (`FooService`, `foo-service`, `foo_service`...)
+pub fn find_service_id(ids: &[ServiceId], name: &str) -> Option<ServiceId> {
+    let name_words = words(name);
+    ids.iter()
+        .find(|id| **id == name)
+        .or_else(|| ids.iter().find(|id| words(id) == name_words))
+        .copied()
+}
+
+/// `'static` copy of `id`, each distinct id is leaked once and reused afterwards
+pub(crate) fn intern(id: String) -> ServiceId {
+    static IDS: OnceLock<Mutex<HashSet<ServiceId>>> = OnceLock::new();
+    let mut ids = IDS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("Interned ids lock not poisoned");
+    if let Some(interned) = ids.get(id.as_str()) {
+        return interned;
+    }
+    let interned = Box::leak(id.into_boxed_str());
+    ids.insert(interned);
+    interned
+}
+
+/// Id of the `service_id` service attached within `namespace`, `<namespace>.<service_id>`
+pub fn namespaced(namespace: Option<ServiceId>, service_id: ServiceId) -> ServiceId {
+    match namespace {
+        Some(namespace) => intern(format!("{namespace}{NAMESPACE_SEPARATOR}{service_id}")),
+        None => service_id,
+    }
+}
+
+/// Id of `service_id` relative to `namespace`, if the service is attached within it
+pub fn strip_namespace(service_id: ServiceId, namespace: Option<&str>) -> Option<ServiceId> {
+    match namespace {
+        Some(namespace) => service_id
+            .strip_prefix(namespace)?
+            .strip_prefix(NAMESPACE_SEPARATOR),
+        None => Some(service_id),
+    }
+}
+
+/// Namespace `service_id` is attached within, `None` outside of bundles
+fn namespace_of(service_id: &str) -> Option<&str> {
+    service_id
+        .rsplit_once(NAMESPACE_SEPARATOR)
+        .map(|(namespace, _)| namespace)
+}
+
+/// Find the id of the service `service_id` refers to from `namespace`, among the `services` and
+/// the members of the `pools`
+/// Ids are taken as they are first, then within `namespace` and its parents from the closest
+/// one, then within any namespace as long as a single service matches. `service_id` is returned
+/// as is if none or several of them do.
+pub(crate) fn resolve(
+    services: &[ServiceId],
+    pools: &[ServiceId],
+    namespace: Option<&str>,
+    service_id: ServiceId,
+) -> ServiceId {
+    let known = |candidate: &str| {
+        services.contains(&candidate) || pools.iter().any(|pool| is_member_of(candidate, pool))
+    };
+    if known(service_id) {
+        return service_id;
+    }
+    let qualified = |namespace: &str| format!("{namespace}{NAMESPACE_SEPARATOR}{service_id}");
+    let mut enclosing = namespace;
+    while let Some(current) = enclosing {
+        let candidate = qualified(current);
+        if known(&candidate) {
+            return intern(candidate);
+        }
+        enclosing = namespace_of(current);
+    }
+    let namespaces: BTreeSet<_> = services
+        .iter()
+        .chain(pools)
+        .filter_map(|id| namespace_of(id))
+        .collect();
+    let mut candidates = namespaces
+        .into_iter()
+        .map(qualified)
+        .filter(|candidate| known(candidate));
+    match (candidates.next(), candidates.next()) {
+        (Some(candidate), None) => intern(candidate),
+        _ => service_id,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::ids::{
+        find_service_id, namespaced, resolve, strip_namespace, to_case, IdCase,
+    };
+
+    #[test]
+    fn render_service_ids() {
+        assert_eq!(to_case("FooService", IdCase::Kebab), "foo-service");
+        assert_eq!(to_case("FooService", IdCase::Snake), "foo_service");
+        assert_eq!(to_case("HTTPServer", IdCase::Snake), "http_server");
+        assert_eq!(
+            to_case("cancel-me-please", IdCase::Snake),
+            "cancel_me_please"
+        );
+        assert_eq!(to_case("try_load", IdCase::Kebab), "try-load");
+        assert_eq!(to_case("S1", IdCase::Kebab), "s1");
+    }
+
+    #[test]
+    fn find_service_ids_by_alias() {
+        let ids = ["FooService", "try_load", "S1"];
+        assert_eq!(find_service_id(&ids, "FooService"), Some("FooService"));
+        assert_eq!(find_service_id(&ids, "foo-service"), Some("FooService"));
+        assert_eq!(find_service_id(&ids, "foo_service"), Some("FooService"));
+        assert_eq!(find_service_id(&ids, "TryLoad"), Some("try_load"));
+        assert_eq!(find_service_id(&ids, "s1"), Some("S1"));
+        assert_eq!(find_service_id(&ids, "bar"), None);
+    }
+
+    #[test]
+    fn namespaced_ids() {
+        assert_eq!(namespaced(None, "store"), "store");
+        assert_eq!(namespaced(Some("core"), "store"), "core.store");
+        assert_eq!(strip_namespace("core.store", Some("core")), Some("store"));
+        assert_eq!(strip_namespace("core.store", None), Some("core.store"));
+        assert_eq!(strip_namespace("corestore", Some("core")), None);
+        assert_eq!(strip_namespace("plugins.store", Some("core")), None);
+    }
+
+    #[test]
+    fn resolve_ids_from_namespaces() {
+        let services = ["a.store", "b.store", "b.nested.cache", "indexer", "a.pool"];
+        let pools = ["a.pool"];
+        let resolve = |namespace, service_id| resolve(&services, &pools, namespace, service_id);
+        assert_eq!(resolve(None, "indexer"), "indexer");
+        assert_eq!(resolve(Some("a"), "b.store"), "b.store");
+        // closest namespace first
+        assert_eq!(resolve(Some("a"), "store"), "a.store");
+        assert_eq!(resolve(Some("b.nested"), "store"), "b.store");
+        // unique matches from anywhere
+        assert_eq!(resolve(None, "cache"), "b.nested.cache");
+        assert_eq!(resolve(Some("b"), "pool#1"), "a.pool#1");
+        // ambiguous or unknown ids are left as they are
+        assert_eq!(resolve(None, "store"), "store");
+        assert_eq!(resolve(Some("a"), "missing"), "missing");
+    }
+}
@@ -5,19 +5,46 @@ use std::error::Error;
 use tokio::sync::broadcast::{channel, Receiver, Sender};
 use tokio_stream::StreamExt;
 
-/// Type alias for an empty signal
-pub type FinishedSignal = ();
+pub use crate::utils::finished_signal;
+
+/// Why a service is being stopped, so it can pick a cleanup strategy accordingly
+/// (e.g. persist caches on shutdown but discard them when its settings change).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StopReason {
+    /// The service was stopped on its own, through the overwatch handle or a lifecycle command
+    Requested,
+    /// The whole overwatch is shutting down
+    Shutdown,
+    /// The service is restarted with new settings
+    SettingsChange,
+    /// The service is replaced by its standby instance
+    Failover,
+    /// The service is a pool member removed when scaling the pool down
+    Scaling,
+    /// The service main loop ended and it is restarted as its
+    /// [`RestartPolicy`](crate::services::supervisor::RestartPolicy) says
+    Supervision,
+}
 
 /// Supported lifecycle messages
 #[derive(Clone, Debug)]
 pub enum LifecycleMessage {
     /// Shutdown
-    /// Hold a sender from a broadcast channel. It is intended to signal when finished handling the
-    /// shutdown process.
-    Shutdown(Sender<FinishedSignal>),
+    /// Hold the sender of a [`finished_signal`]. It is intended to signal when finished handling
+    /// the shutdown process, signaling it more than once is harmless.
+    Shutdown(finished_signal::Sender, StopReason),
     /// Kill
     /// Well, nothing much to explain here, everything should be about to be nuked.
-    Kill,
+    Kill(StopReason),
+}
+
+impl LifecycleMessage {
+    pub fn stop_reason(&self) -> StopReason {
+        match self {
+            Self::Shutdown(_, reason) | Self::Kill(reason) => *reason,
+        }
+    }
 }
 
 /// Handle for lifecycle communications with a `Service`
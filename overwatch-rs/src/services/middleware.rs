@@ -0,0 +1,170 @@
+//! Middleware layered around the delivery of the messages of a service relay
+//!
+//! Cross-cutting concerns like logging, metrics, authorization or rate limiting wrap the
+//! [`InboundRelay`](crate::services::relay::InboundRelay) of a service instead of cluttering its
+//! main loop. Middlewares see every received message, in the order they were added and before
+//! the service does, and can hold it back for a while, change it or drop it.
+//!
+//! ```ignore
+//! fn init(mut service_state: ServiceStateHandle<Self>, ...) -> Result<Self, DynError> {
+//!     service_state.inbound_relay = service_state
+//!         .inbound_relay
+//!         .with_middleware(|message: StoreMessage| {
+//!             debug!(?message, "Store message received");
+//!             Some(message)
+//!         });
+//!     // ...
+//! }
+//! ```
+
+// std
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+// crates
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+/// Layer handling the messages of a service relay before the service gets them, see
+/// [`InboundRelay::with_middleware`](crate::services::relay::InboundRelay::with_middleware)
+#[async_trait]
+pub trait RelayMiddleware<M: Send + 'static>: Send + Sync + 'static {
+    /// Pass `message` on, possibly changed, or drop it returning `None`
+    /// The service gets no other message until this completes.
+    async fn handle(&self, message: M) -> Option<M>;
+}
+
+#[async_trait]
+impl<M, F> RelayMiddleware<M> for F
+where
+    M: Send + 'static,
+    F: Fn(M) -> Option<M> + Send + Sync + 'static,
+{
+    async fn handle(&self, message: M) -> Option<M> {
+        self(message)
+    }
+}
+
+type Layers<M> = Arc<Vec<Arc<dyn RelayMiddleware<M>>>>;
+
+/// Middlewares of a relay, in the order they were added
+pub(crate) struct MiddlewareChain<M> {
+    layers: Layers<M>,
+    /// Runs a message through the layers, typed where the bounds of the messages are known
+    run: fn(Layers<M>, M) -> BoxFuture<'static, Option<M>>,
+    /// Message going through the layers, only behind a lock to keep relays `Sync`
+    in_flight: Mutex<Option<BoxFuture<'static, Option<M>>>>,
+}
+
+impl<M> Debug for MiddlewareChain<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MiddlewareChain")
+            .field("layers", &self.layers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M: Send + 'static> MiddlewareChain<M> {
+    pub(crate) fn new() -> Self {
+        Self {
+            layers: Arc::new(Vec::new()),
+            run: |layers, message| {
+                async move {
+                    let mut message = message;
+                    for layer in layers.iter() {
+                        message = layer.handle(message).await?;
+                    }
+                    Some(message)
+                }
+                .boxed()
+            },
+            in_flight: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn push<L: RelayMiddleware<M>>(&mut self, layer: L) {
+        Arc::make_mut(&mut self.layers).push(Arc::new(layer));
+    }
+}
+
+impl<M> MiddlewareChain<M> {
+    /// Next message passed on by the layers, pulling messages with `receive` until one is
+    pub(crate) fn poll_next(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut receive: impl FnMut(&mut Context<'_>) -> Poll<Option<M>>,
+    ) -> Poll<Option<M>> {
+        let in_flight = self
+            .in_flight
+            .get_mut()
+            .expect("Middleware lock not poisoned");
+        loop {
+            if let Some(handling) = in_flight {
+                let handled = ready!(handling.poll_unpin(cx));
+                *in_flight = None;
+                if handled.is_some() {
+                    return Poll::Ready(handled);
+                }
+                continue;
+            }
+            match ready!(receive(cx)) {
+                Some(message) => *in_flight = Some((self.run)(Arc::clone(&self.layers), message)),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::middleware::RelayMiddleware;
+    use crate::services::relay::relay;
+    use async_trait::async_trait;
+    use futures::FutureExt;
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    /// Holds messages back until given permits
+    struct Gate(Arc<Semaphore>);
+
+    #[async_trait]
+    impl RelayMiddleware<usize> for Gate {
+        async fn handle(&self, message: usize) -> Option<usize> {
+            self.0.acquire().await.ok()?.forget();
+            Some(message)
+        }
+    }
+
+    #[tokio::test]
+    async fn middlewares_run_in_order() {
+        let (inbound, outbound) = relay::<usize>(8);
+        let mut inbound = inbound
+            .with_middleware(|message: usize| (message % 2 == 0).then_some(message))
+            .with_middleware(|message: usize| Some(message * 10));
+        for i in 0..5 {
+            outbound.send(i).await.unwrap();
+        }
+        drop(outbound);
+        let mut received = Vec::new();
+        while let Some(message) = inbound.recv().await {
+            received.push(message);
+        }
+        assert_eq!(received, [0, 20, 40]);
+    }
+
+    #[tokio::test]
+    async fn middleware_holds_messages_back() {
+        let (inbound, outbound) = relay::<usize>(8);
+        let gate = Arc::new(Semaphore::new(0));
+        let mut inbound = inbound.with_middleware(Gate(Arc::clone(&gate)));
+        outbound.send(1).await.unwrap();
+        outbound.send(2).await.unwrap();
+        assert_eq!(inbound.recv().now_or_never(), None);
+        gate.add_permits(1);
+        assert_eq!(inbound.recv().await, Some(1));
+        assert_eq!(inbound.recv().now_or_never(), None);
+        gate.add_permits(1);
+        assert_eq!(inbound.recv().await, Some(2));
+    }
+}
@@ -1,20 +1,51 @@
+pub mod broadcast;
+pub mod clock;
+pub mod context;
+pub mod deadline;
+pub mod envelope;
+pub mod fan_in;
 pub mod handle;
+pub mod ids;
 pub mod life_cycle;
+pub mod middleware;
+pub mod output;
+pub mod persistent_metrics;
+pub mod pipeline;
+pub mod plugin;
+pub mod pool;
+pub mod rate_limit;
+pub mod recording;
+pub mod registry;
 pub mod relay;
+pub(crate) mod relay_cache;
+pub(crate) mod relay_pause;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod resources;
+pub mod runtime;
 pub mod settings;
+pub mod simulation;
 pub mod state;
 pub mod status;
+pub mod stream;
+pub mod supervisor;
+pub mod yielding;
 
 // std
 use std::fmt::Debug;
+use std::time::Duration;
 // crates
 use async_trait::async_trait;
 use thiserror::Error;
-use tokio::runtime;
+use tracing::{info_span, Span};
 
 // internal
-use crate::services::relay::RelayError;
-use crate::services::state::StateOperator;
+use crate::services::rate_limit::RateLimit;
+use crate::services::relay::{BackpressurePolicy, RelayError};
+use crate::services::runtime::RuntimePolicy;
+
+use crate::services::state::{StateOperator, StatePersistencePolicy};
+use crate::services::supervisor::RestartPolicy;
 use handle::ServiceStateHandle;
 use relay::RelayMessage;
 use state::ServiceState;
@@ -23,6 +54,53 @@ use state::ServiceState;
 /// Services identification type
 pub type ServiceId = &'static str;
 
+/// Target of the tracing events the framework emits on behalf of services
+/// Tracing targets are static so they can't carry the service id, instead everything a service
+/// instance does runs within a [`service_span`], so logging can be enabled for a single service
+/// with a span filter like `RUST_LOG="[service{id=my-service}]=debug"`.
+pub const TRACING_TARGET: &str = "overwatch::service";
+
+/// Span every task of the `service_id` service instance runs within
+pub fn service_span(service_id: ServiceId) -> Span {
+    info_span!(target: TRACING_TARGET, "service", id = service_id)
+}
+
+/// How important a service is compared to the others
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub enum ServicePriority {
+    /// Lazy or background work that can wait, e.g. indexing or metrics exports
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// Longest time a service may take from being started to reporting
+/// [`ServiceStatus::Running`](status::ServiceStatus::Running), see
+/// [`ServiceData::START_BUDGET`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StartBudget {
+    pub limit: Duration,
+    /// Abort the service once the budget is exceeded, instead of leaving it running
+    pub abort: bool,
+}
+
+impl StartBudget {
+    pub const fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            abort: false,
+        }
+    }
+
+    pub const fn aborting(self) -> Self {
+        Self {
+            abort: true,
+            ..self
+        }
+    }
+}
+
 /// The core data a service needs to handle
 /// Holds the necessary information of a service
 pub trait ServiceData {
@@ -30,6 +108,38 @@ pub trait ServiceData {
     const SERVICE_ID: ServiceId;
     /// Service relay buffer size
     const SERVICE_RELAY_BUFFER_SIZE: usize = 16;
+    /// Buffer size of the relay lane taking
+    /// [`OutboundRelay::send_priority`](relay::OutboundRelay::send_priority) messages
+    const PRIORITY_RELAY_BUFFER_SIZE: usize = relay::PRIORITY_RELAY_BUFFER_SIZE;
+    /// Number of buffered inbound messages from which the service reports
+    /// [`BackpressureLevel::High`](crate::overwatch::events::BackpressureLevel::High)
+    const BACKPRESSURE_THRESHOLD: usize = (Self::SERVICE_RELAY_BUFFER_SIZE * 3).div_ceil(4);
+    /// What sending to the service does once its relay buffer is full
+    const BACKPRESSURE_POLICY: BackpressurePolicy = BackpressurePolicy::Block;
+    /// Rate the service gets its messages at, see [`rate_limit`]
+    const RATE_LIMIT: Option<RateLimit> = None;
+    /// Messages buffered per subscriber of the service
+    /// [`ServiceBroadcast`](broadcast::ServiceBroadcast), slower subscribers skip older ones
+    const BROADCAST_BUFFER_SIZE: usize = 16;
+    /// Last broadcast messages handed to new subscribers first
+    const BROADCAST_REPLAY: usize = 0;
+    /// Keep an initialized but idle instance ready to take over the running one,
+    /// see [`ServiceHandle::failover`](handle::ServiceHandle::failover)
+    const WARM_STANDBY: bool = false;
+    /// Low priority services are paused under memory pressure, see
+    /// [`MemoryMonitor`](crate::overwatch::memory::MemoryMonitor)
+    const PRIORITY: ServicePriority = ServicePriority::Normal;
+    /// Services not running within their budget are marked
+    /// [`ServiceStatus::Failed`](status::ServiceStatus::Failed) and an
+    /// [`Error::StartTimeout`](crate::overwatch::Error::StartTimeout) is reported, measured
+    /// with the application [`Clock`](clock::Clock)
+    const START_BUDGET: Option<StartBudget> = None;
+    /// Whether the service is started again once its main loop ended
+    const RESTART_POLICY: RestartPolicy = RestartPolicy::Never;
+    /// How often the [`StateOperator`] runs over state updates
+    const STATE_PERSISTENCE: StatePersistencePolicy = StatePersistencePolicy::IMMEDIATE;
+    /// Runtime the service runs on, see [`runtime`]
+    const RUNTIME_POLICY: RuntimePolicy = RuntimePolicy::Shared;
     /// Service settings object
     type Settings: Clone;
     /// Service state object
@@ -38,6 +148,13 @@ pub trait ServiceData {
     type StateOperator: StateOperator<StateInput = Self::State> + Clone;
     /// Service messages that the service itself understands and can react to
     type Message: RelayMessage + Debug;
+
+    /// Check of new settings before they are applied, settings are always valid unless the
+    /// service opts in, e.g. by checking them with their
+    /// [`ValidateSettings`](settings::ValidateSettings) implementation
+    fn validate_settings(_settings: &Self::Settings) -> Result<(), super::DynError> {
+        Ok(())
+    }
 }
 
 /// Main trait for Services initialization and main loop hook
@@ -60,19 +177,19 @@ pub enum ServiceError {
 }
 
 pub enum ServiceRuntime {
-    FromParent(runtime::Handle),
-    Custom(runtime::Runtime),
+    FromParent(tokio::runtime::Handle),
+    Custom(tokio::runtime::Runtime),
 }
 
 impl ServiceRuntime {
-    pub fn handle(&self) -> runtime::Handle {
+    pub fn handle(&self) -> tokio::runtime::Handle {
         match self {
             ServiceRuntime::FromParent(handle) => handle.clone(),
             ServiceRuntime::Custom(runtime) => runtime.handle().clone(),
         }
     }
 
-    pub fn runtime(self) -> Option<runtime::Runtime> {
+    pub fn runtime(self) -> Option<tokio::runtime::Runtime> {
         match self {
             ServiceRuntime::Custom(runtime) => Some(runtime),
             _ => None,
@@ -0,0 +1,60 @@
+// std
+use std::fmt;
+// crates
+use tracing::{info, warn};
+// internal
+use crate::services::ServiceId;
+
+/// Target of the tracing events emitted through a [`ServiceOutput`]
+pub const OUTPUT_TRACING_TARGET: &str = "overwatch::service::output";
+
+/// `println!` replacement for services
+/// Every line is emitted as a tracing event carrying the id of the service that wrote it, so the
+/// output of several services running together can be told apart and filtered.
+/// Usually used through the [`service_println!`](crate::service_println) and
+/// [`service_eprintln!`](crate::service_eprintln) macros.
+#[derive(Clone, Copy, Debug)]
+pub struct ServiceOutput {
+    service_id: ServiceId,
+}
+
+impl ServiceOutput {
+    pub fn new(service_id: ServiceId) -> Self {
+        Self { service_id }
+    }
+
+    pub fn service_id(&self) -> ServiceId {
+        self.service_id
+    }
+
+    /// Standard output line, emitted at `INFO` level
+    pub fn out(&self, line: fmt::Arguments) {
+        info!(target: OUTPUT_TRACING_TARGET, service_id = self.service_id, "{line}");
+    }
+
+    /// Standard error line, emitted at `WARN` level
+    pub fn err(&self, line: fmt::Arguments) {
+        warn!(target: OUTPUT_TRACING_TARGET, service_id = self.service_id, "{line}");
+    }
+}
+
+/// Same as `println!` but writes through a [`ServiceOutput`](crate::services::output::ServiceOutput)
+///
+/// ```ignore
+/// let output = service_state_handle.output();
+/// service_println!(output, "Received {} Pongs", pong_count);
+/// ```
+#[macro_export]
+macro_rules! service_println {
+    ($output:expr, $($arg:tt)*) => {
+        $output.out(::std::format_args!($($arg)*))
+    };
+}
+
+/// Same as `eprintln!` but writes through a [`ServiceOutput`](crate::services::output::ServiceOutput)
+#[macro_export]
+macro_rules! service_eprintln {
+    ($output:expr, $($arg:tt)*) => {
+        $output.err(::std::format_args!($($arg)*))
+    };
+}
@@ -0,0 +1,363 @@
+//! Per-service counters kept on disk between runs
+//!
+//! Enabled with
+//! [`ContextConfig::with_persistent_metrics`](crate::services::context::ContextConfig::with_persistent_metrics),
+//! the selected counters are loaded when the application starts and written back periodically
+//! and when it shuts down, so long-term trends survive redeployments, and crashes, without
+//! external monitoring. Storing them requires the `persistent-metrics` feature, without it
+//! services have no counters.
+
+// std
+#[cfg(feature = "persistent-metrics")]
+use std::collections::{BTreeMap, HashMap, HashSet};
+#[cfg(feature = "persistent-metrics")]
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(feature = "persistent-metrics")]
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+// crates
+#[cfg(feature = "persistent-metrics")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "persistent-metrics")]
+use thiserror::Error;
+#[cfg(feature = "persistent-metrics")]
+use tracing::error;
+// internal
+#[cfg(feature = "persistent-metrics")]
+use crate::overwatch::handle::OverwatchHandle;
+#[cfg(feature = "persistent-metrics")]
+use crate::utils::atomic_file;
+
+/// Interval the counters are written back at unless told otherwise, see
+/// [`PersistentMetrics::with_flush_interval`]
+#[cfg(feature = "persistent-metrics")]
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[cfg(feature = "persistent-metrics")]
+#[derive(Error, Debug)]
+pub enum PersistentMetricsError {
+    #[error("persistent metrics file {path:?} couldn't be accessed: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("persistent metrics file {path:?} is malformed: {source}")]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Counter that can be kept across runs
+#[cfg(feature = "persistent-metrics")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PersistedCounter {
+    /// Times the service was started again after having run before, in this run or a
+    /// previous one
+    Restarts,
+    /// Messages received through the service inbound relay
+    ProcessedMessages,
+    /// Last time the service reported it is running
+    LastReady,
+}
+
+/// Counters of a service as they are stored, those not selected are left out
+/// Counters unknown to this version, e.g. written by a newer one, are ignored when loading and
+/// dropped on the next [`PersistentMetrics::flush`], as those not selected anymore.
+#[cfg(feature = "persistent-metrics")]
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredCounters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    restarts: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    processed_messages: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_ready: Option<u64>,
+}
+
+/// Persisted counters of a single service, `None` for those not selected
+#[derive(Debug)]
+pub struct ServiceCounters {
+    /// Set once the service started, in this run or a previous one
+    started: AtomicBool,
+    restarts: Option<AtomicU64>,
+    processed_messages: Option<AtomicU64>,
+    /// Seconds since the unix epoch, 0 if never ready
+    last_ready: Option<AtomicU64>,
+}
+
+impl ServiceCounters {
+    #[cfg(feature = "persistent-metrics")]
+    fn new(selected: &HashSet<PersistedCounter>) -> Self {
+        let counter = |counter| selected.contains(&counter).then(|| AtomicU64::new(0));
+        Self {
+            started: AtomicBool::new(false),
+            restarts: counter(PersistedCounter::Restarts),
+            processed_messages: counter(PersistedCounter::ProcessedMessages),
+            last_ready: counter(PersistedCounter::LastReady),
+        }
+    }
+
+    #[cfg(feature = "persistent-metrics")]
+    fn load(&self, stored: &StoredCounters) {
+        let load = |counter: &Option<AtomicU64>, value: Option<u64>| {
+            if let (Some(counter), Some(value)) = (counter, value) {
+                counter.store(value, Ordering::Relaxed);
+            }
+        };
+        load(&self.restarts, stored.restarts);
+        load(&self.processed_messages, stored.processed_messages);
+        load(&self.last_ready, stored.last_ready);
+    }
+
+    #[cfg(feature = "persistent-metrics")]
+    fn stored(&self) -> StoredCounters {
+        let value = |counter: &Option<AtomicU64>| {
+            counter
+                .as_ref()
+                .map(|counter| counter.load(Ordering::Relaxed))
+        };
+        StoredCounters {
+            restarts: value(&self.restarts),
+            processed_messages: value(&self.processed_messages),
+            last_ready: value(&self.last_ready),
+        }
+    }
+
+    pub fn restarts(&self) -> Option<u64> {
+        self.restarts
+            .as_ref()
+            .map(|restarts| restarts.load(Ordering::Relaxed))
+    }
+
+    pub fn processed_messages(&self) -> Option<u64> {
+        self.processed_messages
+            .as_ref()
+            .map(|processed| processed.load(Ordering::Relaxed))
+    }
+
+    /// `None` if not selected or the service never reported it is running
+    pub fn last_ready(&self) -> Option<SystemTime> {
+        match self.last_ready.as_ref()?.load(Ordering::Relaxed) {
+            0 => None,
+            secs => Some(UNIX_EPOCH + Duration::from_secs(secs)),
+        }
+    }
+
+    pub(crate) fn record_start(&self) {
+        if self.started.swap(true, Ordering::Relaxed) {
+            if let Some(restarts) = &self.restarts {
+                restarts.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(crate) fn record_message(&self) {
+        if let Some(processed) = &self.processed_messages {
+            processed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_ready(&self) {
+        if let Some(last_ready) = &self.last_ready {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            last_ready.store(now.as_secs(), Ordering::Relaxed);
+        }
+    }
+}
+
+/// Store of the [`PersistedCounter`]s selected for every service, backed by a file
+///
+/// The file holds a JSON object mapping service ids to their counters. Counters not selected
+/// anymore are dropped from it on the next [`PersistentMetrics::flush`], which happens every
+/// [`PersistentMetrics::flush_interval`] while the application runs.
+#[cfg(feature = "persistent-metrics")]
+#[derive(Debug)]
+pub struct PersistentMetrics {
+    path: PathBuf,
+    selected: HashSet<PersistedCounter>,
+    services: Mutex<HashMap<String, Arc<ServiceCounters>>>,
+    flush_interval: Duration,
+}
+
+#[cfg(feature = "persistent-metrics")]
+impl PersistentMetrics {
+    /// Load the `selected` counters stored at `path`, starting from scratch if it doesn't
+    /// exist yet
+    pub fn open(
+        path: impl Into<PathBuf>,
+        selected: impl IntoIterator<Item = PersistedCounter>,
+    ) -> Result<Self, PersistentMetricsError> {
+        let metrics = Self {
+            path: path.into(),
+            selected: selected.into_iter().collect(),
+            services: Mutex::default(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+        };
+        let content = match std::fs::read_to_string(&metrics.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(metrics),
+            Err(source) => return Err(metrics.io_error(source)),
+        };
+        let services: BTreeMap<String, StoredCounters> =
+            serde_json::from_str(&content).map_err(|source| PersistentMetricsError::Malformed {
+                path: metrics.path.clone(),
+                source,
+            })?;
+        for (service_id, stored) in &services {
+            let counters = metrics.counters(service_id);
+            // a service listed in the file ran in a previous run
+            counters.started.store(true, Ordering::Relaxed);
+            counters.load(stored);
+        }
+        Ok(metrics)
+    }
+
+    /// Write the counters back every `interval` instead of [`DEFAULT_FLUSH_INTERVAL`]
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn flush_interval(&self) -> Duration {
+        self.flush_interval
+    }
+
+    /// Counters of `service_id`, all zero if it never ran
+    pub fn counters(&self, service_id: &str) -> Arc<ServiceCounters> {
+        let mut services = self
+            .services
+            .lock()
+            .expect("Persistent metrics lock not poisoned");
+        Arc::clone(
+            services
+                .entry(service_id.to_string())
+                .or_insert_with(|| Arc::new(ServiceCounters::new(&self.selected))),
+        )
+    }
+
+    /// Write the counters of the services that ran, replacing the file atomically
+    pub fn flush(&self) -> Result<(), PersistentMetricsError> {
+        let services: BTreeMap<_, _> = self
+            .services
+            .lock()
+            .expect("Persistent metrics lock not poisoned")
+            .iter()
+            .filter(|(_, counters)| counters.started.load(Ordering::Relaxed))
+            .map(|(service_id, counters)| (service_id.clone(), counters.stored()))
+            .collect();
+        let content =
+            serde_json::to_vec_pretty(&services).expect("Counters to be serializable to JSON");
+        atomic_file::write(&self.path, &content, true).map_err(|source| self.io_error(source))
+    }
+
+    fn io_error(&self, source: std::io::Error) -> PersistentMetricsError {
+        PersistentMetricsError::Io {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
+
+/// Write the counters back every [`PersistentMetrics::flush_interval`] until Overwatch shuts
+/// down, the last flush is made by the runner once the services are stopped
+#[cfg(feature = "persistent-metrics")]
+pub(crate) async fn flush_periodically(handle: OverwatchHandle, metrics: Arc<PersistentMetrics>) {
+    let shutdown = handle.shutdown_token();
+    let clock = Arc::clone(&handle.context_config().clock);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = clock.sleep(metrics.flush_interval()) => {
+                let metrics = Arc::clone(&metrics);
+                match tokio::task::spawn_blocking(move || metrics.flush()).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => error!("Persistent metrics couldn't be saved: {e}"),
+                    Err(e) => error!("Persistent metrics couldn't be saved: {e}"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "persistent-metrics"))]
+mod test {
+    use crate::overwatch::testing::EphemeralDir;
+    use crate::services::persistent_metrics::{
+        PersistedCounter, PersistentMetrics, PersistentMetricsError,
+    };
+
+    #[test]
+    fn counters_survive_reopening() {
+        let dir = EphemeralDir::create().unwrap();
+        let path = dir.path().join("metrics.json");
+        let selected = [
+            PersistedCounter::Restarts,
+            PersistedCounter::ProcessedMessages,
+        ];
+
+        let metrics = PersistentMetrics::open(&path, selected).unwrap();
+        let counters = metrics.counters("storage service");
+        counters.record_start();
+        counters.record_message();
+        counters.record_ready();
+        assert_eq!(counters.restarts(), Some(0));
+        assert_eq!(counters.last_ready(), None);
+        // never started, so left out of the file
+        metrics.counters("idle");
+        metrics.flush().unwrap();
+
+        let metrics = PersistentMetrics::open(&path, selected).unwrap();
+        let counters = metrics.counters("storage service");
+        assert_eq!(counters.processed_messages(), Some(1));
+        // starting again in a new run is a restart
+        counters.record_start();
+        assert_eq!(counters.restarts(), Some(1));
+        metrics.flush().unwrap();
+        let stored: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(
+            stored,
+            serde_json::json!({
+                "storage service": { "restarts": 1, "processed_messages": 1 }
+            })
+        );
+        // the staging file is renamed over the metrics one
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // counters of newer versions are ignored, and dropped once flushed
+        std::fs::write(
+            &path,
+            r#"{ "storage service": { "restarts": 2, "uptime": 1 } }"#,
+        )
+        .unwrap();
+        let metrics = PersistentMetrics::open(&path, selected).unwrap();
+        assert_eq!(metrics.counters("storage service").restarts(), Some(2));
+        metrics.flush().unwrap();
+        let stored: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(
+            stored,
+            serde_json::json!({
+                "storage service": { "restarts": 2, "processed_messages": 0 }
+            })
+        );
+
+        std::fs::write(&path, r#"{ "storage service": { "restarts": "two" } }"#).unwrap();
+        assert!(matches!(
+            PersistentMetrics::open(&path, selected),
+            Err(PersistentMetricsError::Malformed { .. })
+        ));
+        dir.remove().unwrap();
+    }
+}
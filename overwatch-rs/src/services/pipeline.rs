@@ -0,0 +1,115 @@
+// std
+use std::marker::PhantomData;
+use std::pin::Pin;
+// crates
+use futures::{Stream, StreamExt};
+// internal
+use crate::overwatch::events::{Backpressure, BackpressureLevel};
+use crate::overwatch::handle::OverwatchHandle;
+use crate::services::relay::{OutboundRelay, RelayError, RetryPolicy};
+use crate::services::{ServiceData, ServiceId};
+
+/// Service feeding its output to the `Next` service of a [`Pipeline`]
+/// The output message type of the stage is the input message of the next one, so mismatched
+/// stages do not compile.
+pub trait PipelineStage: ServiceData {
+    type Next: ServiceData;
+}
+
+/// Output of a pipeline stage: a relay to the `N` service that slows senders down
+/// cooperatively once `N` reports [`BackpressureLevel::High`]
+pub struct Downstream<N: ServiceData> {
+    relay: OutboundRelay<N::Message>,
+    backpressure: Pin<Box<dyn Stream<Item = Backpressure> + Send>>,
+}
+
+impl<N: ServiceData> Downstream<N> {
+    /// Connect to the `N` service, waiting for it if it is still starting
+    pub async fn connect(overwatch_handle: &OverwatchHandle) -> Result<Self, RelayError> {
+        // subscribe first so no level change after connecting is missed
+        let backpressure = Box::pin(overwatch_handle.backpressure_events());
+        let relay = overwatch_handle
+            .relay_with_retry::<N>(RetryPolicy::default())
+            .await?;
+        Ok(Self {
+            relay,
+            backpressure,
+        })
+    }
+
+    /// Current backpressure level of the `N` service inbound relay
+    pub fn level(&self) -> BackpressureLevel {
+        BackpressureLevel::from_depth(
+            self.relay.queue_depth(),
+            N::BACKPRESSURE_THRESHOLD,
+            self.relay.capacity(),
+        )
+    }
+
+    /// Wait until the `N` service is back to [`BackpressureLevel::Normal`]
+    pub async fn relieved(&mut self) {
+        while self.level() != BackpressureLevel::Normal {
+            // level changes of other services, or stale ones, only trigger a new check
+            if self.backpressure.next().await.is_none() {
+                return;
+            }
+        }
+    }
+
+    /// Send a message to the next stage once it is not under backpressure
+    pub async fn send(&mut self, message: N::Message) -> Result<(), (RelayError, N::Message)> {
+        self.relieved().await;
+        self.relay.send(message).await
+    }
+
+    /// Underlying relay, sending through it skips the cooperative slow down
+    pub fn relay(&self) -> &OutboundRelay<N::Message> {
+        &self.relay
+    }
+}
+
+/// Linear chain of services where each stage feeds the next one, `A -> B -> C`
+/// Every stage but the last one implements [`PipelineStage`] and gets its output with
+/// [`ServiceStateHandle::downstream`](crate::services::handle::ServiceStateHandle::downstream).
+pub struct Pipeline<First, Last> {
+    overwatch_handle: OverwatchHandle,
+    stages: Vec<ServiceId>,
+    _stages: PhantomData<fn() -> (First, Last)>,
+}
+
+impl<First: ServiceData> Pipeline<First, First> {
+    pub fn new(overwatch_handle: OverwatchHandle) -> Self {
+        Self {
+            overwatch_handle,
+            stages: vec![First::SERVICE_ID],
+            _stages: PhantomData,
+        }
+    }
+}
+
+impl<First: ServiceData, Last: ServiceData> Pipeline<First, Last> {
+    /// Append the stage `Last` feeds
+    pub fn then<N>(self) -> Pipeline<First, N>
+    where
+        N: ServiceData,
+        Last: PipelineStage<Next = N>,
+    {
+        let mut stages = self.stages;
+        stages.push(N::SERVICE_ID);
+        Pipeline {
+            overwatch_handle: self.overwatch_handle,
+            stages,
+            _stages: PhantomData,
+        }
+    }
+
+    /// Services ids of the stages, in order
+    pub fn stages(&self) -> &[ServiceId] {
+        &self.stages
+    }
+
+    /// Connect to the first stage, the pipeline input
+    pub async fn connect(&self) -> Result<Downstream<First>, RelayError> {
+        Downstream::connect(&self.overwatch_handle).await
+    }
+}
@@ -0,0 +1,192 @@
+// crates
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+// internal
+use crate::services::registry::{RegistryError, ServiceRegistry};
+use crate::services::relay::{relay, InboundRelay};
+use crate::utils::runtime::spawn_named;
+
+#[derive(Error, Debug)]
+pub enum SwapError {
+    #[error("plugin registered as {name} crashed, its inbound relay and state are lost")]
+    Crashed { name: String },
+}
+
+/// What a running plugin hands over to its replacement: the inbound relay, with the messages
+/// not handled yet, and its latest state
+pub struct Handover<M, State> {
+    pub inbound_relay: InboundRelay<M>,
+    pub state: State,
+}
+
+/// Service implementation added at runtime, e.g. loaded from a dylib, that can be swapped for
+/// a new implementation while running
+/// Implementations handle messages until `drain` is cancelled, and then give back their
+/// inbound relay and state without consuming any further message.
+#[async_trait]
+pub trait Plugin<M, State>: Send + 'static {
+    async fn run(
+        self: Box<Self>,
+        handover: Handover<M, State>,
+        drain: CancellationToken,
+    ) -> Handover<M, State>;
+}
+
+/// Runs a [`Plugin`] registered in a [`ServiceRegistry`] and swaps it without downtime
+/// The relay registered for the plugin stays the same across swaps, so relays obtained
+/// from the registry keep working and messages sent meanwhile wait in its buffer.
+pub struct PluginHost<M, State> {
+    name: String,
+    registry: ServiceRegistry,
+    runtime: Handle,
+    drain: CancellationToken,
+    /// `None` once the running plugin crashed
+    task: Option<JoinHandle<Handover<M, State>>>,
+}
+
+impl<M, State> PluginHost<M, State>
+where
+    M: Send + 'static,
+    State: Send + 'static,
+{
+    /// Register a relay of `buffer_size` under `name` and start `plugin` on it
+    pub fn start(
+        registry: &ServiceRegistry,
+        name: impl Into<String>,
+        buffer_size: usize,
+        state: State,
+        plugin: Box<dyn Plugin<M, State>>,
+        runtime: &Handle,
+    ) -> Result<Self, RegistryError> {
+        let name = name.into();
+        let (inbound_relay, outbound_relay) = relay(buffer_size);
+        registry.register(name.clone(), outbound_relay)?;
+        let drain = CancellationToken::new();
+        let task = spawn_named(
+            runtime,
+            &name,
+            plugin.run(
+                Handover {
+                    inbound_relay,
+                    state,
+                },
+                drain.clone(),
+            ),
+        );
+        Ok(Self {
+            name,
+            registry: registry.clone(),
+            runtime: runtime.clone(),
+            drain,
+            task: Some(task),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Drain the running plugin and start `plugin` with its inbound relay and state
+    /// If the running plugin crashed it is unregistered, as nothing is left to hand over.
+    pub async fn swap(&mut self, plugin: Box<dyn Plugin<M, State>>) -> Result<(), SwapError> {
+        let handover = self.drain().await?;
+        let drain = CancellationToken::new();
+        self.task = Some(spawn_named(
+            &self.runtime,
+            &self.name,
+            plugin.run(handover, drain.clone()),
+        ));
+        self.drain = drain;
+        Ok(())
+    }
+
+    /// Drain the running plugin and unregister it, returning what it handed over
+    pub async fn stop(mut self) -> Result<Handover<M, State>, SwapError> {
+        let handover = self.drain().await;
+        self.registry.unregister(&self.name);
+        handover
+    }
+
+    async fn drain(&mut self) -> Result<Handover<M, State>, SwapError> {
+        self.drain.cancel();
+        let crashed = || SwapError::Crashed {
+            name: self.name.clone(),
+        };
+        let task = self.task.take().ok_or_else(crashed)?;
+        task.await.map_err(|_| {
+            self.registry.unregister(&self.name);
+            crashed()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::plugin::{Handover, Plugin, PluginHost};
+    use crate::services::registry::ServiceRegistry;
+    use async_trait::async_trait;
+    use tokio::sync::mpsc::UnboundedSender;
+    use tokio_util::sync::CancellationToken;
+
+    /// Reports every message it handles along with its version and the count so far
+    struct Counter {
+        version: u8,
+        reports: UnboundedSender<(u8, String, usize)>,
+    }
+
+    #[async_trait]
+    impl Plugin<String, usize> for Counter {
+        async fn run(
+            self: Box<Self>,
+            mut handover: Handover<String, usize>,
+            drain: CancellationToken,
+        ) -> Handover<String, usize> {
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = drain.cancelled() => return handover,
+                    Some(message) = handover.inbound_relay.recv() => {
+                        handover.state += 1;
+                        let _ = self.reports.send((self.version, message, handover.state));
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn swapped_plugin_keeps_relay_and_state() {
+        let registry = ServiceRegistry::new();
+        let (reports_sender, mut reports) = tokio::sync::mpsc::unbounded_channel();
+        let counter = |version| {
+            Box::new(Counter {
+                version,
+                reports: reports_sender.clone(),
+            })
+        };
+        let mut host = PluginHost::start(
+            &registry,
+            "counter",
+            4,
+            0,
+            counter(1),
+            &tokio::runtime::Handle::current(),
+        )
+        .unwrap();
+        let relay = registry.relay::<String>("counter").unwrap();
+
+        relay.send("a".to_string()).await.unwrap();
+        assert_eq!(reports.recv().await, Some((1, "a".to_string(), 1)));
+        host.swap(counter(2)).await.unwrap();
+        // the relay obtained before the swap reaches the new implementation
+        relay.send("b".to_string()).await.unwrap();
+        assert_eq!(reports.recv().await, Some((2, "b".to_string(), 2)));
+
+        let handover = host.stop().await.unwrap();
+        assert_eq!(handover.state, 2);
+        assert!(registry.relay::<String>("counter").is_err());
+    }
+}
@@ -0,0 +1,404 @@
+// std
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+// crates
+// internal
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::Error;
+use crate::services::handle::{ServiceHandle, ServiceTask};
+use crate::services::ids::{intern, namespaced, strip_namespace, POOL_MEMBER_SEPARATOR};
+use crate::services::life_cycle::{LifecycleHandle, StopReason};
+use crate::services::relay::{OutboundRelay, RelayError};
+use crate::services::state::{ServiceState, SnapshotRequest};
+use crate::services::{ServiceCore, ServiceData, ServiceId};
+use crate::DynError;
+
+/// Id of the `index`-th member of a [`ServicePool`] of `S` services, `<SERVICE_ID>#<index>`
+pub fn member_id<S: ServiceData>(index: usize) -> ServiceId {
+    intern(format!("{}{POOL_MEMBER_SEPARATOR}{index}", S::SERVICE_ID))
+}
+
+/// Several instances of the same service, one per settings entry
+/// The number of instances is decided by the settings the pool is created from, member `i` runs
+/// under [`member_id::<S>(i)`](member_id), within the namespace of the bundle the pool is attached
+/// to, and can be reached with [`OverwatchHandle::relay_to`]. It can be changed afterwards with
+/// [`ServicePool::scale`].
+pub struct ServicePool<S: ServiceData> {
+    members: Vec<ServiceHandle<S>>,
+    /// Settings members added by [`ServicePool::scale`] start with, the first settings entry
+    template: Option<S::Settings>,
+    overwatch_handle: OverwatchHandle,
+}
+
+/// Members started and stopped by [`ServicePool::scale`], in the order it did it
+#[derive(Default)]
+pub struct Scaled {
+    pub started: Vec<(ServiceId, LifecycleHandle)>,
+    pub stopped: Vec<ServiceId>,
+}
+
+impl<S: ServiceData> ServicePool<S> {
+    pub fn new(
+        settings: Vec<S::Settings>,
+        overwatch_handle: OverwatchHandle,
+    ) -> Result<Self, <S::State as ServiceState>::Error> {
+        let template = settings.first().cloned();
+        let members = settings
+            .into_iter()
+            .enumerate()
+            .map(|(index, settings)| {
+                ServiceHandle::with_id(
+                    namespaced(overwatch_handle.namespace(), member_id::<S>(index)),
+                    settings,
+                    overwatch_handle.clone(),
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            members,
+            template,
+            overwatch_handle,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Ids of the pool members, in settings order
+    pub fn ids(&self) -> impl Iterator<Item = ServiceId> + '_ {
+        self.members.iter().map(ServiceHandle::id)
+    }
+
+    /// Whether `service_id` is the id of a member, relative to the namespace of the pool like
+    /// [`member_id`] ones
+    pub fn contains(&self, service_id: ServiceId) -> bool {
+        self.member(service_id).is_some()
+    }
+
+    /// Member running under `service_id`, relative to the namespace of the pool
+    pub fn member(&self, service_id: ServiceId) -> Option<&ServiceHandle<S>> {
+        let namespace = self.overwatch_handle.namespace();
+        self.members
+            .iter()
+            .find(|member| strip_namespace(member.id(), namespace) == Some(service_id))
+    }
+
+    /// Member running under `service_id`, relative to the namespace of the pool
+    pub fn member_mut(&mut self, service_id: ServiceId) -> Option<&mut ServiceHandle<S>> {
+        let namespace = self.overwatch_handle.namespace();
+        self.members
+            .iter_mut()
+            .find(|member| strip_namespace(member.id(), namespace) == Some(service_id))
+    }
+
+    /// Settings of every member, entry `i` is the one of member `i`
+    pub fn settings(&self) -> Vec<S::Settings> {
+        self.members.iter().map(ServiceHandle::settings).collect()
+    }
+
+    /// Run the state operators of the running members over their current states, see
+    /// [`ServiceHandle::request_snapshot`]
+    pub fn request_snapshots(&self) -> Vec<SnapshotRequest> {
+        self.members
+            .iter()
+            .filter_map(ServiceHandle::request_snapshot)
+            .collect()
+    }
+
+    /// Update members settings, entry `i` goes to member `i`
+    /// Settings don't resize the pool, so extra entries are ignored.
+    pub fn update_settings(&mut self, settings: Vec<S::Settings>) {
+        if let Some(template) = settings.first() {
+            self.template = Some(template.clone());
+        }
+        for (member, settings) in self.members.iter().zip(settings) {
+            member.update_settings(settings);
+        }
+    }
+}
+
+impl<S> ServicePool<S>
+where
+    S::State: Send + Sync + 'static,
+    S::StateOperator: Send + 'static,
+    S: ServiceCore + 'static,
+{
+    /// Start every member of the pool
+    pub fn start_all(&mut self) -> Result<Vec<(ServiceId, LifecycleHandle)>, Error> {
+        self.members.iter_mut().map(ServiceHandle::start).collect()
+    }
+
+    /// Take the running instance of every member, see [`ServiceHandle::take_task`]
+    pub fn take_tasks(&mut self) -> Vec<ServiceTask> {
+        self.members
+            .iter_mut()
+            .filter_map(ServiceHandle::take_task)
+            .collect()
+    }
+}
+
+impl<S> ServicePool<S>
+where
+    S::State: Send + Sync + 'static,
+    <S::State as ServiceState>::Error: Into<DynError>,
+    S::StateOperator: Send + 'static,
+    S: ServiceCore + 'static,
+{
+    /// Start or stop members until the pool has `members` of them
+    /// New members take the next indexes and start from the first settings entry, members are
+    /// stopped from the highest index down. As [`PooledOutboundRelay::send_routed`] uses
+    /// jump consistent hashing, only the route keys of the added or removed members move, once
+    /// senders get a new relay with
+    /// [`OverwatchHandle::pool_relay`](crate::overwatch::handle::OverwatchHandle::pool_relay).
+    /// If a new member can't be started, the ones started so far are stopped again.
+    pub fn scale(&mut self, members: usize) -> Result<Scaled, Error> {
+        let mut scaled = Scaled::default();
+        while self.members.len() > members {
+            let mut member = self
+                .members
+                .pop()
+                .expect("Pool has more members than the target");
+            member.stop(StopReason::Scaling);
+            scaled.stopped.push(member.id());
+        }
+        while self.members.len() < members {
+            match self.add_member() {
+                Ok(started) => scaled.started.push(started),
+                Err(e) => {
+                    for _ in &scaled.started {
+                        if let Some(mut member) = self.members.pop() {
+                            member.stop(StopReason::Scaling);
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(scaled)
+    }
+
+    fn add_member(&mut self) -> Result<(ServiceId, LifecycleHandle), Error> {
+        let template = self.template.clone().ok_or(Error::NoPoolSettings {
+            service_id: namespaced(self.overwatch_handle.namespace(), S::SERVICE_ID),
+        })?;
+        let mut member = ServiceHandle::with_id(
+            namespaced(
+                self.overwatch_handle.namespace(),
+                member_id::<S>(self.members.len()),
+            ),
+            template,
+            self.overwatch_handle.clone(),
+        )
+        .map_err(|e| Error::from(Into::<DynError>::into(e)))?;
+        let started = member.start()?;
+        self.members.push(member);
+        Ok(started)
+    }
+}
+
+/// Check if `service_id` is the id of a member of the pool `pool_id`, see [`member_id`]
+pub(crate) fn is_member_of(service_id: &str, pool_id: &str) -> bool {
+    service_id
+        .strip_prefix(pool_id)
+        .and_then(|suffix| suffix.strip_prefix(POOL_MEMBER_SEPARATOR))
+        .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// How a [`PooledOutboundRelay`] picks the pool member each message goes to
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BalanceStrategy {
+    /// Each member in turn
+    #[default]
+    RoundRobin,
+    /// Member with the fewest messages waiting in its relay buffer
+    LeastLoaded,
+}
+
+/// Messages that must always be handled by the same pool member
+pub trait RouteKey {
+    type Key: Hash;
+
+    /// Key identifying the messages that must land on the same pool member
+    fn route_key(&self) -> Self::Key;
+}
+
+/// Jump consistent hash (Lamping and Veach), maps `key` to a bucket in `0..buckets`
+/// Growing the number of buckets only moves the keys that land on the new ones.
+fn jump_consistent_hash(mut key: u64, buckets: usize) -> usize {
+    let mut bucket: i64 = -1;
+    let mut next: i64 = 0;
+    while next < buckets as i64 {
+        bucket = next;
+        key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+        next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    bucket as usize
+}
+
+/// Relay to every member of a [`ServicePool`], spreading messages across them
+/// Clones share the round robin position.
+pub struct PooledOutboundRelay<M> {
+    members: Vec<OutboundRelay<M>>,
+    strategy: BalanceStrategy,
+    next: Arc<AtomicUsize>,
+}
+
+impl<M> Clone for PooledOutboundRelay<M> {
+    fn clone(&self) -> Self {
+        Self {
+            members: self.members.clone(),
+            strategy: self.strategy,
+            next: Arc::clone(&self.next),
+        }
+    }
+}
+
+impl<M> PooledOutboundRelay<M> {
+    pub fn new(members: Vec<OutboundRelay<M>>, strategy: BalanceStrategy) -> Self {
+        Self {
+            members,
+            strategy,
+            next: Default::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn strategy(&self) -> BalanceStrategy {
+        self.strategy
+    }
+
+    /// Index of the member the next message goes to
+    fn pick(&self) -> Option<usize> {
+        if self.members.is_empty() {
+            return None;
+        }
+        match self.strategy {
+            BalanceStrategy::RoundRobin => {
+                Some(self.next.fetch_add(1, Ordering::Relaxed) % self.members.len())
+            }
+            BalanceStrategy::LeastLoaded => {
+                (0..self.members.len()).min_by_key(|&index| self.members[index].queue_depth())
+            }
+        }
+    }
+
+    /// Send a message to one of the pool members
+    pub async fn send(&self, message: M) -> Result<(), (RelayError, M)> {
+        match self.pick() {
+            Some(index) => self.members[index].send(message).await,
+            None => Err((RelayError::EmptyPool, message)),
+        }
+    }
+}
+
+impl<M: RouteKey> PooledOutboundRelay<M> {
+    /// Index of the member handling messages with the same route key as `message`
+    fn route(&self, message: &M) -> Option<usize> {
+        if self.members.is_empty() {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        message.route_key().hash(&mut hasher);
+        Some(jump_consistent_hash(hasher.finish(), self.members.len()))
+    }
+
+    /// Send a message to the pool member handling its route key, ignoring the balance strategy
+    pub async fn send_routed(&self, message: M) -> Result<(), (RelayError, M)> {
+        match self.route(&message) {
+            Some(index) => self.members[index].send(message).await,
+            None => Err((RelayError::EmptyPool, message)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::pool::{
+        is_member_of, jump_consistent_hash, BalanceStrategy, PooledOutboundRelay, RouteKey,
+    };
+    use crate::services::relay::relay;
+
+    #[test]
+    fn members_are_told_apart_from_plain_services() {
+        assert!(is_member_of("worker#0", "worker"));
+        assert!(is_member_of("worker#12", "worker"));
+        assert!(!is_member_of("worker-0", "worker"));
+        assert!(!is_member_of("worker#", "worker"));
+        assert!(!is_member_of("workers#0", "worker"));
+    }
+
+    #[tokio::test]
+    async fn pooled_relay_balances_sends() {
+        let (mut first, first_outbound) = relay::<usize>(4);
+        let (mut second, second_outbound) = relay::<usize>(4);
+        let pool = PooledOutboundRelay::new(
+            vec![first_outbound.clone(), second_outbound.clone()],
+            BalanceStrategy::RoundRobin,
+        );
+        for i in 0..4 {
+            pool.send(i).await.unwrap();
+        }
+        assert_eq!((first.len(), second.len()), (2, 2));
+        assert_eq!(first.recv().await, Some(0));
+        assert_eq!(second.recv().await, Some(1));
+
+        // first member has 1 message waiting now, second one has 1 as well
+        first.recv().await.unwrap();
+        let pool = PooledOutboundRelay::new(
+            vec![first_outbound, second_outbound],
+            BalanceStrategy::LeastLoaded,
+        );
+        pool.send(10).await.unwrap();
+        pool.send(11).await.unwrap();
+        assert_eq!((first.len(), second.len()), (2, 1));
+    }
+
+    #[derive(Debug)]
+    struct Keyed(u64, usize);
+
+    impl RouteKey for Keyed {
+        type Key = u64;
+
+        fn route_key(&self) -> Self::Key {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn routed_messages_stick_to_a_member() {
+        let (mut receivers, members): (Vec<_>, Vec<_>) = (0..3).map(|_| relay(16)).unzip();
+        let pool = PooledOutboundRelay::new(members, BalanceStrategy::RoundRobin);
+        for i in 0..4 {
+            pool.send_routed(Keyed(7, i)).await.unwrap();
+        }
+        let lens: Vec<usize> = receivers.iter().map(|receiver| receiver.len()).collect();
+        assert!(lens.contains(&4));
+        let receiver = receivers.iter_mut().find(|r| r.len() == 4).unwrap();
+        for i in 0..4 {
+            assert_eq!(receiver.recv().await.unwrap().1, i);
+        }
+    }
+
+    #[test]
+    fn growing_the_pool_moves_few_keys() {
+        let moved = (0..1000u64)
+            .filter(|&key| jump_consistent_hash(key, 4) != jump_consistent_hash(key, 5))
+            .inspect(|&key| assert_eq!(jump_consistent_hash(key, 5), 4))
+            .count();
+        assert!(moved < 400);
+    }
+}
@@ -0,0 +1,266 @@
+//! Token bucket rate limiting of the messages a service receives
+//!
+//! A service declaring a [`ServiceData::RATE_LIMIT`](crate::services::ServiceData::RATE_LIMIT)
+//! gets at most `burst` messages at once and `per_second` messages per second on average, so a
+//! chatty producer can't overwhelm it. Messages over the limit are left in the relay buffer,
+//! which pushes back on the senders once full, or dropped for rejecting limits. Both are counted
+//! in the service [`RelayMetrics`](crate::overwatch::metrics::RelayMetrics).
+//!
+//! A [`RateLimiter`] is a [`RelayMiddleware`] too, to limit the messages passed on by other
+//! middlewares only.
+
+// std
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+// crates
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tracing::debug;
+// internal
+use crate::overwatch::metrics::RelayStats;
+use crate::services::clock::Clock;
+use crate::services::middleware::RelayMiddleware;
+
+/// Rate messages are let through at, see [`ServiceData::RATE_LIMIT`](crate::services::ServiceData::RATE_LIMIT)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimit {
+    /// Messages let through per second once the burst is used up
+    pub per_second: u32,
+    /// Messages let through at once after an idle period
+    pub burst: u32,
+    /// Drop the messages over the limit instead of holding them back
+    pub reject: bool,
+}
+
+impl RateLimit {
+    pub const fn new(per_second: u32, burst: u32) -> Self {
+        assert!(per_second > 0, "rate limit must let messages through");
+        assert!(burst > 0, "rate limit burst must let messages through");
+        Self {
+            per_second,
+            burst,
+            reject: false,
+        }
+    }
+
+    pub const fn rejecting(self) -> Self {
+        Self {
+            reject: true,
+            ..self
+        }
+    }
+}
+
+/// Tokens left, and when they were last refilled
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket applying a [`RateLimit`], measured with the application clock
+pub struct RateLimiter {
+    limit: RateLimit,
+    clock: Arc<dyn Clock>,
+    bucket: Mutex<Bucket>,
+    /// Where delayed and rejected messages are counted
+    stats: Option<Arc<RelayStats>>,
+}
+
+impl Debug for RateLimiter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("limit", &self.limit)
+            .field("bucket", &self.bucket)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimiter {
+    /// Limiter starting with a full burst
+    pub fn new(limit: RateLimit, clock: Arc<dyn Clock>) -> Self {
+        let bucket = Bucket {
+            tokens: f64::from(limit.burst),
+            refilled_at: clock.now(),
+        };
+        Self {
+            limit,
+            clock,
+            bucket: Mutex::new(bucket),
+            stats: None,
+        }
+    }
+
+    /// Count delayed and rejected messages in the service `stats`
+    pub(crate) fn measured(mut self, stats: Arc<RelayStats>) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Take a token for a message, or tell how long until one is available
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().expect("Rate limit lock not poisoned");
+        let now = self.clock.now();
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        let per_second = f64::from(self.limit.per_second);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * per_second).min(f64::from(self.limit.burst));
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+    }
+
+    /// Wait for a token, `false` if the message is to be dropped as the limit rejects it
+    pub async fn acquire(&self) -> bool {
+        let mut delayed = false;
+        loop {
+            let wait = match self.try_acquire() {
+                Ok(()) => return true,
+                Err(wait) => wait,
+            };
+            if self.limit.reject {
+                self.record_rejected();
+                return false;
+            }
+            if !std::mem::replace(&mut delayed, true) {
+                self.record_delayed();
+            }
+            self.clock.sleep(wait).await;
+        }
+    }
+
+    fn record_delayed(&self) {
+        if let Some(stats) = &self.stats {
+            stats.record_throttled();
+        }
+    }
+
+    fn record_rejected(&self) {
+        debug!("Rate limit exceeded, dropping the received message");
+        if let Some(stats) = &self.stats {
+            stats.record_rate_limited();
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Send + 'static> RelayMiddleware<M> for RateLimiter {
+    async fn handle(&self, message: M) -> Option<M> {
+        self.acquire().await.then_some(message)
+    }
+}
+
+/// Rate limit of a service inbound relay, checked before the messages are pulled from the
+/// channel so held back messages stay in the buffer
+pub(crate) struct RelayThrottle {
+    limiter: RateLimiter,
+    /// A token was taken for the next message
+    holding: bool,
+    /// The next message was counted as delayed already
+    delayed: bool,
+    /// Waiting for a token, only behind a lock to keep relays `Sync`
+    waiting: Mutex<Option<BoxFuture<'static, ()>>>,
+}
+
+impl Debug for RelayThrottle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelayThrottle")
+            .field("limiter", &self.limiter)
+            .field("holding", &self.holding)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RelayThrottle {
+    pub(crate) fn new(limiter: RateLimiter) -> Self {
+        Self {
+            limiter,
+            holding: false,
+            delayed: false,
+            waiting: Mutex::new(None),
+        }
+    }
+
+    /// Next message let through by the limit, pulling messages with `receive`
+    pub(crate) fn poll_next<M>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut receive: impl FnMut(&mut Context<'_>) -> Poll<Option<M>>,
+    ) -> Poll<Option<M>> {
+        if !self.limiter.limit.reject {
+            ready!(self.poll_token(cx));
+            let message = ready!(receive(cx));
+            self.holding = false;
+            return Poll::Ready(message);
+        }
+        loop {
+            let message = ready!(receive(cx));
+            if message.is_none() || self.limiter.try_acquire().is_ok() {
+                return Poll::Ready(message);
+            }
+            self.limiter.record_rejected();
+        }
+    }
+
+    fn poll_token(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let waiting = self
+            .waiting
+            .get_mut()
+            .expect("Rate limit lock not poisoned");
+        while !self.holding {
+            if let Some(sleep) = waiting {
+                ready!(sleep.poll_unpin(cx));
+                *waiting = None;
+            }
+            match self.limiter.try_acquire() {
+                Ok(()) => {
+                    self.holding = true;
+                    self.delayed = false;
+                }
+                Err(wait) => {
+                    if !std::mem::replace(&mut self.delayed, true) {
+                        self.limiter.record_delayed();
+                    }
+                    *waiting = Some(self.limiter.clock.sleep(wait));
+                }
+            }
+        }
+        Poll::Ready(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::clock::ManualClock;
+    use crate::services::rate_limit::{RateLimit, RateLimiter};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn tokens_refill_at_the_limit_rate() {
+        let clock = ManualClock::new();
+        let limiter = RateLimiter::new(RateLimit::new(10, 2), Arc::new(clock.clone()));
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert_eq!(limiter.try_acquire(), Err(Duration::from_millis(100)));
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(limiter.try_acquire(), Err(Duration::from_millis(50)));
+        clock.advance(Duration::from_millis(50));
+        assert!(limiter.try_acquire().is_ok());
+        // the bucket never holds more than the burst
+        clock.advance(Duration::from_secs(10));
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! Recording of the messages sent through relays, and their replay into another application
+//!
+//! A [`MessageRecorder`], set with
+//! [`ContextConfig::with_recorder`](crate::services::context::ContextConfig::with_recorder),
+//! hands every message delivered through a relay to a [`MessageSink`], along with its sender,
+//! receiver and time. Messages of the types registered with [`MessageRecorder::replayable`] are
+//! copied as well, so the recorded trace can be sent again to a fresh application with a
+//! [`MessageReplay`] to reproduce a bug.
+//!
+//! ```ignore
+//! let trace = MessageTrace::default();
+//! let recorder = MessageRecorder::new(trace.clone()).replayable::<StoreMessage>();
+//! let overwatch = OverwatchRunner::<App>::run_with_context_config(
+//!     settings.clone(),
+//!     None,
+//!     ContextConfig::default().with_recorder(recorder),
+//! )?;
+//! // ... once the bug showed up
+//! let fresh = OverwatchRunner::<App>::run(settings, None)?;
+//! MessageReplay::new(trace.messages())
+//!     .from_outside()
+//!     .run(fresh.handle())
+//!     .await?;
+//! ```
+
+// std
+use std::any::{type_name, Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+// crates
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::sync::oneshot;
+// internal
+use crate::overwatch::commands::{OverwatchCommand, RelayCommand, ReplyChannel};
+use crate::overwatch::handle::OverwatchHandle;
+use crate::services::clock::Clock;
+use crate::services::relay::{AnyMessage, OutboundRelay, RelayError};
+use crate::services::ServiceId;
+
+/// Message delivered through a relay, as recorded by a [`MessageRecorder`]
+#[derive(Clone)]
+pub struct RecordedMessage {
+    /// Time since the first recorded message, measured with the application clock
+    pub at: Duration,
+    /// Sending service, `None` if sent from outside any service
+    pub from: Option<ServiceId>,
+    pub to: ServiceId,
+    /// Debug representation of the message
+    pub message: String,
+    /// Copy of the message, for replayable message types
+    payload: Option<Arc<dyn ReplayPayload>>,
+}
+
+impl Debug for RecordedMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordedMessage")
+            .field("at", &self.at)
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("message", &self.message)
+            .field("replayable", &self.is_replayable())
+            .finish()
+    }
+}
+
+impl RecordedMessage {
+    /// Whether a [`MessageReplay`] can send the message again, see
+    /// [`MessageRecorder::replayable`]
+    pub fn is_replayable(&self) -> bool {
+        self.payload.is_some()
+    }
+}
+
+/// Where a [`MessageRecorder`] hands the recorded messages
+/// Called by the sender, right after delivering the message, so it should not block.
+pub trait MessageSink: Send + Sync + 'static {
+    fn record(&self, message: RecordedMessage);
+}
+
+impl<F> MessageSink for F
+where
+    F: Fn(RecordedMessage) + Send + Sync + 'static,
+{
+    fn record(&self, message: RecordedMessage) {
+        self(message);
+    }
+}
+
+/// [`MessageSink`] keeping the recorded messages in memory, clones share them
+#[derive(Clone, Debug, Default)]
+pub struct MessageTrace(Arc<Mutex<Vec<RecordedMessage>>>);
+
+impl MessageTrace {
+    /// Messages recorded so far, in delivery order
+    pub fn messages(&self) -> Vec<RecordedMessage> {
+        self.0.lock().expect("Trace lock not poisoned").clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.lock().expect("Trace lock not poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl MessageSink for MessageTrace {
+    fn record(&self, message: RecordedMessage) {
+        self.0
+            .lock()
+            .expect("Trace lock not poisoned")
+            .push(message);
+    }
+}
+
+/// Copies a message of a replayable type for the trace
+type Copier<M> = fn(&M) -> Arc<dyn ReplayPayload>;
+
+/// Records the messages delivered through the relays of an application
+pub struct MessageRecorder {
+    sink: Arc<dyn MessageSink>,
+    /// Application clock time of the first recorded message
+    origin: OnceLock<Instant>,
+    /// [`Copier`] of each replayable message type
+    copiers: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Debug for MessageRecorder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageRecorder")
+            .field("origin", &self.origin)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MessageRecorder {
+    pub fn new<S: MessageSink>(sink: S) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            origin: OnceLock::new(),
+            copiers: HashMap::new(),
+        }
+    }
+
+    /// Copy the `M` messages into the trace, so a [`MessageReplay`] can send them again
+    pub fn replayable<M: Clone + Send + Sync + 'static>(mut self) -> Self {
+        let copier: Copier<M> = |message| Arc::new(Payload(message.clone()));
+        self.copiers.insert(TypeId::of::<M>(), Box::new(copier));
+        self
+    }
+
+    fn copier<M: 'static>(&self) -> Option<Copier<M>> {
+        self.copiers
+            .get(&TypeId::of::<M>())?
+            .downcast_ref::<Copier<M>>()
+            .copied()
+    }
+}
+
+/// Records the messages sent through an outbound relay
+pub(crate) struct RelayTap<M> {
+    recorder: Arc<MessageRecorder>,
+    clock: Arc<dyn Clock>,
+    from: Option<ServiceId>,
+    to: ServiceId,
+    describe: fn(&M) -> String,
+    copier: Option<Copier<M>>,
+}
+
+// auto derive introduces an unnecessary Clone bound on M
+impl<M> Clone for RelayTap<M> {
+    fn clone(&self) -> Self {
+        Self {
+            recorder: Arc::clone(&self.recorder),
+            clock: Arc::clone(&self.clock),
+            from: self.from,
+            to: self.to,
+            describe: self.describe,
+            copier: self.copier,
+        }
+    }
+}
+
+impl<M> Debug for RelayTap<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelayTap")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M: Debug + 'static> RelayTap<M> {
+    pub(crate) fn new(
+        recorder: Arc<MessageRecorder>,
+        clock: Arc<dyn Clock>,
+        from: Option<ServiceId>,
+        to: ServiceId,
+    ) -> Self {
+        let copier = recorder.copier::<M>();
+        Self {
+            recorder,
+            clock,
+            from,
+            to,
+            describe: |message| format!("{message:?}"),
+            copier,
+        }
+    }
+}
+
+impl<M> RelayTap<M> {
+    /// Take note of `message` before it is sent, it is only recorded once delivered
+    pub(crate) fn capture(&self, message: &M) -> RecordedMessage {
+        let now = self.clock.now();
+        let origin = *self.recorder.origin.get_or_init(|| now);
+        RecordedMessage {
+            at: now.saturating_duration_since(origin),
+            from: self.from,
+            to: self.to,
+            message: (self.describe)(message),
+            payload: self.copier.map(|copier| copier(message)),
+        }
+    }
+
+    pub(crate) fn record(&self, message: RecordedMessage) {
+        self.recorder.sink.record(message);
+    }
+}
+
+/// Copy of a recorded message, sent again through a type erased relay
+trait ReplayPayload: Send + Sync {
+    /// Send the message through `relay`, `None` if it doesn't take this message type
+    fn send(&self, relay: &AnyMessage) -> Option<BoxFuture<'static, Result<(), RelayError>>>;
+
+    fn type_name(&self) -> &'static str;
+}
+
+struct Payload<M>(M);
+
+impl<M: Clone + Send + Sync + 'static> ReplayPayload for Payload<M> {
+    fn send(&self, relay: &AnyMessage) -> Option<BoxFuture<'static, Result<(), RelayError>>> {
+        let relay = relay.downcast_ref::<OutboundRelay<M>>()?.clone();
+        let message = self.0.clone();
+        Some(async move { relay.send(message).await.map_err(|(e, _)| e) }.boxed())
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<M>()
+    }
+}
+
+/// Sends the replayable messages of a recorded trace again, to an application running the same
+/// services
+#[derive(Clone, Debug)]
+pub struct MessageReplay {
+    messages: Vec<RecordedMessage>,
+}
+
+impl MessageReplay {
+    /// Replay `messages`, those that are not replayable are skipped
+    pub fn new(messages: Vec<RecordedMessage>) -> Self {
+        Self { messages }
+    }
+
+    /// Only replay the messages sent from outside any service
+    /// Services send theirs again on their own while handling the replayed messages.
+    pub fn from_outside(mut self) -> Self {
+        self.messages.retain(|message| message.from.is_none());
+        self
+    }
+
+    /// Send the messages to the services of the application behind `handle`, in order and keeping
+    /// their recorded spacing on its clock, returning how many were sent
+    pub async fn run(&self, handle: &OverwatchHandle) -> Result<usize, RelayError> {
+        let clock = Arc::clone(&handle.context_config().clock);
+        let origin = clock.now();
+        let mut relays = HashMap::new();
+        let mut sent = 0;
+        for message in &self.messages {
+            let Some(payload) = &message.payload else {
+                continue;
+            };
+            clock.sleep_until(origin + message.at).await;
+            let relay = match relays.entry(message.to) {
+                Entry::Occupied(relay) => relay.into_mut(),
+                Entry::Vacant(entry) => entry.insert(request_relay(handle, message.to).await?),
+            };
+            let send = payload
+                .send(relay)
+                .ok_or_else(|| RelayError::InvalidMessage {
+                    type_id: payload.type_name().to_owned(),
+                    service_id: message.to,
+                })?;
+            send.await?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+}
+
+/// Type erased relay to `service_id`, as handed out by the overwatch runner
+async fn request_relay(
+    handle: &OverwatchHandle,
+    service_id: ServiceId,
+) -> Result<AnyMessage, RelayError> {
+    let (reply, receiver) = oneshot::channel();
+    handle
+        .send(OverwatchCommand::Relay(RelayCommand {
+            service_id,
+            requester: handle.owner(),
+            reply_channel: ReplyChannel(reply),
+        }))
+        .await;
+    receiver
+        .await
+        .map_err(|e| RelayError::Receiver(Box::new(e)))?
+}
@@ -0,0 +1,170 @@
+// std
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+// crates
+use futures::Stream;
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+// internal
+use crate::services::relay::OutboundRelay;
+
+#[derive(Error, Debug)]
+pub enum RegistryError {
+    #[error("a service is already registered as {name}")]
+    AlreadyRegistered { name: String },
+    #[error("no service is registered as {name}")]
+    NotFound { name: String },
+    #[error("service registered as {name} doesn't take the requested message type")]
+    InvalidMessage { name: String },
+}
+
+/// Notifications about services registered in a [`ServiceRegistry`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RegistryEvent {
+    Added(String),
+    Removed(String),
+}
+
+type AnyRelay = Box<dyn Any + Send + Sync>;
+
+/// Name to relay mapping for services added at runtime
+/// Services register their relay under a name, other services can then look them up and get
+/// notified when services are added or removed.
+#[derive(Clone)]
+pub struct ServiceRegistry {
+    relays: Arc<RwLock<HashMap<String, AnyRelay>>>,
+    events: broadcast::Sender<RegistryEvent>,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(16);
+        Self {
+            relays: Default::default(),
+            events,
+        }
+    }
+
+    /// Register a service relay under `name`
+    pub fn register<M: Send + 'static>(
+        &self,
+        name: impl Into<String>,
+        relay: OutboundRelay<M>,
+    ) -> Result<(), RegistryError> {
+        let name = name.into();
+        let mut relays = self.relays.write().expect("Registry lock not poisoned");
+        if relays.contains_key(&name) {
+            return Err(RegistryError::AlreadyRegistered { name });
+        }
+        relays.insert(name.clone(), Box::new(relay));
+        // nobody listening is fine
+        let _ = self.events.send(RegistryEvent::Added(name));
+        Ok(())
+    }
+
+    /// Remove the service registered under `name`, returns if there was any
+    pub fn unregister(&self, name: &str) -> bool {
+        let removed = self
+            .relays
+            .write()
+            .expect("Registry lock not poisoned")
+            .remove(name)
+            .is_some();
+        if removed {
+            let _ = self.events.send(RegistryEvent::Removed(name.to_string()));
+        }
+        removed
+    }
+
+    /// Get a relay to the service registered under `name`
+    pub fn relay<M: Send + 'static>(&self, name: &str) -> Result<OutboundRelay<M>, RegistryError> {
+        let relays = self.relays.read().expect("Registry lock not poisoned");
+        let relay = relays.get(name).ok_or_else(|| RegistryError::NotFound {
+            name: name.to_string(),
+        })?;
+        relay
+            .downcast_ref::<OutboundRelay<M>>()
+            .cloned()
+            .ok_or_else(|| RegistryError::InvalidMessage {
+                name: name.to_string(),
+            })
+    }
+
+    /// Names of the currently registered services
+    pub fn names(&self) -> Vec<String> {
+        self.relays
+            .read()
+            .expect("Registry lock not poisoned")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Stream of services added or removed after subscribing
+    pub fn subscribe(&self) -> impl Stream<Item = RegistryEvent> {
+        BroadcastStream::new(self.events.subscribe()).filter_map(Result::ok)
+    }
+}
+
+impl Default for ServiceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ServiceRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceRegistry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::registry::{RegistryError, RegistryEvent, ServiceRegistry};
+    use crate::services::relay::relay;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn register_lookup_and_notify() {
+        let registry = ServiceRegistry::new();
+        let mut events = Box::pin(registry.subscribe());
+        let (mut inbound, outbound) = relay::<String>(1);
+
+        registry.register("plugin", outbound).unwrap();
+        assert!(matches!(
+            registry.register("plugin", relay::<String>(1).1),
+            Err(RegistryError::AlreadyRegistered { .. })
+        ));
+        assert!(matches!(
+            registry.relay::<usize>("plugin"),
+            Err(RegistryError::InvalidMessage { .. })
+        ));
+
+        registry
+            .relay::<String>("plugin")
+            .unwrap()
+            .send("hello".to_string())
+            .await
+            .unwrap();
+        assert_eq!(inbound.recv().await.unwrap(), "hello");
+
+        assert!(registry.unregister("plugin"));
+        assert!(matches!(
+            registry.relay::<String>("plugin"),
+            Err(RegistryError::NotFound { .. })
+        ));
+        assert_eq!(
+            events.next().await,
+            Some(RegistryEvent::Added("plugin".to_string()))
+        );
+        assert_eq!(
+            events.next().await,
+            Some(RegistryEvent::Removed("plugin".to_string()))
+        );
+    }
+}
@@ -3,20 +3,38 @@ use std::any::Any;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, Weak};
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 // crates
-use futures::{Sink, Stream};
+use futures::future::{poll_fn, BoxFuture};
+use futures::{FutureExt, Sink, Stream};
 use thiserror::Error;
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender, WeakSender};
 use tokio::sync::oneshot;
 use tokio_util::sync::PollSender;
-use tracing::error;
 #[cfg(feature = "instrumentation")]
 use tracing::instrument;
+use tracing::{debug, info};
 // internal
 use crate::overwatch::commands::{OverwatchCommand, RelayCommand, ReplyChannel};
+use crate::overwatch::events::{Backpressure, BackpressureLevel, EventsSender};
 use crate::overwatch::handle::OverwatchHandle;
-use crate::services::{ServiceData, ServiceId};
+use crate::overwatch::metrics::{RelayStats, SendOutcome};
+use crate::services::clock::{Clock, SystemClock};
+use crate::services::life_cycle::{finished_signal, LifecycleHandle, LifecycleMessage, StopReason};
+use crate::services::middleware::{MiddlewareChain, RelayMiddleware};
+use crate::services::persistent_metrics::ServiceCounters;
+use crate::services::rate_limit::{RateLimiter, RelayThrottle};
+use crate::services::recording::{RecordedMessage, RelayTap};
+use crate::services::relay_pause::RelayPause;
+use crate::services::simulation::RelayLatency;
+use crate::services::status::{ServiceStatus, ServiceStatusError};
+use crate::services::stream::{ResponseStream, StreamRequest};
+use crate::services::yielding::YieldBudget;
+use crate::services::{ServiceData, ServiceId, TRACING_TARGET};
 
 #[derive(Error, Debug)]
 pub enum RelayError {
@@ -30,6 +48,8 @@ pub enum RelayError {
     Disconnected,
     #[error("service {service_id} is not available")]
     Unavailable { service_id: ServiceId },
+    #[error("service {service_id} is disabled")]
+    ServiceDisabled { service_id: ServiceId },
     #[error("invalid message with type id [{type_id}] for service {service_id}")]
     InvalidMessage {
         type_id: String,
@@ -37,6 +57,122 @@ pub enum RelayError {
     },
     #[error("receiver failed due to {0:?}")]
     Receiver(Box<dyn Debug + Send + Sync>),
+    #[error("relay request to {service_id} service timed out")]
+    Timeout { service_id: ServiceId },
+    #[error("service {service_id} dropped its inbound relay while running")]
+    InboundDropped { service_id: ServiceId },
+    #[error("relay pool has no members")]
+    EmptyPool,
+    #[error("service {from} is not allowed to relay messages to {to} service")]
+    Unauthorized { from: ServiceId, to: ServiceId },
+    #[error("service relay is full")]
+    Full,
+    #[error("service {service_id} takes no messages, it has no relay")]
+    NoRelay { service_id: ServiceId },
+    #[error("service {service_id} dropped the request without replying")]
+    NoReply { service_id: ServiceId },
+    #[error("service {service_id} didn't reply within {timeout:?}")]
+    ReplyTimeout {
+        service_id: ServiceId,
+        timeout: Duration,
+    },
+    #[error("service {service_id} failed before being ready: {reason}")]
+    Failed {
+        service_id: ServiceId,
+        reason: String,
+    },
+}
+
+/// Authorization policy checked whenever a service requests a relay to another service, or
+/// subscribes to its broadcasts
+/// Relays requested from outside services (the application itself) are always allowed.
+pub trait RelayPolicy: Send + Sync + 'static {
+    fn allow(&self, from: ServiceId, to: ServiceId) -> bool;
+}
+
+/// [`RelayPolicy`] allowing every service to talk to any other
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AllowAll;
+
+impl RelayPolicy for AllowAll {
+    fn allow(&self, _from: ServiceId, _to: ServiceId) -> bool {
+        true
+    }
+}
+
+impl<F> RelayPolicy for F
+where
+    F: Fn(ServiceId, ServiceId) -> bool + Send + Sync + 'static,
+{
+    fn allow(&self, from: ServiceId, to: ServiceId) -> bool {
+        self(from, to)
+    }
+}
+
+/// What sending to a service does once its relay buffer is full, see
+/// [`ServiceData::BACKPRESSURE_POLICY`]
+/// Discarded and rejected messages are counted in the service
+/// [`RelayMetrics::overflowed`](crate::overwatch::metrics::RelayMetrics::overflowed).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BackpressurePolicy {
+    /// Wait for room in the buffer
+    #[default]
+    Block,
+    /// Discard the message being sent, the send succeeds
+    DropNewest,
+    /// Discard the oldest buffered message to make room for the one being sent
+    /// The send fails with [`RelayError::Full`] if the buffer is only taken by reserved permits,
+    /// e.g. of a relay [sink](OutboundRelay::into_sink), as there is no message to discard.
+    DropOldest,
+    /// Fail the send with [`RelayError::Full`], giving the message back
+    Error,
+}
+
+/// Backoff policy used when retrying a relay request to a service that is still starting.
+/// See [`OverwatchHandle::relay_with_retry`].
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first failed attempt
+    pub max_retries: usize,
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between retries
+    pub max_delay: Duration,
+    /// Factor applied to the delay after each retry
+    pub backoff_factor: u32,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: usize, initial_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_delay,
+            ..Default::default()
+        }
+    }
+
+    /// Delay to wait before the given (zero based) retry attempt
+    pub fn delay_for(&self, attempt: usize) -> Duration {
+        let factor = self
+            .backoff_factor
+            .checked_pow(attempt.try_into().unwrap_or(u32::MAX))
+            .unwrap_or(u32::MAX);
+        self.initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+            backoff_factor: 2,
+        }
+    }
 }
 
 /// Message wrapper type
@@ -47,29 +183,192 @@ pub struct NoMessage;
 
 impl RelayMessage for NoMessage {}
 
+/// Message type of background services that take no messages at all
+/// Unlike [`NoMessage`], no relay channel is allocated for them.
+#[derive(Debug, Clone)]
+pub enum NoRelay {}
+
+impl RelayMessage for NoRelay {
+    const NO_RELAY: bool = true;
+}
+
 /// Result type when creating a relay connection
 pub type RelayResult = Result<AnyMessage, RelayError>;
 
 /// Marker type for relay messages
 /// Notice that it is bound to 'static.
-pub trait RelayMessage: 'static {}
+pub trait RelayMessage: 'static {
+    /// Services taking this message type get no relay channel, and requesting a relay to them
+    /// fails with [`RelayError::NoRelay`]
+    const NO_RELAY: bool = false;
+}
 
 /// Channel receiver of a relay connection
 #[derive(Debug)]
 pub struct InboundRelay<M> {
-    receiver: Receiver<M>,
-    _stats: (), // placeholder
+    /// `None` for services taking no messages, see [`RelayMessage::NO_RELAY`]
+    receiver: Option<RelayReceiver<M>>,
+    /// Drained ahead of `receiver`, see [`OutboundRelay::send_priority`]
+    priority: Option<Receiver<M>>,
+    /// While paused, messages are left in the channel so senders experience backpressure
+    paused: bool,
+    /// Switch Overwatch pauses the relay with, see [`InboundRelay::paused_by`]
+    pause: Option<RelayPause>,
+    backpressure: Option<Arc<BackpressureMonitor>>,
+    /// Dropped along with the relay, see [`InboundRelay::dropped_signal`]
+    dropped: Option<oneshot::Sender<()>>,
+    /// Where received messages are counted, if enabled
+    counters: Option<Arc<ServiceCounters>>,
+    /// Messages handed over before yielding to the executor, see [`InboundRelay::yielding`]
+    yield_budget: Option<YieldBudget>,
+    /// Set once the budget is exhausted, the next receive yields before pulling a message
+    yield_next: bool,
+    /// Where received messages are counted, see [`OverwatchHandle::metrics`]
+    stats: Option<Arc<RelayStats>>,
+    /// Layers received messages go through, see [`InboundRelay::with_middleware`]
+    middleware: Option<MiddlewareChain<M>>,
+    /// Rate limit of the service, applied before the middlewares
+    throttle: Option<RelayThrottle>,
 }
 
 /// Channel sender of a relay connection
 pub struct OutboundRelay<M> {
     sender: Sender<M>,
-    _stats: (), // placeholder
+    priority: Sender<M>,
+    backpressure: Option<Arc<BackpressureMonitor>>,
+    /// Virtual latency waited for before each send, see [`crate::services::simulation`]
+    latency: Option<RelayLatency>,
+    /// Where sends are counted and timed, see [`OverwatchHandle::metrics`]
+    stats: Option<Arc<RelayStats>>,
+    /// Where delivered messages are recorded, see [`crate::services::recording`]
+    tap: Option<RelayTap<M>>,
+    policy: BackpressurePolicy,
+    /// Receiver the oldest messages are discarded from under [`BackpressurePolicy::DropOldest`]
+    evict: Option<Weak<Mutex<Receiver<M>>>>,
+}
+
+/// Receiving end of the relay channel, shared with the senders under
+/// [`BackpressurePolicy::DropOldest`] so they can discard buffered messages
+#[derive(Debug)]
+enum RelayReceiver<M> {
+    Owned(Receiver<M>),
+    Shared(Arc<Mutex<Receiver<M>>>),
+}
+
+impl<M> RelayReceiver<M> {
+    fn with<T>(&mut self, f: impl FnOnce(&mut Receiver<M>) -> T) -> T {
+        match self {
+            Self::Owned(receiver) => f(receiver),
+            Self::Shared(receiver) => f(&mut receiver.lock().expect("Relay lock not poisoned")),
+        }
+    }
+
+    fn inspect<T>(&self, f: impl FnOnce(&Receiver<M>) -> T) -> T {
+        match self {
+            Self::Owned(receiver) => f(receiver),
+            Self::Shared(receiver) => f(&receiver.lock().expect("Relay lock not poisoned")),
+        }
+    }
+}
+
+/// Relay sender that does not keep the relay channel alive, see [`OutboundRelay::downgrade`]
+pub struct WeakOutboundRelay<M> {
+    sender: WeakSender<M>,
+    priority: WeakSender<M>,
+    backpressure: Option<Arc<BackpressureMonitor>>,
+    latency: Option<RelayLatency>,
+    stats: Option<Arc<RelayStats>>,
+    tap: Option<RelayTap<M>>,
+    policy: BackpressurePolicy,
+    evict: Option<Weak<Mutex<Receiver<M>>>>,
+}
+
+impl<M> Clone for WeakOutboundRelay<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            priority: self.priority.clone(),
+            backpressure: self.backpressure.clone(),
+            latency: self.latency.clone(),
+            stats: self.stats.clone(),
+            tap: self.tap.clone(),
+            policy: self.policy,
+            evict: self.evict.clone(),
+        }
+    }
+}
+
+impl<M> Debug for WeakOutboundRelay<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeakOutboundRelay").finish_non_exhaustive()
+    }
+}
+
+impl<M> WeakOutboundRelay<M> {
+    /// Get back a regular relay, `None` if the service no longer consumes its inbound relay
+    pub fn upgrade(&self) -> Option<OutboundRelay<M>> {
+        let sender = self.sender.upgrade()?;
+        let priority = self.priority.upgrade()?;
+        // other senders may keep the channel alive after the receiver is gone
+        if sender.is_closed() {
+            return None;
+        }
+        Some(OutboundRelay {
+            sender,
+            priority,
+            backpressure: self.backpressure.clone(),
+            latency: self.latency.clone(),
+            stats: self.stats.clone(),
+            tap: self.tap.clone(),
+            policy: self.policy,
+            evict: self.evict.clone(),
+        })
+    }
+
+    /// Upgrade the relay and send a message through it
+    pub async fn send(&self, message: M) -> Result<(), (RelayError, M)> {
+        match self.upgrade() {
+            Some(relay) => relay.send(message).await,
+            None => Err((RelayError::Disconnected, message)),
+        }
+    }
+}
+
+/// Tracks the [`BackpressureLevel`] of a service inbound relay, publishing its changes
+/// Sends raise the level and receives lower it, so both relay sides share the monitor.
+#[derive(Debug)]
+pub(crate) struct BackpressureMonitor {
+    service_id: ServiceId,
+    threshold: usize,
+    level: AtomicU8,
+    events: EventsSender,
+}
+
+impl BackpressureMonitor {
+    pub(crate) fn new(service_id: ServiceId, threshold: usize, events: EventsSender) -> Self {
+        Self {
+            service_id,
+            threshold,
+            level: AtomicU8::new(BackpressureLevel::Normal as u8),
+            events,
+        }
+    }
+
+    fn observe(&self, depth: usize, capacity: usize) {
+        let level = BackpressureLevel::from_depth(depth, self.threshold, capacity);
+        if self.level.swap(level as u8, Ordering::AcqRel) != level as u8 {
+            self.events
+                .report_backpressure(Backpressure(self.service_id, level));
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Relay<S> {
     overwatch_handle: OverwatchHandle,
+    /// Id of the target service, [`ServiceData::SERVICE_ID`] within the namespace of its bundle
+    /// unless targeting a pool member
+    service_id: ServiceId,
     _bound: PhantomBound<S>,
 }
 
@@ -77,6 +376,7 @@ impl<T> Clone for Relay<T> {
     fn clone(&self) -> Self {
         Self {
             overwatch_handle: self.overwatch_handle.clone(),
+            service_id: self.service_id,
             _bound: PhantomBound {
                 _inner: PhantomData,
             },
@@ -98,38 +398,486 @@ impl<M> Clone for OutboundRelay<M> {
     fn clone(&self) -> Self {
         Self {
             sender: self.sender.clone(),
-            _stats: (),
+            priority: self.priority.clone(),
+            backpressure: self.backpressure.clone(),
+            latency: self.latency.clone(),
+            stats: self.stats.clone(),
+            tap: self.tap.clone(),
+            policy: self.policy,
+            evict: self.evict.clone(),
         }
     }
 }
 
+/// Priority lane buffer size of the relays built by [`relay`] and [`relay_with_policy`]
+pub const PRIORITY_RELAY_BUFFER_SIZE: usize = 4;
+
 // TODO: make buffer_size const?
 /// Relay channel builder
 pub fn relay<M>(buffer_size: usize) -> (InboundRelay<M>, OutboundRelay<M>) {
+    build_relay(
+        buffer_size,
+        PRIORITY_RELAY_BUFFER_SIZE,
+        BackpressurePolicy::Block,
+        None,
+    )
+}
+
+/// Relay channel applying `policy` once its buffer is full
+pub fn relay_with_policy<M>(
+    buffer_size: usize,
+    policy: BackpressurePolicy,
+) -> (InboundRelay<M>, OutboundRelay<M>) {
+    build_relay(buffer_size, PRIORITY_RELAY_BUFFER_SIZE, policy, None)
+}
+
+/// Relay channel reporting its [`BackpressureLevel`] changes through `monitor`
+pub(crate) fn monitored_relay<M>(
+    buffer_size: usize,
+    priority_buffer_size: usize,
+    policy: BackpressurePolicy,
+    monitor: BackpressureMonitor,
+) -> (InboundRelay<M>, OutboundRelay<M>) {
+    build_relay(
+        buffer_size,
+        priority_buffer_size,
+        policy,
+        Some(Arc::new(monitor)),
+    )
+}
+
+fn build_relay<M>(
+    buffer_size: usize,
+    priority_buffer_size: usize,
+    policy: BackpressurePolicy,
+    backpressure: Option<Arc<BackpressureMonitor>>,
+) -> (InboundRelay<M>, OutboundRelay<M>) {
     let (sender, receiver) = channel(buffer_size);
+    let (priority_sender, priority_receiver) = channel(priority_buffer_size);
+    let (receiver, evict) = match policy {
+        BackpressurePolicy::DropOldest => {
+            let receiver = Arc::new(Mutex::new(receiver));
+            let evict = Arc::downgrade(&receiver);
+            (RelayReceiver::Shared(receiver), Some(evict))
+        }
+        _ => (RelayReceiver::Owned(receiver), None),
+    };
     (
         InboundRelay {
-            receiver,
-            _stats: (),
+            receiver: Some(receiver),
+            priority: Some(priority_receiver),
+            paused: false,
+            pause: None,
+            backpressure: backpressure.clone(),
+            dropped: None,
+            counters: None,
+            yield_budget: None,
+            yield_next: false,
+            stats: None,
+            middleware: None,
+            throttle: None,
+        },
+        OutboundRelay {
+            sender,
+            priority: priority_sender,
+            backpressure,
+            latency: None,
+            stats: None,
+            tap: None,
+            policy,
+            evict,
         },
-        OutboundRelay { sender, _stats: () },
     )
 }
 
 impl<M> InboundRelay<M> {
+    /// Relay without a channel, that never receives anything
+    pub(crate) fn closed() -> Self {
+        Self {
+            receiver: None,
+            priority: None,
+            paused: false,
+            pause: None,
+            backpressure: None,
+            dropped: None,
+            counters: None,
+            yield_budget: None,
+            yield_next: false,
+            stats: None,
+            middleware: None,
+            throttle: None,
+        }
+    }
+
+    /// Yield to the executor once every `budget` received messages, so a service handling a
+    /// full buffer doesn't starve the others sharing the runtime, 0 never yields
+    pub fn yielding(mut self, budget: usize) -> Self {
+        self.yield_budget = Some(YieldBudget::new(budget));
+        self
+    }
+
+    /// Run received messages through `middleware` before handing them over, after the
+    /// middlewares already added
+    pub fn with_middleware<L: RelayMiddleware<M>>(mut self, middleware: L) -> Self
+    where
+        M: Send + 'static,
+    {
+        self.middleware
+            .get_or_insert_with(MiddlewareChain::new)
+            .push(middleware);
+        self
+    }
+
+    /// Let messages through at the rate `limiter` allows
+    pub(crate) fn rate_limited(mut self, limiter: RateLimiter) -> Self {
+        self.throttle = Some(RelayThrottle::new(limiter));
+        self
+    }
+
+    /// Let Overwatch pause the relay through `pause`, along with [`InboundRelay::pause`]
+    pub(crate) fn paused_by(mut self, pause: RelayPause) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+
+    /// Count received messages in the persisted `counters` of the service
+    pub(crate) fn counted(mut self, counters: Option<Arc<ServiceCounters>>) -> Self {
+        self.counters = counters;
+        self
+    }
+
+    /// Count received messages in the service `stats`
+    pub(crate) fn measured(mut self, stats: Arc<RelayStats>) -> Self {
+        stats.set_capacity(self.capacity());
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Receiver completing once this relay is dropped
+    pub(crate) fn dropped_signal(&mut self) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.dropped = Some(sender);
+        receiver
+    }
+
     /// Receive a message from the relay connections
+    /// Messages sent with [`OutboundRelay::send_priority`] are received first. While the relay is
+    /// paused this stays pending, select it along with whatever resumes the relay.
     pub async fn recv(&mut self) -> Option<M> {
-        self.receiver.recv().await
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<M>> {
+        let Some(mut middleware) = self.middleware.take() else {
+            return self.poll_throttled(cx);
+        };
+        let message = middleware.poll_next(cx, |cx| self.poll_throttled(cx));
+        self.middleware = Some(middleware);
+        message
+    }
+
+    /// Pull the next message let through by the rate limit, if any
+    fn poll_throttled(&mut self, cx: &mut Context<'_>) -> Poll<Option<M>> {
+        let Some(mut throttle) = self.throttle.take() else {
+            return self.poll_receive(cx);
+        };
+        let message = throttle.poll_next(cx, |cx| self.poll_receive(cx));
+        self.throttle = Some(throttle);
+        message
+    }
+
+    /// Pull the next message from the relay channel
+    fn poll_receive(&mut self, cx: &mut Context<'_>) -> Poll<Option<M>> {
+        if let Some(stats) = &self.stats {
+            stats.record_poll();
+        }
+        if self.paused
+            || self
+                .pause
+                .as_ref()
+                .is_some_and(|pause| pause.poll_paused(cx))
+        {
+            return Poll::Pending;
+        }
+        if std::mem::take(&mut self.yield_next) {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let Some(receiver) = &mut self.receiver else {
+            return Poll::Ready(None);
+        };
+        let priority = self
+            .priority
+            .as_mut()
+            .map_or(Poll::Ready(None), |priority| priority.poll_recv(cx));
+        let (message, depth, capacity) = receiver.with(|receiver| {
+            let message = match priority {
+                Poll::Ready(Some(message)) => Poll::Ready(Some(message)),
+                // the relay is closed once both lanes are
+                Poll::Pending => match receiver.poll_recv(cx) {
+                    Poll::Ready(None) => Poll::Pending,
+                    message => message,
+                },
+                Poll::Ready(None) => receiver.poll_recv(cx),
+            };
+            (message, receiver.len(), receiver.max_capacity())
+        });
+        if let Poll::Ready(Some(_)) = &message {
+            if let Some(monitor) = &self.backpressure {
+                monitor.observe(depth, capacity);
+            }
+            if let Some(counters) = &self.counters {
+                counters.record_message();
+            }
+            if let Some(stats) = &self.stats {
+                stats.record_receive(depth);
+            }
+            self.yield_next = self.yield_budget.as_ref().is_some_and(YieldBudget::consume);
+        }
+        message
+    }
+
+    /// Stop pulling messages from the relay channel
+    /// Messages are kept buffered, once the buffer is full senders wait until the relay is
+    /// resumed. Useful to apply backpressure while catching up on expensive work.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume pulling messages from the relay channel
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Check if the relay is paused, by the service itself or by Overwatch
+    pub fn is_paused(&self) -> bool {
+        self.paused || self.pause.as_ref().is_some_and(RelayPause::is_paused)
+    }
+
+    /// Number of messages waiting in the relay buffer, priority lane included
+    pub fn len(&self) -> usize {
+        let priority = self.priority.as_ref().map_or(0, Receiver::len);
+        priority
+            + self
+                .receiver
+                .as_ref()
+                .map_or(0, |receiver| receiver.inspect(Receiver::len))
+    }
+
+    /// Check if there are no messages waiting in the relay buffer, priority lane included
+    pub fn is_empty(&self) -> bool {
+        self.priority.as_ref().map_or(true, Receiver::is_empty)
+            && self
+                .receiver
+                .as_ref()
+                .map_or(true, |receiver| receiver.inspect(Receiver::is_empty))
+    }
+
+    /// Maximum number of messages the relay buffer can hold, priority lane excluded
+    pub fn capacity(&self) -> usize {
+        self.receiver
+            .as_ref()
+            .map_or(0, |receiver| receiver.inspect(Receiver::max_capacity))
+    }
+
+    /// Stream of the relay messages, ending with a [`RelayEvent::Closing`] once the service gets
+    /// a lifecycle message, so a service main loop handles both without a `select!`
+    /// A `Shutdown` message is acknowledged as finished when the stream is dropped. Lifecycle
+    /// messages sent before calling this are not seen, as with
+    /// [`LifecycleHandle::message_stream`].
+    pub fn with_lifecycle(self, lifecycle: &LifecycleHandle) -> RelayEvents<M> {
+        RelayEvents {
+            relay: self,
+            lifecycle: Box::pin(lifecycle.message_stream()),
+            closed: false,
+            finished: None,
+        }
     }
 }
 
 impl<M> OutboundRelay<M> {
+    /// Weak version of this relay, for long lived caches that should not hide that the service
+    /// consuming the relay is gone
+    pub fn downgrade(&self) -> WeakOutboundRelay<M> {
+        WeakOutboundRelay {
+            sender: self.sender.downgrade(),
+            priority: self.priority.downgrade(),
+            backpressure: self.backpressure.clone(),
+            latency: self.latency.clone(),
+            stats: self.stats.clone(),
+            tap: self.tap.clone(),
+            policy: self.policy,
+            evict: self.evict.clone(),
+        }
+    }
+
+    /// Maximum number of messages the relay buffer can hold
+    pub fn capacity(&self) -> usize {
+        self.sender.max_capacity()
+    }
+
+    /// Check if the service inbound relay is gone, so messages can't be sent anymore
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Number of messages sent and not yet received by the service, priority lane excluded
+    pub fn queue_depth(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+
+    fn observe_backpressure(&self) {
+        if let Some(monitor) = &self.backpressure {
+            monitor.observe(self.queue_depth(), self.sender.max_capacity());
+        }
+    }
+
+    /// Same relay, delaying every send by `latency`
+    pub(crate) fn with_latency(mut self, latency: RelayLatency) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Count and time sends in the service `stats`
+    pub(crate) fn measured(mut self, stats: Arc<RelayStats>) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Record delivered messages through `tap`
+    pub(crate) fn recorded(mut self, tap: RelayTap<M>) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    /// Take note of `message` before sending it, if recording
+    fn capture(&self, message: &M) -> Option<RecordedMessage> {
+        self.tap.as_ref().map(|tap| tap.capture(message))
+    }
+
+    fn record_send(
+        &self,
+        started_at: Option<Instant>,
+        outcome: SendOutcome,
+        captured: Option<RecordedMessage>,
+    ) {
+        if let (Some(stats), Some(started_at)) = (&self.stats, started_at) {
+            stats.record_send(started_at, outcome, self.queue_depth());
+        }
+        if let (Some(tap), Some(message)) = (&self.tap, captured) {
+            if outcome == SendOutcome::Delivered {
+                tap.record(message);
+            }
+        }
+    }
+
     /// Send a message to the relay connection
+    /// When simulating latencies the sender waits for the message latency before it is
+    /// delivered, spawn the send to keep going meanwhile.
+    /// Once the buffer is full the [`BackpressurePolicy`] of the relay applies.
     pub async fn send(&self, message: M) -> Result<(), (RelayError, M)> {
-        self.sender
-            .send(message)
-            .await
-            .map_err(|e| (RelayError::Send, e.0))
+        let started_at = self.stats.as_ref().map(|stats| stats.now());
+        if let Some(latency) = &self.latency {
+            latency.delay().await;
+        }
+        let captured = self.capture(&message);
+        let (outcome, sent) = match self.policy {
+            BackpressurePolicy::Block => match self.sender.send(message).await {
+                Ok(()) => (SendOutcome::Delivered, Ok(())),
+                Err(e) => (SendOutcome::Closed, Err((RelayError::Send, e.0))),
+            },
+            _ => self.send_now(message),
+        };
+        self.record_send(started_at, outcome, captured);
+        sent?;
+        self.observe_backpressure();
+        Ok(())
+    }
+
+    /// Send a message received ahead of those buffered by [`OutboundRelay::send`]
+    /// Meant for control messages that shouldn't wait behind a backlog of data. The priority lane
+    /// has a buffer of its own and always waits for room in it, whatever the
+    /// [`BackpressurePolicy`] of the relay.
+    pub async fn send_priority(&self, message: M) -> Result<(), (RelayError, M)> {
+        let started_at = self.stats.as_ref().map(|stats| stats.now());
+        if let Some(latency) = &self.latency {
+            latency.delay().await;
+        }
+        let captured = self.capture(&message);
+        let (outcome, sent) = match self.priority.send(message).await {
+            Ok(()) => (SendOutcome::Delivered, Ok(())),
+            Err(e) => (SendOutcome::Closed, Err((RelayError::Send, e.0))),
+        };
+        self.record_send(started_at, outcome, captured);
+        sent
+    }
+
+    /// Send without waiting for room in the buffer, as the non blocking policy of the relay says
+    fn send_now(&self, mut message: M) -> (SendOutcome, Result<(), (RelayError, M)>) {
+        loop {
+            message = match self.sender.try_send(message) {
+                Ok(()) => return (SendOutcome::Delivered, Ok(())),
+                Err(TrySendError::Closed(message)) => {
+                    return (SendOutcome::Closed, Err((RelayError::Send, message)))
+                }
+                Err(TrySendError::Full(message)) => message,
+            };
+            match self.policy {
+                BackpressurePolicy::Block | BackpressurePolicy::DropNewest => {
+                    debug!("Relay is full, dropping the message being sent");
+                    return (SendOutcome::Rejected, Ok(()));
+                }
+                BackpressurePolicy::Error => {
+                    return (SendOutcome::Rejected, Err((RelayError::Full, message)))
+                }
+                BackpressurePolicy::DropOldest => {
+                    let Some(receiver) = self.evict.as_ref().and_then(Weak::upgrade) else {
+                        return (SendOutcome::Closed, Err((RelayError::Send, message)));
+                    };
+                    let evicted = receiver
+                        .lock()
+                        .expect("Relay lock not poisoned")
+                        .try_recv()
+                        .is_ok();
+                    if !evicted {
+                        // the buffer is only taken by reserved permits, nothing to make room from
+                        return (SendOutcome::Rejected, Err((RelayError::Full, message)));
+                    }
+                    debug!("Relay is full, dropping its oldest message");
+                    if let Some(stats) = &self.stats {
+                        stats.record_overflow();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send `request` to the service and get the stream of its responses
+    /// The service answers through the [`ResponseSink`](crate::services::stream::ResponseSink)
+    /// of the [`StreamRequest`] it receives. The stream ends once the service drops it, and the
+    /// service is notified when the stream is dropped.
+    pub async fn open_stream<Req, Resp>(
+        &self,
+        request: Req,
+    ) -> Result<ResponseStream<Resp>, RelayError>
+    where
+        M: From<StreamRequest<Req, Resp>>,
+    {
+        let (request, responses) = StreamRequest::new(request);
+        self.send(request.into()).await.map_err(|(e, _)| e)?;
+        Ok(responses)
+    }
+
+    /// Send `request` to the service and wait for its reply
+    /// The service answers through the [`Reply`] of the [`RpcRequest`] it receives, see
+    /// [`RpcRelay`] for a relay bound to a single request type with timeouts.
+    pub async fn ask<Req, Resp>(&self, request: Req) -> Result<Resp, RelayError>
+    where
+        M: From<RpcRequest<Req, Resp>>,
+    {
+        let (request, reply) = RpcRequest::new(request);
+        self.send(request.into()).await.map_err(|(e, _)| e)?;
+        reply.await.map_err(|_| RelayError::Disconnected)
     }
 
     /// Send a message to the relay connection in a blocking fashion.
@@ -144,9 +892,19 @@ impl<M> OutboundRelay<M> {
     ///
     /// # Exa
     pub fn blocking_send(&self, message: M) -> Result<(), (RelayError, M)> {
-        self.sender
-            .blocking_send(message)
-            .map_err(|e| (RelayError::Send, e.0))
+        let started_at = self.stats.as_ref().map(|stats| stats.now());
+        let captured = self.capture(&message);
+        let (outcome, sent) = match self.policy {
+            BackpressurePolicy::Block => match self.sender.blocking_send(message) {
+                Ok(()) => (SendOutcome::Delivered, Ok(())),
+                Err(e) => (SendOutcome::Closed, Err((RelayError::Send, e.0))),
+            },
+            _ => self.send_now(message),
+        };
+        self.record_send(started_at, outcome, captured);
+        sent?;
+        self.observe_backpressure();
+        Ok(())
     }
 }
 
@@ -158,7 +916,16 @@ impl<M: Send + 'static> OutboundRelay<M> {
 
 impl<S: ServiceData> Relay<S> {
     pub fn new(overwatch_handle: OverwatchHandle) -> Self {
+        Self::with_service_id(overwatch_handle, S::SERVICE_ID)
+    }
+
+    /// Relay to a service of type `S` running under `service_id`, like
+    /// [`ServicePool`](crate::services::pool::ServicePool) members do
+    /// `service_id` is resolved from the namespace of `overwatch_handle`, see
+    /// [`OverwatchHandle::resolve`].
+    pub fn with_service_id(overwatch_handle: OverwatchHandle, service_id: ServiceId) -> Self {
         Self {
+            service_id: overwatch_handle.resolve(service_id),
             overwatch_handle,
             _bound: PhantomBound {
                 _inner: PhantomData,
@@ -168,19 +935,80 @@ impl<S: ServiceData> Relay<S> {
 
     #[cfg_attr(feature = "instrumentation", instrument(skip(self), err(Debug)))]
     pub async fn connect(self) -> Result<OutboundRelay<S::Message>, RelayError> {
+        let cached = self
+            .overwatch_handle
+            .relay_cache()
+            .get(self.overwatch_handle.owner(), self.service_id);
+        if let Some(relay) = cached {
+            return Ok(self.instrumented(relay));
+        }
         let (reply, receiver) = oneshot::channel();
         self.request_relay(reply).await;
         self.handle_relay_response(receiver).await
     }
 
+    /// Connect a relay to send `Req` requests to the service and await `Resp` replies, see
+    /// [`RpcRelay`]
+    pub async fn connect_rpc<Req, Resp>(self) -> Result<RpcRelay<Req, Resp>, RelayError>
+    where
+        S::Message: From<RpcRequest<Req, Resp>> + Send,
+        Req: Send + 'static,
+        Resp: Send + 'static,
+    {
+        let service_id = self.service_id;
+        let clock = Arc::clone(&self.overwatch_handle.context_config().clock);
+        let relay = self.connect().await?;
+        Ok(RpcRelay::new(service_id, relay).with_clock(clock))
+    }
+
+    /// Same as [`Relay::connect`] but gives up if the relay is not obtained within `timeout`.
+    /// Useful when the overwatch runner could be unresponsive and hanging forever is not an option.
+    #[cfg_attr(feature = "instrumentation", instrument(skip(self), err(Debug)))]
+    pub async fn connect_with_timeout(
+        self,
+        timeout: Duration,
+    ) -> Result<OutboundRelay<S::Message>, RelayError> {
+        let service_id = self.service_id;
+        let clock = Arc::clone(&self.overwatch_handle.context_config().clock);
+        clock
+            .timeout(timeout, self.connect())
+            .await
+            .map_err(|_| RelayError::Timeout { service_id })?
+    }
+
     async fn request_relay(&self, reply: oneshot::Sender<RelayResult>) {
         let relay_command = OverwatchCommand::Relay(RelayCommand {
-            service_id: S::SERVICE_ID,
+            service_id: self.service_id,
+            requester: self.overwatch_handle.owner(),
             reply_channel: ReplyChannel(reply),
         });
         self.overwatch_handle.send(relay_command).await;
     }
 
+    /// Apply the configured [`LatencyModel`](crate::services::simulation::LatencyModel) and
+    /// [`MessageRecorder`](crate::services::recording::MessageRecorder), if any
+    fn instrumented(&self, relay: OutboundRelay<S::Message>) -> OutboundRelay<S::Message> {
+        let config = self.overwatch_handle.context_config();
+        let relay = match &config.relay_latency {
+            Some(model) => relay.with_latency(RelayLatency::new(
+                Arc::clone(model),
+                Arc::clone(&config.clock),
+                self.overwatch_handle.owner(),
+                self.service_id,
+            )),
+            None => relay,
+        };
+        match &config.recorder {
+            Some(recorder) => relay.recorded(RelayTap::new(
+                Arc::clone(recorder),
+                Arc::clone(&config.clock),
+                self.overwatch_handle.owner(),
+                self.service_id,
+            )),
+            None => relay,
+        }
+    }
+
     #[cfg_attr(feature = "instrumentation", instrument(skip_all, err(Debug)))]
     async fn handle_relay_response(
         &self,
@@ -188,11 +1016,15 @@ impl<S: ServiceData> Relay<S> {
     ) -> Result<OutboundRelay<S::Message>, RelayError> {
         let response = receiver.await;
         match response {
-            Ok(Ok(message)) => match message.downcast::<OutboundRelay<S::Message>>() {
-                Ok(channel) => Ok(*channel),
+            Ok(Ok(message)) => match self.overwatch_handle.relay_cache().insert(
+                self.overwatch_handle.owner(),
+                self.service_id,
+                message,
+            ) {
+                Ok(channel) => Ok(self.instrumented(channel)),
                 Err(m) => Err(RelayError::InvalidMessage {
                     type_id: format!("{:?}", (*m).type_id()),
-                    service_id: S::SERVICE_ID,
+                    service_id: self.service_id,
                 }),
             },
             Ok(Err(e)) => Err(e),
@@ -201,10 +1033,477 @@ impl<S: ServiceData> Relay<S> {
     }
 }
 
+impl<S: ServiceData> Relay<S> {
+    /// Same as [`Relay::connect`] but keeps retrying with the given [`RetryPolicy`] while the
+    /// target service has not started yet, or is waiting to be restarted by its supervisor.
+    /// Errors not related to the service start up are returned right away.
+    #[cfg_attr(feature = "instrumentation", instrument(skip(self), err(Debug)))]
+    pub async fn connect_with_retry(
+        self,
+        policy: RetryPolicy,
+    ) -> Result<OutboundRelay<S::Message>, RelayError> {
+        let mut attempt = 0;
+        loop {
+            let connected = match self.clone().connect().await {
+                // the relay of an instance that ended is kept until the service is restarted
+                Ok(relay) if relay.is_closed() => Err(RelayError::Disconnected),
+                connected => connected,
+            };
+            match connected {
+                Ok(relay) => return Ok(relay),
+                Err(e) if attempt < policy.max_retries && self.is_starting().await => {
+                    let delay = policy.delay_for(attempt);
+                    info!(
+                        target: TRACING_TARGET,
+                        error=?e,
+                        "Service {} is starting, retrying relay in {delay:?}",
+                        self.service_id
+                    );
+                    self.overwatch_handle
+                        .context_config()
+                        .clock
+                        .sleep(delay)
+                        .await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Same as [`Relay::connect`] but waits for the target service to report it is running
+    /// first, so messages aren't sent to a service still initializing.
+    /// Gives up if the service is not ready, and the relay obtained, within `timeout`.
+    #[cfg_attr(feature = "instrumentation", instrument(skip(self), err(Debug)))]
+    pub async fn connect_when_ready(
+        self,
+        timeout: Duration,
+    ) -> Result<OutboundRelay<S::Message>, RelayError> {
+        let service_id = self.service_id;
+        let clock = Arc::clone(&self.overwatch_handle.context_config().clock);
+        let ready = async {
+            let mut watcher = self
+                .overwatch_handle
+                .status_watcher_for(service_id)
+                .await
+                .map_err(|e| match e {
+                    ServiceStatusError::Unavailable { service_id } => {
+                        RelayError::Unavailable { service_id }
+                    }
+                    ServiceStatusError::ServiceDisabled { service_id } => {
+                        RelayError::ServiceDisabled { service_id }
+                    }
+                    ServiceStatusError::Timeout { service_id } => {
+                        RelayError::Timeout { service_id }
+                    }
+                    ServiceStatusError::Disconnected { .. } => RelayError::Disconnected,
+                })?;
+            let status = watcher
+                .wait_until(|status| status.is_up() || status.is_failed(), None)
+                .await
+                .map_err(|_| RelayError::Disconnected)?;
+            if let ServiceStatus::Failed(reason) = status {
+                return Err(RelayError::Failed { service_id, reason });
+            }
+            self.connect().await
+        };
+        clock
+            .timeout(timeout, ready)
+            .await
+            .map_err(|_| RelayError::Timeout { service_id })?
+    }
+
+    async fn is_starting(&self) -> bool {
+        match self
+            .overwatch_handle
+            .status_watcher_for(self.service_id)
+            .await
+        {
+            Ok(watcher) => matches!(
+                watcher.current(),
+                ServiceStatus::Uninitialized | ServiceStatus::Restarting
+            ),
+            Err(_) => false,
+        }
+    }
+}
+
 impl<M> Stream for InboundRelay<M> {
     type Item = M;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.receiver.poll_recv(cx)
+        self.poll_recv(cx)
+    }
+}
+
+/// Request answered with a single reply, sent by [`OutboundRelay::ask`] and [`RpcRelay`]
+///
+/// The service message type wraps it, e.g. `Get(RpcRequest<Key, Value>)` with a `From`
+/// implementation, instead of carrying its own reply channel.
+#[derive(Debug)]
+pub struct RpcRequest<Req, Resp> {
+    pub request: Req,
+    pub reply: Reply<Resp>,
+}
+
+impl<Req, Resp> RpcRequest<Req, Resp> {
+    fn new(request: Req) -> (Self, oneshot::Receiver<Resp>) {
+        let (sender, receiver) = oneshot::channel();
+        (
+            Self {
+                request,
+                reply: Reply(sender),
+            },
+            receiver,
+        )
+    }
+}
+
+/// Sending half of the reply to an [`RpcRequest`]
+/// Dropping it without replying fails the request.
+#[derive(Debug)]
+pub struct Reply<Resp>(oneshot::Sender<Resp>);
+
+impl<Resp> Reply<Resp> {
+    /// Send the reply, giving it back if the requester stopped waiting for it
+    pub fn send(self, response: Resp) -> Result<(), Resp> {
+        self.0.send(response)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+type SendRequest<Req, Resp> =
+    dyn Fn(RpcRequest<Req, Resp>) -> BoxFuture<'static, Result<(), RelayError>> + Send + Sync;
+
+/// Relay sending `Req` requests to a service and awaiting its `Resp` replies
+///
+/// Built with [`Relay::connect_rpc`], e.g. `handle.relay::<Store>().connect_rpc::<Key, Value>()`,
+/// or from an already connected relay with [`RpcRelay::new`]. The service gets
+/// [`RpcRequest`]s wrapped in its message type.
+pub struct RpcRelay<Req, Resp> {
+    service_id: ServiceId,
+    send: Arc<SendRequest<Req, Resp>>,
+    clock: Arc<dyn Clock>,
+    timeout: Option<Duration>,
+}
+
+impl<Req, Resp> Clone for RpcRelay<Req, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            service_id: self.service_id,
+            send: Arc::clone(&self.send),
+            clock: Arc::clone(&self.clock),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<Req, Resp> Debug for RpcRelay<Req, Resp> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcRelay")
+            .field("service_id", &self.service_id)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Req, Resp> RpcRelay<Req, Resp>
+where
+    Req: Send + 'static,
+    Resp: Send + 'static,
+{
+    /// Send requests through `relay`, connected to the `service_id` service
+    pub fn new<M>(service_id: ServiceId, relay: OutboundRelay<M>) -> Self
+    where
+        M: From<RpcRequest<Req, Resp>> + Send + 'static,
+    {
+        let send = move |request: RpcRequest<Req, Resp>| {
+            let relay = relay.clone();
+            async move { relay.send(request.into()).await.map_err(|(e, _)| e) }.boxed()
+        };
+        Self {
+            service_id,
+            send: Arc::new(send),
+            clock: Arc::new(SystemClock),
+            timeout: None,
+        }
+    }
+}
+
+impl<Req, Resp> RpcRelay<Req, Resp> {
+    /// Timeout applied to every [`RpcRelay::ask`], none by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Clock timeouts are measured with
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn service_id(&self) -> ServiceId {
+        self.service_id
+    }
+
+    /// Send `request` and wait for the reply, up to the configured timeout if any
+    pub async fn ask(&self, request: Req) -> Result<Resp, RelayError> {
+        match self.timeout {
+            Some(timeout) => self.ask_with_timeout(request, timeout).await,
+            None => self.request(request).await,
+        }
+    }
+
+    /// Same as [`RpcRelay::ask`] with a specific `timeout`, covering both sending the request
+    /// and waiting for the reply
+    pub async fn ask_with_timeout(
+        &self,
+        request: Req,
+        timeout: Duration,
+    ) -> Result<Resp, RelayError> {
+        self.clock
+            .timeout(timeout, self.request(request))
+            .await
+            .map_err(|_| RelayError::ReplyTimeout {
+                service_id: self.service_id,
+                timeout,
+            })?
+    }
+
+    async fn request(&self, request: Req) -> Result<Resp, RelayError> {
+        let (request, reply) = RpcRequest::new(request);
+        (self.send)(request).await?;
+        reply.await.map_err(|_| RelayError::NoReply {
+            service_id: self.service_id,
+        })
+    }
+}
+
+/// Item of [`RelayEvents`]
+#[derive(Debug)]
+pub enum RelayEvent<M> {
+    Message(M),
+    /// The service is asked to stop, it is the last event
+    Closing(StopReason),
+}
+
+/// Messages of an [`InboundRelay`] and lifecycle messages of the service as a single stream, see
+/// [`InboundRelay::with_lifecycle`]
+pub struct RelayEvents<M> {
+    relay: InboundRelay<M>,
+    lifecycle: Pin<Box<dyn Stream<Item = LifecycleMessage> + Send>>,
+    closed: bool,
+    /// Acknowledges a `Shutdown` lifecycle message once the events are dropped
+    finished: Option<finished_signal::Sender>,
+}
+
+impl<M> Debug for RelayEvents<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelayEvents")
+            .field("closed", &self.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M> Stream for RelayEvents<M> {
+    type Item = RelayEvent<M>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.closed {
+            return Poll::Ready(None);
+        }
+        // lifecycle messages go first, so a busy relay doesn't delay stopping
+        match self.lifecycle.as_mut().poll_next(cx) {
+            Poll::Ready(Some(message)) => {
+                self.closed = true;
+                if let LifecycleMessage::Shutdown(finished, _) = &message {
+                    self.finished = Some(finished.clone());
+                }
+                return Poll::Ready(Some(RelayEvent::Closing(message.stop_reason())));
+            }
+            Poll::Ready(None) => self.lifecycle = Box::pin(futures::stream::pending()),
+            Poll::Pending => {}
+        }
+        self.relay
+            .poll_recv(cx)
+            .map(|message| message.map(RelayEvent::Message))
+    }
+}
+
+impl<M> Drop for RelayEvents<M> {
+    fn drop(&mut self) {
+        if let Some(finished) = self.finished.take() {
+            finished.send();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::life_cycle::{
+        finished_signal, LifecycleHandle, LifecycleMessage, StopReason,
+    };
+    use crate::services::relay::{
+        relay, relay_with_policy, BackpressurePolicy, RelayError, RelayEvent, RetryPolicy,
+    };
+    use futures::{FutureExt, StreamExt};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn inbound_relay_reports_occupancy() {
+        let (mut inbound, outbound) = relay::<usize>(4);
+        assert!(inbound.is_empty());
+        assert_eq!(inbound.capacity(), 4);
+        for i in 0..3 {
+            outbound.send(i).await.unwrap();
+        }
+        assert_eq!(inbound.len(), 3);
+        inbound.recv().await.unwrap();
+        assert_eq!(inbound.len(), 2);
+        assert_eq!(inbound.capacity(), 4);
+    }
+
+    #[tokio::test]
+    async fn paused_inbound_relay_applies_backpressure() {
+        let (mut inbound, outbound) = relay::<usize>(1);
+        inbound.pause();
+        outbound.send(0).await.unwrap();
+        // buffer is full and nothing is pulled while paused
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), outbound.send(1))
+                .await
+                .is_err()
+        );
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), inbound.recv())
+                .await
+                .is_err()
+        );
+        inbound.resume();
+        assert_eq!(inbound.recv().await, Some(0));
+    }
+
+    #[tokio::test]
+    async fn full_relay_applies_its_backpressure_policy() {
+        let received = |policy| async move {
+            let (mut inbound, outbound) = relay_with_policy::<usize>(2, policy);
+            for i in 0..3 {
+                if let Err((e, message)) = outbound.send(i).await {
+                    assert!(matches!(e, RelayError::Full));
+                    assert_eq!(message, 2);
+                }
+            }
+            drop(outbound);
+            let mut received = Vec::new();
+            while let Some(message) = inbound.recv().await {
+                received.push(message);
+            }
+            received
+        };
+        assert_eq!(received(BackpressurePolicy::DropNewest).await, [0, 1]);
+        assert_eq!(received(BackpressurePolicy::DropOldest).await, [1, 2]);
+        assert_eq!(received(BackpressurePolicy::Error).await, [0, 1]);
+    }
+
+    #[tokio::test]
+    async fn dropping_oldest_fails_without_buffered_messages() {
+        let (_inbound, outbound) = relay_with_policy::<usize>(1, BackpressurePolicy::DropOldest);
+        let _permit = outbound.sender.reserve().await.unwrap();
+        let sent = tokio::time::timeout(Duration::from_millis(50), outbound.send(0))
+            .await
+            .expect("Send not to spin on a buffer taken by permits");
+        assert!(matches!(sent, Err((RelayError::Full, 0))));
+    }
+
+    #[tokio::test]
+    async fn priority_messages_skip_the_backlog() {
+        let (mut inbound, outbound) = relay::<&str>(4);
+        outbound.send("data").await.unwrap();
+        outbound.send("data").await.unwrap();
+        outbound.send_priority("flush").await.unwrap();
+        assert_eq!(inbound.len(), 3);
+        assert_eq!(outbound.queue_depth(), 2);
+        drop(outbound);
+        let mut received = Vec::new();
+        while let Some(message) = inbound.recv().await {
+            received.push(message);
+        }
+        assert_eq!(received, ["flush", "data", "data"]);
+    }
+
+    #[tokio::test]
+    async fn weak_relay_does_not_outlive_consumer() {
+        let (mut inbound, outbound) = relay::<usize>(4);
+        let weak = outbound.downgrade();
+        weak.send(1).await.unwrap();
+        assert_eq!(inbound.recv().await, Some(1));
+
+        // a strong relay is still around but nobody consumes the messages anymore
+        drop(inbound);
+        assert!(weak.upgrade().is_none());
+        assert!(matches!(
+            weak.send(2).await,
+            Err((RelayError::Disconnected, 2))
+        ));
+
+        let (_inbound, outbound) = relay::<usize>(4);
+        let weak = outbound.downgrade();
+        drop(outbound);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[tokio::test]
+    async fn inbound_relay_yields_once_budget_is_exhausted() {
+        let (inbound, outbound) = relay::<usize>(4);
+        let mut inbound = inbound.yielding(2);
+        for i in 0..3 {
+            outbound.send(i).await.unwrap();
+        }
+        assert_eq!(inbound.recv().now_or_never(), Some(Some(0)));
+        assert_eq!(inbound.recv().now_or_never(), Some(Some(1)));
+        // the message is still there after yielding
+        assert_eq!(inbound.recv().now_or_never(), None);
+        assert_eq!(inbound.recv().now_or_never(), Some(Some(2)));
+    }
+
+    #[test]
+    fn retry_policy_backs_off_up_to_max_delay() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            backoff_factor: 2,
+        };
+        assert_eq!(policy.delay_for(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(1), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(800));
+        assert_eq!(policy.delay_for(4), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(usize::MAX), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn relay_events_end_with_closing() {
+        let (inbound, outbound) = relay::<usize>(4);
+        let lifecycle = LifecycleHandle::new();
+        let mut events = inbound.with_lifecycle(&lifecycle);
+        outbound.send(1).await.unwrap();
+        assert!(matches!(events.next().await, Some(RelayEvent::Message(1))));
+
+        let (finished, mut finished_receiver) = finished_signal::channel();
+        lifecycle
+            .send(LifecycleMessage::Shutdown(finished, StopReason::Requested))
+            .unwrap();
+        outbound.send(2).await.unwrap();
+        assert!(matches!(
+            events.next().await,
+            Some(RelayEvent::Closing(StopReason::Requested))
+        ));
+        assert!(events.next().await.is_none());
+        drop(events);
+        finished_receiver.recv().await.unwrap();
     }
 }
@@ -0,0 +1,64 @@
+//! Relays already handed out by the overwatch runner, so asking for them again doesn't need a
+//! round trip to it
+// std
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+// internal
+use crate::services::relay::{AnyMessage, OutboundRelay};
+use crate::services::ServiceId;
+
+/// Requesting service, `None` for the application, and target service of a relay
+/// Requesters are told apart as the runner checks relays against the relay policy.
+type RelayKey = (Option<ServiceId>, ServiceId);
+
+/// Relays cached by requester and target service, shared by all handle clones
+/// Relays to a service are dropped when it is stopped or started again, and ignored once closed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RelayCache(Arc<Mutex<HashMap<RelayKey, AnyMessage>>>);
+
+impl RelayCache {
+    /// Cached relay from `requester` to `service_id`, if it is still open
+    pub(crate) fn get<M: 'static>(
+        &self,
+        requester: Option<ServiceId>,
+        service_id: ServiceId,
+    ) -> Option<OutboundRelay<M>> {
+        let mut relays = self.0.lock().expect("Relays cache lock not poisoned");
+        let key = (requester, service_id);
+        let relay = relays
+            .get(&key)?
+            .downcast_ref::<OutboundRelay<M>>()
+            .filter(|relay| !relay.is_closed())
+            .cloned();
+        if relay.is_none() {
+            relays.remove(&key);
+        }
+        relay
+    }
+
+    /// Keep `relay`, as answered by the runner, unless it isn't an `OutboundRelay<M>`
+    /// Returns the relay back along with a clone of it.
+    pub(crate) fn insert<M: 'static>(
+        &self,
+        requester: Option<ServiceId>,
+        service_id: ServiceId,
+        relay: AnyMessage,
+    ) -> Result<OutboundRelay<M>, AnyMessage> {
+        let Some(outbound) = relay.downcast_ref::<OutboundRelay<M>>().cloned() else {
+            return Err(relay);
+        };
+        self.0
+            .lock()
+            .expect("Relays cache lock not poisoned")
+            .insert((requester, service_id), relay);
+        Ok(outbound)
+    }
+
+    /// Drop the relays to `service_id`, its instance is gone
+    pub(crate) fn invalidate(&self, service_id: ServiceId) {
+        self.0
+            .lock()
+            .expect("Relays cache lock not poisoned")
+            .retain(|(_, target), _| *target != service_id);
+    }
+}
@@ -0,0 +1,85 @@
+//! Pausing the inbound relay of a service from outside of it, so Overwatch can hold back a
+//! service without stopping it, see
+//! [`MemoryMonitor::pausing_low_priority`](crate::overwatch::memory::MemoryMonitor::pausing_low_priority)
+// std
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Waker};
+// internal
+use crate::services::ServiceId;
+
+#[derive(Debug, Default)]
+struct PauseState {
+    paused: bool,
+    /// Tasks receiving from the paused relays, woken up on resume
+    wakers: Vec<Waker>,
+}
+
+/// Switch pausing every inbound relay of a service it is given to, shared by its instances
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RelayPause(Arc<Mutex<PauseState>>);
+
+impl RelayPause {
+    pub(crate) fn pause(&self) {
+        self.state().paused = true;
+    }
+
+    pub(crate) fn resume(&self) {
+        let mut state = self.state();
+        state.paused = false;
+        for waker in std::mem::take(&mut state.wakers) {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.state().paused
+    }
+
+    /// Check if the relay is paused, registering the receiving task to be woken up on resume
+    pub(crate) fn poll_paused(&self, cx: &Context<'_>) -> bool {
+        let mut state = self.state();
+        if state.paused && !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        state.paused
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, PauseState> {
+        self.0.lock().expect("Relay pause lock not poisoned")
+    }
+}
+
+/// Pause switches by service id, shared by all handle clones
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RelayPauses(Arc<Mutex<HashMap<ServiceId, RelayPause>>>);
+
+impl RelayPauses {
+    /// Switch of `service_id`, created on first use
+    pub(crate) fn get(&self, service_id: ServiceId) -> RelayPause {
+        self.0
+            .lock()
+            .expect("Relay pauses lock not poisoned")
+            .entry(service_id)
+            .or_default()
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::relay_pause::RelayPauses;
+    use futures::task::noop_waker_ref;
+    use std::task::Context;
+
+    #[test]
+    fn switches_are_shared_by_service() {
+        let pauses = RelayPauses::default();
+        let cx = Context::from_waker(noop_waker_ref());
+        pauses.get("indexer").pause();
+        assert!(pauses.get("indexer").poll_paused(&cx));
+        assert!(!pauses.get("consensus").is_paused());
+        pauses.get("indexer").resume();
+        assert!(!pauses.get("indexer").poll_paused(&cx));
+    }
+}
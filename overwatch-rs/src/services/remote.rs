@@ -0,0 +1,368 @@
+//! Relays to services running in another process
+//!
+//! A large application can be split into several processes, each running its own Overwatch. The
+//! process running a service exposes it with a [`RemoteRelayListener`], and the others get an
+//! [`OutboundRelay`] to it from [`RemoteRelay::connect`], used like any other relay. Messages are
+//! serialized with serde and sent over TCP or a Unix socket as JSON frames, each prefixed with
+//! its length as a big endian `u32`. Connections sending a frame longer than
+//! [`MAX_FRAME_LENGTH`] are closed.
+//!
+//! ```ignore
+//! // process running the store
+//! RemoteRelayListener::new(RemoteAddress::Unix("/run/app/store.sock".into()))
+//!     .expose::<StoreService>()
+//!     .serve(overwatch.handle())
+//!     .await?;
+//! // other processes
+//! let store = RemoteRelay::new(RemoteAddress::Unix("/run/app/store.sock".into()))
+//!     .connect::<StoreService>(overwatch.handle())
+//!     .await?;
+//! store.send(StoreMessage::Put(key, value)).await?;
+//! ```
+//!
+//! Remote relays don't reconnect: once the connection is lost, sending fails with
+//! [`RelayError::Disconnected`] and a new relay has to be connected.
+//!
+//! Connections are not authenticated, anyone able to connect to a listener can send messages to
+//! the services it exposes. Listeners only take loopback TCP addresses, or Unix sockets, unless
+//! [`RemoteRelayListener::allow_non_loopback`] is set.
+
+// std
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::Arc;
+// crates
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_util::codec::LengthDelimitedCodec;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+// internal
+use crate::overwatch::handle::OverwatchHandle;
+use crate::overwatch::server::ServerHandle;
+use crate::services::relay::{relay, InboundRelay, OutboundRelay, RelayError};
+use crate::services::{ServiceData, ServiceId};
+use crate::utils::listener::{Connection, ListenAddress, Listener};
+use crate::utils::runtime::{spawn_checked, REMOTE_RELAY_TASK};
+
+/// Longest frame taken, in bytes
+pub const MAX_FRAME_LENGTH: usize = 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum RemoteError {
+    #[error("remote relay connection failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("remote message couldn't be serialized: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("remote message of {0} bytes is longer than the {MAX_FRAME_LENGTH} bytes allowed")]
+    TooLong(usize),
+    #[error("service {0} is not exposed to remote relays")]
+    NotExposed(String),
+    #[error(transparent)]
+    Relay(#[from] RelayError),
+    #[error(transparent)]
+    Overwatch(#[from] crate::overwatch::Error),
+}
+
+/// Where remote relays connect to, and listeners listen on
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RemoteAddress {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Message to a service as sent over the connection
+#[derive(Serialize)]
+struct OutgoingFrame<'a, M> {
+    service: ServiceId,
+    message: &'a M,
+}
+
+#[derive(Deserialize)]
+struct IncomingFrame {
+    service: String,
+    message: serde_json::Value,
+}
+
+/// Connects relays to the services of another process, see [`crate::services::remote`]
+#[derive(Clone, Debug)]
+pub struct RemoteRelay {
+    address: RemoteAddress,
+}
+
+impl RemoteRelay {
+    pub fn new(address: RemoteAddress) -> Self {
+        Self { address }
+    }
+
+    /// Relay to the `S` service exposed by the process listening on the address
+    /// Messages are buffered up to the service buffer size while being written to the connection.
+    /// `send` returns once a message is buffered, those failing to be serialized afterwards are
+    /// dropped and reported as errors of `S`, see
+    /// [`OverwatchHandle::error_events`](crate::overwatch::handle::OverwatchHandle::error_events).
+    pub async fn connect<S: ServiceData>(
+        &self,
+        handle: &OverwatchHandle,
+    ) -> Result<OutboundRelay<S::Message>, RemoteError>
+    where
+        S::Message: Serialize + Send,
+    {
+        let (inbound, outbound) = relay(S::SERVICE_RELAY_BUFFER_SIZE);
+        let shutdown = handle.shutdown_token();
+        let reporter = handle.clone();
+        match &self.address {
+            RemoteAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address).await?;
+                spawn_checked(handle.runtime(), REMOTE_RELAY_TASK, async move {
+                    tokio::select! {
+                        _ = shutdown.cancelled() => {}
+                        _ = forward(S::SERVICE_ID, inbound, stream, reporter) => {}
+                    }
+                })?;
+            }
+            #[cfg(unix)]
+            RemoteAddress::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                spawn_checked(handle.runtime(), REMOTE_RELAY_TASK, async move {
+                    tokio::select! {
+                        _ = shutdown.cancelled() => {}
+                        _ = forward(S::SERVICE_ID, inbound, stream, reporter) => {}
+                    }
+                })?;
+            }
+        }
+        Ok(outbound)
+    }
+}
+
+/// Write the messages of `inbound` to `connection` until either is closed
+async fn forward<M, W>(
+    service_id: ServiceId,
+    mut inbound: InboundRelay<M>,
+    mut connection: W,
+    handle: OverwatchHandle,
+) where
+    M: Serialize,
+    W: AsyncWrite + Unpin,
+{
+    while let Some(message) = inbound.recv().await {
+        let frame = match frame(service_id, &message) {
+            Ok(frame) => frame,
+            Err(e) => {
+                handle.report_error(service_id, Box::new(e));
+                continue;
+            }
+        };
+        if let Err(e) = connection.write_all(&frame).await {
+            error!("Connection to remote service {service_id} lost: {e}");
+            return;
+        }
+    }
+}
+
+/// Length prefixed frame sent over the connection for `message`
+fn frame<M: Serialize>(service: ServiceId, message: &M) -> Result<Vec<u8>, RemoteError> {
+    let json = serde_json::to_vec(&OutgoingFrame { service, message })?;
+    let length = u32::try_from(json.len())
+        .ok()
+        .filter(|&length| length as usize <= MAX_FRAME_LENGTH)
+        .ok_or(RemoteError::TooLong(json.len()))?;
+    let mut frame = length.to_be_bytes().to_vec();
+    frame.extend(json);
+    Ok(frame)
+}
+
+/// Relay to an exposed service, typed where its messages are known
+trait Delivery: Send + Sync {
+    fn deliver(&self, message: serde_json::Value) -> BoxFuture<'_, Result<(), RemoteError>>;
+}
+
+impl<M: DeserializeOwned + Send + 'static> Delivery for OutboundRelay<M> {
+    fn deliver(&self, message: serde_json::Value) -> BoxFuture<'_, Result<(), RemoteError>> {
+        async move {
+            let message = serde_json::from_value::<M>(message)?;
+            self.send(message).await.map_err(|(e, _)| e)?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Connects a [`Delivery`] to an exposed service
+type Connect = fn(OverwatchHandle) -> BoxFuture<'static, Result<Box<dyn Delivery>, RemoteError>>;
+
+/// Takes the messages of remote relays to the exposed services of this process
+#[derive(Clone, Debug)]
+pub struct RemoteRelayListener {
+    address: RemoteAddress,
+    services: HashMap<ServiceId, Connect>,
+    non_loopback: bool,
+}
+
+impl RemoteRelayListener {
+    /// Listen on `address`, replacing a stale Unix socket left there
+    /// The socket is only accessible to the current user and removed once the server stops.
+    /// TCP addresses have to be loopback ones, as messages are not authenticated, unless
+    /// [`RemoteRelayListener::allow_non_loopback`] is set.
+    pub fn new(address: RemoteAddress) -> Self {
+        Self {
+            address,
+            services: HashMap::new(),
+            non_loopback: false,
+        }
+    }
+
+    /// Listen on a TCP address reachable from other hosts, anyone able to connect can then send
+    /// messages to the exposed services
+    pub fn allow_non_loopback(mut self) -> Self {
+        self.non_loopback = true;
+        self
+    }
+
+    /// Take messages to the `S` service, those to services not exposed are dropped
+    pub fn expose<S: ServiceData>(mut self) -> Self
+    where
+        S::Message: DeserializeOwned + Send,
+    {
+        let connect: Connect = |handle| {
+            async move {
+                let relay = handle.relay::<S>().connect().await?;
+                Ok(Box::new(relay) as Box<dyn Delivery>)
+            }
+            .boxed()
+        };
+        self.services.insert(S::SERVICE_ID, connect);
+        self
+    }
+
+    /// Start listening, until Overwatch shuts down or the server is aborted
+    pub async fn serve(self, handle: &OverwatchHandle) -> Result<RemoteServer, RemoteError> {
+        let Self {
+            address,
+            services,
+            non_loopback,
+        } = self;
+        if let RemoteAddress::Tcp(address) = address {
+            if !non_loopback && !address.ip().is_loopback() {
+                return Err(RemoteError::Io(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("remote relay address {address} is not a loopback one"),
+                )));
+            }
+        }
+        let listener = Listener::bind(address.into()).await?;
+        let address = RemoteAddress::from(listener.address()?);
+        let server = RemoteServer::new(address, handle);
+        let exposed = Exposed {
+            handle: handle.clone(),
+            services: Arc::new(services),
+            cancellation_token: server.cancellation_token().clone(),
+        };
+        spawn_checked(handle.runtime(), REMOTE_RELAY_TASK, async move {
+            loop {
+                tokio::select! {
+                    _ = exposed.cancellation_token.cancelled() => return,
+                    accepted = listener.accept() => match accepted {
+                        Ok(connection) => exposed.spawn_connection(connection),
+                        Err(e) => error!("Remote relay connection couldn't be accepted: {e}"),
+                    },
+                }
+            }
+        })?;
+        info!("Remote relays listening on {:?}", server.address());
+        Ok(server)
+    }
+}
+
+/// Listening [`RemoteRelayListener`]
+pub type RemoteServer = ServerHandle<RemoteAddress>;
+
+impl From<RemoteAddress> for ListenAddress {
+    fn from(address: RemoteAddress) -> Self {
+        match address {
+            RemoteAddress::Tcp(address) => Self::Tcp(address),
+            #[cfg(unix)]
+            RemoteAddress::Unix(path) => Self::Unix(path),
+        }
+    }
+}
+
+impl From<ListenAddress> for RemoteAddress {
+    fn from(address: ListenAddress) -> Self {
+        match address {
+            ListenAddress::Tcp(address) => Self::Tcp(address),
+            #[cfg(unix)]
+            ListenAddress::Unix(path) => Self::Unix(path),
+        }
+    }
+}
+
+/// Services exposed to remote relays
+#[derive(Clone)]
+struct Exposed {
+    handle: OverwatchHandle,
+    services: Arc<HashMap<ServiceId, Connect>>,
+    cancellation_token: CancellationToken,
+}
+
+impl Exposed {
+    fn spawn_connection(&self, connection: Box<dyn Connection>) {
+        let exposed = self.clone();
+        if let Err(e) = spawn_checked(self.handle.runtime(), REMOTE_RELAY_TASK, async move {
+            tokio::select! {
+                _ = exposed.cancellation_token.cancelled() => {}
+                closed = exposed.receive(connection) => if let Err(e) = closed {
+                    debug!("Remote relay connection closed: {e}");
+                },
+            }
+        }) {
+            error!("Remote relay connection couldn't be handled: {e}");
+        }
+    }
+
+    /// Deliver the messages read from `connection`, in order, until it is closed
+    /// Relays to the exposed services are connected once per connection, when first needed.
+    async fn receive<T: AsyncRead + Unpin>(&self, connection: T) -> std::io::Result<()> {
+        let mut frames = LengthDelimitedCodec::builder()
+            .max_frame_length(MAX_FRAME_LENGTH)
+            .new_read(connection);
+        let mut relays = HashMap::new();
+        while let Some(frame) = frames.next().await {
+            if let Err(e) = self.deliver(&frame?, &mut relays).await {
+                warn!("Remote message dropped: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    async fn deliver(
+        &self,
+        frame: &[u8],
+        relays: &mut HashMap<ServiceId, Box<dyn Delivery>>,
+    ) -> Result<(), RemoteError> {
+        let frame = serde_json::from_slice::<IncomingFrame>(frame)?;
+        let (&service_id, connect) = self
+            .services
+            .get_key_value(frame.service.as_str())
+            .ok_or(RemoteError::NotExposed(frame.service))?;
+        let relay = match relays.entry(service_id) {
+            Entry::Occupied(relay) => relay.into_mut(),
+            Entry::Vacant(entry) => entry.insert(connect(self.handle.clone()).await?),
+        };
+        let delivered = relay.deliver(frame.message).await;
+        // the service was stopped or restarted, its new instance is connected to next time
+        if let Err(RemoteError::Relay(_)) = delivered {
+            relays.remove(service_id);
+        }
+        delivered
+    }
+}
@@ -0,0 +1,96 @@
+// std
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+// crates
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceError {
+    #[error("no shared resource of type {type_name} was provided")]
+    Missing { type_name: &'static str },
+}
+
+type AnyResource = Arc<dyn Any + Send + Sync>;
+
+/// Heavyweight objects shared by several services, e.g. database pools or keys, looked up by
+/// type
+///
+/// Resources are provided before running the application with
+/// [`ContextConfig::with_resource`](crate::services::context::ContextConfig::with_resource)
+/// and are read-only afterwards. Services get them through
+/// [`ServiceStateHandle::resources`](crate::services::handle::ServiceStateHandle::resources),
+/// all of them sharing the same instance.
+#[derive(Clone, Default)]
+pub struct SharedResources(Arc<HashMap<TypeId, (&'static str, AnyResource)>>);
+
+impl Debug for SharedResources {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.0.values().map(|(type_name, _)| type_name))
+            .finish()
+    }
+}
+
+impl SharedResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `resource`, replacing the one of the same type if any
+    pub fn with<T: Send + Sync + 'static>(self, resource: T) -> Self {
+        self.with_shared(Arc::new(resource))
+    }
+
+    /// Same as [`SharedResources::with`] for a resource the caller keeps a reference to
+    pub fn with_shared<T: Send + Sync + 'static>(mut self, resource: Arc<T>) -> Self {
+        Arc::make_mut(&mut self.0).insert(TypeId::of::<T>(), (type_name::<T>(), resource));
+        self
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let (_, resource) = self.0.get(&TypeId::of::<T>())?;
+        Arc::clone(resource).downcast().ok()
+    }
+
+    /// Same as [`SharedResources::get`] but failing with an error naming the missing type,
+    /// handy in [`ServiceCore::init`](crate::services::ServiceCore::init)
+    pub fn require<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, ResourceError> {
+        self.get().ok_or(ResourceError::Missing {
+            type_name: type_name::<T>(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::resources::{ResourceError, SharedResources};
+    use std::sync::Arc;
+
+    struct Pool(usize);
+
+    #[test]
+    fn resources_are_shared_by_type() {
+        let key = Arc::new("secret".to_string());
+        let resources = SharedResources::new()
+            .with(Pool(1))
+            .with(Pool(4))
+            .with_shared(Arc::clone(&key));
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources.require::<Pool>().unwrap().0, 4);
+        assert!(Arc::ptr_eq(&resources.get::<String>().unwrap(), &key));
+        assert_eq!(
+            resources.require::<u64>().unwrap_err(),
+            ResourceError::Missing { type_name: "u64" }
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Runtimes services run on, see [`ServiceData::RUNTIME_POLICY`]
+//!
+//! Services run on the Overwatch runtime by default. Blocking-heavy services can be moved to a
+//! runtime of their own so they don't starve the others, and latency-critical ones away from
+//! the busy shared runtime. Several services can share a named thread pool instead, sized with
+//! [`ContextConfig::with_thread_pool`](crate::services::context::ContextConfig::with_thread_pool).
+//!
+//! Only the service main loop and its state handling run there, tasks spawned through the
+//! [`OverwatchHandle`](crate::overwatch::handle::OverwatchHandle) runtime still run on the shared
+//! one. Dedicated runtimes run on the system time, whatever the configured
+//! [`Clock`](crate::services::clock::Clock) is.
+
+// std
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+// crates
+use tokio::runtime::{Builder, Handle, Runtime};
+// internal
+use crate::overwatch::Error;
+use crate::services::context::ContextConfig;
+use crate::services::{ServiceData, ServiceId};
+
+/// Runtime a service runs on
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RuntimePolicy {
+    /// The runtime Overwatch runs on
+    #[default]
+    Shared,
+    /// A runtime of its own, kept across restarts of the service
+    /// `worker_threads` has to be nonzero.
+    Dedicated { worker_threads: usize },
+    /// The thread pool of that name, shared by every service naming it
+    Pool(&'static str),
+}
+
+impl RuntimePolicy {
+    /// Whether runtimes can be built from this policy, checked at compile time for the services
+    /// of a `Services` struct
+    pub const fn is_valid(&self) -> bool {
+        !matches!(self, Self::Dedicated { worker_threads: 0 })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum RuntimeKey {
+    Service(ServiceId),
+    Pool(&'static str),
+}
+
+/// Runtime left to shut down in the background when dropped, as blocking on it could happen
+/// within an async context
+struct OwnedRuntime(Option<Runtime>);
+
+impl Drop for OwnedRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Runtimes built for the services not running on the shared one, built on first use
+#[derive(Clone, Default)]
+pub(crate) struct ServiceRuntimes {
+    runtimes: Arc<Mutex<HashMap<RuntimeKey, OwnedRuntime>>>,
+}
+
+impl Debug for ServiceRuntimes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceRuntimes").finish_non_exhaustive()
+    }
+}
+
+impl ServiceRuntimes {
+    /// Runtime the `service_id` instance of `S` runs on, `shared` unless it asks for another one
+    pub(crate) fn handle_for<S: ServiceData>(
+        &self,
+        service_id: ServiceId,
+        shared: &Handle,
+        context_config: &ContextConfig,
+    ) -> Result<Handle, Error> {
+        let (key, worker_threads, name) = match S::RUNTIME_POLICY {
+            RuntimePolicy::Shared => return Ok(shared.clone()),
+            RuntimePolicy::Dedicated { worker_threads } => (
+                RuntimeKey::Service(service_id),
+                Some(worker_threads),
+                service_id,
+            ),
+            RuntimePolicy::Pool(pool) => (
+                RuntimeKey::Pool(pool),
+                context_config.thread_pools.get(pool).copied(),
+                pool,
+            ),
+        };
+        let mut runtimes = self
+            .runtimes
+            .lock()
+            .expect("Runtimes lock not to be poisoned");
+        if let Some(runtime) = runtimes.get(&key) {
+            return Ok(runtime_handle(runtime));
+        }
+        let mut builder = Builder::new_multi_thread();
+        builder
+            .enable_all()
+            .thread_name(format!("overwatch-{name}"));
+        match worker_threads {
+            // tokio panics on empty runtimes
+            Some(0) => return Err(Error::NoWorkerThreads { runtime: name }),
+            Some(worker_threads) => {
+                builder.worker_threads(worker_threads);
+            }
+            None => {}
+        }
+        let runtime = OwnedRuntime(Some(builder.build().map_err(Error::any)?));
+        let handle = runtime_handle(&runtime);
+        runtimes.insert(key, runtime);
+        Ok(handle)
+    }
+}
+
+fn runtime_handle(runtime: &OwnedRuntime) -> Handle {
+    runtime
+        .0
+        .as_ref()
+        .expect("Runtime to be owned until dropped")
+        .handle()
+        .clone()
+}
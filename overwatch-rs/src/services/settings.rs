@@ -1,10 +1,65 @@
 //std
 //crates
+use futures::Stream;
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch::error::RecvError;
 use tokio::sync::watch::{channel, Receiver, Sender};
+use tokio_stream::wrappers::WatchStream;
 use tracing::error;
 #[cfg(feature = "instrumentation")]
 use tracing::instrument;
 //internal
+use crate::DynError;
+
+/// Check of new settings before they are applied
+/// Settings updates sent through
+/// [`OverwatchHandle::update_settings`](crate::overwatch::handle::OverwatchHandle::update_settings)
+/// are only applied if the new settings of every service are valid. Services opt in by checking
+/// their settings in [`ServiceData::validate_settings`](crate::services::ServiceData::validate_settings),
+/// settings of any other service are always valid:
+///
+/// ```ignore
+/// fn validate_settings(settings: &Self::Settings) -> Result<(), DynError> {
+///     settings.validate()
+/// }
+/// ```
+pub trait ValidateSettings {
+    fn validate(&self) -> Result<(), DynError>;
+}
+
+/// Settings of a service attached as an `Option<ServiceHandle<S>>` field of a `Services` struct
+/// Disabled services are never instantiated: starting, relaying to or watching them fails with a
+/// `ServiceDisabled` error. Whether a service is enabled is only looked at when Overwatch starts,
+/// later settings updates just reach enabled services.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+pub struct OptionalSettings<S> {
+    #[cfg_attr(feature = "config", serde(default = "enabled_by_default"))]
+    pub enabled: bool,
+    pub settings: S,
+}
+
+impl<S> OptionalSettings<S> {
+    pub fn new(settings: S) -> Self {
+        Self {
+            enabled: true,
+            settings,
+        }
+    }
+
+    pub fn disabled(settings: S) -> Self {
+        Self {
+            enabled: false,
+            settings,
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+fn enabled_by_default() -> bool {
+    true
+}
 
 /// Wrapper around [`tokio::sync::watch::Receiver`]
 pub struct SettingsNotifier<S> {
@@ -27,6 +82,21 @@ impl<S: Clone> SettingsNotifier<S> {
     pub fn get_updated_settings(&self) -> S {
         self.notifier_channel.borrow().clone()
     }
+
+    /// Wait for the settings to be updated, as [`Receiver::changed`] does
+    /// Completes right away if there is an update this notifier hasn't seen yet, errors once the
+    /// updating end is gone.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        self.notifier_channel.changed().await
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static> SettingsNotifier<S> {
+    /// Stream of the settings updates, starting from the next one
+    /// Updates made in quick succession may be skipped in favour of the latest.
+    pub fn watch(&self) -> impl Stream<Item = S> {
+        WatchStream::from_changes(self.notifier_channel.clone())
+    }
 }
 
 /// Settings update notification sender
@@ -61,6 +131,7 @@ impl<S> SettingsUpdater<S> {
 #[cfg(test)]
 mod test {
     use crate::services::settings::SettingsUpdater;
+    use futures::StreamExt;
     use std::collections::HashSet;
     use std::time::Duration;
     use tokio::time::sleep;
@@ -89,4 +160,25 @@ mod test {
         let success: Result<bool, _> = handle.await.unwrap();
         assert!(success.unwrap());
     }
+
+    #[tokio::test]
+    async fn settings_updates_are_notified() {
+        let updater = SettingsUpdater::new(0usize);
+        let mut notifier = updater.notifier();
+        let mut updates = notifier.watch();
+        assert!(timeout(Duration::from_millis(50), notifier.changed())
+            .await
+            .is_err());
+        updater.update(1);
+        notifier.changed().await.unwrap();
+        assert_eq!(notifier.get_updated_settings(), 1);
+        assert_eq!(updates.next().await, Some(1));
+        updater.update(2);
+        assert_eq!(updates.next().await, Some(2));
+        // the second update is still unseen by the notifier
+        notifier.changed().await.unwrap();
+        drop(updater);
+        assert!(notifier.changed().await.is_err());
+        assert_eq!(updates.next().await, None);
+    }
 }
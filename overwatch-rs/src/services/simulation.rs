@@ -0,0 +1,92 @@
+//! Virtual relay latencies, to simulate a network between services
+//!
+//! Combined with a [`VirtualClock`](crate::services::clock::VirtualClock) on a tokio runtime
+//! started paused, latencies cost no real time: the runtime jumps to the next delivery once every
+//! service is idle, so protocols can be simulated on the production services code.
+//!
+//! ```ignore
+//! let runtime = tokio::runtime::Builder::new_current_thread()
+//!     .enable_all()
+//!     .start_paused(true)
+//!     .build()?;
+//! let overwatch = OverwatchRunner::<App>::run_with_context_config(
+//!     settings,
+//!     Some(runtime),
+//!     ContextConfig::simulation(FixedLatency(Duration::from_millis(80))),
+//! )?;
+//! ```
+
+// std
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+// internal
+use crate::services::clock::Clock;
+use crate::services::ServiceId;
+
+/// Virtual delay of the messages sent from a service to another
+pub trait LatencyModel: Send + Sync + 'static {
+    /// Latency of a message sent by `from`, `None` if sent from outside any service, to `to`
+    fn latency(&self, from: Option<ServiceId>, to: ServiceId) -> Duration;
+}
+
+impl<F> LatencyModel for F
+where
+    F: Fn(Option<ServiceId>, ServiceId) -> Duration + Send + Sync + 'static,
+{
+    fn latency(&self, from: Option<ServiceId>, to: ServiceId) -> Duration {
+        self(from, to)
+    }
+}
+
+/// Same latency for every message
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FixedLatency(pub Duration);
+
+impl LatencyModel for FixedLatency {
+    fn latency(&self, _from: Option<ServiceId>, _to: ServiceId) -> Duration {
+        self.0
+    }
+}
+
+/// Latency applied by an outbound relay, measured with the application clock
+#[derive(Clone)]
+pub(crate) struct RelayLatency {
+    model: Arc<dyn LatencyModel>,
+    clock: Arc<dyn Clock>,
+    from: Option<ServiceId>,
+    to: ServiceId,
+}
+
+impl Debug for RelayLatency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelayLatency")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RelayLatency {
+    pub(crate) fn new(
+        model: Arc<dyn LatencyModel>,
+        clock: Arc<dyn Clock>,
+        from: Option<ServiceId>,
+        to: ServiceId,
+    ) -> Self {
+        Self {
+            model,
+            clock,
+            from,
+            to,
+        }
+    }
+
+    /// Wait for the latency of the next message to elapse
+    pub(crate) async fn delay(&self) {
+        let latency = self.model.latency(self.from, self.to);
+        if !latency.is_zero() {
+            self.clock.sleep(latency).await;
+        }
+    }
+}
@@ -0,0 +1,760 @@
+use std::convert::Infallible;
+use std::error::Error;
+// std
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+// crates
+use async_trait::async_trait;
+use futures::StreamExt;
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+use tokio::sync::watch::{channel, Receiver, Ref, Sender};
+use tokio::time::{Instant, Interval};
+use tokio_stream::wrappers::WatchStream;
+use tracing::error;
+// internal
+use crate::overwatch::history::{History, HistoryEvent};
+use crate::services::{ServiceId, TRACING_TARGET};
+
+#[cfg(feature = "state-storage")]
+pub mod operators;
+
+#[derive(Error, Debug)]
+pub enum StateUpdateError {
+    #[error("service is stopped, state updates are rejected")]
+    Stopped,
+    #[error("state handle is not listening for updates anymore")]
+    Closed,
+}
+
+// TODO: Constrain this, probably with needed serialize/deserialize options.
+/// Service state initialization traits
+/// It defines what is needed for a service state to be initialized.
+/// Need what set of settings information is required for it to be initialized [`ServiceState::Settings`]
+/// which usually is bound to the service itself [`crate::services::ServiceData::Settings`]
+pub trait ServiceState: Sized {
+    /// Settings object that the state can be initialized from
+    type Settings;
+    /// Errors that can occur during state initialization
+    type Error;
+    /// Initialize a state using the provided settings.
+    /// This is called when [StateOperator::try_load] doesn't return a state.
+    fn from_settings(settings: &Self::Settings) -> Result<Self, Self::Error>;
+}
+
+/// Why a [`StateOperator`] is run over a state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PersistContext {
+    /// The service pushed a new state through its [`StateUpdater`]
+    Update,
+    /// The service is stopping, the state is the last one it pushed
+    StopFlush,
+    /// The service asked for it with [`StateUpdater::request_snapshot`]
+    Snapshot,
+    /// The service panicked, the state is the last one it pushed
+    PanicFlush,
+}
+
+impl PersistContext {
+    /// Whether the service instance is gone, so no other state follows
+    pub fn is_final(self) -> bool {
+        matches!(self, Self::StopFlush | Self::PanicFlush)
+    }
+}
+
+/// When the [`StateOperator`] runs over state updates, see
+/// [`ServiceData::STATE_PERSISTENCE`](crate::services::ServiceData::STATE_PERSISTENCE)
+/// Updates coming in meanwhile are coalesced, the operator only sees the latest state. Flushes
+/// always run right away, and a pending state is handed to the operator before the service stops,
+/// so none is lost on shutdown.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StatePersistencePolicy {
+    /// Time without updates to wait for before running the operator
+    pub debounce: Option<Duration>,
+    /// Longest time an updated state waits for the operator, however frequent updates are
+    pub max_dirty: Option<Duration>,
+    /// Interval [`StateOperator::compact`] runs at, besides when the service stops
+    pub compaction: Option<Duration>,
+}
+
+impl StatePersistencePolicy {
+    /// Run the operator on every update
+    pub const IMMEDIATE: Self = Self {
+        debounce: None,
+        max_dirty: None,
+        compaction: None,
+    };
+
+    pub const fn debounced(debounce: Duration) -> Self {
+        Self {
+            debounce: Some(debounce),
+            max_dirty: None,
+            compaction: None,
+        }
+    }
+
+    pub const fn with_max_dirty(self, max_dirty: Duration) -> Self {
+        Self {
+            max_dirty: Some(max_dirty),
+            ..self
+        }
+    }
+
+    pub const fn with_compaction(self, interval: Duration) -> Self {
+        Self {
+            compaction: Some(interval),
+            ..self
+        }
+    }
+
+    fn is_immediate(&self) -> bool {
+        self.debounce.is_none() && self.max_dirty.is_none()
+    }
+
+    /// When to run the operator over a state first updated at `dirty_since` and last at `updated_at`
+    fn deadline(&self, dirty_since: Instant, updated_at: Instant) -> Instant {
+        let debounced = self.debounce.map(|debounce| updated_at + debounce);
+        let capped = self.max_dirty.map(|max_dirty| dirty_since + max_dirty);
+        debounced
+            .into_iter()
+            .chain(capped)
+            .min()
+            .unwrap_or(updated_at)
+    }
+}
+
+/// A state operator is an entity that can handle a state in a point of time
+/// to perform any operation based on it.
+/// A typical use case is to handle recovery: Saving and loading state.
+#[async_trait]
+pub trait StateOperator {
+    /// The type of state that the operator can handle
+    type StateInput: ServiceState;
+    /// Errors that can occur during state loading
+    type LoadError: Error;
+    /// State initialization method
+    /// In contrast to [ServiceState::from_settings], this is used to try to initialize
+    /// a (saved) [ServiceState] from an external source (e.g. file, database, etc.)
+    fn try_load(
+        settings: &<Self::StateInput as ServiceState>::Settings,
+    ) -> Result<Option<Self::StateInput>, Self::LoadError>;
+    /// Operator initialization method. Can be implemented over some subset of settings
+    fn from_settings(settings: <Self::StateInput as ServiceState>::Settings) -> Self;
+    /// Asynchronously perform an operation for a given state
+    /// `context` tells why it is run, so costly operations (e.g. fsync) can be limited to
+    /// [`PersistContext::StopFlush`] and skipped for frequent updates.
+    async fn run(&mut self, state: Self::StateInput, context: PersistContext);
+    /// Prune data older states left behind, e.g. for operators appending snapshots
+    /// Run every [`StatePersistencePolicy::compaction`] interval and once the state handle is done.
+    // spelled out, a default `async fn` would require operators to be `Send`
+    fn compact<'borrow, 'fut>(
+        &'borrow mut self,
+    ) -> Pin<Box<dyn std::future::Future<Output = ()> + Send + 'fut>>
+    where
+        'borrow: 'fut,
+        Self: 'fut,
+    {
+        Box::pin(async {})
+    }
+}
+
+/// Operator that doesn't perform any operation upon state update
+#[derive(Copy)]
+pub struct NoOperator<StateInput>(PhantomData<*const StateInput>);
+
+// NoOperator does not actually hold anything and is thus Sync.
+// Note that we don't use PhantomData<StateInput> as that would
+// suggest we indeed hold an instance of StateInput, see
+// https://doc.rust-lang.org/std/marker/struct.PhantomData.html#ownership-and-the-drop-check
+unsafe impl<T> Send for NoOperator<T> {}
+
+// auto derive introduces unnecessary Clone bound on T
+impl<T> Clone for NoOperator<T> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[async_trait]
+impl<StateInput: ServiceState> StateOperator for NoOperator<StateInput> {
+    type StateInput = StateInput;
+    type LoadError = Infallible;
+
+    fn try_load(
+        _settings: &<Self::StateInput as ServiceState>::Settings,
+    ) -> Result<Option<Self::StateInput>, Self::LoadError> {
+        Ok(None)
+    }
+
+    fn from_settings(_settings: <Self::StateInput as ServiceState>::Settings) -> Self {
+        NoOperator(PhantomData)
+    }
+
+    fn run<'borrow, 'fut>(
+        &'borrow mut self,
+        _state: Self::StateInput,
+        _context: PersistContext,
+    ) -> Pin<Box<dyn std::future::Future<Output = ()> + Send + 'fut>>
+    where
+        'borrow: 'fut,
+        Self: 'fut,
+    {
+        Box::pin(async {})
+    }
+}
+
+/// Empty state
+#[derive(Copy)]
+pub struct NoState<Settings>(PhantomData<Settings>);
+
+// auto derive introduces unnecessary Clone bound on T
+impl<T> Clone for NoState<T> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Settings> ServiceState for NoState<Settings> {
+    type Settings = Settings;
+
+    type Error = crate::DynError;
+
+    fn from_settings(_settings: &Self::Settings) -> Result<Self, Self::Error> {
+        Ok(Self(Default::default()))
+    }
+}
+
+/// Flush requests a [`StateHandle`] can be behind on, older ones are skipped
+const FLUSHES_BUFFER_SIZE: usize = 16;
+
+/// Request to run the operator over the current state outside of regular updates
+/// Ids grow with every request, a flush handled covers the ones of lower ids.
+#[derive(Clone, Copy, Debug)]
+struct Flush {
+    id: u64,
+    context: PersistContext,
+}
+
+/// Receiver part of the state handling mechanism.
+/// A state handle watches a stream of incoming states and triggers the attached operator handling
+/// method over it.
+pub struct StateHandle<S, Operator> {
+    watcher: StateWatcher<S>,
+    flushes: broadcast::Receiver<Flush>,
+    /// Id of the last flush handled
+    flushed: Arc<watch::Sender<u64>>,
+    operator: Operator,
+    /// Where state snapshots are recorded, if enabled
+    history: Option<(ServiceId, History)>,
+    persistence: StatePersistencePolicy,
+}
+
+// auto derive introduces unnecessary Clone bound on T
+impl<S, O> Clone for StateHandle<S, O>
+where
+    O: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            watcher: self.watcher.clone(),
+            flushes: self.flushes.resubscribe(),
+            flushed: Arc::clone(&self.flushed),
+            operator: self.operator.clone(),
+            history: self.history.clone(),
+            persistence: self.persistence,
+        }
+    }
+}
+
+/// Sender part of the state handling mechanism.
+/// Update the current state and notifies the [`StateHandle`].
+pub struct StateUpdater<S> {
+    sender: Arc<Sender<S>>,
+    /// Requests to run the operator over the current state outside of regular updates
+    flushes: broadcast::Sender<Flush>,
+    /// Id of the last flush requested
+    flush_ids: Arc<AtomicU64>,
+    flushed: watch::Receiver<u64>,
+    /// Set once the owning service is stopped, shared across all clones
+    stopped: Arc<AtomicBool>,
+}
+
+// auto derive introduces unnecessary Clone bound on T
+impl<T> Clone for StateUpdater<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            flushes: self.flushes.clone(),
+            flush_ids: Arc::clone(&self.flush_ids),
+            flushed: self.flushed.clone(),
+            stopped: self.stopped.clone(),
+        }
+    }
+}
+
+/// Wrapper over [`tokio::sync::watch::Receiver`]
+pub struct StateWatcher<S> {
+    receiver: Receiver<S>,
+}
+
+// auto derive introduces unnecessary Clone bound on T
+impl<T> Clone for StateWatcher<T> {
+    fn clone(&self) -> Self {
+        Self {
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+impl<S: ServiceState> StateUpdater<S> {
+    /// Send a new state and notify the [`StateWatcher`]
+    /// Updates are rejected once the service that owns this updater has been stopped, so a
+    /// restarted instance never observes states pushed by a previous one.
+    pub fn update(&self, new_state: S) -> Result<(), StateUpdateError> {
+        if self.is_stopped() {
+            return Err(StateUpdateError::Stopped);
+        }
+        self.sender.send(new_state).map_err(|_e| {
+            error!(target: TRACING_TARGET, "Error updating state");
+            StateUpdateError::Closed
+        })
+    }
+
+    /// Run the operator over the current state with [`PersistContext::Snapshot`]
+    /// The returned request tells once the operator is done with it.
+    pub fn request_snapshot(&self) -> Result<SnapshotRequest, StateUpdateError> {
+        if self.is_stopped() {
+            return Err(StateUpdateError::Stopped);
+        }
+        self.snapshot()
+    }
+}
+
+impl<S> StateUpdater<S> {
+    /// Mark the owning service as stopped, rejecting any further update from this updater clones
+    pub(crate) fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+
+    /// Run the operator over the current state with `context`
+    pub(crate) fn flush(&self, context: PersistContext) -> Result<(), StateUpdateError> {
+        self.send_flush(context).map(|_| ())
+    }
+
+    /// Run the operator over the current state with [`PersistContext::Snapshot`], whether the
+    /// owning service is stopped or not
+    pub(crate) fn snapshot(&self) -> Result<SnapshotRequest, StateUpdateError> {
+        let flushed = self.flushed.clone();
+        let id = self.send_flush(PersistContext::Snapshot)?;
+        Ok(SnapshotRequest { id, flushed })
+    }
+
+    fn send_flush(&self, context: PersistContext) -> Result<u64, StateUpdateError> {
+        let id = self.flush_ids.fetch_add(1, Ordering::Relaxed) + 1;
+        self.flushes
+            .send(Flush { id, context })
+            .map(|_| id)
+            .map_err(|_| StateUpdateError::Closed)
+    }
+}
+
+/// Snapshot requested from a [`StateHandle`], see [`StateUpdater::request_snapshot`]
+#[derive(Debug)]
+pub struct SnapshotRequest {
+    id: u64,
+    flushed: watch::Receiver<u64>,
+}
+
+impl SnapshotRequest {
+    /// Wait until the operator ran over the snapshot, or a later flush
+    /// It returns right away if the state handle is gone, after its final flush if it had one.
+    pub async fn done(mut self) {
+        let id = self.id;
+        let _ = self.flushed.wait_for(|&flushed| flushed >= id).await;
+    }
+}
+
+impl<S> StateWatcher<S>
+where
+    S: ServiceState + Clone,
+{
+    /// Get a copy of the most updated state
+    pub fn state_cloned(&self) -> S {
+        self.receiver.borrow().clone()
+    }
+}
+
+impl<S> StateWatcher<S>
+where
+    S: ServiceState,
+{
+    /// Get a [`Ref`](tokio::sync::watch::Ref) to the last state, this blocks incoming updates until
+    /// the `Ref` is dropped. Use with caution.
+    pub fn state_ref(&self) -> Ref<'_, S> {
+        self.receiver.borrow()
+    }
+}
+
+impl<S, O> StateHandle<S, O> {
+    pub fn new(initial_state: S, operator: O) -> (Self, StateUpdater<S>) {
+        let (sender, receiver) = channel(initial_state);
+        let (flushes_sender, flushes) = broadcast::channel(FLUSHES_BUFFER_SIZE);
+        let (flushed_sender, flushed) = watch::channel(0);
+        let watcher = StateWatcher { receiver };
+        let updater = StateUpdater {
+            sender: Arc::new(sender),
+            flushes: flushes_sender,
+            flush_ids: Arc::default(),
+            flushed,
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+
+        (
+            Self {
+                watcher,
+                flushes,
+                flushed: Arc::new(flushed_sender),
+                operator,
+                history: None,
+                persistence: StatePersistencePolicy::IMMEDIATE,
+            },
+            updater,
+        )
+    }
+
+    /// Record every handled state in the overwatch history, if enabled
+    pub(crate) fn recorded(mut self, service_id: ServiceId, history: &History) -> Self {
+        self.history = history.is_enabled().then(|| (service_id, history.clone()));
+        self
+    }
+
+    /// Run the operator over updates as `policy` says
+    pub fn with_persistence(mut self, policy: StatePersistencePolicy) -> Self {
+        self.persistence = policy;
+        self
+    }
+}
+
+impl<S, Operator> StateHandle<S, Operator>
+where
+    S: ServiceState + Clone + Send + Sync + 'static,
+    Operator: StateOperator<StateInput = S>,
+{
+    /// Wait for new state updates and run the operator handling method
+    /// It finishes once the updaters are gone or after a final flush, see
+    /// [`PersistContext::is_final`], compacting the stored states a last time.
+    pub async fn run(self) {
+        let Self {
+            watcher,
+            mut flushes,
+            flushed,
+            mut operator,
+            history,
+            persistence,
+        } = self;
+        let latest = watcher.receiver.clone();
+        let mut state_stream = WatchStream::new(watcher.receiver);
+        // first and last update times of a state the operator didn't run over yet
+        let mut dirty: Option<(Instant, Instant)> = None;
+        let mut compaction = persistence
+            .compaction
+            .map(|interval| tokio::time::interval_at(Instant::now() + interval, interval));
+        loop {
+            let deadline = dirty.map(|(since, updated_at)| persistence.deadline(since, updated_at));
+            tokio::select! {
+                // pending updates go first, so flushes see the latest state
+                biased;
+                state = state_stream.next() => {
+                    let Some(state) = state else {
+                        if dirty.is_some() {
+                            let state = latest.borrow().clone();
+                            operator.run(state, PersistContext::Update).await;
+                        }
+                        break;
+                    };
+                    if let Some((service_id, history)) = &history {
+                        history.record(service_id, HistoryEvent::State(Arc::new(state.clone())));
+                    }
+                    if persistence.is_immediate() {
+                        operator.run(state, PersistContext::Update).await;
+                    } else {
+                        let now = Instant::now();
+                        dirty = Some((dirty.map_or(now, |(since, _)| since), now));
+                    }
+                }
+                Some(Flush { id, context }) = next_flush(&mut flushes) => {
+                    dirty = None;
+                    let state = latest.borrow().clone();
+                    operator.run(state, context).await;
+                    flushed.send_if_modified(|flushed| {
+                        let newer = id > *flushed;
+                        if newer {
+                            *flushed = id;
+                        }
+                        newer
+                    });
+                    if context.is_final() {
+                        break;
+                    }
+                }
+                () = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    dirty = None;
+                    let state = latest.borrow().clone();
+                    operator.run(state, PersistContext::Update).await;
+                }
+                () = next_compaction(&mut compaction) => {
+                    operator.compact().await;
+                }
+            }
+        }
+        operator.compact().await;
+    }
+}
+
+/// Wait for the next compaction, forever if compactions aren't scheduled
+async fn next_compaction(compaction: &mut Option<Interval>) {
+    match compaction {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for the next flush request, `None` once the updaters are gone
+async fn next_flush(flushes: &mut broadcast::Receiver<Flush>) -> Option<Flush> {
+    loop {
+        match flushes.recv().await {
+            Ok(context) => return Some(context),
+            // flushes run over the latest state, skipping some of them loses nothing
+            Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::state::{
+        PersistContext, ServiceState, StateHandle, StateOperator, StatePersistencePolicy,
+        StateUpdateError, StateUpdater,
+    };
+    use async_trait::async_trait;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io;
+    use tokio::io::AsyncWriteExt;
+    use tokio::time::sleep;
+
+    #[derive(Clone)]
+    struct UsizeCounter(usize);
+
+    impl ServiceState for UsizeCounter {
+        type Settings = ();
+        type Error = crate::DynError;
+        fn from_settings(_settings: &Self::Settings) -> Result<Self, crate::DynError> {
+            Ok(Self(0))
+        }
+    }
+
+    struct PanicOnGreaterThanTen;
+
+    #[async_trait]
+    impl StateOperator for PanicOnGreaterThanTen {
+        type StateInput = UsizeCounter;
+        type LoadError = Infallible;
+
+        fn try_load(
+            _settings: &<Self::StateInput as ServiceState>::Settings,
+        ) -> Result<Option<Self::StateInput>, Self::LoadError> {
+            Ok(None)
+        }
+
+        fn from_settings(_settings: <Self::StateInput as ServiceState>::Settings) -> Self {
+            Self
+        }
+
+        async fn run(&mut self, state: Self::StateInput, _context: PersistContext) {
+            let mut stdout = io::stdout();
+            let UsizeCounter(value) = state;
+            stdout
+                .write_all(format!("{value}\n").as_bytes())
+                .await
+                .expect("stop Output wrote");
+            assert!(value < 10);
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn state_stream_collects() {
+        let (handle, updater): (
+            StateHandle<UsizeCounter, PanicOnGreaterThanTen>,
+            StateUpdater<UsizeCounter>,
+        ) = StateHandle::new(
+            UsizeCounter::from_settings(&()).unwrap(),
+            PanicOnGreaterThanTen::from_settings(()),
+        );
+        tokio::task::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            for i in 0..15 {
+                updater.update(UsizeCounter(i)).unwrap();
+                sleep(Duration::from_millis(50)).await;
+            }
+        });
+        handle.run().await;
+    }
+
+    /// Records the runs of the operator and counts compactions
+    #[derive(Clone)]
+    struct Recording {
+        runs: tokio::sync::mpsc::UnboundedSender<(usize, PersistContext)>,
+        compactions: Arc<AtomicUsize>,
+    }
+
+    impl Recording {
+        fn new(runs: tokio::sync::mpsc::UnboundedSender<(usize, PersistContext)>) -> Self {
+            Self {
+                runs,
+                compactions: Arc::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl StateOperator for Recording {
+        type StateInput = UsizeCounter;
+        type LoadError = Infallible;
+
+        fn try_load(
+            _settings: &<Self::StateInput as ServiceState>::Settings,
+        ) -> Result<Option<Self::StateInput>, Self::LoadError> {
+            Ok(None)
+        }
+
+        fn from_settings(_settings: <Self::StateInput as ServiceState>::Settings) -> Self {
+            // nobody listens to the runs of an operator built from its settings
+            Self::new(tokio::sync::mpsc::unbounded_channel().0)
+        }
+
+        async fn run(&mut self, state: Self::StateInput, context: PersistContext) {
+            let _ = self.runs.send((state.0, context));
+        }
+
+        async fn compact(&mut self) {
+            self.compactions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn operator_is_told_why_it_runs() {
+        let (sender, mut runs) = tokio::sync::mpsc::unbounded_channel();
+        let (handle, updater) = StateHandle::new(UsizeCounter(0), Recording::new(sender));
+        let task = tokio::spawn(handle.run());
+        assert_eq!(runs.recv().await, Some((0, PersistContext::Update)));
+        updater.update(UsizeCounter(1)).unwrap();
+        assert_eq!(runs.recv().await, Some((1, PersistContext::Update)));
+        // snapshot requests are done once the operator ran over them
+        updater.request_snapshot().unwrap().done().await;
+        assert_eq!(runs.try_recv(), Ok((1, PersistContext::Snapshot)));
+        updater.flush(PersistContext::StopFlush).unwrap();
+        assert_eq!(runs.recv().await, Some((1, PersistContext::StopFlush)));
+        // nothing follows a final flush
+        task.await.unwrap();
+        assert_eq!(runs.recv().await, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn persistence_is_debounced_and_capped() {
+        let (sender, mut runs) = tokio::sync::mpsc::unbounded_channel();
+        let (handle, updater) = StateHandle::new(UsizeCounter(0), Recording::new(sender));
+        let policy = StatePersistencePolicy::debounced(Duration::from_millis(100))
+            .with_max_dirty(Duration::from_millis(250));
+        let task = tokio::spawn(handle.with_persistence(policy).run());
+        assert_eq!(runs.recv().await, Some((0, PersistContext::Update)));
+
+        for i in 1..4 {
+            updater.update(UsizeCounter(i)).unwrap();
+        }
+        assert_eq!(runs.recv().await, Some((3, PersistContext::Update)));
+
+        // updates never settle down, the state is still persisted once dirty for too long
+        let busy = updater.clone();
+        let updates = tokio::spawn(async move {
+            for i in 10..20 {
+                busy.update(UsizeCounter(i)).unwrap();
+                sleep(Duration::from_millis(60)).await;
+            }
+        });
+        let (value, _) = runs.recv().await.unwrap();
+        assert!((10..19).contains(&value));
+        updates.await.unwrap();
+
+        // the pending state is persisted when the updaters are gone
+        updater.update(UsizeCounter(42)).unwrap();
+        drop(updater);
+        task.await.unwrap();
+        let last = std::iter::from_fn(|| runs.try_recv().ok()).last();
+        assert_eq!(last, Some((42, PersistContext::Update)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn compaction_is_scheduled_and_run_on_stop() {
+        let (runs, _) = tokio::sync::mpsc::unbounded_channel();
+        let operator = Recording::new(runs);
+        let compactions = Arc::clone(&operator.compactions);
+        let (handle, updater) = StateHandle::new(UsizeCounter(0), operator);
+        let policy = StatePersistencePolicy::IMMEDIATE.with_compaction(Duration::from_millis(100));
+        let task = tokio::spawn(handle.with_persistence(policy).run());
+        sleep(Duration::from_millis(350)).await;
+        assert_eq!(compactions.load(Ordering::Relaxed), 3);
+        updater.flush(PersistContext::StopFlush).unwrap();
+        task.await.unwrap();
+        assert_eq!(compactions.load(Ordering::Relaxed), 4);
+    }
+
+    #[tokio::test]
+    async fn cloned_handles_run_their_operator() {
+        let (sender, mut runs) = tokio::sync::mpsc::unbounded_channel();
+        let (handle, updater) = StateHandle::new(UsizeCounter(0), Recording::new(sender));
+        let tasks = [
+            tokio::spawn(handle.clone().run()),
+            tokio::spawn(handle.run()),
+        ];
+        updater.flush(PersistContext::StopFlush).unwrap();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let flushes = std::iter::from_fn(|| runs.try_recv().ok())
+            .filter(|(_, context)| *context == PersistContext::StopFlush)
+            .count();
+        assert_eq!(flushes, 2);
+    }
+
+    #[test]
+    fn stopped_updater_rejects_updates() {
+        let (_handle, updater): (StateHandle<UsizeCounter, PanicOnGreaterThanTen>, _) =
+            StateHandle::new(
+                UsizeCounter::from_settings(&()).unwrap(),
+                PanicOnGreaterThanTen::from_settings(()),
+            );
+        let clone = updater.clone();
+        assert!(clone.update(UsizeCounter(1)).is_ok());
+        updater.stop();
+        assert!(matches!(
+            clone.update(UsizeCounter(2)),
+            Err(StateUpdateError::Stopped)
+        ));
+    }
+}
@@ -0,0 +1,404 @@
+//! Ready made [`StateOperator`]s, saving the service state on every run and loading it back when
+//! the service starts
+//! A [`StorageOperator`] encodes the state with a [`StateCodec`] and keeps it in a
+//! [`StateStore`], the aliases below cover the usual combinations. Where the state is kept is told
+//! by the service settings, see [`StorageSettings`]. They require the `state-storage` feature,
+//! the bincode codec and the sled store the `state-bincode` and `state-sled` ones. Stores are written from the blocking thread
+//! pool, as their I/O would stall the runtime.
+// std
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+#[cfg(feature = "state-sled")]
+use std::sync::OnceLock;
+use std::sync::{Arc, Mutex};
+// crates
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::error;
+// internal
+use crate::services::state::{PersistContext, ServiceState, StateOperator};
+use crate::services::TRACING_TARGET;
+use crate::utils::atomic_file;
+use crate::DynError;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("state store couldn't be accessed: {0}")]
+    Store(#[source] DynError),
+    #[error("state couldn't be encoded or decoded: {0}")]
+    Codec(#[source] DynError),
+}
+
+/// Settings of services keeping their state with a [`StorageOperator`]
+pub trait StorageSettings {
+    /// File the state is kept in, or directory of the database for key-value stores
+    fn state_path(&self) -> PathBuf;
+
+    /// Key of the state in key-value stores, services sharing a database need one each
+    fn state_key(&self) -> String {
+        "state".to_owned()
+    }
+
+    /// States kept by a [`MemoryStore`], services only find theirs again on restarts if their
+    /// settings hand the same [`MemoryStates`] every time
+    fn memory_states(&self) -> MemoryStates {
+        MemoryStates::default()
+    }
+}
+
+/// Format states are stored in
+pub trait StateCodec {
+    fn encode<S: Serialize>(state: &S) -> Result<Vec<u8>, DynError>;
+    fn decode<S: DeserializeOwned>(bytes: &[u8]) -> Result<S, DynError>;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonCodec;
+
+impl StateCodec for JsonCodec {
+    fn encode<S: Serialize>(state: &S) -> Result<Vec<u8>, DynError> {
+        Ok(serde_json::to_vec(state)?)
+    }
+
+    fn decode<S: DeserializeOwned>(bytes: &[u8]) -> Result<S, DynError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+#[cfg(feature = "state-bincode")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "state-bincode")]
+impl StateCodec for BincodeCodec {
+    fn encode<S: Serialize>(state: &S) -> Result<Vec<u8>, DynError> {
+        Ok(bincode::serialize(state)?)
+    }
+
+    fn decode<S: DeserializeOwned>(bytes: &[u8]) -> Result<S, DynError> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// Where encoded states are kept
+pub trait StateStore: Sized {
+    fn open<Settings: StorageSettings>(settings: &Settings) -> Self;
+
+    /// Last written state, `None` if there is none yet
+    fn read(&self) -> Result<Option<Vec<u8>>, DynError>;
+
+    /// Replace the stored state, `durable` writes must survive a crash, they are asked for when
+    /// the service is gone, see [`PersistContext::is_final`]
+    fn write(&mut self, state: &[u8], durable: bool) -> Result<(), DynError>;
+}
+
+/// State kept in a file, replaced atomically on every write
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl StateStore for FileStore {
+    fn open<Settings: StorageSettings>(settings: &Settings) -> Self {
+        Self {
+            path: settings.state_path(),
+        }
+    }
+
+    fn read(&self) -> Result<Option<Vec<u8>>, DynError> {
+        match std::fs::read(&self.path) {
+            Ok(state) => Ok(Some(state)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write(&mut self, state: &[u8], durable: bool) -> Result<(), DynError> {
+        Ok(atomic_file::write(&self.path, state, durable)?)
+    }
+}
+
+/// States of [`MemoryStore`]s, shared by its clones
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStates(Arc<Mutex<HashMap<MemoryKey, Vec<u8>>>>);
+
+/// Stored states are told apart by both [`StorageSettings`] values
+type MemoryKey = (PathBuf, String);
+
+/// State kept in the [`MemoryStates`] handed by the settings, meant for tests
+#[derive(Clone, Debug)]
+pub struct MemoryStore {
+    states: MemoryStates,
+    key: MemoryKey,
+}
+
+impl StateStore for MemoryStore {
+    fn open<Settings: StorageSettings>(settings: &Settings) -> Self {
+        Self {
+            states: settings.memory_states(),
+            key: (settings.state_path(), settings.state_key()),
+        }
+    }
+
+    fn read(&self) -> Result<Option<Vec<u8>>, DynError> {
+        let states = self.states.0.lock().expect("States lock not poisoned");
+        Ok(states.get(&self.key).cloned())
+    }
+
+    fn write(&mut self, state: &[u8], _durable: bool) -> Result<(), DynError> {
+        let mut states = self.states.0.lock().expect("States lock not poisoned");
+        states.insert(self.key.clone(), state.to_vec());
+        Ok(())
+    }
+}
+
+/// State kept under a key of a sled database, which services can share
+#[cfg(feature = "state-sled")]
+#[derive(Clone, Debug)]
+pub struct SledStore {
+    path: PathBuf,
+    key: String,
+}
+
+#[cfg(feature = "state-sled")]
+impl SledStore {
+    /// Databases are opened once per process, sled locks them
+    fn database(&self) -> Result<sled::Db, DynError> {
+        static DATABASES: OnceLock<Mutex<HashMap<PathBuf, sled::Db>>> = OnceLock::new();
+        let mut databases = DATABASES
+            .get_or_init(Mutex::default)
+            .lock()
+            .expect("Databases lock not poisoned");
+        if let Some(database) = databases.get(&self.path) {
+            return Ok(database.clone());
+        }
+        let database = sled::open(&self.path)?;
+        databases.insert(self.path.clone(), database.clone());
+        Ok(database)
+    }
+}
+
+#[cfg(feature = "state-sled")]
+impl StateStore for SledStore {
+    fn open<Settings: StorageSettings>(settings: &Settings) -> Self {
+        Self {
+            path: settings.state_path(),
+            key: settings.state_key(),
+        }
+    }
+
+    fn read(&self) -> Result<Option<Vec<u8>>, DynError> {
+        let state = self.database()?.get(&self.key)?;
+        Ok(state.map(|state| state.to_vec()))
+    }
+
+    fn write(&mut self, state: &[u8], durable: bool) -> Result<(), DynError> {
+        let database = self.database()?;
+        database.insert(&self.key, state)?;
+        if durable {
+            database.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Operator saving the state to `Store` encoded with `Codec`, and loading it back on start
+pub struct StorageOperator<S, Store, Codec = JsonCodec> {
+    // moved to the blocking thread pool for every write
+    store: Arc<Mutex<Store>>,
+    _state: PhantomData<fn() -> (S, Codec)>,
+}
+
+pub type JsonFileOperator<S> = StorageOperator<S, FileStore, JsonCodec>;
+
+#[cfg(feature = "state-bincode")]
+pub type BincodeFileOperator<S> = StorageOperator<S, FileStore, BincodeCodec>;
+
+#[cfg(feature = "state-sled")]
+pub type SledOperator<S, Codec = JsonCodec> = StorageOperator<S, SledStore, Codec>;
+
+pub type MemoryOperator<S> = StorageOperator<S, MemoryStore, JsonCodec>;
+
+// auto derive introduces unnecessary Clone bounds on S and Codec
+impl<S, Store: Clone, Codec> Clone for StorageOperator<S, Store, Codec> {
+    fn clone(&self) -> Self {
+        let store = self.store.lock().expect("Store lock not poisoned").clone();
+        Self {
+            store: Arc::new(Mutex::new(store)),
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<S, Store, Codec> StorageOperator<S, Store, Codec>
+where
+    S: Serialize,
+    Store: StateStore + Send + 'static,
+    Codec: StateCodec,
+{
+    async fn save(&mut self, state: S, durable: bool) -> Result<(), StorageError> {
+        let state = Codec::encode(&state).map_err(StorageError::Codec)?;
+        let store = Arc::clone(&self.store);
+        tokio::task::spawn_blocking(move || {
+            store
+                .lock()
+                .map_err(|_| DynError::from("state store lock poisoned"))?
+                .write(&state, durable)
+        })
+        .await
+        .map_err(|e| StorageError::Store(e.into()))?
+        .map_err(StorageError::Store)
+    }
+}
+
+#[async_trait]
+impl<S, Store, Codec> StateOperator for StorageOperator<S, Store, Codec>
+where
+    S: ServiceState + Serialize + DeserializeOwned + Send,
+    S::Settings: StorageSettings,
+    Store: StateStore + Send + 'static,
+    Codec: StateCodec,
+{
+    type StateInput = S;
+    type LoadError = StorageError;
+
+    fn try_load(settings: &S::Settings) -> Result<Option<S>, StorageError> {
+        let Some(state) = Store::open(settings).read().map_err(StorageError::Store)? else {
+            return Ok(None);
+        };
+        Codec::decode(&state).map(Some).map_err(StorageError::Codec)
+    }
+
+    fn from_settings(settings: S::Settings) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(Store::open(&settings))),
+            _state: PhantomData,
+        }
+    }
+
+    async fn run(&mut self, state: S, context: PersistContext) {
+        if let Err(e) = self.save(state, context.is_final()).await {
+            error!(target: TRACING_TARGET, error = %e, "Service state couldn't be saved");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::testing::EphemeralDir;
+    use crate::services::state::operators::{
+        FileStore, JsonFileOperator, MemoryOperator, MemoryStates, MemoryStore, StateStore,
+        StorageSettings,
+    };
+    use crate::services::state::{PersistContext, ServiceState, StateOperator};
+    use serde::{Deserialize, Serialize};
+    use std::path::PathBuf;
+
+    #[derive(Clone, Debug)]
+    struct Location {
+        path: PathBuf,
+        memory: MemoryStates,
+    }
+
+    impl Location {
+        fn new(path: PathBuf) -> Self {
+            Self {
+                path,
+                memory: MemoryStates::default(),
+            }
+        }
+    }
+
+    impl StorageSettings for Location {
+        fn state_path(&self) -> PathBuf {
+            self.path.clone()
+        }
+
+        fn memory_states(&self) -> MemoryStates {
+            self.memory.clone()
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Counter(usize);
+
+    impl ServiceState for Counter {
+        type Settings = Location;
+        type Error = crate::DynError;
+
+        fn from_settings(_settings: &Self::Settings) -> Result<Self, Self::Error> {
+            Ok(Self(0))
+        }
+    }
+
+    async fn state_survives_restarts<O>(settings: Location)
+    where
+        O: StateOperator<StateInput = Counter>,
+    {
+        assert_eq!(O::try_load(&settings).unwrap(), None);
+        let mut operator = O::from_settings(settings.clone());
+        operator.run(Counter(1), PersistContext::Update).await;
+        operator.run(Counter(2), PersistContext::StopFlush).await;
+        assert_eq!(O::try_load(&settings).unwrap(), Some(Counter(2)));
+    }
+
+    #[tokio::test]
+    async fn json_file_state_survives_restarts() {
+        let dir = EphemeralDir::create().unwrap();
+        let path = dir.path().join("state.json");
+        state_survives_restarts::<JsonFileOperator<Counter>>(Location::new(path.clone())).await;
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "2");
+        // staging files are renamed over the state
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        dir.remove().unwrap();
+    }
+
+    #[tokio::test]
+    async fn files_sharing_a_stem_keep_their_states() {
+        let dir = EphemeralDir::create().unwrap();
+        let mut json = FileStore::open(&Location::new(dir.path().join("state.json")));
+        let mut bin = FileStore::open(&Location::new(dir.path().join("state.bin")));
+        json.write(b"1", false).unwrap();
+        bin.write(b"2", true).unwrap();
+        assert_eq!(json.read().unwrap(), Some(b"1".to_vec()));
+        assert_eq!(bin.read().unwrap(), Some(b"2".to_vec()));
+        dir.remove().unwrap();
+    }
+
+    #[tokio::test]
+    async fn memory_state_survives_restarts() {
+        let location = Location::new(PathBuf::from("memory-state"));
+        state_survives_restarts::<MemoryOperator<Counter>>(location.clone()).await;
+        let store = MemoryStore::open(&location);
+        assert_eq!(store.read().unwrap(), Some(b"2".to_vec()));
+        // states aren't shared with other settings
+        let other = MemoryStore::open(&Location::new(PathBuf::from("memory-state")));
+        assert_eq!(other.read().unwrap(), None);
+    }
+
+    #[cfg(feature = "state-bincode")]
+    #[tokio::test]
+    async fn bincode_file_state_survives_restarts() {
+        use crate::services::state::operators::BincodeFileOperator;
+        let dir = EphemeralDir::create().unwrap();
+        state_survives_restarts::<BincodeFileOperator<Counter>>(Location::new(
+            dir.path().join("state"),
+        ))
+        .await;
+        dir.remove().unwrap();
+    }
+
+    #[cfg(feature = "state-sled")]
+    #[tokio::test]
+    async fn sled_state_survives_restarts() {
+        use crate::services::state::operators::SledOperator;
+        let dir = EphemeralDir::create().unwrap();
+        state_survives_restarts::<SledOperator<Counter>>(Location::new(dir.path().join("sled")))
+            .await;
+        dir.remove().unwrap();
+    }
+}
@@ -4,53 +4,150 @@ use std::marker::PhantomData;
 use std::sync::Arc;
 use std::time::Duration;
 // crates
-use crate::services::{ServiceData, ServiceId};
+use crate::overwatch::events::{EventsSender, LifecycleEvent};
+use crate::overwatch::history::{History, HistoryEvent};
+use crate::services::persistent_metrics::ServiceCounters;
+use crate::services::{ServiceData, ServiceId, TRACING_TARGET};
 use thiserror::Error;
 use tokio::sync::watch;
+use tracing::debug;
 // internal
 
 #[derive(Error, Debug)]
 pub enum ServiceStatusError {
     #[error("service {service_id} is not available")]
     Unavailable { service_id: ServiceId },
+    #[error("service {service_id} is disabled")]
+    ServiceDisabled { service_id: ServiceId },
+    #[error("status watcher request for service {service_id} timed out")]
+    Timeout { service_id: ServiceId },
+    #[error("overwatch dropped the status watcher request for service {service_id}")]
+    Disconnected { service_id: ServiceId },
 }
 
 pub type ServiceStatusResult = Result<StatusWatcher, ServiceStatusError>;
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum ServiceStatus {
+    /// The service was never started
     Uninitialized,
     Running,
+    /// The service is running with reduced capabilities, e.g. while one of its dependencies is
+    /// unreachable, it still counts as started
+    Degraded,
+    /// The service is still running but dropped its inbound relay, so messages can't reach it
+    Detached,
     Stopped,
+    /// The service main loop ended and it is waiting to be started again, see
+    /// [`RestartPolicy`](crate::services::supervisor::RestartPolicy)
+    Restarting,
+    /// The service couldn't be started, its initialization failed or panicked, see
+    /// [`PanicPolicy`](crate::services::context::PanicPolicy), or its main loop panicked and it
+    /// isn't restarted, with the reason why
+    /// Services can report themselves failed as well, with [`StatusUpdater::failed`].
+    Failed(String),
 }
 
-pub struct StatusUpdater(watch::Sender<ServiceStatus>);
+impl ServiceStatus {
+    /// Whether the service reported being started, even if degraded
+    pub fn is_up(&self) -> bool {
+        matches!(self, Self::Running | Self::Degraded)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+}
+
+pub struct StatusUpdater {
+    sender: watch::Sender<ServiceStatus>,
+    /// Where status transitions are recorded, if enabled
+    history: Option<(ServiceId, History)>,
+    /// Where the last time the service was running is kept, if enabled
+    counters: Option<Arc<ServiceCounters>>,
+    /// Where the service starting and stopping is published, if overwatch runs it
+    events: Option<(ServiceId, EventsSender)>,
+}
 
 impl StatusUpdater {
     pub fn update(&self, status: ServiceStatus) {
-        self.0
+        debug!(target: TRACING_TARGET, ?status, "Service status updated");
+        if let Some((service_id, history)) = &self.history {
+            history.record(service_id, HistoryEvent::Status(status.clone()));
+        }
+        if let (true, Some(counters)) = (status.is_up(), &self.counters) {
+            counters.record_ready();
+        }
+        if let Some((service_id, events)) = &self.events {
+            let service_id = *service_id;
+            let previous = self.sender.borrow().clone();
+            // going from running to degraded and back isn't starting again
+            let event = match status {
+                ServiceStatus::Running | ServiceStatus::Degraded if !previous.is_up() => {
+                    Some(LifecycleEvent::ServiceStarted { service_id })
+                }
+                ServiceStatus::Stopped if previous != ServiceStatus::Stopped => {
+                    Some(LifecycleEvent::ServiceStopped { service_id })
+                }
+                _ => None,
+            };
+            if let Some(event) = event {
+                events.report_lifecycle(event);
+            }
+        }
+        self.sender
             .send(status)
             .expect("Overwatch always maintain an open watcher, send should always succeed")
     }
+
+    pub fn running(&self) {
+        self.update(ServiceStatus::Running);
+    }
+
+    /// Report the service keeps running with reduced capabilities, see [`ServiceStatus::Degraded`]
+    pub fn degraded(&self) {
+        self.update(ServiceStatus::Degraded);
+    }
+
+    /// Report the service can't work anymore, because of `reason`
+    pub fn failed(&self, reason: impl Into<String>) {
+        self.update(ServiceStatus::Failed(reason.into()));
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct StatusWatcher(watch::Receiver<ServiceStatus>);
 
 impl StatusWatcher {
+    /// Latest reported status
+    pub fn current(&self) -> ServiceStatus {
+        self.0.borrow().clone()
+    }
+
     pub async fn wait_for(
         &mut self,
         status: ServiceStatus,
         timeout_duration: Option<Duration>,
     ) -> Result<ServiceStatus, ServiceStatus> {
-        let current = *self.0.borrow();
-        if status == current {
+        self.wait_until(|current| current == &status, timeout_duration)
+            .await
+    }
+
+    /// Same as [`StatusWatcher::wait_for`] for any status matching `condition`, like
+    /// [`ServiceStatus::is_up`] or [`ServiceStatus::is_failed`]
+    pub async fn wait_until(
+        &mut self,
+        mut condition: impl FnMut(&ServiceStatus) -> bool,
+        timeout_duration: Option<Duration>,
+    ) -> Result<ServiceStatus, ServiceStatus> {
+        let current = self.current();
+        if condition(&current) {
             return Ok(current);
         }
         let timeout_duration = timeout_duration.unwrap_or_else(|| Duration::from_secs(u64::MAX));
-        tokio::time::timeout(timeout_duration, self.0.wait_for(|s| s == &status))
+        tokio::time::timeout(timeout_duration, self.0.wait_for(|s| condition(s)))
             .await
-            .map(|r| r.map(|s| *s).map_err(|_| current))
+            .map(|r| r.map(|s| s.clone()).map_err(|_| current.clone()))
             .unwrap_or(Err(current))
     }
 }
@@ -58,7 +155,8 @@ impl StatusWatcher {
 pub struct StatusHandle<S: ServiceData> {
     updater: Arc<StatusUpdater>,
     watcher: StatusWatcher,
-    _phantom: PhantomData<S>,
+    // `fn() -> S` so the handle is `Send` regardless of `S`, it never holds an actual `S`
+    _phantom: PhantomData<fn() -> S>,
 }
 
 impl<S: ServiceData> Clone for StatusHandle<S> {
@@ -73,8 +171,37 @@ impl<S: ServiceData> Clone for StatusHandle<S> {
 
 impl<S: ServiceData> StatusHandle<S> {
     pub fn new() -> Self {
-        let (updater, watcher) = watch::channel(ServiceStatus::Uninitialized);
-        let updater = Arc::new(StatusUpdater(updater));
+        Self::with_history(None, None, None)
+    }
+
+    /// Status handle recording its transitions in the overwatch history, and when the service
+    /// was last running in its persisted `counters`, if enabled
+    /// The service starting and stopping is published to the overwatch lifecycle `events`.
+    pub(crate) fn recorded(
+        service_id: ServiceId,
+        history: &History,
+        counters: Option<Arc<ServiceCounters>>,
+        events: &EventsSender,
+    ) -> Self {
+        Self::with_history(
+            history.is_enabled().then(|| (service_id, history.clone())),
+            counters,
+            Some((service_id, events.clone())),
+        )
+    }
+
+    fn with_history(
+        history: Option<(ServiceId, History)>,
+        counters: Option<Arc<ServiceCounters>>,
+        events: Option<(ServiceId, EventsSender)>,
+    ) -> Self {
+        let (sender, watcher) = watch::channel(ServiceStatus::Uninitialized);
+        let updater = Arc::new(StatusUpdater {
+            sender,
+            history,
+            counters,
+            events,
+        });
         let watcher = StatusWatcher(watcher);
         Self {
             updater,
@@ -0,0 +1,131 @@
+// std
+use std::pin::Pin;
+use std::task::{Context, Poll};
+// crates
+use futures::Stream;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+// internal
+use crate::services::relay::RelayError;
+
+/// Number of responses buffered by a stream opened with
+/// [`OutboundRelay::open_stream`](crate::services::relay::OutboundRelay::open_stream)
+pub const STREAM_BUFFER_SIZE: usize = 16;
+
+/// Request answered with a stream of responses, sent by
+/// [`OutboundRelay::open_stream`](crate::services::relay::OutboundRelay::open_stream)
+///
+/// The service message type wraps it, e.g. `Subscribe(StreamRequest<Topic, Event>)` with a
+/// `From` implementation, instead of carrying its own response sender.
+#[derive(Debug)]
+pub struct StreamRequest<Req, Resp> {
+    pub request: Req,
+    pub responses: ResponseSink<Resp>,
+}
+
+impl<Req, Resp> StreamRequest<Req, Resp> {
+    pub(crate) fn new(request: Req) -> (Self, ResponseStream<Resp>) {
+        let (sender, receiver) = channel(STREAM_BUFFER_SIZE);
+        (
+            Self {
+                request,
+                responses: ResponseSink(sender),
+            },
+            ResponseStream(receiver),
+        )
+    }
+}
+
+/// Sending half of a response stream, held by the service answering the request
+/// Dropping it ends the stream on the requester side.
+#[derive(Debug)]
+pub struct ResponseSink<Resp>(Sender<Resp>);
+
+impl<Resp> ResponseSink<Resp> {
+    /// Send a response, fails with [`RelayError::Disconnected`] once the requester dropped
+    /// the stream
+    pub async fn send(&self, response: Resp) -> Result<(), (RelayError, Resp)> {
+        self.0
+            .send(response)
+            .await
+            .map_err(|e| (RelayError::Disconnected, e.0))
+    }
+
+    /// Completes once the requester dropped the stream, to stop producing responses
+    pub async fn closed(&self) {
+        self.0.closed().await;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+/// Receiving half of a response stream, ending once the answering service drops its
+/// [`ResponseSink`]
+#[derive(Debug)]
+pub struct ResponseStream<Resp>(Receiver<Resp>);
+
+impl<Resp> ResponseStream<Resp> {
+    pub async fn recv(&mut self) -> Option<Resp> {
+        self.0.recv().await
+    }
+}
+
+impl<Resp> Stream for ResponseStream<Resp> {
+    type Item = Resp;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::relay::{relay, RelayError};
+    use crate::services::stream::StreamRequest;
+    use futures::StreamExt;
+
+    #[derive(Debug)]
+    enum Message {
+        Subscribe(StreamRequest<u32, u32>),
+    }
+
+    impl From<StreamRequest<u32, u32>> for Message {
+        fn from(request: StreamRequest<u32, u32>) -> Self {
+            Self::Subscribe(request)
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_ends_when_either_side_drops() {
+        let (mut inbound, outbound) = relay::<Message>(4);
+
+        let mut responses = outbound.open_stream::<u32, u32>(3).await.unwrap();
+        let Some(Message::Subscribe(StreamRequest {
+            request,
+            responses: sink,
+        })) = inbound.recv().await
+        else {
+            panic!("expected a subscription");
+        };
+        for response in 0..request {
+            sink.send(response).await.unwrap();
+        }
+        drop(sink);
+        assert_eq!(responses.by_ref().collect::<Vec<_>>().await, [0, 1, 2]);
+
+        let responses = outbound.open_stream::<u32, u32>(1).await.unwrap();
+        let Some(Message::Subscribe(StreamRequest {
+            responses: sink, ..
+        })) = inbound.recv().await
+        else {
+            panic!("expected a subscription");
+        };
+        drop(responses);
+        sink.closed().await;
+        assert!(matches!(
+            sink.send(0).await,
+            Err((RelayError::Disconnected, 0))
+        ));
+    }
+}
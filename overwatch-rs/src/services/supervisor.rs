@@ -0,0 +1,272 @@
+// std
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+// crates
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+// internal
+use crate::overwatch::commands::ServiceAction;
+use crate::overwatch::handle::OverwatchHandle;
+use crate::services::status::{ServiceStatus, StatusWatcher};
+use crate::services::{ServiceId, TRACING_TARGET};
+use crate::utils::runtime::spawn_checked;
+
+/// Delays between restarts of a supervised service
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Backoff {
+    /// Delay before the first restart
+    pub initial: Duration,
+    /// Factor the delay is multiplied by after each restart
+    pub factor: u32,
+    /// Upper bound of the delay
+    pub max_delay: Duration,
+    /// Consecutive restarts after which the service is left stopped, unlimited if `None`
+    pub max_retries: Option<usize>,
+    /// Time a restarted service has to stay up for its previous restarts to be forgotten
+    pub stable_after: Duration,
+}
+
+impl Backoff {
+    /// Same `delay` before every restart
+    pub const fn fixed(delay: Duration) -> Self {
+        Self {
+            initial: delay,
+            factor: 1,
+            max_delay: delay,
+            max_retries: None,
+            stable_after: delay,
+        }
+    }
+
+    /// Delay doubling after each restart, from `initial` up to `max_delay`
+    pub const fn exponential(initial: Duration, max_delay: Duration) -> Self {
+        Self {
+            initial,
+            factor: 2,
+            max_delay,
+            max_retries: None,
+            stable_after: max_delay,
+        }
+    }
+
+    pub const fn with_max_retries(self, max_retries: usize) -> Self {
+        Self {
+            max_retries: Some(max_retries),
+            ..self
+        }
+    }
+
+    pub const fn with_stable_after(self, stable_after: Duration) -> Self {
+        Self {
+            stable_after,
+            ..self
+        }
+    }
+
+    /// Delay before the restart following `attempt` previous consecutive ones, `None` once
+    /// retries are exhausted
+    pub fn delay(&self, attempt: usize) -> Option<Duration> {
+        if self
+            .max_retries
+            .is_some_and(|max_retries| attempt >= max_retries)
+        {
+            return None;
+        }
+        let factor = u32::try_from(attempt)
+            .ok()
+            .and_then(|attempt| self.factor.checked_pow(attempt))
+            .unwrap_or(u32::MAX);
+        Some(
+            self.initial
+                .checked_mul(factor)
+                .map_or(self.max_delay, |delay| delay.min(self.max_delay)),
+        )
+    }
+}
+
+/// Whether a service is started again once its main loop ended, see
+/// [`ServiceData::RESTART_POLICY`](crate::services::ServiceData::RESTART_POLICY)
+///
+/// A service waiting to be restarted is
+/// [`ServiceStatus::Restarting`](crate::services::status::ServiceStatus::Restarting).
+/// Consecutive restarts are counted until the service is stopped, or stays up for
+/// [`Backoff::stable_after`], which gives it a fresh [`Backoff`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    /// Restart services returning an error from
+    /// [`ServiceCore::run`](crate::services::ServiceCore::run) or panicking
+    OnFailure(Backoff),
+    /// Restart services whenever their main loop ends, successfully or not
+    Always(Backoff),
+}
+
+impl RestartPolicy {
+    /// Delay before restarting a service that ended, `failed` or not, after `attempt` previous
+    /// consecutive restarts, `None` if it isn't restarted
+    pub fn delay(&self, failed: bool, attempt: usize) -> Option<Duration> {
+        match self {
+            Self::Never => None,
+            Self::OnFailure(backoff) if failed => backoff.delay(attempt),
+            Self::OnFailure(_) => None,
+            Self::Always(backoff) => backoff.delay(attempt),
+        }
+    }
+
+    fn backoff(&self) -> Option<&Backoff> {
+        match self {
+            Self::Never => None,
+            Self::OnFailure(backoff) | Self::Always(backoff) => Some(backoff),
+        }
+    }
+}
+
+/// Restarts the instances of a service as its [`RestartPolicy`] says
+#[derive(Clone, Debug)]
+pub(crate) struct Supervisor {
+    service_id: ServiceId,
+    policy: RestartPolicy,
+    /// Consecutive restarts, shared by every instance of the service
+    restarts: Arc<AtomicUsize>,
+    overwatch_handle: OverwatchHandle,
+}
+
+impl Supervisor {
+    pub(crate) fn new(
+        service_id: ServiceId,
+        policy: RestartPolicy,
+        restarts: Arc<AtomicUsize>,
+        overwatch_handle: OverwatchHandle,
+    ) -> Self {
+        Self {
+            service_id,
+            policy,
+            restarts,
+            overwatch_handle,
+        }
+    }
+
+    /// Delay before restarting the service which just ended, `failed` or not, `None` if it
+    /// isn't restarted
+    /// The restart is counted, it has to be [`Supervisor::schedule`]d right after.
+    pub(crate) fn restart_delay(&self, failed: bool) -> Option<Duration> {
+        if self.overwatch_handle.shutdown_token().is_cancelled() {
+            return None;
+        }
+        let attempt = self.restarts.load(Ordering::Relaxed);
+        let Some(delay) = self.policy.delay(failed, attempt) else {
+            if attempt > 0 {
+                error!(
+                    target: TRACING_TARGET,
+                    "Service {} gave up restarting after {attempt} attempts", self.service_id
+                );
+            }
+            return None;
+        };
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+        info!(
+            target: TRACING_TARGET,
+            "Restarting service {} in {delay:?} (attempt {})",
+            self.service_id,
+            attempt + 1
+        );
+        Some(delay)
+    }
+
+    /// Forget the previous restarts once the instance reported through `status` stays up for
+    /// [`Backoff::stable_after`], until `cancellation` is cancelled
+    pub(crate) fn watch_stability(&self, status: StatusWatcher, cancellation: CancellationToken) {
+        let Some(stable_after) = self.policy.backoff().map(|backoff| backoff.stable_after) else {
+            return;
+        };
+        let service_id = self.service_id;
+        let restarts = Arc::clone(&self.restarts);
+        let clock = Arc::clone(&self.overwatch_handle.context_config().clock);
+        let stable = async move {
+            let mut status = status;
+            loop {
+                if status.wait_until(ServiceStatus::is_up, None).await.is_err() {
+                    return;
+                }
+                let down = status.wait_until(|status| !status.is_up(), None);
+                if clock.timeout(stable_after, down).await.is_err() {
+                    restarts.store(0, Ordering::Relaxed);
+                    return;
+                }
+            }
+        };
+        if let Err(e) = spawn_checked(
+            self.overwatch_handle.runtime(),
+            &format!("{service_id}:supervisor"),
+            cancellation.run_until_cancelled_owned(stable),
+        ) {
+            error!(target: TRACING_TARGET, "Service {service_id} stability isn't watched: {e}");
+        }
+    }
+
+    /// Restart the service once `delay` elapsed
+    /// The restart is skipped if the service is stopped or started meanwhile.
+    pub(crate) fn schedule(&self, delay: Duration) {
+        let service_id = self.service_id;
+        let overwatch_handle = self.overwatch_handle.clone();
+        let shutdown = overwatch_handle.shutdown_token();
+        let clock = Arc::clone(&overwatch_handle.context_config().clock);
+        let restart = async move {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = clock.sleep(delay) => {}
+            }
+            if let Err(e) = overwatch_handle
+                .control_service(service_id, ServiceAction::Restart)
+                .await
+            {
+                error!(target: TRACING_TARGET, "Service {service_id} couldn't be restarted: {e}");
+            }
+        };
+        if let Err(e) = spawn_checked(
+            self.overwatch_handle.runtime(),
+            &format!("{service_id}:restart"),
+            restart,
+        ) {
+            error!(target: TRACING_TARGET, "Service {service_id} couldn't be restarted: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::supervisor::{Backoff, RestartPolicy};
+    use std::time::Duration;
+
+    #[test]
+    fn backoff_grows_up_to_its_bounds() {
+        let backoff = Backoff::exponential(Duration::from_millis(10), Duration::from_millis(50))
+            .with_max_retries(4);
+        let delays: Vec<_> = (0..5).map(|attempt| backoff.delay(attempt)).collect();
+        assert_eq!(
+            delays,
+            [
+                Some(Duration::from_millis(10)),
+                Some(Duration::from_millis(20)),
+                Some(Duration::from_millis(40)),
+                Some(Duration::from_millis(50)),
+                None,
+            ]
+        );
+        assert_eq!(
+            Backoff::fixed(Duration::from_secs(1)).delay(usize::MAX),
+            Some(Duration::from_secs(1))
+        );
+
+        let on_failure = RestartPolicy::OnFailure(backoff);
+        assert_eq!(on_failure.delay(false, 0), None);
+        assert_eq!(on_failure.delay(true, 0), Some(Duration::from_millis(10)));
+        assert_eq!(
+            RestartPolicy::Always(backoff).delay(false, 1),
+            backoff.delay(1)
+        );
+        assert_eq!(RestartPolicy::Never.delay(true, 0), None);
+    }
+}
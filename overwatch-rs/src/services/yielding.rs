@@ -0,0 +1,57 @@
+//! Cooperative yielding for services handling many messages in a row
+//! Services share the runtime, a service going through a large batch without awaiting anything
+//! pending keeps its worker thread busy and starves the others. Counting the handled messages,
+//! or any other unit of work, against a [`YieldBudget`] tells when to give the executor a chance
+//! to run other tasks.
+// std
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Units of work done between yields to the executor when not set in the
+/// [`ContextConfig`](crate::services::context::ContextConfig)
+pub const DEFAULT_YIELD_BUDGET: usize = 128;
+
+/// Counter of units of work telling when to yield to the executor, a budget of 0 never yields
+#[derive(Debug, Default)]
+pub struct YieldBudget {
+    budget: usize,
+    used: AtomicUsize,
+}
+
+impl YieldBudget {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Count a unit of work, returns `true` once every `budget` units
+    pub fn consume(&self) -> bool {
+        if self.budget == 0 {
+            return false;
+        }
+        let used = self.used.fetch_add(1, Ordering::Relaxed) + 1;
+        used % self.budget == 0
+    }
+
+    /// Count a unit of work, yielding to the executor if the budget is exhausted
+    pub async fn maybe_yield(&self) {
+        if self.consume() {
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::yielding::YieldBudget;
+
+    #[test]
+    fn budget_is_exhausted_periodically() {
+        let budget = YieldBudget::new(3);
+        let exhausted: Vec<_> = (0..7).map(|_| budget.consume()).collect();
+        assert_eq!(exhausted, [false, false, true, false, false, true, false]);
+        let unlimited = YieldBudget::new(0);
+        assert!((0..10).all(|_| !unlimited.consume()));
+    }
+}
@@ -0,0 +1,90 @@
+//! Files replaced atomically, written to a staging sibling renamed over them afterwards
+
+// std
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Replace the content of the file at `path`, `durable` writes are synced to disk along with
+/// the rename
+#[cfg(any(
+    feature = "checkpoint",
+    feature = "persistent-metrics",
+    feature = "state-storage"
+))]
+pub(crate) fn write(path: &Path, content: &[u8], durable: bool) -> io::Result<()> {
+    let staging = staging_path(path)?;
+    let written = write_staging(&staging, content, durable).and_then(|()| {
+        std::fs::rename(&staging, path)?;
+        if durable {
+            sync_directory(path)?;
+        }
+        Ok(())
+    });
+    if written.is_err() {
+        let _ = std::fs::remove_file(&staging);
+    }
+    written
+}
+
+/// Hidden sibling of `path`, unique so concurrent writes to files sharing a stem don't clobber
+/// each other
+pub(crate) fn staging_path(path: &Path) -> io::Result<PathBuf> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file", path.display()),
+        )
+    })?;
+    Ok(path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )))
+}
+
+#[cfg(any(
+    feature = "checkpoint",
+    feature = "persistent-metrics",
+    feature = "state-storage"
+))]
+fn write_staging(path: &Path, content: &[u8], durable: bool) -> io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    io::Write::write_all(&mut &file, content)?;
+    if durable {
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Make the rename of `path` itself durable
+#[cfg(all(
+    unix,
+    any(
+        feature = "checkpoint",
+        feature = "persistent-metrics",
+        feature = "state-storage"
+    )
+))]
+fn sync_directory(path: &Path) -> io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::File::open(directory)?.sync_all()
+}
+
+/// Directories can't be opened to be synced outside of unix
+#[cfg(all(
+    not(unix),
+    any(
+        feature = "checkpoint",
+        feature = "persistent-metrics",
+        feature = "state-storage"
+    )
+))]
+fn sync_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
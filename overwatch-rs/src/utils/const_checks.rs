@@ -19,9 +19,23 @@ pub const fn unique_ids(to_check: &[ServiceId]) -> bool {
     true
 }
 
+/// Whether `id` is free of the `.` separating the namespace of bundled services from their id and
+/// of the `#` separating the id of pool members from their index
+pub const fn unnamespaced(id: ServiceId) -> bool {
+    let bytes = id.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'.' || bytes[i] == b'#' {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 #[cfg(test)]
 mod test {
-    use crate::utils::const_checks::unique_ids;
+    use crate::utils::const_checks::{unique_ids, unnamespaced};
 
     #[test]
     fn test_unique_ids() {
@@ -32,4 +46,11 @@ mod test {
         const _: () = assert!(unique_ids(&[]));
         const _: () = assert!(unique_ids(&["A"]));
     }
+
+    #[test]
+    fn test_unnamespaced() {
+        const _: () = assert!(unnamespaced("store"));
+        const _: () = assert!(!unnamespaced("core.store"));
+        const _: () = assert!(!unnamespaced("worker#0"));
+    }
 }
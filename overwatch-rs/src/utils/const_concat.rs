@@ -0,0 +1,157 @@
+/// Number of items in all `parts`, the length of their [`concat`]
+pub const fn concat_len<T>(parts: &[&[T]]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        len += parts[i].len();
+        i += 1;
+    }
+    len
+}
+
+/// Items of all `parts` one after the other, `N` being their [`concat_len`]
+/// `fill` is only a placeholder while building the array, it is overwritten.
+pub const fn concat<T: Copy, const N: usize>(parts: &[&[T]], fill: T) -> [T; N] {
+    let mut items = [fill; N];
+    let mut index = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        let mut j = 0;
+        while j < parts[i].len() {
+            items[index] = parts[i][j];
+            index += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    assert!(index == N, "concatenated length must be the parts length");
+    items
+}
+
+/// Number of bytes of all `items` prefixed with `namespace.`, the length of their
+/// [`prefixed_bytes`]
+pub const fn prefixed_len(namespace: &str, items: &[&str]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < items.len() {
+        len += namespace.len() + 1 + items[i].len();
+        i += 1;
+    }
+    len
+}
+
+/// Bytes of all `items` prefixed with `namespace.` one after the other, `N` being their
+/// [`prefixed_len`]
+pub const fn prefixed_bytes<const N: usize>(namespace: &str, items: &[&str]) -> [u8; N] {
+    let mut bytes = [0; N];
+    let mut index = 0;
+    let mut i = 0;
+    while i < items.len() {
+        let namespace = namespace.as_bytes();
+        let mut j = 0;
+        while j < namespace.len() {
+            bytes[index] = namespace[j];
+            index += 1;
+            j += 1;
+        }
+        bytes[index] = b'.';
+        index += 1;
+        let item = items[i].as_bytes();
+        let mut j = 0;
+        while j < item.len() {
+            bytes[index] = item[j];
+            index += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    assert!(index == N, "prefixed length must be the items length");
+    bytes
+}
+
+/// `items` prefixed with `namespace.`, read from their [`prefixed_bytes`], `N` being the number
+/// of `items`
+pub const fn prefixed<const N: usize>(
+    bytes: &'static [u8],
+    namespace: &str,
+    items: &[&str],
+) -> [&'static str; N] {
+    let mut prefixed = [""; N];
+    let mut rest = bytes;
+    let mut i = 0;
+    while i < N {
+        let (item, next) = rest.split_at(namespace.len() + 1 + items[i].len());
+        prefixed[i] = match core::str::from_utf8(item) {
+            Ok(item) => item,
+            Err(_) => panic!("prefixed items must be split at their boundaries"),
+        };
+        rest = next;
+        i += 1;
+    }
+    prefixed
+}
+
+/// `items` with the id of item `i` replaced by `ids[i]`
+/// `fill` is only a placeholder while building the array, it is overwritten.
+pub const fn with_ids<T: Copy, const N: usize>(
+    items: &[(&'static str, T)],
+    ids: &[&'static str],
+    fill: T,
+) -> [(&'static str, T); N] {
+    assert!(
+        items.len() == N && ids.len() == N,
+        "each item must have an id"
+    );
+    let mut replaced = [("", fill); N];
+    let mut i = 0;
+    while i < N {
+        replaced[i] = (ids[i], items[i].1);
+        i += 1;
+    }
+    replaced
+}
+
+/// Items of `ids` at the position of each of the `selected` items in `all`
+pub const fn select<const N: usize>(
+    selected: &[&str],
+    all: &[&str],
+    ids: &[&'static str],
+) -> [&'static str; N] {
+    let mut picked = [""; N];
+    let mut i = 0;
+    while i < N {
+        let mut j = 0;
+        while !const_str::equal!(selected[i], all[j]) {
+            j += 1;
+        }
+        picked[i] = ids[j];
+        i += 1;
+    }
+    picked
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::const_concat::{
+        concat, concat_len, prefixed, prefixed_bytes, prefixed_len, select, with_ids,
+    };
+
+    #[test]
+    fn slices_are_concatenated_at_compile_time() {
+        const PARTS: &[&[&str]] = &[&["a", "b"], &[], &["c"]];
+        const ITEMS: [&str; concat_len(PARTS)] = concat(PARTS, "");
+        assert_eq!(ITEMS, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn slices_are_prefixed_at_compile_time() {
+        const ITEMS: &[&str] = &["a", "bc"];
+        const BYTES: [u8; prefixed_len("ns", ITEMS)] = prefixed_bytes("ns", ITEMS);
+        const PREFIXED: [&str; ITEMS.len()] = prefixed(&BYTES, "ns", ITEMS);
+        assert_eq!(PREFIXED, ["ns.a", "ns.bc"]);
+        const SIZES: [(&str, usize); 2] = with_ids(&[("a", 1), ("bc", 2)], &PREFIXED, 0);
+        assert_eq!(SIZES, [("ns.a", 1), ("ns.bc", 2)]);
+        const SELECTED: [&str; 1] = select(&["bc"], ITEMS, &PREFIXED);
+        assert_eq!(SELECTED, ["ns.bc"]);
+    }
+}
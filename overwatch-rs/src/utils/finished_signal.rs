@@ -0,0 +1,199 @@
+//! Completion signal sent once some work (a service shutdown, the runner execution...) finished
+//! Sending is idempotent: the first send completes the signal and the following ones are no-ops,
+//! counted as duplicates instead of failing, so paths racing to report the same completion
+//! don't need to coordinate. Receivers can be cloned, each of them sees the completion.
+// std
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+// crates
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing::debug;
+// internal
+use crate::services::clock::Clock;
+use crate::services::context::MetricsRecorder;
+use crate::services::ServiceId;
+
+/// Counter incremented each time a finished signal is sent again, see [`Sender::with_metrics`]
+pub const DUPLICATES_METRIC: &str = "overwatch_finished_signal_duplicates";
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    #[error("finished signal not sent yet")]
+    Pending,
+    #[error("finished signal senders dropped without sending it")]
+    Dropped,
+    #[error("finished signal not sent within {0:?}")]
+    Elapsed(Duration),
+}
+
+/// Create a finished signal, not sent yet
+pub fn channel() -> (Sender, Receiver) {
+    let (finished, receiver) = watch::channel(false);
+    let duplicates = Arc::new(AtomicU64::new(0));
+    let sender = Sender {
+        finished: Arc::new(finished),
+        duplicates: Arc::clone(&duplicates),
+        metrics: None,
+    };
+    let receiver = Receiver {
+        finished: receiver,
+        duplicates,
+    };
+    (sender, receiver)
+}
+
+/// Sending side of a finished signal, clones send the same signal
+#[derive(Clone)]
+pub struct Sender {
+    finished: Arc<watch::Sender<bool>>,
+    duplicates: Arc<AtomicU64>,
+    metrics: Option<(Arc<dyn MetricsRecorder>, ServiceId)>,
+}
+
+impl Debug for Sender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender")
+            .field("finished", &*self.finished.borrow())
+            .field("duplicates", &self.duplicates.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Sender {
+    /// Report duplicate sends to `metrics` as [`DUPLICATES_METRIC`], attributed to `service_id`
+    pub fn with_metrics(self, metrics: Arc<dyn MetricsRecorder>, service_id: ServiceId) -> Self {
+        Self {
+            metrics: Some((metrics, service_id)),
+            ..self
+        }
+    }
+
+    /// Signal the completion, returns `false` if it was already signaled
+    /// Nobody waiting for the signal is fine, the completion is kept for receivers polling it.
+    pub fn send(&self) -> bool {
+        if self
+            .finished
+            .send_if_modified(|finished| !std::mem::replace(finished, true))
+        {
+            return true;
+        }
+        self.duplicates.fetch_add(1, Ordering::Relaxed);
+        debug!("Finished signal sent again, ignoring it");
+        if let Some((metrics, service_id)) = &self.metrics {
+            metrics.increment_counter(service_id, DUPLICATES_METRIC, 1);
+        }
+        false
+    }
+
+    pub fn is_sent(&self) -> bool {
+        *self.finished.borrow()
+    }
+
+    /// Number of sends ignored because the signal was already sent
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+
+    pub fn subscribe(&self) -> Receiver {
+        Receiver {
+            finished: self.finished.subscribe(),
+            duplicates: Arc::clone(&self.duplicates),
+        }
+    }
+}
+
+/// Receiving side of a finished signal
+#[derive(Clone, Debug)]
+pub struct Receiver {
+    finished: watch::Receiver<bool>,
+    duplicates: Arc<AtomicU64>,
+}
+
+impl Receiver {
+    /// Wait for the signal, failing with [`RecvError::Dropped`] if every sender is dropped before
+    /// sending it
+    pub async fn recv(&mut self) -> Result<(), RecvError> {
+        self.finished
+            .wait_for(|finished| *finished)
+            .await
+            .map(|_| ())
+            .map_err(|_| RecvError::Dropped)
+    }
+
+    /// Check the signal without waiting for it
+    pub fn try_recv(&self) -> Result<(), RecvError> {
+        if *self.finished.borrow() {
+            Ok(())
+        } else if self.finished.has_changed().is_err() {
+            Err(RecvError::Dropped)
+        } else {
+            Err(RecvError::Pending)
+        }
+    }
+
+    /// Same as [`Receiver::recv`] but failing with [`RecvError::Elapsed`] if the signal isn't sent
+    /// within `timeout` on `clock`
+    pub async fn recv_timeout(
+        &mut self,
+        clock: &dyn Clock,
+        timeout: Duration,
+    ) -> Result<(), RecvError> {
+        clock
+            .timeout(timeout, self.recv())
+            .await
+            .map_err(|_| RecvError::Elapsed(timeout))?
+    }
+
+    /// Number of sends ignored because the signal was already sent
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::clock::{Clock, ManualClock};
+    use crate::utils::finished_signal::{channel, RecvError};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn sending_again_is_a_counted_no_op() {
+        let (sender, mut receiver) = channel();
+        assert_eq!(receiver.try_recv(), Err(RecvError::Pending));
+        assert!(sender.send());
+        assert!(!sender.clone().send());
+        assert_eq!(receiver.duplicates(), 1);
+        receiver.recv().await.unwrap();
+        // the completion outlives the senders
+        drop(sender);
+        assert_eq!(receiver.try_recv(), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn receivers_know_when_signal_will_never_come() {
+        let (sender, mut receiver) = channel();
+        drop(sender);
+        assert_eq!(receiver.try_recv(), Err(RecvError::Dropped));
+        assert_eq!(receiver.recv().await, Err(RecvError::Dropped));
+
+        let (_sender, mut receiver) = channel();
+        let clock = ManualClock::new();
+        let waiting = tokio::spawn({
+            let clock = clock.clone();
+            async move {
+                receiver
+                    .recv_timeout(&clock as &dyn Clock, Duration::from_secs(1))
+                    .await
+            }
+        });
+        tokio::task::yield_now().await;
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(
+            waiting.await.unwrap(),
+            Err(RecvError::Elapsed(Duration::from_secs(1)))
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Instrumentation of the code generated by `overwatch-derive`
+//! The derive crate wraps the methods it generates in [`instrumented!`], which expands according
+//! to the `instrumentation` feature of this crate. Spans are then emitted based on the feature the
+//! application enabled on `overwatch-rs`, whatever the features of `overwatch-derive` are.
+
+/// Annotate `$item` with `#[tracing::instrument($args)]` with the `instrumentation` feature,
+/// leave it untouched otherwise
+#[cfg(feature = "instrumentation")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __instrumented {
+    (($($args:tt)*) $item:item) => {
+        #[tracing::instrument($($args)*)]
+        $item
+    };
+}
+
+/// Annotate `$item` with `#[tracing::instrument($args)]` with the `instrumentation` feature,
+/// leave it untouched otherwise
+#[cfg(not(feature = "instrumentation"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __instrumented {
+    (($($args:tt)*) $item:item) => {
+        $item
+    };
+}
+
+pub use crate::__instrumented as instrumented;
+
+#[cfg(test)]
+mod test {
+    use crate::utils::instrumentation::instrumented;
+
+    struct Counter(usize);
+
+    impl Counter {
+        instrumented! {
+            (skip(self), err)
+            fn increment(&mut self, by: usize) -> Result<usize, String> {
+                self.0 += by;
+                Ok(self.0)
+            }
+        }
+    }
+
+    #[test]
+    fn instrumented_items_keep_their_behavior() {
+        let mut counter = Counter(1);
+        assert_eq!(counter.increment(2), Ok(3));
+    }
+}
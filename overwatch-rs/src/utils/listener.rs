@@ -0,0 +1,124 @@
+//! Listeners of the servers taking connections from other processes, like the
+//! [`control`](crate::overwatch::control) plane and remote relays
+
+// std
+use std::io;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+// crates
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+// internal
+#[cfg(unix)]
+use crate::utils::atomic_file;
+
+/// Where a [`Listener`] listens, each server has its own public address type converted to it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ListenAddress {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Connection accepted by a [`Listener`]
+pub(crate) trait Connection: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + 'static> Connection for T {}
+
+pub(crate) enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, SocketFile),
+}
+
+impl Listener {
+    /// Listen on `address`
+    /// A Unix socket left by a previous run is replaced, but binding fails if another process
+    /// still listens on it or if something else than a socket is there. The socket is only
+    /// accessible to the current user, and removed once the listener is dropped.
+    pub(crate) async fn bind(address: ListenAddress) -> io::Result<Self> {
+        match address {
+            ListenAddress::Tcp(address) => Ok(Self::Tcp(TcpListener::bind(address).await?)),
+            #[cfg(unix)]
+            ListenAddress::Unix(path) => {
+                remove_stale_socket(&path).await?;
+                let listener = bind_private(&path)?;
+                Ok(Self::Unix(listener, SocketFile(path)))
+            }
+        }
+    }
+
+    /// Address actually listened on, with the port picked when asked to listen on port 0
+    pub(crate) fn address(&self) -> io::Result<ListenAddress> {
+        match self {
+            Self::Tcp(listener) => Ok(ListenAddress::Tcp(listener.local_addr()?)),
+            #[cfg(unix)]
+            Self::Unix(_, socket) => Ok(ListenAddress::Unix(socket.0.clone())),
+        }
+    }
+
+    pub(crate) async fn accept(&self) -> io::Result<Box<dyn Connection>> {
+        match self {
+            Self::Tcp(listener) => Ok(Box::new(listener.accept().await?.0)),
+            #[cfg(unix)]
+            Self::Unix(listener, _) => Ok(Box::new(listener.accept().await?.0)),
+        }
+    }
+}
+
+/// Socket file of a Unix listener, removed when dropped
+#[cfg(unix)]
+pub(crate) struct SocketFile(PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Bind a socket at `path` only the current user can connect to
+/// The socket is bound in a private staging directory and made private there, then linked to
+/// `path`, so it is never reachable with wider permissions. Linking fails if something took
+/// `path` in the meantime.
+#[cfg(unix)]
+fn bind_private(path: &Path) -> io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    let staging = atomic_file::staging_path(path)?;
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let bound = {
+        let socket = SocketFile(staging.join("socket"));
+        tokio::net::UnixListener::bind(&socket.0).and_then(|listener| {
+            std::fs::set_permissions(&socket.0, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::hard_link(&socket.0, path)?;
+            Ok(listener)
+        })
+    };
+    let _ = std::fs::remove_dir(&staging);
+    bound
+}
+
+/// Remove the socket a previous run left at `path`, if any
+#[cfg(unix)]
+async fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+    if tokio::net::UnixStream::connect(path).await.is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is listened on by another process", path.display()),
+        ));
+    }
+    std::fs::remove_file(path)
+}
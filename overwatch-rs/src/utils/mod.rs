@@ -1,2 +1,15 @@
+#[cfg(any(
+    feature = "checkpoint",
+    feature = "persistent-metrics",
+    feature = "state-storage",
+    all(unix, any(feature = "cli", feature = "remote"))
+))]
+pub(crate) mod atomic_file;
 pub mod const_checks;
+pub mod const_concat;
+pub mod finished_signal;
+pub mod instrumentation;
+#[cfg(any(feature = "cli", feature = "remote"))]
+pub(crate) mod listener;
 pub mod runtime;
+mod services_settings;
@@ -1,4 +1,30 @@
-use crate::overwatch::OVERWATCH_THREAD_NAME;
+// std
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+// crates
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+// internal
+use crate::overwatch::{Error, OVERWATCH_THREAD_NAME};
+
+/// Overwatch own tasks names, service tasks are named after their service id
+pub(crate) const RUNNER_TASK: &str = "overwatch-runner";
+pub(crate) const BOOT_REPORT_TASK: &str = "overwatch-boot-report";
+#[cfg(feature = "checkpoint")]
+pub(crate) const CHECKPOINT_TASK: &str = "overwatch-checkpoint";
+pub(crate) const MEMORY_MONITOR_TASK: &str = "overwatch-memory-monitor";
+#[cfg(feature = "admin-http")]
+pub(crate) const ADMIN_HTTP_TASK: &str = "overwatch-admin-http";
+#[cfg(feature = "cli")]
+pub(crate) const CONTROL_TASK: &str = "overwatch-control";
+#[cfg(feature = "remote")]
+pub(crate) const REMOTE_RELAY_TASK: &str = "overwatch-remote-relay";
+pub(crate) const WATCHDOG_TASK: &str = "overwatch-watchdog";
+pub(crate) const HEALTH_MONITOR_TASK: &str = "overwatch-health-monitor";
+pub(crate) const METRICS_EXPORT_TASK: &str = "overwatch-metrics-export";
+#[cfg(feature = "persistent-metrics")]
+pub(crate) const PERSISTENT_METRICS_TASK: &str = "overwatch-persistent-metrics";
 
 pub fn default_multithread_runtime() -> tokio::runtime::Runtime {
     tokio::runtime::Builder::new_multi_thread()
@@ -7,3 +33,76 @@ pub fn default_multithread_runtime() -> tokio::runtime::Runtime {
         .build()
         .expect("Async runtime to build properly")
 }
+
+/// Spawn a task on `handle`, named `name` in tokio-console
+/// Names are only set with the `tokio-console` feature, on builds with
+/// `RUSTFLAGS="--cfg tokio_unstable"` as tokio requires.
+pub(crate) fn spawn_named<F>(handle: &Handle, name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(feature = "tokio-console", tokio_unstable))]
+    return tokio::task::Builder::new()
+        .name(name)
+        .spawn_on(future, handle)
+        .expect("Named tasks to be spawned like unnamed ones");
+    #[cfg(not(all(feature = "tokio-console", tokio_unstable)))]
+    {
+        let _ = name;
+        handle.spawn(future)
+    }
+}
+
+/// Spawn a task named `name` on `handle`, failing with [`Error::RuntimeUnavailable`] if the
+/// runtime is shutting down
+/// Such a runtime drops new tasks right away instead of running them, which is detected as the
+/// task being finished without ever being polled. The task output is left in the returned handle.
+pub(crate) fn spawn_checked<F>(
+    handle: &Handle,
+    name: &str,
+    future: F,
+) -> Result<JoinHandle<F::Output>, Error>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let polled = Arc::new(AtomicBool::new(false));
+    let task = spawn_named(handle, name, {
+        let polled = Arc::clone(&polled);
+        async move {
+            polled.store(true, Ordering::Release);
+            future.await
+        }
+    });
+    if task.is_finished() && !polled.load(Ordering::Acquire) {
+        return Err(Error::RuntimeUnavailable);
+    }
+    Ok(task)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::overwatch::Error;
+    use crate::utils::runtime::{default_multithread_runtime, spawn_checked};
+    use std::time::Duration;
+
+    #[test]
+    fn finished_tasks_keep_their_output() {
+        let runtime = default_multithread_runtime();
+        let task = spawn_checked(runtime.handle(), "finished", async { 42 }).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(runtime.block_on(task).unwrap(), 42);
+    }
+
+    #[test]
+    fn tasks_are_refused_by_shut_down_runtimes() {
+        let runtime = default_multithread_runtime();
+        let handle = runtime.handle().clone();
+        runtime.shutdown_timeout(Duration::from_secs(1));
+        assert!(matches!(
+            spawn_checked(&handle, "dropped", async {}),
+            Err(Error::RuntimeUnavailable)
+        ));
+    }
+}
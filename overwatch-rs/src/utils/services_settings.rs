@@ -0,0 +1,27 @@
+/// Settings struct generated for the services by `overwatch-derive`
+/// With the `config` feature it implements `serde::Deserialize` and `serde::Serialize` when every
+/// service settings does, under the given `bound`s, see [`crate::overwatch::config`].
+#[cfg(feature = "config")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __services_settings {
+    (bound($($bound:tt)*) $settings:item) => {
+        #[derive(
+            ::std::fmt::Debug,
+            $crate::overwatch::config::serde::Deserialize,
+            $crate::overwatch::config::serde::Serialize,
+        )]
+        #[serde(crate = "::overwatch_rs::overwatch::config::serde", bound($($bound)*))]
+        $settings
+    };
+}
+
+#[cfg(not(feature = "config"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __services_settings {
+    (bound($($bound:tt)*) $settings:item) => {
+        #[derive(::std::fmt::Debug)]
+        $settings
+    };
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "admin-http")]
+
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::admin::OverwatchAdmin;
+use overwatch_rs::overwatch::admin_http::{self, AdminHttp};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct CurrentLevel(oneshot::Sender<u32>);
+
+impl RelayMessage for CurrentLevel {}
+
+/// Answers with its current settings
+struct Logger {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Logger {
+    const SERVICE_ID: ServiceId = "logger";
+    type Settings = u32;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = CurrentLevel;
+}
+
+#[async_trait]
+impl ServiceCore for Logger {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        self.service_state.status_handle.updater().running();
+        while let Some(CurrentLevel(reply)) = self.service_state.inbound_relay.recv().await {
+            let _ = reply.send(self.service_state.settings_reader.get_updated_settings());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    logger: ServiceHandle<Logger>,
+}
+
+/// Status line and body of the response to `method` `path`
+async fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, body.to_owned())
+}
+
+#[test]
+fn services_are_operated_through_the_admin_api() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { logger: 1 }, None).unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.spawn(async move {
+        handle
+            .relay_when_ready::<Logger>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        let admin = OverwatchAdmin::<App>::new(handle.clone());
+        let server = admin_http::serve(admin, "127.0.0.1:0".parse().unwrap(), &handle)
+            .await
+            .unwrap();
+        let address = *server.address();
+
+        assert_eq!(
+            request(address, "GET", "/services", "").await,
+            (200, r#"[{"id":"logger","status":"running"}]"#.to_owned())
+        );
+        assert_eq!(
+            request(address, "POST", "/services/logger/stop", "")
+                .await
+                .0,
+            204
+        );
+        assert_eq!(
+            request(address, "GET", "/services/logger/status", "").await,
+            (200, r#"{"id":"logger","status":"stopped"}"#.to_owned())
+        );
+        assert_eq!(
+            request(address, "POST", "/services/logger/start", "")
+                .await
+                .0,
+            204
+        );
+        let logger = handle
+            .relay_when_ready::<Logger>(Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            request(address, "PUT", "/settings", r#"{"logger": 3}"#)
+                .await
+                .0,
+            204
+        );
+        let (reply, level) = oneshot::channel();
+        logger.send(CurrentLevel(reply)).await.unwrap();
+        assert_eq!(level.await.unwrap(), 3);
+        assert_eq!(
+            request(address, "PUT", "/settings", r#"{"logger": "loud"}"#)
+                .await
+                .0,
+            400
+        );
+        assert_eq!(
+            request(address, "POST", "/services/api/stop", "").await,
+            (404, r#"{"error":"unknown service api"}"#.to_owned())
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn non_loopback_addresses_are_refused_unless_allowed() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { logger: 1 }, None).unwrap();
+    let handle = overwatch.handle().clone();
+    let (refused, allowed) = overwatch.block_on(async {
+        let address = "0.0.0.0:0".parse().unwrap();
+        let admin = OverwatchAdmin::<App>::new(handle.clone());
+        let refused = admin_http::serve(admin, address, &handle).await.is_err();
+        let admin = OverwatchAdmin::<App>::new(handle.clone());
+        let allowed = AdminHttp::new(admin, address)
+            .allow_non_loopback()
+            .serve(&handle)
+            .await
+            .is_ok();
+        (refused, allowed)
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert!(refused);
+    assert!(allowed);
+}
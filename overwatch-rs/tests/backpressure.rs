@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::events::{Backpressure, BackpressureLevel};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{BackpressurePolicy, RelayMessage};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio::sync::Notify;
+
+#[derive(Debug)]
+struct Item;
+
+impl RelayMessage for Item {}
+
+/// Only starts draining its inbound relay once notified
+struct Sink {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Sink {
+    const SERVICE_ID: ServiceId = "sink";
+    const SERVICE_RELAY_BUFFER_SIZE: usize = 4;
+    type Settings = Arc<Notify>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Item;
+}
+
+#[async_trait]
+impl ServiceCore for Sink {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let drain = self.service_state.settings_reader.get_updated_settings();
+        drain.notified().await;
+        while self.service_state.inbound_relay.recv().await.is_some() {}
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct Pipeline {
+    sink: ServiceHandle<Sink>,
+}
+
+#[test]
+fn backpressure_level_changes_are_published() {
+    let drain = Arc::new(Notify::new());
+    let settings = PipelineServiceSettings {
+        sink: drain.clone(),
+    };
+    let overwatch = OverwatchRunner::<Pipeline>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut events = Box::pin(handle.backpressure_events());
+        let relay = handle.relay::<Sink>().connect().await.unwrap();
+        for _ in 0..Sink::SERVICE_RELAY_BUFFER_SIZE {
+            relay.send(Item).await.unwrap();
+        }
+        assert_eq!(relay.queue_depth(), 4);
+        drain.notify_one();
+
+        let mut levels = Vec::new();
+        while levels.last() != Some(&BackpressureLevel::Normal) {
+            let Backpressure(service_id, level) = events.next().await.unwrap();
+            assert_eq!(service_id, "sink");
+            levels.push(level);
+        }
+        assert_eq!(
+            levels,
+            [
+                BackpressureLevel::High,
+                BackpressureLevel::Full,
+                BackpressureLevel::High,
+                BackpressureLevel::Normal
+            ]
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+/// Never reads its inbound relay, dropping what doesn't fit in it
+struct Lossy {
+    _service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Lossy {
+    const SERVICE_ID: ServiceId = "lossy";
+    const SERVICE_RELAY_BUFFER_SIZE: usize = 2;
+    const BACKPRESSURE_POLICY: BackpressurePolicy = BackpressurePolicy::DropNewest;
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Item;
+}
+
+#[async_trait]
+impl ServiceCore for Lossy {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self {
+            _service_state: service_state,
+        })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct LossyPipeline {
+    lossy: ServiceHandle<Lossy>,
+}
+
+#[test]
+fn overflowing_messages_are_dropped_and_counted() {
+    let overwatch =
+        OverwatchRunner::<LossyPipeline>::run(LossyPipelineServiceSettings { lossy: () }, None)
+            .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let relay = handle.relay::<Lossy>().connect().await.unwrap();
+        for _ in 0..5 {
+            // the send never waits for room in the buffer
+            relay.send(Item).await.unwrap();
+        }
+        let lossy = handle.metrics().service("lossy").copied().unwrap();
+        assert_eq!((lossy.relay.sent, lossy.relay.overflowed), (2, 3));
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
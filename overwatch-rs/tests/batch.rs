@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::latency::CommandKind;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+struct Worker<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Worker<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        0 => "first",
+        _ => "second",
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Worker<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct WorkersApp {
+    first: ServiceHandle<Worker<0>>,
+    second: ServiceHandle<Worker<1>>,
+}
+
+#[test]
+fn batch_is_handled_as_a_single_command() {
+    let overwatch = OverwatchRunner::<WorkersApp>::run(
+        WorkersAppServiceSettings {
+            first: (),
+            second: (),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut first = handle.status_watcher::<Worker<0>>().await.unwrap();
+        let mut second = handle.status_watcher::<Worker<1>>().await.unwrap();
+        for watcher in [&mut first, &mut second] {
+            watcher
+                .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+        }
+
+        let batch = handle
+            .batch()
+            .stop::<Worker<0>>()
+            .stop::<Worker<1>>()
+            .update_settings::<WorkersApp>(WorkersAppServiceSettings {
+                first: (),
+                second: (),
+            })
+            .start::<Worker<1>>();
+        assert_eq!(batch.len(), 4);
+        let results = batch.submit().await.unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(Result::is_ok), "{results:?}");
+        assert_eq!(first.current(), ServiceStatus::Stopped);
+        second
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+
+        let latency = handle.command_latency(CommandKind::Batch).unwrap();
+        assert_eq!(latency.samples, 1);
+        assert!(handle.batch().submit().await.unwrap().is_empty());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::pool::ServicePool;
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+const READY: u8 = 0;
+const SILENT: u8 = 1;
+const WORKER: u8 = 2;
+
+/// Reports `Running` right away unless it is the silent one
+struct Idle<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Idle<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        READY => "ready",
+        SILENT => "silent",
+        _ => "worker",
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Idle<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        if ID != SILENT {
+            self.service_state
+                .status_handle
+                .updater()
+                .update(ServiceStatus::Running);
+        }
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    ready: ServiceHandle<Idle<READY>>,
+    silent: ServiceHandle<Idle<SILENT>>,
+    workers: ServicePool<Idle<WORKER>>,
+}
+
+#[test]
+fn boot_report_summarizes_startup() {
+    let settings = AppServiceSettings {
+        ready: (),
+        silent: (),
+        workers: Vec::new(),
+    };
+    let config = ContextConfig::default()
+        .with_config_source("/etc/app.toml")
+        .with_boot_report_timeout(Duration::from_millis(200));
+    let overwatch =
+        OverwatchRunner::<App>::run_with_context_config(settings, None, config).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let report = handle.wait_for_boot_report().await;
+        let ready: Vec<_> = report
+            .started
+            .iter()
+            .map(|boot| (boot.service_id, boot.time_to_ready.is_some()))
+            .collect();
+        assert_eq!(ready, [("ready", true), ("silent", false)]);
+        assert_eq!(report.skipped, ["worker"]);
+        assert_eq!(report.config_sources, ["/etc/app.toml"]);
+        assert!(report.elapsed >= Duration::from_millis(200));
+        assert!(handle.boot_report().is_some());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
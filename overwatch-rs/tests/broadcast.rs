@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::broadcast::BroadcastingService;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{NoMessage, RelayError, RelayMessage};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Sum of the values received by the listener
+static RECEIVED: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+struct Publish(u32);
+
+impl RelayMessage for Publish {}
+
+/// Broadcasts every value it is sent
+struct Publisher {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Publisher {
+    const SERVICE_ID: ServiceId = "publisher";
+    const BROADCAST_REPLAY: usize = 1;
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Publish;
+}
+
+impl BroadcastingService for Publisher {
+    type Broadcast = u32;
+}
+
+#[async_trait]
+impl ServiceCore for Publisher {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let broadcast = self.service_state.broadcast()?;
+        while let Some(Publish(value)) = self.service_state.inbound_relay.recv().await {
+            broadcast.send(value);
+        }
+        Ok(())
+    }
+}
+
+/// Adds up the first two values the publisher broadcasts
+struct Listener {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Listener {
+    const SERVICE_ID: ServiceId = "listener";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Listener {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let mut subscription = self
+            .service_state
+            .overwatch_handle
+            .subscribe::<Publisher>()
+            .await?;
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        for _ in 0..2 {
+            let value = subscription.recv().await.ok_or("publisher is gone")?;
+            RECEIVED.fetch_add(value, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct BroadcastApp {
+    publisher: ServiceHandle<Publisher>,
+    listener: ServiceHandle<Listener>,
+}
+
+#[test]
+fn broadcasts_reach_every_subscriber() {
+    let settings = BroadcastAppServiceSettings {
+        publisher: (),
+        listener: (),
+    };
+    let overwatch = OverwatchRunner::<BroadcastApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut listener = handle.status_watcher::<Listener>().await.unwrap();
+        listener
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let mut subscription = handle.subscribe::<Publisher>().await.unwrap();
+
+        let publisher = handle.relay::<Publisher>().connect().await.unwrap();
+        publisher.send(Publish(1)).await.unwrap();
+        publisher.send(Publish(2)).await.unwrap();
+        assert_eq!(subscription.recv().await, Some(1));
+        assert_eq!(subscription.recv().await, Some(2));
+        listener
+            .wait_for(ServiceStatus::Stopped, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert_eq!(RECEIVED.load(Ordering::SeqCst), 3);
+
+        // late subscribers start from the replayed messages
+        let mut late = handle.subscribe::<Publisher>().await.unwrap();
+        assert_eq!(late.recv().await, Some(2));
+
+        let publisher = handle.broadcast_for("publisher").await.unwrap();
+        assert!(matches!(
+            publisher.relay::<String>(),
+            Err(RelayError::InvalidMessage {
+                service_id: "publisher",
+                ..
+            })
+        ));
+        assert!(matches!(
+            handle.broadcast_for("unknown").await,
+            Err(RelayError::Unavailable {
+                service_id: "unknown"
+            })
+        ));
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -2,7 +2,7 @@ use overwatch_derive::Services;
 use overwatch_rs::overwatch::commands::{OverwatchCommand, ServiceLifeCycleCommand};
 use overwatch_rs::overwatch::OverwatchRunner;
 use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
-use overwatch_rs::services::life_cycle::LifecycleMessage;
+use overwatch_rs::services::life_cycle::{finished_signal, LifecycleMessage, StopReason};
 use overwatch_rs::services::relay::NoMessage;
 use overwatch_rs::services::state::{NoOperator, NoState};
 use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
@@ -39,11 +39,11 @@ impl ServiceCore for CancellableService {
             tokio::select! {
                 msg = lifecycle_stream.next() => {
                     match msg {
-                        Some(LifecycleMessage::Shutdown(reply)) => {
-                            reply.send(()).unwrap();
+                        Some(LifecycleMessage::Shutdown(reply, _)) => {
+                            reply.send();
                             break;
                         }
-                        Some(LifecycleMessage::Kill) => {
+                        Some(LifecycleMessage::Kill(_)) => {
                             break;
                         }
                         _ => {
@@ -70,14 +70,14 @@ fn run_overwatch_then_shutdown_service_and_kill() {
     let settings = CancelableServicesServiceSettings { cancelable: () };
     let overwatch = OverwatchRunner::<CancelableServices>::run(settings, None).unwrap();
     let handle = overwatch.handle().clone();
-    let (sender, mut receiver) = tokio::sync::broadcast::channel(1);
+    let (sender, mut receiver) = finished_signal::channel();
     overwatch.spawn(async move {
         sleep(Duration::from_millis(500)).await;
         handle
             .send(OverwatchCommand::ServiceLifeCycle(
                 ServiceLifeCycleCommand {
                     service_id: <CancellableService as ServiceData>::SERVICE_ID,
-                    msg: LifecycleMessage::Shutdown(sender),
+                    msg: LifecycleMessage::Shutdown(sender, StopReason::Requested),
                 },
             ))
             .await;
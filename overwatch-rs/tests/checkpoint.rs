@@ -0,0 +1,380 @@
+#![cfg(all(feature = "checkpoint", feature = "state-storage"))]
+
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::checkpoint::{Checkpoint, CheckpointError, CHECKPOINT_VERSION};
+use overwatch_rs::overwatch::testing::EphemeralDir;
+use overwatch_rs::overwatch::{Error, OverwatchRunner};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::settings::OptionalSettings;
+use overwatch_rs::services::state::operators::{JsonFileOperator, StorageSettings};
+use overwatch_rs::services::state::{
+    NoOperator, NoState, PersistContext, ServiceState, StateOperator,
+};
+use overwatch_rs::services::status::{ServiceStatus, ServiceStatusError};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CounterSettings {
+    state_path: PathBuf,
+    step: u64,
+}
+
+impl StorageSettings for CounterSettings {
+    fn state_path(&self) -> PathBuf {
+        self.state_path.clone()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Count(u64);
+
+impl ServiceState for Count {
+    type Settings = CounterSettings;
+    type Error = DynError;
+
+    fn from_settings(_settings: &Self::Settings) -> Result<Self, Self::Error> {
+        Ok(Self(0))
+    }
+}
+
+/// Adds its step to the count it starts from once running
+struct Counter {
+    service_state: ServiceStateHandle<Self>,
+    count: Count,
+}
+
+impl ServiceData for Counter {
+    const SERVICE_ID: ServiceId = "counter";
+    type Settings = CounterSettings;
+    type State = Count;
+    type StateOperator = JsonFileOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Counter {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self {
+            service_state,
+            count: initial_state,
+        })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let step = self
+            .service_state
+            .settings_reader
+            .get_updated_settings()
+            .step;
+        self.service_state
+            .state_updater
+            .update(Count(self.count.0 + step))?;
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending().await
+    }
+}
+
+struct Idle;
+
+impl ServiceData for Idle {
+    const SERVICE_ID: ServiceId = "idle";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Idle {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending().await
+    }
+}
+
+/// Lets the snapshots of [`Slow`] through
+static SNAPSHOTS_RELEASE: Notify = Notify::const_new();
+
+/// Holds snapshots back until [`SNAPSHOTS_RELEASE`] is notified
+#[derive(Clone)]
+struct SlowOperator;
+
+#[async_trait]
+impl StateOperator for SlowOperator {
+    type StateInput = NoState<()>;
+    type LoadError = std::convert::Infallible;
+
+    fn try_load(_settings: &()) -> Result<Option<Self::StateInput>, Self::LoadError> {
+        Ok(None)
+    }
+
+    fn from_settings(_settings: ()) -> Self {
+        Self
+    }
+
+    async fn run(&mut self, _state: Self::StateInput, context: PersistContext) {
+        if context == PersistContext::Snapshot {
+            SNAPSHOTS_RELEASE.notified().await;
+        }
+    }
+}
+
+struct Slow;
+
+impl ServiceData for Slow {
+    const SERVICE_ID: ServiceId = "slow";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = SlowOperator;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Slow {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct App {
+    counter: ServiceHandle<Counter>,
+    idle: ServiceHandle<Idle>,
+}
+
+#[derive(Services)]
+struct SlowApp {
+    slow: ServiceHandle<Slow>,
+    idle: ServiceHandle<Idle>,
+}
+
+#[derive(Services)]
+struct OptionalApp {
+    idle: Option<ServiceHandle<Idle>>,
+}
+
+#[derive(Services)]
+struct OptionalCounterApp {
+    counter: Option<ServiceHandle<Counter>>,
+    idle: ServiceHandle<Idle>,
+}
+
+fn stored_count(path: &Path) -> Count {
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn restored_overwatch_runs_as_it_was_checkpointed() {
+    let dir = EphemeralDir::create().unwrap();
+    let checkpoint_path = dir.path().join("checkpoint.json");
+    let state_path = dir.path().join("counter.json");
+    let settings = AppServiceSettings {
+        counter: CounterSettings {
+            state_path: state_path.clone(),
+            step: 1,
+        },
+        idle: (),
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings.clone(), None).unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.block_on(async {
+        handle
+            .status_watcher::<Counter>()
+            .await
+            .unwrap()
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        // the running counter keeps the step it started with, the restored one takes the new one
+        let updated = AppServiceSettings {
+            counter: CounterSettings {
+                step: 5,
+                ..settings.counter.clone()
+            },
+            idle: (),
+        };
+        handle
+            .update_settings_and_wait::<App>(updated)
+            .await
+            .unwrap();
+        handle.stop_service::<Idle>().await.unwrap();
+    });
+    overwatch.checkpoint::<App>(&checkpoint_path).unwrap();
+    overwatch.block_on(handle.shutdown_and_wait()).unwrap();
+    assert_eq!(stored_count(&state_path), Count(1));
+
+    let checkpoint = Checkpoint::load(&checkpoint_path).unwrap();
+    assert_eq!(checkpoint.version, CHECKPOINT_VERSION);
+    assert_eq!(checkpoint.stopped, ["idle"]);
+
+    let overwatch = OverwatchRunner::<App>::restore(&checkpoint_path, None).unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.block_on(async {
+        handle
+            .status_watcher::<Counter>()
+            .await
+            .unwrap()
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert_eq!(
+            handle.status_watcher::<Idle>().await.unwrap().current(),
+            ServiceStatus::Stopped
+        );
+        // services stopped when checkpointed are not started at all
+        let boot = handle.wait_for_boot_report().await;
+        assert_eq!(boot.skipped, ["idle"]);
+        assert!(boot.started.iter().all(|boot| boot.service_id != "idle"));
+        handle.shutdown_and_wait().await.unwrap();
+    });
+    // the restored counter started from the snapshotted count, with the checkpointed step
+    assert_eq!(stored_count(&state_path), Count(6));
+    dir.remove().unwrap();
+}
+
+#[test]
+fn stopped_services_stay_stopped_across_restores() {
+    let dir = EphemeralDir::create().unwrap();
+    let checkpoint_path = dir.path().join("checkpoint.json");
+    let settings = AppServiceSettings {
+        counter: CounterSettings {
+            state_path: dir.path().join("counter.json"),
+            step: 1,
+        },
+        idle: (),
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings, None).unwrap();
+    overwatch
+        .block_on(overwatch.handle().stop_service::<Idle>())
+        .unwrap();
+    overwatch.checkpoint::<App>(&checkpoint_path).unwrap();
+    overwatch.block_on(overwatch.handle().shutdown_and_wait());
+    for _ in 0..2 {
+        let overwatch = OverwatchRunner::<App>::restore(&checkpoint_path, None).unwrap();
+        overwatch.checkpoint::<App>(&checkpoint_path).unwrap();
+        overwatch.block_on(overwatch.handle().shutdown_and_wait());
+        assert_eq!(
+            Checkpoint::load(&checkpoint_path).unwrap().stopped,
+            ["idle"]
+        );
+    }
+    dir.remove().unwrap();
+}
+
+#[test]
+fn disabled_services_are_restored_disabled() {
+    let dir = EphemeralDir::create().unwrap();
+    let checkpoint_path = dir.path().join("checkpoint.json");
+    let counter = CounterSettings {
+        state_path: dir.path().join("counter.json"),
+        step: 3,
+    };
+    let settings = OptionalCounterAppServiceSettings {
+        counter: OptionalSettings::disabled(counter.clone()),
+        idle: (),
+    };
+    let overwatch = OverwatchRunner::<OptionalCounterApp>::run(settings, None).unwrap();
+    overwatch
+        .checkpoint::<OptionalCounterApp>(&checkpoint_path)
+        .unwrap();
+    overwatch.abort();
+    let checkpoint = Checkpoint::load(&checkpoint_path).unwrap();
+    assert!(checkpoint.stopped.is_empty());
+
+    let overwatch = OverwatchRunner::<OptionalCounterApp>::restore(&checkpoint_path, None).unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.block_on(async {
+        assert!(matches!(
+            handle.status_watcher::<Counter>().await,
+            Err(ServiceStatusError::ServiceDisabled {
+                service_id: "counter"
+            })
+        ));
+    });
+    // the disabled service keeps its settings from a checkpoint to the next
+    overwatch
+        .checkpoint::<OptionalCounterApp>(&checkpoint_path)
+        .unwrap();
+    overwatch.abort();
+    let restored = Checkpoint::load(&checkpoint_path).unwrap();
+    assert_eq!(restored.settings, checkpoint.settings);
+    let restored: OptionalCounterAppServiceSettings =
+        serde_json::from_value(restored.settings).unwrap();
+    assert!(!restored.counter.enabled);
+    assert_eq!(restored.counter.settings, counter);
+    assert!(!dir.path().join("counter.json").exists());
+    dir.remove().unwrap();
+}
+
+#[test]
+fn checkpoints_of_other_services_are_refused() {
+    let dir = EphemeralDir::create().unwrap();
+    let path = dir.path().join("checkpoint.json");
+    let settings = OptionalAppServiceSettings {
+        idle: OptionalSettings::new(()),
+    };
+    let overwatch = OverwatchRunner::<OptionalApp>::run(settings, None).unwrap();
+    assert!(matches!(
+        overwatch.checkpoint::<App>(&path),
+        Err(CheckpointError::Overwatch(Error::InvalidServicesSettings))
+    ));
+    // the runner is still there to take the checkpoint of the services it runs
+    overwatch.checkpoint::<OptionalApp>(&path).unwrap();
+    overwatch.abort();
+    dir.remove().unwrap();
+}
+
+#[test]
+fn slow_snapshots_do_not_hold_back_other_commands() {
+    let dir = EphemeralDir::create().unwrap();
+    let path = dir.path().join("checkpoint.json");
+    let settings = SlowAppServiceSettings { slow: (), idle: () };
+    let overwatch = OverwatchRunner::<SlowApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.block_on(async {
+        let checkpoint = tokio::spawn({
+            let handle = handle.clone();
+            let path = path.clone();
+            async move { handle.checkpoint::<SlowApp>(path).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // the runner still handles commands while the snapshot is held back
+        tokio::time::timeout(Duration::from_secs(1), handle.stop_service::<Idle>())
+            .await
+            .expect("Commands to be handled during a checkpoint")
+            .unwrap();
+        assert!(!checkpoint.is_finished());
+        SNAPSHOTS_RELEASE.notify_one();
+        checkpoint.await.unwrap().unwrap();
+    });
+    // taken before the service was stopped
+    assert!(Checkpoint::load(&path).unwrap().stopped.is_empty());
+    overwatch.abort();
+    dir.remove().unwrap();
+}
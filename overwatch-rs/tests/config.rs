@@ -0,0 +1,172 @@
+#![cfg(feature = "config")]
+
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::config::{ConfigError, ConfigFormat, ConfigLoader};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::pool::ServicePool;
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct StoreSettings {
+    path: String,
+    limits: Limits,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Limits {
+    max_entries: usize,
+    max_age_secs: u64,
+}
+
+struct Store;
+
+impl ServiceData for Store {
+    const SERVICE_ID: ServiceId = "BlobStore";
+    type Settings = StoreSettings;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+struct Worker;
+
+impl ServiceData for Worker {
+    const SERVICE_ID: ServiceId = "worker";
+    type Settings = u8;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+macro_rules! idle_service {
+    ($service:ty) => {
+        #[async_trait]
+        impl ServiceCore for $service {
+            fn init(
+                _service_state: ServiceStateHandle<Self>,
+                _initial_state: Self::State,
+            ) -> Result<Self, DynError> {
+                Ok(Self)
+            }
+
+            async fn run(self) -> Result<(), DynError> {
+                Ok(())
+            }
+        }
+    };
+}
+
+idle_service!(Store);
+idle_service!(Worker);
+
+#[derive(Services)]
+struct App {
+    store: ServiceHandle<Store>,
+    workers: ServicePool<Worker>,
+}
+
+#[derive(Services)]
+struct Optional {
+    worker: Option<ServiceHandle<Worker>>,
+}
+
+const CONFIG: &str = r#"{
+    "store": {"path": "/var/lib/store", "limits": {"maxEntries": 100, "maxAgeSecs": 60}},
+    "workers": [1, 2]
+}"#;
+
+#[test]
+fn settings_are_loaded_per_service() {
+    let settings = ConfigLoader::<App>::contents(CONFIG, ConfigFormat::Json)
+        .load()
+        .unwrap();
+    assert_eq!(settings.store.path, "/var/lib/store");
+    assert_eq!(settings.store.limits.max_entries, 100);
+    assert_eq!(settings.workers, [1, 2]);
+
+    let missing = ConfigLoader::<App>::contents(r#"{"workers": []}"#, ConfigFormat::Json).load();
+    assert!(matches!(missing, Err(ConfigError::Settings(_))));
+}
+
+fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+        .collect()
+}
+
+#[test]
+fn environment_overrides_settings() {
+    let settings = ConfigLoader::<App>::contents(CONFIG, ConfigFormat::Json)
+        .with_env_prefix("APP")
+        .with_env_vars(vars(&[
+            ("APP_BLOB_STORE__LIMITS__MAX_ENTRIES", "5"),
+            ("APP_BLOB_STORE__PATH", "/tmp/store"),
+            ("APP_WORKER", "[3]"),
+            ("OTHER_WORKER", "[4]"),
+        ]))
+        .load()
+        .unwrap();
+    assert_eq!(
+        settings.store,
+        StoreSettings {
+            path: "/tmp/store".to_owned(),
+            limits: Limits {
+                max_entries: 5,
+                max_age_secs: 60,
+            },
+        }
+    );
+    assert_eq!(settings.workers, [3]);
+}
+
+#[test]
+fn string_settings_are_overridden_as_they_are() {
+    for path in ["8080", "true", "null", "[1]"] {
+        let settings = ConfigLoader::<App>::contents(CONFIG, ConfigFormat::Json)
+            .with_env_prefix("APP")
+            .with_env_vars(vars(&[("APP_BLOB_STORE__PATH", path)]))
+            .load()
+            .unwrap();
+        assert_eq!(settings.store.path, path);
+    }
+}
+
+#[test]
+fn optional_services_settings_are_overridden() {
+    let settings = ConfigLoader::<Optional>::contents(
+        r#"{"worker": {"enabled": false, "settings": 1}}"#,
+        ConfigFormat::Json,
+    )
+    .with_env_prefix("APP")
+    .with_env_vars(vars(&[("APP_WORKER", "7")]))
+    .load()
+    .unwrap();
+    assert!(!settings.worker.enabled);
+    assert_eq!(settings.worker.settings, 7);
+}
+
+#[cfg(feature = "config-toml")]
+#[test]
+fn settings_are_loaded_from_toml() {
+    let config = r#"
+        workers = [4]
+
+        [store]
+        path = "/srv/store"
+
+        [store.limits]
+        maxEntries = 10
+        maxAgeSecs = 30
+    "#;
+    let settings = ConfigLoader::<App>::contents(config, ConfigFormat::Toml)
+        .load()
+        .unwrap();
+    assert_eq!(settings.store.limits.max_age_secs, 30);
+    assert_eq!(settings.workers, [4]);
+}
@@ -0,0 +1,260 @@
+#![cfg(feature = "cli")]
+
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::control::{ControlAddress, ControlPlane, MAX_COMMAND_LENGTH};
+use overwatch_rs::overwatch::testing::{MockService, MockSettings};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::ServiceHandle;
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+#[derive(Debug)]
+struct Put;
+
+impl RelayMessage for Put {}
+
+struct Store;
+
+impl ServiceData for Store {
+    const SERVICE_ID: ServiceId = "store";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Put;
+}
+
+type StoreService = MockService<Store>;
+
+#[derive(Services)]
+struct App {
+    store: ServiceHandle<StoreService>,
+}
+
+/// Send `command`, returning the response lines up to the final `ok` or `error` one
+async fn request(connection: &mut BufReader<TcpStream>, command: &str) -> Vec<String> {
+    connection
+        .get_mut()
+        .write_all(format!("{command}\n").as_bytes())
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    loop {
+        let mut line = String::new();
+        connection.read_line(&mut line).await.unwrap();
+        let line = line.trim_end().to_owned();
+        let last = line == "ok" || line.starts_with("error");
+        response.push(line);
+        if last {
+            return response;
+        }
+    }
+}
+
+#[test]
+fn services_are_operated_through_the_control_plane() {
+    let settings = MockSettings::new();
+    let overwatch = OverwatchRunner::<App>::run(
+        AppServiceSettings {
+            store: settings.clone(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.spawn(async move {
+        handle
+            .relay_when_ready::<Store>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        let server = ControlPlane::tcp("127.0.0.1:0".parse().unwrap())
+            .with_reload::<App, _>(move || -> Result<AppServiceSettings, DynError> {
+                Ok(AppServiceSettings {
+                    store: settings.clone(),
+                })
+            })
+            .serve(&handle)
+            .await
+            .unwrap();
+        let ControlAddress::Tcp(address) = server.address().clone() else {
+            unreachable!("listening on TCP");
+        };
+        let mut connection = BufReader::new(TcpStream::connect(address).await.unwrap());
+
+        assert_eq!(
+            request(&mut connection, "list").await,
+            ["store running", "ok"]
+        );
+        assert_eq!(request(&mut connection, "stop store").await, ["ok"]);
+        assert_eq!(
+            request(&mut connection, "status store").await,
+            ["stopped", "ok"]
+        );
+        assert_eq!(request(&mut connection, "start store").await, ["ok"]);
+        handle
+            .relay_when_ready::<Store>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            request(&mut connection, "status store").await,
+            ["running", "ok"]
+        );
+        assert_eq!(request(&mut connection, "reload").await, ["ok"]);
+        assert_eq!(
+            request(&mut connection, "stop api").await,
+            ["error unknown service api"]
+        );
+        assert_eq!(
+            request(&mut connection, "restart").await,
+            ["error unknown command restart"]
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn sessions_sending_overlong_lines_are_closed() {
+    let overwatch = OverwatchRunner::<App>::run(
+        AppServiceSettings {
+            store: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let response = overwatch.block_on(async {
+        let server = ControlPlane::tcp("127.0.0.1:0".parse().unwrap())
+            .serve(&handle)
+            .await
+            .unwrap();
+        let ControlAddress::Tcp(address) = server.address().clone() else {
+            unreachable!("listening on TCP");
+        };
+        let mut connection = TcpStream::connect(address).await.unwrap();
+        // no newline, the line would grow for as long as the client sends
+        let line = vec![b'a'; MAX_COMMAND_LENGTH + 1];
+        connection.write_all(&line).await.unwrap();
+        let mut response = String::new();
+        let _ = tokio::time::timeout(
+            Duration::from_secs(1),
+            connection.read_to_string(&mut response),
+        )
+        .await;
+        response
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert_eq!(response, "error command too long\n");
+}
+
+#[test]
+fn non_loopback_addresses_are_refused_unless_allowed() {
+    let overwatch = OverwatchRunner::<App>::run(
+        AppServiceSettings {
+            store: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let (refused, allowed) = overwatch.block_on(async {
+        let address = "0.0.0.0:0".parse().unwrap();
+        let refused = ControlPlane::tcp(address).serve(&handle).await.is_err();
+        let allowed = ControlPlane::tcp(address)
+            .allow_non_loopback()
+            .serve(&handle)
+            .await
+            .is_ok();
+        (refused, allowed)
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert!(refused);
+    assert!(allowed);
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_sockets_are_private_and_not_taken_over() {
+    use overwatch_rs::overwatch::testing::EphemeralDir;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = EphemeralDir::create().unwrap();
+    let path = dir.path().join("control.sock");
+    let not_a_socket = dir.path().join("settings.json");
+    std::fs::write(&not_a_socket, "{}").unwrap();
+    let overwatch = OverwatchRunner::<App>::run(
+        AppServiceSettings {
+            store: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let (mode, entries, taken_over, overwritten) = overwatch.block_on(async {
+        let _server = ControlPlane::unix(&path).serve(&handle).await.unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        // the socket is bound in a private staging directory, gone once it is in place
+        let entries = std::fs::read_dir(dir.path()).unwrap().count();
+        let taken_over = ControlPlane::unix(&path).serve(&handle).await.is_ok();
+        let overwritten = ControlPlane::unix(&not_a_socket)
+            .serve(&handle)
+            .await
+            .is_ok();
+        (mode, entries, taken_over, overwritten)
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    let removed = !path.exists();
+    let kept = not_a_socket.exists();
+    dir.remove().unwrap();
+    assert_eq!(mode, 0o600);
+    assert_eq!(entries, 2);
+    assert!(!taken_over);
+    assert!(!overwritten);
+    assert!(kept);
+    assert!(removed);
+}
+
+#[test]
+fn aborting_the_server_closes_its_sessions() {
+    let overwatch = OverwatchRunner::<App>::run(
+        AppServiceSettings {
+            store: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let (before, after) = overwatch.block_on(async {
+        handle
+            .relay_when_ready::<Store>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        let server = ControlPlane::tcp("127.0.0.1:0".parse().unwrap())
+            .serve(&handle)
+            .await
+            .unwrap();
+        let ControlAddress::Tcp(address) = server.address().clone() else {
+            unreachable!("listening on TCP");
+        };
+        let mut connection = BufReader::new(TcpStream::connect(address).await.unwrap());
+        let before = request(&mut connection, "status store").await;
+        server.abort();
+        let mut rest = String::new();
+        let after =
+            tokio::time::timeout(Duration::from_secs(1), connection.read_to_string(&mut rest))
+                .await
+                .map(|read| read.map_or(true, |read| read == 0));
+        (before, after)
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert_eq!(before, ["running", "ok"]);
+    assert_eq!(after, Ok(true));
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::backtrace::BacktraceStatus;
+use std::time::Duration;
+
+struct Panicking;
+
+impl ServiceData for Panicking {
+    const SERVICE_ID: ServiceId = "panicking";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Panicking {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        panic!("out of cheese");
+    }
+}
+
+struct Failing;
+
+impl ServiceData for Failing {
+    const SERVICE_ID: ServiceId = "failing";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Failing {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        Err("disk is full".into())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    panicking: ServiceHandle<Panicking>,
+    failing: ServiceHandle<Failing>,
+}
+
+#[test]
+fn crashes_are_kept_with_their_cause() {
+    let settings = AppServiceSettings {
+        panicking: (),
+        failing: (),
+    };
+    let overwatch = OverwatchRunner::<App>::run_with_context_config(
+        settings,
+        None,
+        ContextConfig::default().with_panic_backtraces(true),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        for (service_id, status) in [
+            (
+                Panicking::SERVICE_ID,
+                ServiceStatus::Failed("out of cheese".to_owned()),
+            ),
+            (Failing::SERVICE_ID, ServiceStatus::Stopped),
+        ] {
+            handle
+                .status_watcher_for(service_id)
+                .await
+                .unwrap()
+                .wait_for(status, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+        }
+
+        let panicked = handle.last_error::<Panicking>().unwrap();
+        assert_eq!(panicked.error, "out of cheese");
+        assert!(panicked.panicked());
+        // captured as `RUST_BACKTRACE` says
+        let backtrace = panicked.backtrace.unwrap();
+        if backtrace.status() == BacktraceStatus::Captured {
+            let backtrace = backtrace.to_string();
+            assert!(backtrace.contains("crash_report"), "{backtrace}");
+        }
+
+        let failed = handle.last_error::<Failing>().unwrap();
+        assert_eq!(failed.error, "disk is full");
+        assert!(!failed.panicked());
+        assert!(failed.backtrace.is_none());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
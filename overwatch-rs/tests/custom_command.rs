@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::commands::CustomCommand;
+use overwatch_rs::overwatch::handle::OverwatchHandle;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio::sync::oneshot;
+
+struct Idle;
+
+impl ServiceData for Idle {
+    const SERVICE_ID: ServiceId = "idle";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Idle {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    idle: ServiceHandle<Idle>,
+}
+
+/// Command of a scheduler-like extension
+struct Echo(String, oneshot::Sender<String>);
+
+/// Command of an admin-like extension, answered from the overwatch handle
+struct IdleStatus(oneshot::Sender<ServiceStatus>);
+
+fn echo(command: CustomCommand, _handle: &OverwatchHandle) -> Result<(), CustomCommand> {
+    let Echo(message, reply) = command.downcast::<Echo>()?;
+    let _ = reply.send(message);
+    Ok(())
+}
+
+fn idle_status(command: CustomCommand, handle: &OverwatchHandle) -> Result<(), CustomCommand> {
+    let IdleStatus(reply) = command.downcast::<IdleStatus>()?;
+    let handle = handle.clone();
+    // the runner is busy handling this command, so the status is requested from another task
+    handle.runtime().clone().spawn(async move {
+        let status = handle.status_watcher::<Idle>().await.unwrap().current();
+        let _ = reply.send(status);
+    });
+    Ok(())
+}
+
+#[test]
+fn custom_commands_reach_their_handler() {
+    let context_config = ContextConfig::default()
+        .with_command_handler(echo)
+        .with_command_handler(idle_status);
+    let overwatch = OverwatchRunner::<App>::run_with_context_config(
+        AppServiceSettings { idle: () },
+        None,
+        context_config,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let (reply, echoed) = oneshot::channel();
+        handle
+            .send_custom(Echo("hello".to_string(), reply))
+            .await
+            .unwrap();
+        assert_eq!(echoed.await.unwrap(), "hello");
+
+        let (reply, status) = oneshot::channel();
+        handle.send_custom(IdleStatus(reply)).await.unwrap();
+        assert_ne!(status.await.unwrap(), ServiceStatus::Stopped);
+
+        // nobody handles it, the runner drops it and keeps going
+        let (reply, unhandled) = oneshot::channel::<()>();
+        handle.send_custom(reply).await.unwrap();
+        assert!(unhandled.await.is_err());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
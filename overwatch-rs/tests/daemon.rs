@@ -0,0 +1,54 @@
+#![cfg(all(unix, feature = "signal"))]
+
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+
+struct Idle;
+
+impl ServiceData for Idle {
+    const SERVICE_ID: ServiceId = "idle";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Idle {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct Daemon {
+    idle: ServiceHandle<Idle>,
+}
+
+#[test]
+fn sigterm_shuts_overwatch_down() {
+    let overwatch =
+        OverwatchRunner::<Daemon>::run(DaemonServiceSettings { idle: () }, None).unwrap();
+    overwatch.handle().shutdown_on_terminate().unwrap();
+
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &std::process::id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    overwatch.wait_finished();
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+#[derive(Debug)]
+enum Outage {
+    Partial,
+    Recovered,
+    Total,
+}
+
+impl RelayMessage for Outage {}
+
+struct Gateway {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Gateway {
+    const SERVICE_ID: ServiceId = "gateway";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Outage;
+}
+
+#[async_trait]
+impl ServiceCore for Gateway {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let status = self.service_state.status_handle.updater();
+        // the upstream is not reachable yet
+        status.degraded();
+        while let Some(outage) = self.service_state.inbound_relay.recv().await {
+            match outage {
+                Outage::Partial => status.degraded(),
+                Outage::Recovered => status.running(),
+                Outage::Total => status.failed("upstream is gone"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    gateway: ServiceHandle<Gateway>,
+}
+
+#[test]
+fn degraded_and_failed_statuses_are_propagated() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { gateway: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        // degraded services are started nonetheless
+        let boot = handle.wait_for_boot_report().await;
+        assert!(boot.started[0].time_to_ready.is_some());
+        let mut watcher = handle.status_watcher::<Gateway>().await.unwrap();
+        assert_eq!(watcher.current(), ServiceStatus::Degraded);
+
+        let mut events = handle.lifecycle_events();
+        let relay = handle.relay::<Gateway>().connect().await.unwrap();
+        for outage in [Outage::Recovered, Outage::Partial, Outage::Total] {
+            relay.send(outage).await.unwrap();
+        }
+        let failed = watcher
+            .wait_until(ServiceStatus::is_failed, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert_eq!(failed, ServiceStatus::Failed("upstream is gone".to_owned()));
+
+        // recovering from a degraded state is not starting again
+        let event = tokio::time::timeout(Duration::from_millis(50), events.next()).await;
+        assert!(event.is_err(), "{event:?}");
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{NoMessage, RelayError};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+/// Keeps running after giving up its inbound relay
+struct Deaf {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Deaf {
+    const SERVICE_ID: ServiceId = "deaf";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Deaf {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        // give the test some time to subscribe to error events
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(self.service_state);
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    deaf: ServiceHandle<Deaf>,
+}
+
+#[test]
+fn dropped_inbound_relay_is_reported() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { deaf: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut errors = Box::pin(handle.error_events());
+        let mut status = handle.status_watcher::<Deaf>().await.unwrap();
+        assert!(status
+            .wait_for(ServiceStatus::Detached, Some(Duration::from_secs(1)))
+            .await
+            .is_ok());
+        let event = errors.next().await.unwrap();
+        assert_eq!(event.service_id, "deaf");
+        assert!(matches!(
+            event.error.downcast_ref::<RelayError>(),
+            Some(RelayError::InboundDropped { service_id: "deaf" })
+        ));
+        assert!(handle
+            .relay::<Deaf>()
+            .connect()
+            .await
+            .unwrap()
+            .send(NoMessage)
+            .await
+            .is_err());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
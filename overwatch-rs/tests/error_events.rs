@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio_stream::StreamExt;
+
+#[derive(Debug)]
+struct FailWith(String);
+
+impl RelayMessage for FailWith {}
+
+struct FailingService {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for FailingService {
+    const SERVICE_ID: ServiceId = "failing";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = FailWith;
+}
+
+#[async_trait]
+impl ServiceCore for FailingService {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        while let Some(FailWith(reason)) = self.service_state.inbound_relay.recv().await {
+            self.service_state.report_error(reason);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct ErrorsApp {
+    failing: ServiceHandle<FailingService>,
+}
+
+#[test]
+fn reported_errors_reach_error_events() {
+    let overwatch =
+        OverwatchRunner::<ErrorsApp>::run(ErrorsAppServiceSettings { failing: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut errors = Box::pin(handle.error_events());
+        let relay = handle.relay::<FailingService>().connect().await.unwrap();
+        relay
+            .send(FailWith("disk is full".to_string()))
+            .await
+            .unwrap();
+
+        let event = errors.next().await.unwrap();
+        assert_eq!(event.service_id, "failing");
+        assert_eq!(event.error.to_string(), "disk is full");
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
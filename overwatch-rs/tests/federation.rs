@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::{MockService, MockSettings};
+use overwatch_rs::overwatch::{OverwatchRunner, Services};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{NoMessage, RelayMessage};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum StoreMessage {
+    Put(String),
+}
+
+impl RelayMessage for StoreMessage {}
+
+struct Store;
+
+impl ServiceData for Store {
+    const SERVICE_ID: ServiceId = "store";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = StoreMessage;
+}
+
+/// Stores every document it is given
+struct Indexer {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Indexer {
+    const SERVICE_ID: ServiceId = "indexer";
+    type Settings = Vec<String>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Indexer {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state.status_handle.updater().running();
+        let store = self
+            .service_state
+            .overwatch_handle
+            .relay_when_ready::<Store>(Duration::from_secs(1))
+            .await?;
+        for document in self.service_state.settings_reader.get_updated_settings() {
+            store
+                .send(StoreMessage::Put(document))
+                .await
+                .map_err(|(e, _)| e)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct Core {
+    store: ServiceHandle<MockService<Store>>,
+}
+
+#[derive(Services)]
+struct Plugins {
+    indexer: ServiceHandle<Indexer>,
+}
+
+#[derive(Services)]
+struct Node {
+    #[bundle]
+    core: Core,
+    #[bundle]
+    plugins: Plugins,
+}
+
+#[test]
+fn bundled_services_relay_to_each_other() {
+    let store = MockSettings::new();
+    let overwatch = OverwatchRunner::<Node>::run(
+        NodeServiceSettings {
+            core: CoreServiceSettings {
+                store: store.clone(),
+            },
+            plugins: PluginsServiceSettings {
+                indexer: vec!["a".to_owned(), "b".to_owned()],
+            },
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.spawn(async move {
+        assert_eq!(store.wait_received(2).await, [r#"Put("a")"#, r#"Put("b")"#]);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[derive(Services)]
+struct Region {
+    store: ServiceHandle<MockService<Store>>,
+    indexer: ServiceHandle<Indexer>,
+}
+
+#[derive(Services)]
+struct Federation {
+    #[bundle]
+    east: Region,
+    #[bundle]
+    west: Region,
+}
+
+#[test]
+fn bundled_services_relay_to_their_siblings() {
+    let (east, west) = (MockSettings::new(), MockSettings::new());
+    let overwatch = OverwatchRunner::<Federation>::run(
+        FederationServiceSettings {
+            east: RegionServiceSettings {
+                store: east.clone(),
+                indexer: vec!["e".to_owned()],
+            },
+            west: RegionServiceSettings {
+                store: west.clone(),
+                indexer: vec!["w".to_owned()],
+            },
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let received = overwatch.block_on(async {
+        let received = (east.wait_received(1).await, west.wait_received(1).await);
+        handle.shutdown().await;
+        received
+    });
+    overwatch.wait_finished();
+    assert_eq!(received.0, [r#"Put("e")"#]);
+    assert_eq!(received.1, [r#"Put("w")"#]);
+}
+
+#[test]
+fn bundled_services_are_namespaced() {
+    assert_eq!(Node::SERVICES_IDS, ["core.store", "plugins.indexer"]);
+    assert_eq!(
+        Node::service_id_from_str("indexer"),
+        Some("plugins.indexer")
+    );
+    assert_eq!(Node::service_id_from_str("core.store"), Some("core.store"));
+    assert_eq!(Node::service_id_from_str("plugins.store"), None);
+    assert_eq!(
+        Federation::SERVICES_IDS,
+        ["east.store", "east.indexer", "west.store", "west.indexer"]
+    );
+    assert_eq!(
+        Federation::SERVICES_SETTINGS,
+        [
+            ("east.store", "store"),
+            ("east.indexer", "indexer"),
+            ("west.store", "store"),
+            ("west.indexer", "indexer"),
+        ]
+    );
+    // the same service of two bundles has to be named with its namespace
+    assert_eq!(Federation::service_id_from_str("store"), None);
+    assert_eq!(
+        Federation::service_id_from_str("west.store"),
+        Some("west.store")
+    );
+}
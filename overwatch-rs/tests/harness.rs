@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::{AppHarness, EphemeralDir};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Writes the file it is given as settings, then checks it is within the harness directory
+struct Writer {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Writer {
+    const SERVICE_ID: ServiceId = "writer";
+    type Settings = PathBuf;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Writer {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let path = self.service_state.settings_reader.get_updated_settings();
+        let dir = self.service_state.resources().require::<EphemeralDir>()?;
+        if !path.starts_with(dir.path()) {
+            return Err("settings point outside the harness directory".into());
+        }
+        std::fs::write(&path, "written")?;
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct WriterApp {
+    writer: ServiceHandle<Writer>,
+}
+
+#[test]
+fn harness_runs_the_app_in_its_own_directory() {
+    let harness = AppHarness::<WriterApp>::run(|dir| WriterAppServiceSettings {
+        writer: dir.join("output"),
+    })
+    .unwrap();
+    let dir = harness.dir().to_path_buf();
+
+    harness.block_on(async {
+        harness
+            .wait_ready::<Writer>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        harness.wait_booted().await.unwrap();
+    });
+    assert_eq!(
+        std::fs::read_to_string(dir.join("output")).unwrap(),
+        "written"
+    );
+    let report = harness.shutdown().unwrap();
+    assert_eq!(report.exits.len(), 1);
+    assert!(!dir.exists());
+
+    // dropping the harness tears the app down as well
+    let harness = AppHarness::<WriterApp>::run(|dir| WriterAppServiceSettings {
+        writer: dir.join("output"),
+    })
+    .unwrap();
+    let dir = harness.dir().to_path_buf();
+    let shutdown = harness.handle().shutdown_token();
+    drop(harness);
+    assert!(shutdown.is_cancelled());
+    assert!(!dir.exists());
+}
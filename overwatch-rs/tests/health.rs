@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::health::{Health, HealthReport, LivenessProbe};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+struct SlowDisk;
+
+#[async_trait]
+impl LivenessProbe for SlowDisk {
+    async fn health(&self) -> HealthReport {
+        HealthReport::degraded("disk is slow")
+    }
+}
+
+struct Probed {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Probed {
+    const SERVICE_ID: ServiceId = "probed";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Probed {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        service_state.context.register_liveness_probe(SlowDisk);
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct ProbedApp {
+    probed: ServiceHandle<Probed>,
+}
+
+#[test]
+fn health_report_aggregates_running_services_probes() {
+    let overwatch = OverwatchRunner::<ProbedApp>::run_with_context_config(
+        ProbedAppServiceSettings { probed: () },
+        None,
+        ContextConfig::default().with_health_check_interval(Duration::from_millis(20)),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let report = handle.health_report();
+        assert_eq!(report.overall(), Health::Degraded);
+        assert_eq!(
+            report.service("probed").unwrap().report,
+            HealthReport::degraded("disk is slow")
+        );
+
+        handle.stop_service::<Probed>().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // a stopped service is not probed anymore
+        assert!(handle.health_report().services.is_empty());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::history::HistoryEvent;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, ServiceState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Counter(usize);
+
+impl ServiceState for Counter {
+    type Settings = ();
+    type Error = DynError;
+
+    fn from_settings(_settings: &Self::Settings) -> Result<Self, Self::Error> {
+        Ok(Self(0))
+    }
+}
+
+struct CountingService {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for CountingService {
+    const SERVICE_ID: ServiceId = "counting";
+    type Settings = ();
+    type State = Counter;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for CountingService {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        self.service_state.state_updater.update(Counter(1))?;
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct HistoryApp {
+    counting: ServiceHandle<CountingService>,
+}
+
+#[test]
+fn status_and_state_are_recorded() {
+    let start = Instant::now();
+    let overwatch = OverwatchRunner::<HistoryApp>::run_with_context_config(
+        HistoryAppServiceSettings { counting: () },
+        None,
+        ContextConfig::default().with_history_capacity(16),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut watcher = handle.status_watcher::<CountingService>().await.unwrap();
+        watcher
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        // give the state handle a chance to pick the update
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let history = handle.history_since(start);
+        assert!(history
+            .iter()
+            .all(|entry| entry.service_id == "counting" && entry.at >= start));
+        assert!(history
+            .iter()
+            .any(|entry| matches!(entry.event, HistoryEvent::Status(ServiceStatus::Running))));
+        assert!(history
+            .iter()
+            .any(|entry| entry.event.state::<Counter>() == Some(&Counter(1))));
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
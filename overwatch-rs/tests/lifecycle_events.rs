@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::events::LifecycleEvent;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio_stream::StreamExt;
+
+#[derive(Debug)]
+struct Fail;
+
+impl RelayMessage for Fail {}
+
+struct Fragile {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Fragile {
+    const SERVICE_ID: ServiceId = "fragile";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Fail;
+}
+
+#[async_trait]
+impl ServiceCore for Fragile {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        match self.service_state.inbound_relay.recv().await {
+            Some(Fail) => Err("broken on purpose".into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Services)]
+struct App {
+    fragile: ServiceHandle<Fragile>,
+}
+
+#[test]
+fn lifecycle_transitions_are_streamed() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { fragile: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut status = handle.status_watcher::<Fragile>().await.unwrap();
+        status.wait_for(ServiceStatus::Running, None).await.unwrap();
+        let mut events = handle.lifecycle_events();
+        let relay = handle.relay::<Fragile>().connect().await.unwrap();
+        relay.send(Fail).await.unwrap();
+        assert_eq!(
+            events.next().await,
+            Some(LifecycleEvent::ServiceCrashed {
+                service_id: "fragile",
+                error: "broken on purpose".to_owned(),
+            })
+        );
+        assert_eq!(
+            events.next().await,
+            Some(LifecycleEvent::ServiceStopped {
+                service_id: "fragile"
+            })
+        );
+
+        // the crashed instance is still tracked, it has to be stopped to be started again
+        handle.stop_service::<Fragile>().await.unwrap();
+        handle.start_service::<Fragile>().await.unwrap();
+        assert_eq!(
+            events.next().await,
+            Some(LifecycleEvent::ServiceStarting {
+                service_id: "fragile"
+            })
+        );
+        assert_eq!(
+            events.next().await,
+            Some(LifecycleEvent::ServiceStarted {
+                service_id: "fragile"
+            })
+        );
+
+        handle.shutdown().await;
+        let rest: Vec<_> = events.take(2).collect().await;
+        assert_eq!(
+            rest,
+            [
+                LifecycleEvent::ServiceStopped {
+                    service_id: "fragile"
+                },
+                LifecycleEvent::OverwatchShutdown
+            ]
+        );
+    });
+    overwatch.wait_finished();
+}
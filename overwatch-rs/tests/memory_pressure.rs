@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::events::{MemoryPressure, MemoryPressureLevel};
+use overwatch_rs::overwatch::memory::MemoryMonitor;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId, ServicePriority};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct Job(oneshot::Sender<()>);
+
+impl RelayMessage for Job {}
+
+struct Worker<const LOW: bool> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const LOW: bool> ServiceData for Worker<LOW> {
+    const SERVICE_ID: ServiceId = if LOW { "indexer" } else { "consensus" };
+    const PRIORITY: ServicePriority = if LOW {
+        ServicePriority::Low
+    } else {
+        ServicePriority::Normal
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Job;
+}
+
+#[async_trait]
+impl<const LOW: bool> ServiceCore for Worker<LOW> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let Self { mut service_state } = self;
+        service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        while let Some(Job(done)) = service_state.inbound_relay.recv().await {
+            let _ = done.send(());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct Node {
+    indexer: ServiceHandle<Worker<true>>,
+    consensus: ServiceHandle<Worker<false>>,
+}
+
+#[test]
+fn low_priority_services_are_paused_under_critical_pressure() {
+    let used = Arc::new(AtomicU64::new(0));
+    let probe = {
+        let used = Arc::clone(&used);
+        move || Some(used.load(Ordering::SeqCst))
+    };
+    let monitor = MemoryMonitor::new(100, 200)
+        .with_probe(probe)
+        .with_interval(Duration::from_millis(10))
+        .pausing_low_priority();
+    let overwatch = OverwatchRunner::<Node>::run_with_context_config(
+        NodeServiceSettings {
+            indexer: (),
+            consensus: (),
+        },
+        None,
+        ContextConfig::default().with_memory_monitor(monitor),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    let (paused, held_back, indexer_status, consensus_status, resumed) =
+        overwatch.block_on(async {
+            let mut indexer = handle.status_watcher_for("indexer").await.unwrap();
+            let consensus = handle.status_watcher_for("consensus").await.unwrap();
+            indexer
+                .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+            let indexer_relay = handle.relay::<Worker<true>>().connect().await.unwrap();
+            let mut pressure = Box::pin(handle.memory_pressure_events());
+
+            used.store(250, Ordering::SeqCst);
+            let paused = pressure.next().await;
+            let (done, mut job_done) = oneshot::channel();
+            indexer_relay.send(Job(done)).await.unwrap();
+            let held_back = tokio::time::timeout(Duration::from_millis(50), &mut job_done)
+                .await
+                .is_err();
+            let indexer_status = indexer.current();
+            let consensus_status = consensus.current();
+
+            used.store(150, Ordering::SeqCst);
+            pressure.next().await;
+            let resumed = tokio::time::timeout(Duration::from_secs(1), job_done).await;
+            (
+                paused,
+                held_back,
+                indexer_status,
+                consensus_status,
+                resumed.is_ok_and(|done| done.is_ok()),
+            )
+        });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert_eq!(paused, Some(MemoryPressure(MemoryPressureLevel::Critical)));
+    // paused services keep running, their messages wait in the relay
+    assert!(held_back);
+    assert_eq!(indexer_status, ServiceStatus::Running);
+    assert_eq!(consensus_status, ServiceStatus::Running);
+    assert!(resumed);
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::{MockService, MockSettings};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::recording::{MessageRecorder, MessageReplay, MessageTrace};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+struct Add(u32);
+
+impl RelayMessage for Add {}
+
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+struct Total(u32);
+
+impl RelayMessage for Total {}
+
+/// Adds up the numbers it gets, publishing the running total to the ledger
+struct Counter {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Counter {
+    const SERVICE_ID: ServiceId = "counter";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Add;
+}
+
+#[async_trait]
+impl ServiceCore for Counter {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let ledger = self
+            .service_state
+            .overwatch_handle
+            .relay::<Ledger>()
+            .connect()
+            .await?;
+        self.service_state.status_handle.updater().running();
+        let mut total = 0;
+        while let Some(Add(value)) = self.service_state.inbound_relay.recv().await {
+            total += value;
+            ledger.send(Total(total)).await.map_err(|(e, _)| e)?;
+        }
+        Ok(())
+    }
+}
+
+struct Ledger;
+
+impl ServiceData for Ledger {
+    const SERVICE_ID: ServiceId = "ledger";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Total;
+}
+
+type LedgerService = MockService<Ledger>;
+
+#[derive(Services)]
+struct App {
+    counter: ServiceHandle<Counter>,
+    ledger: ServiceHandle<LedgerService>,
+}
+
+#[test]
+fn recorded_messages_are_replayed_into_a_fresh_application() {
+    let trace = MessageTrace::default();
+    let recorder = MessageRecorder::new(trace.clone()).replayable::<Add>();
+    let ledger = MockSettings::new();
+    let overwatch = OverwatchRunner::<App>::run_with_context_config(
+        AppServiceSettings {
+            counter: (),
+            ledger: ledger.clone(),
+        },
+        None,
+        ContextConfig::default().with_recorder(recorder),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.spawn(async move {
+        let counter = handle
+            .relay_when_ready::<Counter>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        counter.send(Add(2)).await.unwrap();
+        counter.send(Add(3)).await.unwrap();
+        ledger.wait_received(2).await;
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+
+    let messages = trace.messages();
+    assert_eq!(messages.len(), 4);
+    let sent: Vec<_> = messages
+        .iter()
+        .map(|message| (message.from, message.to, message.message.as_str()))
+        .collect();
+    assert!(sent.contains(&(None, "counter", "Add(2)")));
+    assert!(sent.contains(&(Some("counter"), "ledger", "Total(5)")));
+    // totals are not registered as replayable
+    assert!(messages
+        .iter()
+        .all(|message| message.is_replayable() == message.from.is_none()));
+
+    let ledger = MockSettings::new();
+    let fresh = OverwatchRunner::<App>::run(
+        AppServiceSettings {
+            counter: (),
+            ledger: ledger.clone(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = fresh.handle().clone();
+    fresh.spawn(async move {
+        handle
+            .relay_when_ready::<Counter>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        let replayed = MessageReplay::new(messages)
+            .from_outside()
+            .run(&handle)
+            .await
+            .unwrap();
+        assert_eq!(replayed, 2);
+        assert_eq!(ledger.wait_received(2).await, ["Total(2)", "Total(5)"]);
+        handle.shutdown().await;
+    });
+    fresh.wait_finished();
+}
@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::metrics::{MetricsExporter, MetricsSnapshot};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Ping;
+
+impl RelayMessage for Ping {}
+
+struct Sink {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Sink {
+    const SERVICE_ID: ServiceId = "sink";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Ping;
+}
+
+#[async_trait]
+impl ServiceCore for Sink {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        while self.service_state.inbound_relay.recv().await.is_some() {}
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    sink: ServiceHandle<Sink>,
+}
+
+#[test]
+fn relay_and_lifecycle_metrics_are_collected() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { sink: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let relay = handle.relay::<Sink>().connect().await.unwrap();
+        for _ in 0..3 {
+            relay.send(Ping).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let sink = handle.metrics().service("sink").copied().unwrap();
+        assert_eq!((sink.relay.sent, sink.relay.received), (3, 3));
+        assert_eq!((sink.relay.dropped, sink.relay.queue_depth), (0, 0));
+        assert_eq!(sink.lifecycle.starts, 1);
+        assert!(sink.lifecycle.uptime.is_some());
+
+        handle.stop_service::<Sink>().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(relay.send(Ping).await.is_err());
+        let sink = handle.metrics().service("sink").copied().unwrap();
+        assert_eq!(sink.relay.dropped, 1);
+        assert_eq!((sink.lifecycle.starts, sink.lifecycle.stops), (1, 1));
+        assert_eq!(sink.lifecycle.uptime, None);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[derive(Clone, Default)]
+struct Exported(Arc<Mutex<Vec<MetricsSnapshot>>>);
+
+impl MetricsExporter for Exported {
+    fn export(&self, snapshot: &MetricsSnapshot) {
+        self.0.lock().unwrap().push(snapshot.clone());
+    }
+}
+
+#[test]
+fn exporters_get_snapshots_until_shutdown() {
+    let exported = Exported::default();
+    let overwatch = OverwatchRunner::<App>::run_with_context_config(
+        AppServiceSettings { sink: () },
+        None,
+        ContextConfig::default()
+            .with_metrics_exporter(exported.clone())
+            .with_metrics_export_interval(Duration::from_millis(20)),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+    let exported = exported.0.lock().unwrap();
+    assert!(exported.len() >= 2);
+    // the last snapshot is taken once the services are stopped
+    let sink = exported.last().unwrap().service("sink").unwrap();
+    assert_eq!(sink.lifecycle.stops, 1);
+}
+
+#[cfg(feature = "metrics-prometheus")]
+#[test]
+fn prometheus_renders_the_current_metrics() {
+    use overwatch_rs::overwatch::prometheus::PrometheusExporter;
+
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { sink: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+    let exporter = PrometheusExporter::new(handle.clone());
+    let rendered = overwatch.block_on(async {
+        let relay = handle.relay::<Sink>().connect().await.unwrap();
+        let before = exporter.render();
+        relay.send(Ping).await.unwrap();
+        (before, exporter.render())
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    let (before, after) = rendered;
+    assert!(before.contains("overwatch_relay_messages_sent_total{service=\"sink\"} 0\n"));
+    assert!(after.contains("overwatch_relay_messages_sent_total{service=\"sink\"} 1\n"));
+}
@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::{MockService, MockSettings};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{NoMessage, RelayMessage};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+enum StoreMessage {
+    Put(String, u32),
+    Get(String, oneshot::Sender<Option<u32>>),
+}
+
+impl RelayMessage for StoreMessage {}
+
+/// Real store, which the tests replace with a mock
+struct Store {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Store {
+    const SERVICE_ID: ServiceId = "store";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = StoreMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Store {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let mut entries = HashMap::new();
+        while let Some(message) = self.service_state.inbound_relay.recv().await {
+            match message {
+                StoreMessage::Put(key, value) => {
+                    entries.insert(key, value);
+                }
+                StoreMessage::Get(key, reply) => {
+                    let _ = reply.send(entries.get(&key).copied());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Saves a value in the store as soon as it starts
+struct Writer {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Writer {
+    const SERVICE_ID: ServiceId = "writer";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Writer {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let store = self
+            .service_state
+            .overwatch_handle
+            .relay_when_ready::<Store>(Duration::from_secs(1))
+            .await?;
+        store
+            .send(StoreMessage::Put("answer".to_owned(), 42))
+            .await
+            .map_err(|(e, _)| e)?;
+        Ok(())
+    }
+}
+
+// applications would only swap the store under `cfg(test)`
+type StoreService = MockService<Store>;
+
+#[derive(Services)]
+struct App {
+    store: ServiceHandle<StoreService>,
+    writer: ServiceHandle<Writer>,
+}
+
+#[test]
+fn mocks_stand_in_for_real_services() {
+    let store = MockSettings::new().reply_with(|message| {
+        if let StoreMessage::Get(_, reply) = message {
+            let _ = reply.send(Some(7));
+        }
+    });
+    let settings = AppServiceSettings {
+        store: store.clone(),
+        writer: (),
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        assert_eq!(
+            store.wait_received(1).await,
+            [r#"Put("answer", 42)"#.to_owned()]
+        );
+
+        let relay = handle.relay::<Store>().connect().await.unwrap();
+        let (reply, value) = oneshot::channel();
+        relay
+            .send(StoreMessage::Get("answer".to_owned(), reply))
+            .await
+            .unwrap();
+        assert_eq!(value.await.unwrap(), Some(7));
+        assert_eq!(store.received().len(), 2);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
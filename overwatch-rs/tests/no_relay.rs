@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{NoRelay, RelayError};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+/// Background worker, its relay never yields anything
+struct Worker {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Worker {
+    const SERVICE_ID: ServiceId = "worker";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoRelay;
+}
+
+#[async_trait]
+impl ServiceCore for Worker {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        assert!(self.service_state.inbound_relay.recv().await.is_none());
+        assert_eq!(self.service_state.inbound_relay.capacity(), 0);
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct WorkerApp {
+    worker: ServiceHandle<Worker>,
+}
+
+#[test]
+fn message_less_service_has_no_relay() {
+    let overwatch =
+        OverwatchRunner::<WorkerApp>::run(WorkerAppServiceSettings { worker: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        handle
+            .status_watcher::<Worker>()
+            .await
+            .unwrap()
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            handle.relay::<Worker>().connect().await,
+            Err(RelayError::NoRelay {
+                service_id: "worker"
+            })
+        ));
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
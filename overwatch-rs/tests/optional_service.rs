@@ -0,0 +1,116 @@
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::{MockService, MockSettings};
+use overwatch_rs::overwatch::{Error, Overwatch, OverwatchRunner};
+use overwatch_rs::services::handle::ServiceHandle;
+use overwatch_rs::services::relay::{NoMessage, RelayError};
+use overwatch_rs::services::settings::OptionalSettings;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatusError;
+use overwatch_rs::services::{ServiceData, ServiceId};
+use std::time::Duration;
+
+struct Store;
+
+impl ServiceData for Store {
+    const SERVICE_ID: ServiceId = "store";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+struct Metrics;
+
+impl ServiceData for Metrics {
+    const SERVICE_ID: ServiceId = "metrics";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[derive(Services)]
+struct App {
+    store: ServiceHandle<MockService<Store>>,
+    metrics: Option<ServiceHandle<MockService<Metrics>>>,
+}
+
+fn run(metrics: OptionalSettings<MockSettings<NoMessage>>) -> Overwatch {
+    OverwatchRunner::<App>::run(
+        AppServiceSettings {
+            store: MockSettings::new(),
+            metrics,
+        },
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn enabled_optional_services_run() {
+    let overwatch = run(OptionalSettings::new(MockSettings::new()));
+    let handle = overwatch.handle().clone();
+    overwatch.spawn(async move {
+        handle
+            .relay_when_ready::<Metrics>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn disabled_optional_services_are_not_started() {
+    let overwatch = run(OptionalSettings::disabled(MockSettings::new()));
+    let handle = overwatch.handle().clone();
+    overwatch.spawn(async move {
+        handle
+            .relay_when_ready::<Store>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert!(matches!(
+            handle.relay::<Metrics>().connect().await,
+            Err(RelayError::ServiceDisabled {
+                service_id: "metrics"
+            })
+        ));
+        assert!(matches!(
+            handle.status_watcher::<Metrics>().await,
+            Err(ServiceStatusError::ServiceDisabled {
+                service_id: "metrics"
+            })
+        ));
+        assert!(matches!(
+            handle
+                .relay_when_ready::<Metrics>(Duration::from_secs(1))
+                .await,
+            Err(RelayError::ServiceDisabled { .. })
+        ));
+        assert!(matches!(
+            handle.start_service::<Metrics>().await,
+            Err(Error::ServiceDisabled {
+                service_id: "metrics"
+            })
+        ));
+        let topology = handle.topology().await.unwrap();
+        let metrics = topology
+            .services
+            .iter()
+            .find(|service| service.service_id == "metrics")
+            .unwrap();
+        assert_eq!(metrics.status, None);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn optional_services_are_enabled_by_default() {
+    let settings: OptionalSettings<u32> = serde_json::from_str(r#"{"settings": 3}"#).unwrap();
+    assert!(settings.enabled);
+    let settings: OptionalSettings<u32> =
+        serde_json::from_str(r#"{"enabled": false, "settings": 3}"#).unwrap();
+    assert!(!settings.enabled);
+}
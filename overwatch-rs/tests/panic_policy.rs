@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::{ContextConfig, PanicPolicy};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Number of times `init` panics before succeeding, and number of `init` calls so far
+type FragileSettings = (usize, Arc<AtomicUsize>);
+
+struct Fragile {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Fragile {
+    const SERVICE_ID: ServiceId = "fragile";
+    type Settings = FragileSettings;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Fragile {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        let (panics, inits) = service_state.settings_reader.get_updated_settings();
+        if inits.fetch_add(1, Ordering::SeqCst) < panics {
+            panic!("fragile init");
+        }
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+struct Steady {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Steady {
+    const SERVICE_ID: ServiceId = "steady";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Steady {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct FragileApp {
+    fragile: ServiceHandle<Fragile>,
+    steady: ServiceHandle<Steady>,
+}
+
+fn run(panics: usize, policy: PanicPolicy, expected: ServiceStatus) -> usize {
+    let inits = Arc::new(AtomicUsize::new(0));
+    let settings = FragileAppServiceSettings {
+        fragile: (panics, inits.clone()),
+        steady: (),
+    };
+    // nothing runs before waiting on the runtime, so no error event is missed
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let overwatch = OverwatchRunner::<FragileApp>::run_with_context_config(
+        settings,
+        Some(runtime),
+        ContextConfig::default().with_panic_policy(policy),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let mut errors = Box::pin(handle.error_events());
+
+    overwatch.spawn(async move {
+        for (service_id, status) in [
+            (Steady::SERVICE_ID, ServiceStatus::Running),
+            (Fragile::SERVICE_ID, expected.clone()),
+        ] {
+            handle
+                .status_watcher_for(service_id)
+                .await
+                .unwrap()
+                .wait_for(status, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+        }
+        if expected.is_failed() {
+            let event = errors.next().await.unwrap();
+            assert_eq!(event.service_id, Fragile::SERVICE_ID);
+            assert!(event.error.to_string().contains("fragile init"));
+        }
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+    inits.load(Ordering::SeqCst)
+}
+
+fn failed() -> ServiceStatus {
+    ServiceStatus::Failed("fragile init".to_owned())
+}
+
+#[test]
+fn panicking_service_is_marked_failed() {
+    assert_eq!(run(usize::MAX, PanicPolicy::MarkFailed, failed()), 1);
+}
+
+#[test]
+fn panicking_service_is_initialized_again() {
+    assert_eq!(
+        run(
+            2,
+            PanicPolicy::Restart { retries: 2 },
+            ServiceStatus::Running
+        ),
+        3
+    );
+    assert_eq!(run(2, PanicPolicy::Restart { retries: 1 }, failed()), 2);
+}
@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{PersistContext, ServiceState, StateOperator};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+type Reporter = UnboundedSender<(usize, PersistContext)>;
+
+#[derive(Clone)]
+struct Counter(usize);
+
+impl ServiceState for Counter {
+    type Settings = Reporter;
+    type Error = DynError;
+
+    fn from_settings(_settings: &Self::Settings) -> Result<Self, Self::Error> {
+        Ok(Self(0))
+    }
+}
+
+#[derive(Clone)]
+struct ReportingOperator(Reporter);
+
+#[async_trait]
+impl StateOperator for ReportingOperator {
+    type StateInput = Counter;
+    type LoadError = Infallible;
+
+    fn try_load(_settings: &Reporter) -> Result<Option<Self::StateInput>, Self::LoadError> {
+        Ok(None)
+    }
+
+    fn from_settings(settings: Reporter) -> Self {
+        Self(settings)
+    }
+
+    async fn run(&mut self, state: Self::StateInput, context: PersistContext) {
+        let _ = self.0.send((state.0, context));
+    }
+}
+
+/// Pushes a single state and then either panics or waits to be stopped
+struct Counting<const PANICS: bool> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const PANICS: bool> ServiceData for Counting<PANICS> {
+    const SERVICE_ID: ServiceId = if PANICS { "panicking" } else { "counting" };
+    type Settings = Reporter;
+    type State = Counter;
+    type StateOperator = ReportingOperator;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const PANICS: bool> ServiceCore for Counting<PANICS> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state.state_updater.update(Counter(1))?;
+        // let the update go through before leaving
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        if PANICS {
+            panic!("counting went wrong");
+        }
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct StoppingApp {
+    counting: ServiceHandle<Counting<false>>,
+}
+
+#[derive(Services)]
+struct PanickingApp {
+    panicking: ServiceHandle<Counting<true>>,
+}
+
+async fn final_flush(
+    reports: &mut tokio::sync::mpsc::UnboundedReceiver<(usize, PersistContext)>,
+) -> (usize, PersistContext) {
+    loop {
+        let report = reports.recv().await.unwrap();
+        if report.1.is_final() {
+            return report;
+        }
+    }
+}
+
+#[test]
+fn stopped_service_state_is_flushed() {
+    let (reporter, mut reports) = tokio::sync::mpsc::unbounded_channel();
+    let settings = StoppingAppServiceSettings { counting: reporter };
+    let overwatch = OverwatchRunner::<StoppingApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.stop_service::<Counting<false>>().await.unwrap();
+        assert_eq!(
+            final_flush(&mut reports).await,
+            (1, PersistContext::StopFlush)
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn panicking_service_state_is_flushed() {
+    let (reporter, mut reports) = tokio::sync::mpsc::unbounded_channel();
+    let settings = PanickingAppServiceSettings {
+        panicking: reporter,
+    };
+    let overwatch = OverwatchRunner::<PanickingApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        assert_eq!(
+            final_flush(&mut reports).await,
+            (1, PersistContext::PanicFlush)
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
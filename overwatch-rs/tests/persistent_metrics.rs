@@ -0,0 +1,158 @@
+#![cfg(feature = "persistent-metrics")]
+
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::EphemeralDir;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::persistent_metrics::{PersistedCounter, PersistentMetrics};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Tick;
+
+impl RelayMessage for Tick {}
+
+struct Ticked {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Ticked {
+    const SERVICE_ID: ServiceId = "ticked";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Tick;
+}
+
+#[async_trait]
+impl ServiceCore for Ticked {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let Self { mut service_state } = self;
+        service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        while service_state.inbound_relay.recv().await.is_some() {}
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct TickedApp {
+    ticked: ServiceHandle<Ticked>,
+}
+
+/// Run the application, sending it `ticks` messages before shutting it down
+fn run(path: &Path, ticks: usize) {
+    let metrics = PersistentMetrics::open(
+        path,
+        [
+            PersistedCounter::Restarts,
+            PersistedCounter::ProcessedMessages,
+            PersistedCounter::LastReady,
+        ],
+    )
+    .unwrap();
+    let overwatch = OverwatchRunner::<TickedApp>::run_with_context_config(
+        TickedAppServiceSettings { ticked: () },
+        None,
+        ContextConfig::default().with_persistent_metrics(metrics),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        handle
+            .status_watcher::<Ticked>()
+            .await
+            .unwrap()
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let relay = handle.relay::<Ticked>().connect().await.unwrap();
+        for _ in 0..ticks {
+            relay.send(Tick).await.unwrap();
+        }
+        // wait for the service to catch up before shutting down
+        while relay.queue_depth() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn counters_are_kept_between_runs() {
+    let dir = EphemeralDir::create().unwrap();
+    let path = dir.path().join("metrics.json");
+
+    run(&path, 3);
+    run(&path, 2);
+
+    let metrics = PersistentMetrics::open(&path, [PersistedCounter::ProcessedMessages]).unwrap();
+    let counters = metrics.counters("ticked");
+    assert_eq!(counters.processed_messages(), Some(5));
+    // not selected when reopened
+    assert_eq!(counters.restarts(), None);
+
+    let metrics = PersistentMetrics::open(
+        &path,
+        [PersistedCounter::Restarts, PersistedCounter::LastReady],
+    )
+    .unwrap();
+    let counters = metrics.counters("ticked");
+    assert_eq!(counters.restarts(), Some(1));
+    assert!(counters.last_ready().is_some());
+    dir.remove().unwrap();
+}
+
+#[test]
+fn counters_are_flushed_while_running() {
+    let dir = EphemeralDir::create().unwrap();
+    let path = dir.path().join("metrics.json");
+    let metrics = PersistentMetrics::open(&path, [PersistedCounter::ProcessedMessages])
+        .unwrap()
+        .with_flush_interval(Duration::from_millis(20));
+    let overwatch = OverwatchRunner::<TickedApp>::run_with_context_config(
+        TickedAppServiceSettings { ticked: () },
+        None,
+        ContextConfig::default().with_persistent_metrics(metrics),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let flushed = overwatch.block_on(async {
+        let relay = handle.relay::<Ticked>().connect().await.unwrap();
+        relay.send(Tick).await.unwrap();
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let reopened = PersistentMetrics::open(&path, [PersistedCounter::ProcessedMessages]);
+            let processed = reopened
+                .ok()
+                .and_then(|metrics| metrics.counters("ticked").processed_messages());
+            if processed == Some(1) {
+                return true;
+            }
+        }
+        false
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert!(flushed);
+    dir.remove().unwrap();
+}
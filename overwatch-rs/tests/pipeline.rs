@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::pipeline::{Pipeline, PipelineStage};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio::sync::mpsc;
+
+#[derive(Debug)]
+struct Number(usize);
+
+impl RelayMessage for Number {}
+
+/// Pipeline input, forwards `Number`s as they come
+struct Source {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Source {
+    const SERVICE_ID: ServiceId = "source";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Number;
+}
+
+impl PipelineStage for Source {
+    type Next = Doubler;
+}
+
+#[async_trait]
+impl ServiceCore for Source {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let mut downstream = self.service_state.downstream().await?;
+        while let Some(number) = self.service_state.inbound_relay.recv().await {
+            downstream.send(number).await.map_err(|(e, _)| e)?;
+        }
+        Ok(())
+    }
+}
+
+struct Doubler {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Doubler {
+    const SERVICE_ID: ServiceId = "doubler";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Number;
+}
+
+impl PipelineStage for Doubler {
+    type Next = Collector;
+}
+
+#[async_trait]
+impl ServiceCore for Doubler {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let mut downstream = self.service_state.downstream().await?;
+        while let Some(Number(n)) = self.service_state.inbound_relay.recv().await {
+            downstream.send(Number(n * 2)).await.map_err(|(e, _)| e)?;
+        }
+        Ok(())
+    }
+}
+
+/// Last stage, reports what it gets
+struct Collector {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Collector {
+    const SERVICE_ID: ServiceId = "collector";
+    type Settings = mpsc::Sender<usize>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Number;
+}
+
+#[async_trait]
+impl ServiceCore for Collector {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let reporter = self.service_state.settings_reader.get_updated_settings();
+        while let Some(Number(n)) = self.service_state.inbound_relay.recv().await {
+            reporter.send(n).await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct Etl {
+    source: ServiceHandle<Source>,
+    doubler: ServiceHandle<Doubler>,
+    collector: ServiceHandle<Collector>,
+}
+
+#[test]
+fn pipeline_chains_stages() {
+    let (reporter, mut reports) = mpsc::channel(8);
+    let settings = EtlServiceSettings {
+        source: (),
+        doubler: (),
+        collector: reporter,
+    };
+    let overwatch = OverwatchRunner::<Etl>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let pipeline = Pipeline::<Source, Source>::new(handle.clone())
+            .then::<Doubler>()
+            .then::<Collector>();
+        assert_eq!(pipeline.stages(), ["source", "doubler", "collector"]);
+
+        let mut input = pipeline.connect().await.unwrap();
+        for n in 1..=3 {
+            input.send(Number(n)).await.unwrap();
+        }
+        let mut outputs = Vec::new();
+        for _ in 0..3 {
+            outputs.push(reports.recv().await.unwrap());
+        }
+        assert_eq!(outputs, [2, 4, 6]);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
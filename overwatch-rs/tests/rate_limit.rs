@@ -0,0 +1,89 @@
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::{MockService, MockSettings};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::ServiceHandle;
+use overwatch_rs::services::rate_limit::RateLimit;
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceData, ServiceId};
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+struct Event(usize);
+
+impl RelayMessage for Event {}
+
+struct Throttled;
+
+impl ServiceData for Throttled {
+    const SERVICE_ID: ServiceId = "throttled";
+    const RATE_LIMIT: Option<RateLimit> = Some(RateLimit::new(20, 2));
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Event;
+}
+
+struct Rejecting;
+
+impl ServiceData for Rejecting {
+    const SERVICE_ID: ServiceId = "rejecting";
+    const RATE_LIMIT: Option<RateLimit> = Some(RateLimit::new(1, 2).rejecting());
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Event;
+}
+
+type ThrottledService = MockService<Throttled>;
+type RejectingService = MockService<Rejecting>;
+
+#[derive(Services)]
+struct App {
+    throttled: ServiceHandle<ThrottledService>,
+    rejecting: ServiceHandle<RejectingService>,
+}
+
+#[test]
+fn messages_over_the_rate_limit_are_delayed_or_rejected() {
+    let throttled = MockSettings::new();
+    let rejecting = MockSettings::new();
+    let settings = AppServiceSettings {
+        throttled: throttled.clone(),
+        rejecting: rejecting.clone(),
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let relay = handle
+            .relay_when_ready::<Throttled>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        for i in 0..6 {
+            relay.send(Event(i)).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(throttled.received().len() < 6);
+        assert_eq!(throttled.wait_received(6).await.len(), 6);
+        let metrics = handle.metrics();
+        let relay_metrics = metrics.service("throttled").unwrap().relay;
+        assert!(relay_metrics.throttled > 0);
+        assert_eq!(relay_metrics.rate_limited, 0);
+
+        let relay = handle
+            .relay_when_ready::<Rejecting>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        for i in 0..5 {
+            relay.send(Event(i)).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(rejecting.received(), ["Event(0)", "Event(1)"]);
+        let metrics = handle.metrics();
+        assert_eq!(metrics.service("rejecting").unwrap().relay.rate_limited, 3);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
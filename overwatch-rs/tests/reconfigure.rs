@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::commands::RestartMode;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, ServiceState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+#[derive(Clone, Debug)]
+struct ReconfigureSettings {
+    name: String,
+    reporter: broadcast::Sender<(String, String)>,
+}
+
+#[derive(Clone)]
+struct NameState(String);
+
+impl ServiceState for NameState {
+    type Settings = ReconfigureSettings;
+    type Error = DynError;
+
+    fn from_settings(settings: &Self::Settings) -> Result<Self, Self::Error> {
+        Ok(Self(settings.name.clone()))
+    }
+}
+
+struct ReconfigurableService {
+    service_state: ServiceStateHandle<Self>,
+    initial_state: NameState,
+}
+
+impl ServiceData for ReconfigurableService {
+    const SERVICE_ID: ServiceId = "reconfigurable";
+    type Settings = ReconfigureSettings;
+    type State = NameState;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for ReconfigurableService {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self {
+            service_state,
+            initial_state,
+        })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let settings = self.service_state.settings_reader.get_updated_settings();
+        settings
+            .reporter
+            .send((settings.name.clone(), self.initial_state.0))?;
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct ReconfigureApp {
+    reconfigurable: ServiceHandle<ReconfigurableService>,
+}
+
+#[test]
+fn reconfigure_restarts_service_with_new_settings() {
+    let (reporter, mut receiver) = broadcast::channel(4);
+    let settings = ReconfigureAppServiceSettings {
+        reconfigurable: ReconfigureSettings {
+            name: "old".to_string(),
+            reporter: reporter.clone(),
+        },
+    };
+    let overwatch = OverwatchRunner::<ReconfigureApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        assert_eq!(
+            receiver.recv().await.unwrap(),
+            ("old".to_string(), "old".to_string())
+        );
+        handle
+            .reconfigure_service::<ReconfigurableService>(
+                ReconfigureSettings {
+                    name: "new".to_string(),
+                    reporter,
+                },
+                RestartMode::Graceful {
+                    timeout: Duration::from_millis(50),
+                },
+            )
+            .await
+            .expect("Service to be reconfigured");
+        // settings and state both come from the new settings
+        assert_eq!(
+            receiver.recv().await.unwrap(),
+            ("new".to_string(), "new".to_string())
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn reconfigurations_of_a_service_are_serialized() {
+    let (reporter, mut receiver) = broadcast::channel(4);
+    let settings_named = |name: &str| ReconfigureSettings {
+        name: name.to_string(),
+        reporter: reporter.clone(),
+    };
+    let settings = ReconfigureAppServiceSettings {
+        reconfigurable: settings_named("old"),
+    };
+    let overwatch = OverwatchRunner::<ReconfigureApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+    let (first, second) = (settings_named("first"), settings_named("second"));
+
+    overwatch.spawn(async move {
+        assert_eq!(receiver.recv().await.unwrap().0, "old");
+        // the service ignores shutdown requests, so the graceful restart waits the whole timeout
+        let graceful = tokio::spawn({
+            let handle = handle.clone();
+            async move {
+                handle
+                    .reconfigure_service::<ReconfigurableService>(
+                        first,
+                        RestartMode::Graceful {
+                            timeout: Duration::from_millis(300),
+                        },
+                    )
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let immediate = tokio::spawn({
+            let handle = handle.clone();
+            async move {
+                handle
+                    .reconfigure_service::<ReconfigurableService>(second, RestartMode::Immediate)
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(handle.lifecycle_queue_depth::<ReconfigurableService>(), 1);
+        assert!(!immediate.is_finished());
+        // other commands are not blocked meanwhile
+        assert!(handle
+            .status_watcher_with_timeout::<ReconfigurableService>(Duration::from_millis(100))
+            .await
+            .is_ok());
+
+        graceful
+            .await
+            .unwrap()
+            .expect("First reconfiguration to succeed");
+        immediate
+            .await
+            .unwrap()
+            .expect("Second reconfiguration to succeed");
+        // the first instance can be replaced before it gets to report, the last one can't
+        while receiver.recv().await.unwrap().0 != "second" {}
+        assert_eq!(handle.lifecycle_queue_depth::<ReconfigurableService>(), 0);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
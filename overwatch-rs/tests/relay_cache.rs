@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::handle::OverwatchHandle;
+use overwatch_rs::overwatch::latency::CommandKind;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{OutboundRelay, RelayMessage};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct Ping(oneshot::Sender<()>);
+
+impl RelayMessage for Ping {}
+
+struct Ponger {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Ponger {
+    const SERVICE_ID: ServiceId = "ponger";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Ping;
+}
+
+#[async_trait]
+impl ServiceCore for Ponger {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        while let Some(Ping(reply)) = self.service_state.inbound_relay.recv().await {
+            let _ = reply.send(());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    ponger: ServiceHandle<Ponger>,
+}
+
+fn relay_requests(handle: &OverwatchHandle) -> usize {
+    handle
+        .command_latency(CommandKind::Relay)
+        .map_or(0, |latency| latency.samples)
+}
+
+#[test]
+fn relays_are_cached_until_the_service_restarts() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { ponger: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let ping = |relay: OutboundRelay<Ping>| async move {
+            let (reply, replied) = oneshot::channel();
+            relay.send(Ping(reply)).await.unwrap();
+            replied.await.unwrap();
+        };
+        for _ in 0..3 {
+            ping(handle.relay::<Ponger>().connect().await.unwrap()).await;
+        }
+        assert_eq!(relay_requests(&handle), 1);
+
+        handle.stop_service::<Ponger>().await.unwrap();
+        handle.start_service::<Ponger>().await.unwrap();
+        ping(handle.relay::<Ponger>().connect().await.unwrap()).await;
+        assert_eq!(relay_requests(&handle), 2);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::latency::CommandKind;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{NoMessage, RelayError};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio::sync::mpsc;
+
+/// Outcome of reaching the vault relay, and of subscribing to its broadcasts
+type Reporter = mpsc::Sender<(ServiceId, Result<(), String>, Result<(), String>)>;
+
+fn denied(e: RelayError) -> String {
+    match e {
+        RelayError::Unauthorized { from, to } => format!("{from}->{to}"),
+        e => e.to_string(),
+    }
+}
+
+struct Vault;
+
+impl ServiceData for Vault {
+    const SERVICE_ID: ServiceId = "vault";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Vault {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+/// Tries to reach the vault and reports the outcome
+struct Caller<const TRUSTED: bool> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const TRUSTED: bool> ServiceData for Caller<TRUSTED> {
+    const SERVICE_ID: ServiceId = if TRUSTED { "trusted" } else { "plugin" };
+    type Settings = Reporter;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const TRUSTED: bool> ServiceCore for Caller<TRUSTED> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let reporter = self.service_state.settings_reader.get_updated_settings();
+        let handle = &self.service_state.overwatch_handle;
+        let relay = handle
+            .relay::<Vault>()
+            .connect()
+            .await
+            .map(|_| ())
+            .map_err(denied);
+        let broadcast = handle
+            .broadcast_for(Vault::SERVICE_ID)
+            .await
+            .map(|_| ())
+            .map_err(denied);
+        reporter.send((Self::SERVICE_ID, relay, broadcast)).await?;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct PolicyApp {
+    vault: ServiceHandle<Vault>,
+    trusted: ServiceHandle<Caller<true>>,
+    plugin: ServiceHandle<Caller<false>>,
+}
+
+#[test]
+fn relay_policy_restricts_services() {
+    let (reporter, mut reports) = mpsc::channel(2);
+    let settings = PolicyAppServiceSettings {
+        vault: (),
+        trusted: reporter.clone(),
+        plugin: reporter,
+    };
+    let config = ContextConfig::default()
+        .with_relay_policy(|from: ServiceId, to: ServiceId| to != "vault" || from != "plugin");
+    let overwatch =
+        OverwatchRunner::<PolicyApp>::run_with_context_config(settings, None, config).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut results = vec![reports.recv().await.unwrap(), reports.recv().await.unwrap()];
+        results.sort();
+        assert_eq!(
+            results,
+            [
+                (
+                    "plugin",
+                    Err("plugin->vault".to_string()),
+                    Err("plugin->vault".to_string())
+                ),
+                ("trusted", Ok(()), Ok(())),
+            ]
+        );
+        // the application itself is not restricted
+        assert!(handle.relay::<Vault>().connect().await.is_ok());
+        assert!(handle.broadcast_for(Vault::SERVICE_ID).await.is_ok());
+        // commands are handled in order, so the last relay is accounted for once this returns
+        handle.status_watcher::<Vault>().await.unwrap();
+        let latency = handle.command_latency(CommandKind::Relay).unwrap();
+        assert_eq!(latency.samples, 3);
+        assert!(latency.p50 <= latency.p99);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,269 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::handle::OverwatchHandle;
+use overwatch_rs::overwatch::{
+    AnySettings, Error, OverwatchRunner, Services, ServicesLifeCycleHandle,
+};
+use overwatch_rs::services::broadcast::BroadcastResult;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle, ServiceTask};
+use overwatch_rs::services::life_cycle::StopReason;
+use overwatch_rs::services::pool::Scaled;
+use overwatch_rs::services::relay::{
+    AnyMessage, RelayError, RelayMessage, RelayResult, RetryPolicy,
+};
+use overwatch_rs::services::state::{NoOperator, NoState, SnapshotRequest};
+use overwatch_rs::services::status::{ServiceStatus, ServiceStatusError, ServiceStatusResult};
+use overwatch_rs::services::supervisor::{Backoff, RestartPolicy};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Ping;
+
+impl RelayMessage for Ping {}
+
+struct Late {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Late {
+    const SERVICE_ID: ServiceId = "late";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Ping;
+}
+
+#[async_trait]
+impl ServiceCore for Late {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        self.service_state.status_handle.updater().running();
+        while self.service_state.inbound_relay.recv().await.is_some() {}
+        Ok(())
+    }
+}
+
+/// Never attached to [`LateStart`]
+struct Missing;
+
+impl ServiceData for Missing {
+    const SERVICE_ID: ServiceId = "missing";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Ping;
+}
+
+/// Leaves its service uninitialized, without a relay, until it is started on request
+struct LateStart {
+    late: ServiceHandle<Late>,
+}
+
+impl Services for LateStart {
+    type Settings = ();
+
+    const SERVICES_IDS: &'static [ServiceId] = &[Late::SERVICE_ID];
+
+    fn new(_settings: Self::Settings, overwatch_handle: OverwatchHandle) -> Result<Self, DynError> {
+        Ok(Self {
+            late: ServiceHandle::new((), overwatch_handle)?,
+        })
+    }
+
+    fn start(&mut self, service_id: ServiceId) -> Result<(), Error> {
+        match service_id {
+            Late::SERVICE_ID => self.late.start().map(|_| ()),
+            service_id => Err(Error::Unavailable { service_id }),
+        }
+    }
+
+    fn start_all_except(
+        &mut self,
+        _stopped: &[ServiceId],
+    ) -> Result<ServicesLifeCycleHandle, Error> {
+        Ok(ServicesLifeCycleHandle::empty())
+    }
+
+    fn stop(&mut self, service_id: ServiceId, reason: StopReason) -> Result<(), Error> {
+        match service_id {
+            Late::SERVICE_ID => {
+                self.late.stop(reason);
+                Ok(())
+            }
+            service_id => Err(Error::Unavailable { service_id }),
+        }
+    }
+
+    fn request_relay(&mut self, service_id: ServiceId) -> RelayResult {
+        match service_id {
+            Late::SERVICE_ID => Ok(Box::new(self.late.request_relay()?) as AnyMessage),
+            service_id => Err(RelayError::Unavailable { service_id }),
+        }
+    }
+
+    fn request_status_watcher(&self, service_id: ServiceId) -> ServiceStatusResult {
+        match service_id {
+            Late::SERVICE_ID => Ok(self.late.status_watcher()),
+            service_id => Err(ServiceStatusError::Unavailable { service_id }),
+        }
+    }
+
+    fn is_running(&self, service_id: ServiceId) -> Result<bool, Error> {
+        match service_id {
+            Late::SERVICE_ID => Ok(self.late.is_running()),
+            service_id => Err(Error::Unavailable { service_id }),
+        }
+    }
+
+    fn request_broadcast(&self, service_id: ServiceId) -> BroadcastResult {
+        Err(RelayError::Unavailable { service_id })
+    }
+
+    fn update_settings(&mut self, _settings: Self::Settings) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn update_service_settings(
+        &mut self,
+        service_id: ServiceId,
+        _settings: AnySettings,
+    ) -> Result<(), Error> {
+        Err(Error::Unavailable { service_id })
+    }
+
+    fn failover(&mut self, service_id: ServiceId) -> Result<(), Error> {
+        Err(Error::Unavailable { service_id })
+    }
+
+    fn scale(&mut self, service_id: ServiceId, _members: usize) -> Result<Scaled, Error> {
+        Err(Error::NotAPool { service_id })
+    }
+
+    fn teardown(&mut self) -> Vec<ServiceTask> {
+        self.late.take_task().into_iter().collect()
+    }
+
+    fn current_settings(&self, _settings: &Self::Settings) -> Self::Settings {}
+
+    fn request_snapshots(&self) -> Vec<SnapshotRequest> {
+        self.late.request_snapshot().into_iter().collect()
+    }
+}
+
+/// Number of times the flaky service main loop was run
+static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Fails the first time it runs, then takes messages like any other service
+struct Flaky {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Flaky {
+    const SERVICE_ID: ServiceId = "flaky";
+    const RESTART_POLICY: RestartPolicy =
+        RestartPolicy::OnFailure(Backoff::fixed(Duration::from_millis(200)));
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Ping;
+}
+
+#[async_trait]
+impl ServiceCore for Flaky {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        if FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err("flaky".into());
+        }
+        self.service_state.status_handle.updater().running();
+        while self.service_state.inbound_relay.recv().await.is_some() {}
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct Supervised {
+    flaky: ServiceHandle<Flaky>,
+}
+
+#[test]
+fn relays_are_retried_until_the_service_starts() {
+    let overwatch = OverwatchRunner::<LateStart>::run((), None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.block_on(async {
+        let status = handle.status_watcher::<Late>().await.unwrap();
+        assert_eq!(status.current(), ServiceStatus::Uninitialized);
+        assert!(handle.relay::<Late>().connect().await.is_err());
+
+        let starter = handle.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            starter.start_service::<Late>().await.unwrap();
+        });
+        let relay = handle
+            .relay_with_retry::<Late>(RetryPolicy::new(10, Duration::from_millis(20)))
+            .await
+            .unwrap();
+        relay.send(Ping).await.unwrap();
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+}
+
+#[test]
+fn unknown_services_are_not_retried() {
+    let overwatch = OverwatchRunner::<LateStart>::run((), None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    let policy = RetryPolicy::new(5, Duration::from_secs(1));
+    let started = Instant::now();
+    let relay = overwatch.block_on(handle.relay_with_retry::<Missing>(policy));
+    assert!(matches!(
+        relay,
+        Err(RelayError::Unavailable {
+            service_id: "missing"
+        })
+    ));
+    assert!(started.elapsed() < policy.initial_delay);
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+}
+
+#[test]
+fn relays_are_retried_while_the_service_restarts() {
+    let overwatch =
+        OverwatchRunner::<Supervised>::run(SupervisedServiceSettings { flaky: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.block_on(async {
+        let mut status = handle.status_watcher::<Flaky>().await.unwrap();
+        status
+            .wait_until(|status| *status == ServiceStatus::Restarting, None)
+            .await
+            .unwrap();
+        let relay = handle
+            .relay_with_retry::<Flaky>(RetryPolicy::new(10, Duration::from_millis(20)))
+            .await
+            .unwrap();
+        relay.send(Ping).await.unwrap();
+        assert_eq!(FLAKY_RUNS.load(Ordering::SeqCst), 2);
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+}
@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{RelayError, RelayMessage};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Ping;
+
+impl RelayMessage for Ping {}
+
+/// Reports it is running after `DELAY` milliseconds, or that it failed if `FAIL`
+struct Slow<const DELAY: u64, const FAIL: bool> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const DELAY: u64, const FAIL: bool> ServiceData for Slow<DELAY, FAIL> {
+    const SERVICE_ID: ServiceId = if FAIL { "broken" } else { "slow" };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Ping;
+}
+
+#[async_trait]
+impl<const DELAY: u64, const FAIL: bool> ServiceCore for Slow<DELAY, FAIL> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        tokio::time::sleep(Duration::from_millis(DELAY)).await;
+        let status = self.service_state.status_handle.updater();
+        if FAIL {
+            status.failed("no disk");
+        } else {
+            status.running();
+        }
+        while self.service_state.inbound_relay.recv().await.is_some() {}
+        Ok(())
+    }
+}
+
+type SlowService = Slow<100, false>;
+type BrokenService = Slow<0, true>;
+
+#[derive(Services)]
+struct App {
+    slow: ServiceHandle<SlowService>,
+    broken: ServiceHandle<BrokenService>,
+}
+
+#[test]
+fn relays_are_returned_once_services_are_ready() {
+    let settings = AppServiceSettings {
+        slow: (),
+        broken: (),
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let too_early = handle
+            .relay_when_ready::<SlowService>(Duration::from_millis(10))
+            .await;
+        assert!(matches!(too_early, Err(RelayError::Timeout { service_id: "slow" })));
+
+        let relay = handle
+            .relay_when_ready::<SlowService>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        let status = handle.status_watcher::<SlowService>().await.unwrap();
+        assert_eq!(status.current(), ServiceStatus::Running);
+        relay.send(Ping).await.unwrap();
+
+        let failed = handle
+            .relay_when_ready::<BrokenService>(Duration::from_secs(1))
+            .await;
+        assert!(
+            matches!(&failed, Err(RelayError::Failed { service_id: "broken", reason }) if reason == "no disk")
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
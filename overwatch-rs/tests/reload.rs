@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::reload::ReloadEvent;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct CurrentLevel(oneshot::Sender<u32>);
+
+impl RelayMessage for CurrentLevel {}
+
+/// Answers with its current settings
+struct Logger {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Logger {
+    const SERVICE_ID: ServiceId = "logger";
+    type Settings = u32;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = CurrentLevel;
+}
+
+#[async_trait]
+impl ServiceCore for Logger {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        while let Some(CurrentLevel(reply)) = self.service_state.inbound_relay.recv().await {
+            let _ = reply.send(self.service_state.settings_reader.get_updated_settings());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct Daemon {
+    logger: ServiceHandle<Logger>,
+}
+
+fn load(level: &str) -> Result<DaemonServiceSettings, DynError> {
+    Ok(DaemonServiceSettings {
+        logger: level.parse()?,
+    })
+}
+
+#[test]
+fn reload_applies_valid_settings_only() {
+    let overwatch = OverwatchRunner::<Daemon>::run(load("1").unwrap(), None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut events = Box::pin(handle.reload_events());
+        let logger = handle.relay::<Logger>().connect().await.unwrap();
+        let level = || async {
+            let (sender, receiver) = oneshot::channel();
+            logger.send(CurrentLevel(sender)).await.unwrap();
+            receiver.await.unwrap()
+        };
+
+        assert!(handle
+            .reload_settings::<Daemon>(&|| load("2"))
+            .await
+            .is_ok());
+        assert!(matches!(events.next().await, Some(ReloadEvent::Applied)));
+        assert_eq!(level().await, 2);
+
+        assert!(handle
+            .reload_settings::<Daemon>(&|| load("verbose"))
+            .await
+            .is_err());
+        assert!(matches!(events.next().await, Some(ReloadEvent::Failed(_))));
+        assert_eq!(level().await, 2);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[cfg(all(unix, feature = "signal"))]
+#[test]
+fn sighup_reloads_settings() {
+    let overwatch = OverwatchRunner::<Daemon>::run(load("1").unwrap(), None).unwrap();
+    let handle = overwatch.handle().clone();
+    handle.reload_on_sighup::<Daemon>(|| load("3")).unwrap();
+
+    overwatch.spawn(async move {
+        let mut events = Box::pin(handle.reload_events());
+        let status = std::process::Command::new("kill")
+            .args(["-HUP", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert!(matches!(events.next().await, Some(ReloadEvent::Applied)));
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,255 @@
+#![cfg(feature = "remote")]
+
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::testing::{MockService, MockSettings};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::ServiceHandle;
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::remote::{
+    RemoteAddress, RemoteRelay, RemoteRelayListener, MAX_FRAME_LENGTH,
+};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceData, ServiceId};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum StoreMessage {
+    Put(String, u32),
+}
+
+impl RelayMessage for StoreMessage {}
+
+struct Store;
+
+impl ServiceData for Store {
+    const SERVICE_ID: ServiceId = "store";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = StoreMessage;
+}
+
+type StoreService = MockService<Store>;
+
+/// Process running the store
+#[derive(Services)]
+struct Backend {
+    store: ServiceHandle<StoreService>,
+}
+
+struct Api;
+
+impl ServiceData for Api {
+    const SERVICE_ID: ServiceId = "api";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = StoreMessage;
+}
+
+type ApiService = MockService<Api>;
+
+/// Process relaying to the store of the backend
+#[derive(Services)]
+struct Frontend {
+    api: ServiceHandle<ApiService>,
+}
+
+fn relay_to_remote_store(address: RemoteAddress) {
+    let store = MockSettings::new();
+    let backend = OverwatchRunner::<Backend>::run(
+        BackendServiceSettings {
+            store: store.clone(),
+        },
+        None,
+    )
+    .unwrap();
+    let backend_handle = backend.handle().clone();
+    let address = backend.block_on(async {
+        backend_handle
+            .relay_when_ready::<Store>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        let server = RemoteRelayListener::new(address)
+            .expose::<Store>()
+            .serve(&backend_handle)
+            .await
+            .unwrap();
+        server.address().clone()
+    });
+
+    let frontend = OverwatchRunner::<Frontend>::run(
+        FrontendServiceSettings {
+            api: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = frontend.handle().clone();
+    frontend.spawn(async move {
+        let relay = RemoteRelay::new(address)
+            .connect::<Store>(&handle)
+            .await
+            .unwrap();
+        relay
+            .send(StoreMessage::Put("a".to_owned(), 1))
+            .await
+            .unwrap();
+        relay
+            .send(StoreMessage::Put("b".to_owned(), 2))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.wait_received(2).await,
+            [r#"Put("a", 1)"#, r#"Put("b", 2)"#]
+        );
+        handle.shutdown().await;
+        backend_handle.shutdown().await;
+    });
+    frontend.wait_finished();
+    backend.wait_finished();
+}
+
+#[test]
+fn messages_are_relayed_over_tcp() {
+    relay_to_remote_store(RemoteAddress::Tcp("127.0.0.1:0".parse().unwrap()));
+}
+
+#[cfg(unix)]
+#[test]
+fn messages_are_relayed_over_unix_sockets() {
+    use overwatch_rs::overwatch::testing::EphemeralDir;
+
+    let dir = EphemeralDir::create().unwrap();
+    relay_to_remote_store(RemoteAddress::Unix(dir.path().join("store.sock")));
+    dir.remove().unwrap();
+}
+
+#[test]
+fn connections_sending_overlong_frames_are_closed() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let backend = OverwatchRunner::<Backend>::run(
+        BackendServiceSettings {
+            store: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = backend.handle().clone();
+    let closed = backend.block_on(async {
+        let server = RemoteRelayListener::new(RemoteAddress::Tcp("127.0.0.1:0".parse().unwrap()))
+            .expose::<Store>()
+            .serve(&handle)
+            .await
+            .unwrap();
+        let RemoteAddress::Tcp(address) = server.address().clone() else {
+            unreachable!("listening on TCP");
+        };
+        let mut connection = tokio::net::TcpStream::connect(address).await.unwrap();
+        // the frame is refused from its length, before its content is buffered
+        let length = u32::try_from(MAX_FRAME_LENGTH + 1).unwrap();
+        connection.write_all(&length.to_be_bytes()).await.unwrap();
+        let mut buffer = [0; 1];
+        tokio::time::timeout(Duration::from_secs(1), connection.read(&mut buffer))
+            .await
+            .map(|read| read.map_or(true, |read| read == 0))
+    });
+    backend.block_on(handle.shutdown());
+    backend.wait_finished();
+    assert_eq!(closed, Ok(true));
+}
+
+/// Message whose serialization always fails
+#[derive(Debug)]
+struct Unserializable;
+
+impl Serialize for Unserializable {
+    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("not serializable"))
+    }
+}
+
+impl RelayMessage for Unserializable {}
+
+struct Sink;
+
+impl ServiceData for Sink {
+    const SERVICE_ID: ServiceId = "sink";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Unserializable;
+}
+
+#[test]
+fn unserializable_messages_are_reported() {
+    use futures::StreamExt;
+
+    let backend = OverwatchRunner::<Backend>::run(
+        BackendServiceSettings {
+            store: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = backend.handle().clone();
+    let reported = backend.block_on(async {
+        let server = RemoteRelayListener::new(RemoteAddress::Tcp("127.0.0.1:0".parse().unwrap()))
+            .serve(&handle)
+            .await
+            .unwrap();
+        let mut errors = Box::pin(handle.error_events());
+        let relay = RemoteRelay::new(server.address().clone())
+            .connect::<Sink>(&handle)
+            .await
+            .unwrap();
+        relay.send(Unserializable).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), errors.next())
+            .await
+            .ok()
+            .flatten()
+            .map(|event| (event.service_id, event.error.to_string()))
+    });
+    backend.block_on(handle.shutdown());
+    backend.wait_finished();
+    assert_eq!(
+        reported,
+        Some((
+            "sink",
+            "remote message couldn't be serialized: not serializable".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn non_loopback_addresses_are_refused_unless_allowed() {
+    let backend = OverwatchRunner::<Backend>::run(
+        BackendServiceSettings {
+            store: MockSettings::new(),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = backend.handle().clone();
+    let (refused, allowed) = backend.block_on(async {
+        let address = RemoteAddress::Tcp("0.0.0.0:0".parse().unwrap());
+        let refused = RemoteRelayListener::new(address.clone())
+            .expose::<StoreService>()
+            .serve(&handle)
+            .await
+            .is_err();
+        let allowed = RemoteRelayListener::new(address)
+            .expose::<StoreService>()
+            .allow_non_loopback()
+            .serve(&handle)
+            .await
+            .is_ok();
+        (refused, allowed)
+    });
+    backend.block_on(handle.shutdown());
+    backend.wait_finished();
+    assert!(refused);
+    assert!(allowed);
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{RelayError, RelayMessage, RpcRequest};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Debug)]
+enum StoreMessage {
+    Get(RpcRequest<&'static str, Option<u32>>),
+    /// Never replied to, the request is kept pending if `true` and dropped otherwise
+    Ignored(RpcRequest<bool, ()>),
+}
+
+impl RelayMessage for StoreMessage {}
+
+impl From<RpcRequest<&'static str, Option<u32>>> for StoreMessage {
+    fn from(request: RpcRequest<&'static str, Option<u32>>) -> Self {
+        Self::Get(request)
+    }
+}
+
+impl From<RpcRequest<bool, ()>> for StoreMessage {
+    fn from(request: RpcRequest<bool, ()>) -> Self {
+        Self::Ignored(request)
+    }
+}
+
+struct Store {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Store {
+    const SERVICE_ID: ServiceId = "store";
+    type Settings = HashMap<&'static str, u32>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = StoreMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Store {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let entries = self.service_state.settings_reader.get_updated_settings();
+        let mut pending = Vec::new();
+        while let Some(message) = self.service_state.inbound_relay.recv().await {
+            match message {
+                StoreMessage::Get(RpcRequest { request, reply }) => {
+                    let _ = reply.send(entries.get(request).copied());
+                }
+                StoreMessage::Ignored(RpcRequest {
+                    request: keep,
+                    reply,
+                }) => {
+                    if keep {
+                        pending.push(reply);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct StoreApp {
+    store: ServiceHandle<Store>,
+}
+
+#[test]
+fn requests_get_typed_replies() {
+    let overwatch = OverwatchRunner::<StoreApp>::run(
+        StoreAppServiceSettings {
+            store: HashMap::from([("answer", 42)]),
+        },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let store = handle
+            .relay::<Store>()
+            .connect_rpc::<&'static str, Option<u32>>()
+            .await
+            .unwrap()
+            .with_timeout(Duration::from_secs(1));
+        assert_eq!(store.ask("answer").await.unwrap(), Some(42));
+        assert_eq!(store.ask("question").await.unwrap(), None);
+
+        let ignored = handle
+            .relay::<Store>()
+            .connect_rpc::<bool, ()>()
+            .await
+            .unwrap();
+        assert!(matches!(
+            ignored.ask(false).await,
+            Err(RelayError::NoReply {
+                service_id: "store"
+            })
+        ));
+        assert!(matches!(
+            ignored
+                .ask_with_timeout(true, Duration::from_millis(20))
+                .await,
+            Err(RelayError::ReplyTimeout { .. })
+        ));
+
+        let outbound = handle.relay::<Store>().connect().await.unwrap();
+        assert_eq!(outbound.ask("answer").await.unwrap(), Some(42));
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,255 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::sequence::{Sequence, SequenceError};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const A: u8 = 0;
+const B: u8 = 1;
+const C: u8 = 2;
+const D: u8 = 3;
+const SILENT: u8 = 4;
+
+/// Reports `Running` right away unless it is the silent one
+struct Idle<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Idle<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        A => "a",
+        B => "b",
+        C => "c",
+        D => "d",
+        _ => "silent",
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Idle<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        if ID != SILENT {
+            self.service_state
+                .status_handle
+                .updater()
+                .update(ServiceStatus::Running);
+        }
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    a: ServiceHandle<Idle<A>>,
+    b: ServiceHandle<Idle<B>>,
+    c: ServiceHandle<Idle<C>>,
+    d: ServiceHandle<Idle<D>>,
+    silent: ServiceHandle<Idle<SILENT>>,
+}
+
+fn settings() -> AppServiceSettings {
+    AppServiceSettings {
+        a: (),
+        b: (),
+        c: (),
+        d: (),
+        silent: (),
+    }
+}
+
+#[test]
+fn sequence_steps_run_in_order() {
+    let overwatch = OverwatchRunner::<App>::run(settings(), None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        handle.stop_service::<Idle<A>>().await.unwrap();
+        let a = handle.status_watcher::<Idle<A>>().await.unwrap();
+        assert_eq!(a.current(), ServiceStatus::Stopped);
+
+        let sequence = Sequence::new()
+            .start::<Idle<A>>()
+            .wait_ready::<Idle<A>>()
+            .start_group([Idle::<B>::SERVICE_ID, Idle::<C>::SERVICE_ID])
+            .stop::<Idle<D>>();
+        assert_eq!(sequence.steps().len(), 4);
+        handle.run_sequence(&sequence).await.unwrap();
+
+        assert_eq!(a.current(), ServiceStatus::Running);
+        let d = handle.status_watcher::<Idle<D>>().await.unwrap();
+        assert_eq!(d.current(), ServiceStatus::Stopped);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn sequence_stops_at_a_service_not_getting_ready() {
+    let overwatch = OverwatchRunner::<App>::run(settings(), None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let sequence = Sequence::new()
+            .wait_ready::<Idle<SILENT>>()
+            .stop::<Idle<D>>()
+            .with_ready_timeout(Duration::from_millis(100));
+        let result = handle.run_sequence(&sequence).await;
+        assert!(matches!(
+            result,
+            Err(SequenceError::NotReady {
+                step: 0,
+                service_id: "silent",
+                ..
+            })
+        ));
+        // the steps after the failing one are not run
+        let mut d = handle.status_watcher::<Idle<D>>().await.unwrap();
+        assert!(d
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .is_ok());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Only reports `Running` from its second run on, like a service whose dependency is not up
+/// yet at boot
+struct Flaky {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Flaky {
+    const SERVICE_ID: ServiceId = "flaky";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Flaky {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        if FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) > 0 {
+            self.service_state
+                .status_handle
+                .updater()
+                .update(ServiceStatus::Running);
+        }
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct FlakyApp {
+    flaky: ServiceHandle<Flaky>,
+}
+
+#[test]
+fn wait_ready_step_is_retried() {
+    let overwatch =
+        OverwatchRunner::<FlakyApp>::run(FlakyAppServiceSettings { flaky: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let sequence = Sequence::new()
+            .wait_ready::<Flaky>()
+            .retry(2, Duration::from_millis(10))
+            .with_ready_timeout(Duration::from_millis(100));
+        handle.run_sequence(&sequence).await.unwrap();
+        assert_eq!(FLAKY_RUNS.load(Ordering::SeqCst), 2);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+static ONESHOT_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Main loop ending right away, without being stopped
+struct Oneshot;
+
+impl ServiceData for Oneshot {
+    const SERVICE_ID: ServiceId = "oneshot";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Oneshot {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        ONESHOT_RUNS.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct OneshotApp {
+    oneshot: ServiceHandle<Oneshot>,
+}
+
+#[test]
+fn start_step_runs_services_that_ended_on_their_own_again() {
+    let overwatch =
+        OverwatchRunner::<OneshotApp>::run(OneshotAppServiceSettings { oneshot: () }, None)
+            .unwrap();
+    let handle = overwatch.handle().clone();
+
+    let rerun = overwatch.block_on(async {
+        while ONESHOT_RUNS.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // let the task of the first run finish after counting it
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle
+            .run_sequence(&Sequence::new().start::<Oneshot>())
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while ONESHOT_RUNS.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert!(rerun.is_ok());
+}
@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::metrics::TaskMetrics;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+static BLOCKING_FINISHED: AtomicBool = AtomicBool::new(false);
+static LOCAL_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Leaves a blocking and a local task behind when it stops
+struct Worker {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Worker {
+    const SERVICE_ID: ServiceId = "worker";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Worker {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state.status_handle.updater().running();
+        self.service_state.spawn_blocking(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            BLOCKING_FINISHED.store(true, Ordering::SeqCst);
+        });
+        let (started, local_started) = tokio::sync::oneshot::channel();
+        self.service_state.spawn_local(|| async move {
+            // not `Send`, it could not be held across an await on the shared runtime
+            let local = Rc::new(());
+            LOCAL_STARTED.store(true, Ordering::SeqCst);
+            let _ = started.send(());
+            std::future::pending::<()>().await;
+            drop(local);
+        });
+        local_started.await?;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    worker: ServiceHandle<Worker>,
+}
+
+static LOCAL_THREADS: Mutex<Vec<ThreadId>> = Mutex::new(Vec::new());
+
+/// Runs two local tasks and leaves a blocking task running for long behind
+struct Lingering {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Lingering {
+    const SERVICE_ID: ServiceId = "lingering";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Lingering {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state.status_handle.updater().running();
+        for _ in 0..2 {
+            let thread = self
+                .service_state
+                .spawn_local(|| async { std::thread::current().id() })
+                .await?;
+            LOCAL_THREADS.lock().unwrap().extend(thread);
+        }
+        self.service_state.spawn_blocking(|| {
+            std::thread::sleep(Duration::from_secs(1));
+        });
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct LingeringApp {
+    lingering: ServiceHandle<Lingering>,
+}
+
+#[test]
+fn services_are_stopped_once_their_tasks_are_done() {
+    let overwatch = OverwatchRunner::<App>::run(AppServiceSettings { worker: () }, None).unwrap();
+    let handle = overwatch.handle().clone();
+    let (stopped, finished, tasks) = overwatch.block_on(async {
+        let mut worker = handle.status_watcher::<Worker>().await.unwrap();
+        let stopped = worker
+            .wait_for(ServiceStatus::Stopped, Some(Duration::from_secs(2)))
+            .await;
+        let finished = BLOCKING_FINISHED.load(Ordering::SeqCst);
+        let tasks = handle
+            .metrics()
+            .service("worker")
+            .map(|worker| worker.tasks);
+        (stopped, finished, tasks)
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert_eq!(stopped, Ok(ServiceStatus::Stopped));
+    assert!(finished);
+    assert!(LOCAL_STARTED.load(Ordering::SeqCst));
+    assert_eq!(
+        tasks,
+        Some(TaskMetrics {
+            spawned: 2,
+            running: 0,
+        })
+    );
+}
+
+#[test]
+fn leftover_tasks_are_waited_for_up_to_the_shutdown_grace() {
+    let overwatch = OverwatchRunner::<LingeringApp>::run_with_context_config(
+        LingeringAppServiceSettings { lingering: () },
+        None,
+        ContextConfig::default().with_shutdown_grace(Duration::from_millis(100)),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let started = Instant::now();
+    let stopped = overwatch.block_on(async {
+        let mut lingering = handle.status_watcher::<Lingering>().await.unwrap();
+        lingering
+            .wait_for(ServiceStatus::Stopped, Some(Duration::from_secs(2)))
+            .await
+    });
+    let waited = started.elapsed();
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert_eq!(stopped, Ok(ServiceStatus::Stopped));
+    assert!(waited < Duration::from_secs(1), "waited {waited:?}");
+    let threads = LOCAL_THREADS.lock().unwrap().clone();
+    assert_eq!(threads.len(), 2);
+    // local tasks share the thread of the service
+    assert_eq!(threads[0], threads[1]);
+    assert_ne!(threads[0], std::thread::current().id());
+}
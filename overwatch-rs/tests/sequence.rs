@@ -1,5 +1,5 @@
 use overwatch_derive::Services;
-use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::overwatch::{OverwatchRunner, Services};
 use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
 use overwatch_rs::services::relay::NoMessage;
 use overwatch_rs::services::state::{NoOperator, NoState};
@@ -87,7 +87,7 @@ impl ServiceCore for AwaitService2 {
             .service_state
             .overwatch_handle
             .status_watcher::<AwaitService1>()
-            .await;
+            .await?;
 
         watcher
             .wait_for(ServiceStatus::Running, Some(Duration::from_millis(50)))
@@ -127,7 +127,7 @@ impl ServiceCore for AwaitService3 {
             .service_state
             .overwatch_handle
             .status_watcher::<AwaitService2>()
-            .await;
+            .await?;
 
         watcher
             .wait_for(ServiceStatus::Running, Some(Duration::from_millis(50)))
@@ -171,3 +171,30 @@ fn sequenced_services_startup() {
     });
     overwatch.wait_finished();
 }
+
+#[test]
+fn services_ids_from_str() {
+    assert_eq!(SequenceServices::SERVICES_IDS, ["S3", "S2", "S1"]);
+    assert_eq!(SequenceServices::service_id_from_str("s2"), Some("S2"));
+    assert_eq!(SequenceServices::service_id_from_str("S4"), None);
+}
+
+#[test]
+fn status_watchers_are_obtained_within_timeout() {
+    let settings = SequenceServicesServiceSettings {
+        a: (),
+        b: (),
+        c: (),
+    };
+    let overwatch = OverwatchRunner::<SequenceServices>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let watcher = handle
+            .status_watcher_with_timeout::<AwaitService1>(Duration::from_secs(1))
+            .await;
+        assert!(watcher.is_ok());
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
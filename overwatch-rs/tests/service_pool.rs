@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::events::{ScalingChange, ScalingEvent};
+use overwatch_rs::overwatch::{Error, OverwatchRunner};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::pool::{member_id, BalanceStrategy, ServicePool};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct WhoAmI(oneshot::Sender<(ServiceId, String)>);
+
+impl RelayMessage for WhoAmI {}
+
+struct Worker {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Worker {
+    const SERVICE_ID: ServiceId = "worker";
+    type Settings = String;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = WhoAmI;
+}
+
+#[async_trait]
+impl ServiceCore for Worker {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let shard = self.service_state.settings_reader.get_updated_settings();
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        while let Some(WhoAmI(reply)) = self.service_state.inbound_relay.recv().await {
+            let _ = reply.send((self.service_state.id(), shard.clone()));
+        }
+        Ok(())
+    }
+}
+
+struct Coordinator;
+
+impl ServiceData for Coordinator {
+    const SERVICE_ID: ServiceId = "coordinator";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = WhoAmI;
+}
+
+#[async_trait]
+impl ServiceCore for Coordinator {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct ShardedApp {
+    coordinator: ServiceHandle<Coordinator>,
+    workers: ServicePool<Worker>,
+}
+
+#[test]
+fn pool_members_come_from_settings() {
+    let settings = ShardedAppServiceSettings {
+        coordinator: (),
+        workers: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+    };
+    let overwatch = OverwatchRunner::<ShardedApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        for (index, shard) in ["a", "b", "c"].into_iter().enumerate() {
+            let id = member_id::<Worker>(index);
+            assert_eq!(id, format!("worker#{index}"));
+            handle
+                .status_watcher_for(id)
+                .await
+                .unwrap()
+                .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+            let relay = handle.relay_to::<Worker>(id).connect().await.unwrap();
+            let (sender, receiver) = oneshot::channel();
+            relay.send(WhoAmI(sender)).await.unwrap();
+            assert_eq!(receiver.await.unwrap(), (id, shard.to_string()));
+        }
+        assert!(handle
+            .relay_to::<Worker>(member_id::<Worker>(3))
+            .connect()
+            .await
+            .is_err());
+        assert!(handle.relay::<Coordinator>().connect().await.is_ok());
+
+        let pool = handle
+            .pool_relay::<Worker>(BalanceStrategy::RoundRobin)
+            .await
+            .unwrap();
+        assert_eq!(pool.len(), 3);
+        let mut shards = Vec::new();
+        for _ in 0..3 {
+            let (sender, receiver) = oneshot::channel();
+            pool.send(WhoAmI(sender)).await.unwrap();
+            shards.push(receiver.await.unwrap().1);
+        }
+        assert_eq!(shards, ["a", "b", "c"]);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn pool_scales_to_target() {
+    let settings = ShardedAppServiceSettings {
+        coordinator: (),
+        workers: vec!["a".to_string(), "b".to_string()],
+    };
+    let overwatch = OverwatchRunner::<ShardedApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut events = Box::pin(handle.scaling_events());
+        handle.scale::<Worker>(4).await.unwrap();
+        for (index, members) in [(2, 3), (3, 4)] {
+            assert_eq!(
+                events.next().await.unwrap(),
+                ScalingEvent {
+                    pool: Worker::SERVICE_ID,
+                    member: member_id::<Worker>(index),
+                    change: ScalingChange::Started,
+                    members,
+                    target: 4,
+                }
+            );
+        }
+        let id = member_id::<Worker>(3);
+        handle
+            .status_watcher_for(id)
+            .await
+            .unwrap()
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        // new members start from the first settings entry
+        let relay = handle.relay_to::<Worker>(id).connect().await.unwrap();
+        let (sender, receiver) = oneshot::channel();
+        relay.send(WhoAmI(sender)).await.unwrap();
+        assert_eq!(receiver.await.unwrap(), (id, "a".to_string()));
+
+        handle.scale::<Worker>(1).await.unwrap();
+        // members are stopped from the highest index down
+        for (index, members) in [(3, 3), (2, 2), (1, 1)] {
+            let event = events.next().await.unwrap();
+            assert_eq!(event.change, ScalingChange::Stopped);
+            assert_eq!(
+                (event.member, event.members),
+                (member_id::<Worker>(index), members)
+            );
+        }
+        let pool = handle
+            .pool_relay::<Worker>(BalanceStrategy::RoundRobin)
+            .await
+            .unwrap();
+        assert_eq!(pool.len(), 1);
+
+        assert!(matches!(
+            handle.scale::<Coordinator>(2).await,
+            Err(Error::NotAPool { .. })
+        ));
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
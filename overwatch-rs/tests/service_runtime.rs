@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::handle::OverwatchHandle;
+use overwatch_rs::overwatch::{OverwatchRunner, OVERWATCH_THREAD_NAME};
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::runtime::RuntimePolicy;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::marker::PhantomData;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct ThreadName(oneshot::Sender<String>);
+
+impl RelayMessage for ThreadName {}
+
+trait Placement: Send + 'static {
+    const ID: ServiceId;
+    const POLICY: RuntimePolicy;
+}
+
+/// Answers with the name of the thread it runs on
+struct Reporter<P: Placement> {
+    service_state: ServiceStateHandle<Self>,
+    _placement: PhantomData<P>,
+}
+
+impl<P: Placement> ServiceData for Reporter<P> {
+    const SERVICE_ID: ServiceId = P::ID;
+    const RUNTIME_POLICY: RuntimePolicy = P::POLICY;
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = ThreadName;
+}
+
+#[async_trait]
+impl<P: Placement> ServiceCore for Reporter<P> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self {
+            service_state,
+            _placement: PhantomData,
+        })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        self.service_state.status_handle.updater().running();
+        while let Some(ThreadName(reply)) = self.service_state.inbound_relay.recv().await {
+            let name = std::thread::current().name().unwrap_or_default().to_owned();
+            let _ = reply.send(name);
+        }
+        Ok(())
+    }
+}
+
+struct Shared;
+
+impl Placement for Shared {
+    const ID: ServiceId = "shared";
+    const POLICY: RuntimePolicy = RuntimePolicy::Shared;
+}
+
+struct Dedicated;
+
+impl Placement for Dedicated {
+    const ID: ServiceId = "dedicated";
+    const POLICY: RuntimePolicy = RuntimePolicy::Dedicated { worker_threads: 1 };
+}
+
+struct Storage;
+
+impl Placement for Storage {
+    const ID: ServiceId = "storage";
+    const POLICY: RuntimePolicy = RuntimePolicy::Pool("io");
+}
+
+struct Network;
+
+impl Placement for Network {
+    const ID: ServiceId = "network";
+    const POLICY: RuntimePolicy = RuntimePolicy::Pool("io");
+}
+
+#[derive(Services)]
+struct App {
+    shared: ServiceHandle<Reporter<Shared>>,
+    dedicated: ServiceHandle<Reporter<Dedicated>>,
+    storage: ServiceHandle<Reporter<Storage>>,
+    network: ServiceHandle<Reporter<Network>>,
+}
+
+async fn thread_name<P: Placement>(handle: &OverwatchHandle) -> String {
+    let relay = handle
+        .relay_when_ready::<Reporter<P>>(Duration::from_secs(1))
+        .await
+        .unwrap();
+    let (reply, name) = oneshot::channel();
+    relay.send(ThreadName(reply)).await.unwrap();
+    name.await.unwrap()
+}
+
+#[test]
+fn services_run_on_the_runtime_of_their_policy() {
+    let overwatch = OverwatchRunner::<App>::run_with_context_config(
+        AppServiceSettings {
+            shared: (),
+            dedicated: (),
+            storage: (),
+            network: (),
+        },
+        None,
+        ContextConfig::default().with_thread_pool("io", 2),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let names = overwatch.block_on(async {
+        [
+            thread_name::<Shared>(&handle).await,
+            thread_name::<Dedicated>(&handle).await,
+            thread_name::<Storage>(&handle).await,
+            thread_name::<Network>(&handle).await,
+        ]
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert_eq!(
+        names,
+        [
+            OVERWATCH_THREAD_NAME,
+            "overwatch-dedicated",
+            "overwatch-io",
+            "overwatch-io"
+        ]
+    );
+}
+
+#[derive(Services)]
+struct PooledApp {
+    storage: ServiceHandle<Reporter<Storage>>,
+}
+
+#[test]
+fn services_do_not_start_on_empty_thread_pools() {
+    let overwatch = OverwatchRunner::<PooledApp>::run_with_context_config(
+        PooledAppServiceSettings { storage: () },
+        None,
+        ContextConfig::default().with_thread_pool("io", 0),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    // the runner gives up on booting instead of panicking on the empty pool
+    overwatch.wait_finished();
+    assert!(handle.shutdown_token().is_cancelled());
+}
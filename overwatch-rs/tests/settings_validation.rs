@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::{Error, OverwatchRunner};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::settings::ValidateSettings;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static APPLIED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Debug)]
+struct Capacity(usize);
+
+impl ValidateSettings for Capacity {
+    fn validate(&self) -> Result<(), DynError> {
+        if self.0 == 0 {
+            return Err("capacity must be nonzero".into());
+        }
+        Ok(())
+    }
+}
+
+struct Cache;
+
+impl ServiceData for Cache {
+    const SERVICE_ID: ServiceId = "cache";
+    type Settings = Capacity;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+
+    fn validate_settings(settings: &Self::Settings) -> Result<(), DynError> {
+        settings.validate()
+    }
+}
+
+#[async_trait]
+impl ServiceCore for Cache {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+struct Greeter {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Greeter {
+    const SERVICE_ID: ServiceId = "greeter";
+    type Settings = String;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Greeter {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        let settings = &mut self.service_state.settings_reader;
+        while settings.changed().await.is_ok() {
+            APPLIED
+                .lock()
+                .unwrap()
+                .push(settings.get_updated_settings());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    cache: ServiceHandle<Cache>,
+    greeter: ServiceHandle<Greeter>,
+}
+
+#[test]
+fn invalid_settings_are_rejected_for_every_service() {
+    let settings = AppServiceSettings {
+        cache: Capacity(8),
+        greeter: "hello".to_owned(),
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let rejected = handle
+            .update_settings::<App>(AppServiceSettings {
+                cache: Capacity(0),
+                greeter: "hi".to_owned(),
+            })
+            .await;
+        assert!(matches!(
+            rejected,
+            Err(Error::RejectedSettings {
+                service_id: "cache",
+                ..
+            })
+        ));
+        handle
+            .update_settings_and_wait::<App>(AppServiceSettings {
+                cache: Capacity(16),
+                greeter: "hey".to_owned(),
+            })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(*APPLIED.lock().unwrap(), ["hey"]);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::resources::SharedResources;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stands for an expensive connection pool, counting the services using it
+#[derive(Default)]
+struct Pool {
+    users: AtomicUsize,
+}
+
+struct PoolUser<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+    _pool: Arc<Pool>,
+}
+
+impl<const ID: u8> ServiceData for PoolUser<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        0 => "first",
+        _ => "second",
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for PoolUser<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        let pool = service_state.resources().require::<Pool>()?;
+        pool.users.fetch_add(1, Ordering::SeqCst);
+        Ok(Self {
+            service_state,
+            _pool: pool,
+        })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct PoolApp {
+    first: ServiceHandle<PoolUser<0>>,
+    second: ServiceHandle<PoolUser<1>>,
+}
+
+#[test]
+fn services_share_injected_resources() {
+    let pool = Arc::new(Pool::default());
+    let overwatch = OverwatchRunner::<PoolApp>::run_with_context_config(
+        PoolAppServiceSettings {
+            first: (),
+            second: (),
+        },
+        None,
+        ContextConfig::default()
+            .with_resources(SharedResources::new().with_shared(Arc::clone(&pool))),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    let users = Arc::clone(&pool);
+    overwatch.spawn(async move {
+        for service_id in ["first", "second"] {
+            handle
+                .status_watcher_for(service_id)
+                .await
+                .unwrap()
+                .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(users.users.load(Ordering::SeqCst), 2);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
+
+const ROUNDS: u32 = 10;
+
+#[derive(Debug)]
+struct Ping;
+
+impl RelayMessage for Ping {}
+
+#[derive(Debug)]
+struct Pong;
+
+impl RelayMessage for Pong {}
+
+/// Plays `ROUNDS` ping pong rounds and reports how long they took on the application clock
+struct Pinger {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Pinger {
+    const SERVICE_ID: ServiceId = "pinger";
+    type Settings = UnboundedSender<Duration>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Pong;
+}
+
+#[async_trait]
+impl ServiceCore for Pinger {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let Self { mut service_state } = self;
+        let ponger = service_state
+            .overwatch_handle
+            .relay::<Ponger>()
+            .connect()
+            .await?;
+        let clock = service_state.context.clock();
+        let started_at = clock.now();
+        for _ in 0..ROUNDS {
+            ponger.send(Ping).await.map_err(|(e, _)| e)?;
+            service_state.inbound_relay.recv().await.ok_or("no pong")?;
+        }
+        service_state
+            .settings_reader
+            .get_updated_settings()
+            .send(clock.now().saturating_duration_since(started_at))?;
+        Ok(())
+    }
+}
+
+struct Ponger {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Ponger {
+    const SERVICE_ID: ServiceId = "ponger";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Ping;
+}
+
+#[async_trait]
+impl ServiceCore for Ponger {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let Self { mut service_state } = self;
+        let pinger = service_state
+            .overwatch_handle
+            .relay::<Pinger>()
+            .connect()
+            .await?;
+        while service_state.inbound_relay.recv().await.is_some() {
+            pinger.send(Pong).await.map_err(|(e, _)| e)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct PingPong {
+    pinger: ServiceHandle<Pinger>,
+    ponger: ServiceHandle<Ponger>,
+}
+
+fn latency(from: Option<ServiceId>, to: ServiceId) -> Duration {
+    match (from, to) {
+        (Some("pinger"), "ponger") => Duration::from_secs(3),
+        (Some("ponger"), "pinger") => Duration::from_secs(2),
+        _ => Duration::ZERO,
+    }
+}
+
+#[test]
+fn relay_latencies_elapse_in_virtual_time() {
+    let (reports, mut report) = tokio::sync::mpsc::unbounded_channel();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()
+        .unwrap();
+    let overwatch = OverwatchRunner::<PingPong>::run_with_context_config(
+        PingPongServiceSettings {
+            pinger: reports,
+            ponger: (),
+        },
+        Some(runtime),
+        ContextConfig::simulation(latency),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let started_at = Instant::now();
+
+    overwatch.spawn(async move {
+        let elapsed = report.recv().await.unwrap();
+        assert_eq!(elapsed, Duration::from_secs(5) * ROUNDS);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+    assert!(started_at.elapsed() < Duration::from_secs(5));
+}
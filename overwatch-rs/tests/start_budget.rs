@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::teardown::ServiceExit;
+use overwatch_rs::overwatch::{Error, OverwatchRunner};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId, StartBudget};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+const QUICK: u8 = 0;
+const LAGGING: u8 = 1;
+const STUCK: u8 = 2;
+
+const BUDGET: Duration = Duration::from_millis(50);
+
+/// Reports `Running` after the delay it is given as settings, never if `None`
+struct Starting<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Starting<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        QUICK => "quick",
+        LAGGING => "lagging",
+        _ => "stuck",
+    };
+    const START_BUDGET: Option<StartBudget> = match ID {
+        STUCK => Some(StartBudget::new(BUDGET).aborting()),
+        _ => Some(StartBudget::new(BUDGET)),
+    };
+    type Settings = Option<Duration>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Starting<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        if let Some(delay) = self.service_state.settings_reader.get_updated_settings() {
+            tokio::time::sleep(delay).await;
+            self.service_state
+                .status_handle
+                .updater()
+                .update(ServiceStatus::Running);
+        }
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct StartingApp {
+    quick: ServiceHandle<Starting<QUICK>>,
+    lagging: ServiceHandle<Starting<LAGGING>>,
+    stuck: ServiceHandle<Starting<STUCK>>,
+}
+
+#[test]
+fn services_exceeding_their_start_budget_fail() {
+    let settings = StartingAppServiceSettings {
+        quick: Some(Duration::ZERO),
+        lagging: Some(BUDGET * 4),
+        stuck: None,
+    };
+    let overwatch = OverwatchRunner::<StartingApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+    let errors = handle.error_events();
+
+    overwatch.spawn(async move {
+        let report = handle.wait_for_boot_report().await;
+        let mut failed: Vec<_> = report
+            .started
+            .iter()
+            .filter(|boot| boot.failed)
+            .map(|boot| boot.service_id)
+            .collect();
+        failed.sort_unstable();
+        assert_eq!(failed, ["lagging", "stuck"]);
+
+        let mut timed_out: Vec<_> = errors
+            .take(2)
+            .map(|event| {
+                let error = event.error.downcast_ref::<Error>();
+                assert!(
+                    matches!(error, Some(Error::StartTimeout { budget, .. }) if *budget == BUDGET),
+                    "{error:?}"
+                );
+                event.service_id
+            })
+            .collect()
+            .await;
+        timed_out.sort_unstable();
+        assert_eq!(timed_out, ["lagging", "stuck"]);
+
+        // the lagging service was left running and recovers, the stuck one was aborted
+        let mut lagging = handle.status_watcher::<Starting<LAGGING>>().await.unwrap();
+        lagging
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let stuck = handle.status_watcher::<Starting<STUCK>>().await.unwrap();
+        assert!(stuck.current().is_failed());
+
+        let report = handle.shutdown_and_wait().await.unwrap();
+        assert!(
+            matches!(report.exit("stuck"), Some(ServiceExit::Failed(_))),
+            "{report:?}"
+        );
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Makes [`Unreliable::init`] fail from then on
+static BROKEN: AtomicBool = AtomicBool::new(false);
+
+struct Unreliable {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Unreliable {
+    const SERVICE_ID: ServiceId = "unreliable";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Unreliable {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        if BROKEN.load(Ordering::SeqCst) {
+            return Err("broken".into());
+        }
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct UnreliableApp {
+    unreliable: ServiceHandle<Unreliable>,
+}
+
+#[test]
+fn failed_start_is_reported_to_watchers() {
+    let overwatch = OverwatchRunner::<UnreliableApp>::run(
+        UnreliableAppServiceSettings { unreliable: () },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut watcher = handle.status_watcher::<Unreliable>().await.unwrap();
+        watcher
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        handle.stop_service::<Unreliable>().await.unwrap();
+        assert_eq!(watcher.current(), ServiceStatus::Stopped);
+
+        BROKEN.store(true, Ordering::SeqCst);
+        assert!(handle.start_service::<Unreliable>().await.is_err());
+        assert!(watcher.current().is_failed());
+
+        BROKEN.store(false, Ordering::SeqCst);
+        handle.start_service::<Unreliable>().await.unwrap();
+        watcher
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::handle::OverwatchHandle;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::pool::ServicePool;
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+struct Idle<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Idle<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        0 => "idle",
+        _ => "worker",
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Idle<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct App {
+    idle: ServiceHandle<Idle<0>>,
+    workers: ServicePool<Idle<1>>,
+}
+
+const SERVICES: [ServiceId; 3] = ["idle", "worker#0", "worker#1"];
+
+async fn wait_for_all(handle: &OverwatchHandle, status: ServiceStatus) {
+    for service_id in SERVICES {
+        let reached = handle
+            .status_watcher_for(service_id)
+            .await
+            .unwrap()
+            .wait_for(status.clone(), Some(Duration::from_secs(1)))
+            .await;
+        assert_eq!(reached, Ok(status.clone()), "{service_id}");
+    }
+}
+
+#[test]
+fn stopped_services_are_started_again() {
+    let settings = AppServiceSettings {
+        idle: (),
+        workers: vec![(), ()],
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.runtime().block_on(async {
+        wait_for_all(&handle, ServiceStatus::Running).await;
+        handle.stop_all().await.unwrap();
+        wait_for_all(&handle, ServiceStatus::Stopped).await;
+        handle.start_all().await.unwrap();
+        wait_for_all(&handle, ServiceStatus::Running).await;
+    });
+    overwatch.spawn(async move { handle.shutdown().await });
+    overwatch.wait_finished();
+}
@@ -3,7 +3,7 @@ use overwatch_derive::Services;
 use overwatch_rs::overwatch::OverwatchRunner;
 use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
 use overwatch_rs::services::relay::RelayMessage;
-use overwatch_rs::services::state::{ServiceState, StateOperator};
+use overwatch_rs::services::state::{PersistContext, ServiceState, StateOperator};
 use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
 use std::convert::Infallible;
 use std::time::Duration;
@@ -15,6 +15,7 @@ pub struct UpdateStateService {
 }
 
 #[derive(Clone, Debug)]
+#[allow(dead_code)]
 pub struct UpdateStateServiceMessage(String);
 
 impl RelayMessage for UpdateStateServiceMessage {}
@@ -62,7 +63,7 @@ impl StateOperator for CounterStateOperator {
         CounterStateOperator
     }
 
-    async fn run(&mut self, state: Self::StateInput) {
+    async fn run(&mut self, state: Self::StateInput, _context: PersistContext) {
         let value = state.value;
         let mut stdout = io::stdout();
         stdout
@@ -95,7 +96,7 @@ impl ServiceCore for UpdateStateService {
             state: ServiceStateHandle { state_updater, .. },
         } = self;
         for value in 0..10 {
-            state_updater.update(CounterState { value });
+            state_updater.update(CounterState { value })?;
             sleep(Duration::from_millis(50)).await;
         }
         Ok(())
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::commands::RestartMode;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::life_cycle::{LifecycleMessage, StopReason};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Reports the reason it was stopped for
+struct Reporting {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Reporting {
+    const SERVICE_ID: ServiceId = "reporting";
+    type Settings = broadcast::Sender<StopReason>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Reporting {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let reporter = self.service_state.settings_reader.get_updated_settings();
+        let mut lifecycle_stream = self.service_state.lifecycle_handle.message_stream();
+        if let Some(message) = lifecycle_stream.next().await {
+            reporter.send(message.stop_reason())?;
+            if let LifecycleMessage::Shutdown(finished, _) = message {
+                finished.send();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    reporting: ServiceHandle<Reporting>,
+}
+
+#[test]
+fn graceful_reconfiguration_is_reported_as_settings_change() {
+    let (reporter, mut reasons) = broadcast::channel(4);
+    let settings = AppServiceSettings {
+        reporting: reporter.clone(),
+    };
+    let overwatch = OverwatchRunner::<App>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        // let the service subscribe to lifecycle messages
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle
+            .reconfigure_service::<Reporting>(
+                reporter,
+                RestartMode::Graceful {
+                    timeout: Duration::from_secs(1),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(reasons.recv().await.unwrap(), StopReason::SettingsChange);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::supervisor::{Backoff, RestartPolicy};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const FLAKY: u8 = 0;
+const FINISHING: u8 = 1;
+const STEADY: u8 = 2;
+
+/// Number of times each service main loop was run
+static RUNS: [AtomicUsize; 3] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+const BACKOFF: Duration = Duration::from_millis(50);
+
+/// Flaky fails, the others finish successfully
+struct Supervised<const ID: u8>;
+
+impl<const ID: u8> ServiceData for Supervised<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        FLAKY => "flaky",
+        FINISHING => "finishing",
+        _ => "steady",
+    };
+    const RESTART_POLICY: RestartPolicy = match ID {
+        FLAKY => RestartPolicy::OnFailure(Backoff::fixed(BACKOFF).with_max_retries(2)),
+        FINISHING => RestartPolicy::Always(Backoff::fixed(BACKOFF).with_max_retries(1)),
+        _ => RestartPolicy::OnFailure(Backoff::fixed(BACKOFF)),
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Supervised<ID> {
+    fn init(
+        _service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self)
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        RUNS[ID as usize].fetch_add(1, Ordering::SeqCst);
+        if ID == FLAKY {
+            return Err("flaky".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct SupervisedApp {
+    flaky: ServiceHandle<Supervised<FLAKY>>,
+    finishing: ServiceHandle<Supervised<FINISHING>>,
+    steady: ServiceHandle<Supervised<STEADY>>,
+}
+
+#[test]
+fn services_are_restarted_as_their_policy_says() {
+    let settings = SupervisedAppServiceSettings {
+        flaky: (),
+        finishing: (),
+        steady: (),
+    };
+    let overwatch = OverwatchRunner::<SupervisedApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        let mut flaky = handle.status_watcher::<Supervised<FLAKY>>().await.unwrap();
+        flaky
+            .wait_for(ServiceStatus::Restarting, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+
+        // enough for every retry to be used up
+        tokio::time::sleep(BACKOFF * 8).await;
+        let runs: Vec<_> = RUNS
+            .iter()
+            .map(|runs| runs.load(Ordering::SeqCst))
+            .collect();
+        assert_eq!(runs, [3, 2, 1]);
+        assert_eq!(flaky.current(), ServiceStatus::Stopped);
+
+        // stopping the service gives it a fresh backoff
+        handle.stop_service::<Supervised<FLAKY>>().await.unwrap();
+        handle.start_service::<Supervised<FLAKY>>().await.unwrap();
+        tokio::time::sleep(BACKOFF * 8).await;
+        assert_eq!(RUNS[FLAKY as usize].load(Ordering::SeqCst), 6);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+/// Runs of the recovering service
+static RECOVERING_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Stays up for a while before failing, but from its third run on which fails right away
+struct Recovering {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Recovering {
+    const SERVICE_ID: ServiceId = "recovering";
+    const RESTART_POLICY: RestartPolicy = RestartPolicy::OnFailure(
+        Backoff::fixed(Duration::from_millis(10))
+            .with_max_retries(1)
+            .with_stable_after(BACKOFF),
+    );
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Recovering {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let run = RECOVERING_RUNS.fetch_add(1, Ordering::SeqCst);
+        self.service_state.status_handle.updater().running();
+        if run < 2 {
+            tokio::time::sleep(BACKOFF * 2).await;
+        }
+        Err("recovering".into())
+    }
+}
+
+#[derive(Services)]
+struct RecoveringApp {
+    recovering: ServiceHandle<Recovering>,
+}
+
+#[test]
+fn restarts_are_forgotten_once_services_are_stable() {
+    let overwatch = OverwatchRunner::<RecoveringApp>::run(
+        RecoveringAppServiceSettings { recovering: () },
+        None,
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    let status = overwatch.block_on(async {
+        let mut recovering = handle.status_watcher::<Recovering>().await.unwrap();
+        // the first two runs stay up long enough to get a fresh backoff, the third one uses
+        // up the only retry
+        recovering
+            .wait_until(
+                |status| {
+                    *status == ServiceStatus::Stopped && RECOVERING_RUNS.load(Ordering::SeqCst) == 3
+                },
+                Some(BACKOFF * 20),
+            )
+            .await
+    });
+    overwatch.block_on(handle.shutdown());
+    overwatch.wait_finished();
+    assert_eq!(status, Ok(ServiceStatus::Stopped));
+    assert_eq!(RECOVERING_RUNS.load(Ordering::SeqCst), 3);
+}
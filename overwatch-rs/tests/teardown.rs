@@ -0,0 +1,258 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::teardown::{ServiceExit, ShutdownOrder};
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::clock::ManualClock;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::life_cycle::LifecycleMessage;
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How a service reacts to the kill message sent on shutdown
+#[derive(Clone, Copy, Debug)]
+enum OnKill {
+    Return,
+    Fail,
+    Ignore,
+}
+
+struct Killable<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Killable<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        0 => "returning",
+        1 => "failing",
+        _ => "stubborn",
+    };
+    type Settings = OnKill;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Killable<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let on_kill = self.service_state.settings_reader.get_updated_settings();
+        let mut lifecycle = self.service_state.lifecycle_handle.message_stream();
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        while let Some(message) = lifecycle.next().await {
+            if let LifecycleMessage::Kill(_) = message {
+                match on_kill {
+                    OnKill::Return => return Ok(()),
+                    OnKill::Fail => return Err("killed".into()),
+                    OnKill::Ignore => {}
+                }
+            }
+        }
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct KillableApp {
+    returning: ServiceHandle<Killable<0>>,
+    failing: ServiceHandle<Killable<1>>,
+    stubborn: ServiceHandle<Killable<2>>,
+}
+
+#[test]
+fn shutdown_reports_how_services_ended() {
+    let settings = KillableAppServiceSettings {
+        returning: OnKill::Return,
+        failing: OnKill::Fail,
+        stubborn: OnKill::Ignore,
+    };
+    let overwatch = OverwatchRunner::<KillableApp>::run_with_context_config(
+        settings,
+        None,
+        ContextConfig::default().with_shutdown_grace(Duration::from_millis(200)),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        for service_id in ["returning", "failing", "stubborn"] {
+            handle
+                .status_watcher_for(service_id)
+                .await
+                .unwrap()
+                .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+        }
+        let report = handle.shutdown_and_wait().await.unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.exits,
+            [
+                ("failing", ServiceExit::Failed("killed".to_string())),
+                ("returning", ServiceExit::Finished),
+                ("stubborn", ServiceExit::Aborted),
+            ]
+        );
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn shutdown_grace_follows_the_application_clock() {
+    let settings = KillableAppServiceSettings {
+        returning: OnKill::Return,
+        failing: OnKill::Return,
+        stubborn: OnKill::Ignore,
+    };
+    let clock = ManualClock::new();
+    let overwatch = OverwatchRunner::<KillableApp>::run_with_context_config(
+        settings,
+        None,
+        ContextConfig::default()
+            .with_clock(clock.clone())
+            .with_shutdown_grace(Duration::from_secs(3600)),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        handle
+            .status_watcher_for("stubborn")
+            .await
+            .unwrap()
+            .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let shutdown = tokio::spawn(async move { handle.shutdown_and_wait().await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!shutdown.is_finished());
+        // the stubborn service is only aborted once the grace period passed on the clock
+        clock.advance(Duration::from_secs(3600));
+        let report = shutdown.await.unwrap().unwrap();
+        assert_eq!(report.exit("stubborn"), Some(&ServiceExit::Aborted));
+    });
+    overwatch.wait_finished();
+}
+
+#[test]
+fn waiting_for_shutdown_ends_when_the_runner_is_aborted() {
+    let settings = KillableAppServiceSettings {
+        returning: OnKill::Return,
+        failing: OnKill::Return,
+        stubborn: OnKill::Ignore,
+    };
+    let overwatch = OverwatchRunner::<KillableApp>::run(settings, None).unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.abort();
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let report = runtime.block_on(async {
+        tokio::time::timeout(Duration::from_secs(1), handle.shutdown_and_wait()).await
+    });
+    assert_eq!(report, Ok(None));
+}
+
+/// Records when it ends after being killed, the provider ends right away and the consumer takes
+/// a while to
+struct Recording<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Recording<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        0 => "provider",
+        _ => "consumer",
+    };
+    type Settings = Arc<Mutex<Vec<ServiceId>>>;
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Recording<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let ended = self.service_state.settings_reader.get_updated_settings();
+        let mut lifecycle = self.service_state.lifecycle_handle.message_stream();
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        while let Some(message) = lifecycle.next().await {
+            if let LifecycleMessage::Kill(_) = message {
+                if Self::SERVICE_ID == "consumer" {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                ended.lock().unwrap().push(Self::SERVICE_ID);
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct DependentApp {
+    provider: ServiceHandle<Recording<0>>,
+    consumer: ServiceHandle<Recording<1>>,
+}
+
+#[test]
+fn shutdown_stops_consumers_before_their_providers() {
+    let ended = Arc::new(Mutex::new(Vec::new()));
+    let settings = DependentAppServiceSettings {
+        provider: Arc::clone(&ended),
+        consumer: Arc::clone(&ended),
+    };
+    let order = ShutdownOrder::ReverseDependencies(HashMap::from([("consumer", vec!["provider"])]));
+    let overwatch = OverwatchRunner::<DependentApp>::run_with_context_config(
+        settings,
+        None,
+        ContextConfig::default()
+            .with_shutdown_grace(Duration::from_secs(1))
+            .with_shutdown_order(order),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        for service_id in ["provider", "consumer"] {
+            handle
+                .status_watcher_for(service_id)
+                .await
+                .unwrap()
+                .wait_for(ServiceStatus::Running, Some(Duration::from_secs(1)))
+                .await
+                .unwrap();
+        }
+        let report = handle.shutdown_and_wait().await.unwrap();
+        assert!(report.is_clean());
+    });
+    overwatch.wait_finished();
+    assert_eq!(*ended.lock().unwrap(), ["consumer", "provider"]);
+}
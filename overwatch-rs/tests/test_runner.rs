@@ -0,0 +1,71 @@
+#![cfg(feature = "test-util")]
+
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::time::Duration;
+
+/// Warms up for a minute before running
+struct Sluggish {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Sluggish {
+    const SERVICE_ID: ServiceId = "sluggish";
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl ServiceCore for Sluggish {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .context
+            .clock()
+            .sleep(Duration::from_secs(60))
+            .await;
+        self.service_state.status_handle.updater().running();
+        std::future::pending().await
+    }
+}
+
+#[derive(Services)]
+struct App {
+    sluggish: ServiceHandle<Sluggish>,
+}
+
+#[test]
+fn time_only_moves_when_advanced() {
+    use overwatch_rs::overwatch::testing::OverwatchTestRunner;
+    use overwatch_rs::services::status::ServiceStatus;
+    use std::time::Instant;
+
+    let started = Instant::now();
+    let runner = OverwatchTestRunner::<App>::run(AppServiceSettings { sluggish: () }).unwrap();
+    let origin = runner.now();
+    let status = runner
+        .block_on(runner.handle().status_watcher::<Sluggish>())
+        .unwrap();
+
+    runner.advance(Duration::from_secs(59));
+    assert_eq!(status.current(), ServiceStatus::Uninitialized);
+    runner.advance(Duration::from_secs(1));
+    assert_eq!(status.current(), ServiceStatus::Running);
+    assert_eq!(runner.now() - origin, Duration::from_secs(60));
+
+    assert!(runner.shutdown().is_some());
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "topology-json")]
+
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::teardown::ShutdownOrder;
+use overwatch_rs::overwatch::topology::TOPOLOGY_VERSION;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::pool::ServicePool;
+use overwatch_rs::services::relay::NoMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::status::ServiceStatus;
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId, ServicePriority};
+use overwatch_rs::DynError;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const GATEWAY: u8 = 0;
+const WORKER: u8 = 1;
+
+struct Idle<const ID: u8> {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl<const ID: u8> ServiceData for Idle<ID> {
+    const SERVICE_ID: ServiceId = match ID {
+        GATEWAY => "gateway",
+        _ => "worker",
+    };
+    const SERVICE_RELAY_BUFFER_SIZE: usize = 4;
+    const PRIORITY: ServicePriority = match ID {
+        GATEWAY => ServicePriority::High,
+        _ => ServicePriority::Normal,
+    };
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = NoMessage;
+}
+
+#[async_trait]
+impl<const ID: u8> ServiceCore for Idle<ID> {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        self.service_state
+            .status_handle
+            .updater()
+            .update(ServiceStatus::Running);
+        std::future::pending::<()>().await;
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct App {
+    gateway: ServiceHandle<Idle<GATEWAY>>,
+    workers: ServicePool<Idle<WORKER>>,
+}
+
+#[test]
+fn topology_is_exported_as_json() {
+    let settings = AppServiceSettings {
+        gateway: (),
+        workers: vec![(), ()],
+    };
+    let config = ContextConfig::default().with_shutdown_order(ShutdownOrder::ReverseDependencies(
+        HashMap::from([("gateway", vec!["worker"])]),
+    ));
+    let overwatch =
+        OverwatchRunner::<App>::run_with_context_config(settings, None, config).unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let topology: Value = serde_json::from_str(&handle.topology_json().await.unwrap()).unwrap();
+        let service = |id, group, priority| {
+            json!({
+                "id": id,
+                "group": group,
+                "priority": priority,
+                "relay_buffer_size": 4,
+                "status": "running",
+            })
+        };
+        assert_eq!(
+            topology,
+            json!({
+                "version": TOPOLOGY_VERSION,
+                "services": [
+                    service("gateway", None, "high"),
+                    service("worker#0", Some("worker"), "normal"),
+                    service("worker#1", Some("worker"), "normal"),
+                ],
+                "groups": [{"id": "worker", "size": 2}],
+                "dependencies": [{"service": "gateway", "depends_on": "worker"}],
+            })
+        );
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
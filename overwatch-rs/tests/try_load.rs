@@ -6,7 +6,7 @@ use overwatch_derive::Services;
 use overwatch_rs::overwatch::OverwatchRunner;
 use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
 use overwatch_rs::services::relay::NoMessage;
-use overwatch_rs::services::state::{ServiceState, StateOperator};
+use overwatch_rs::services::state::{PersistContext, ServiceState, StateOperator};
 use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
 use overwatch_rs::DynError;
 use tokio::sync::broadcast;
@@ -47,7 +47,7 @@ impl StateOperator for TryLoadOperator {
         Self {}
     }
 
-    async fn run(&mut self, _state: Self::StateInput) {}
+    async fn run(&mut self, _state: Self::StateInput, _context: PersistContext) {}
 }
 
 #[derive(Debug, Clone)]
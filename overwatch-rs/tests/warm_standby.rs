@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::{Error, OverwatchRunner};
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::RelayMessage;
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use tokio::sync::oneshot;
+
+static INSTANCES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct WhoAmI(oneshot::Sender<usize>);
+
+impl RelayMessage for WhoAmI {}
+
+struct ConnectionService {
+    service_state: ServiceStateHandle<Self>,
+    instance: usize,
+}
+
+impl ServiceData for ConnectionService {
+    const SERVICE_ID: ServiceId = "connection";
+    const WARM_STANDBY: bool = true;
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = WhoAmI;
+}
+
+#[async_trait]
+impl ServiceCore for ConnectionService {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        // stands for the slow connection setup
+        let instance = INSTANCES.fetch_add(1, Ordering::SeqCst);
+        Ok(Self {
+            service_state,
+            instance,
+        })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        while let Some(WhoAmI(reply)) = self.service_state.inbound_relay.recv().await {
+            let _ = reply.send(self.instance);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct StandbyApp {
+    connection: ServiceHandle<ConnectionService>,
+}
+
+async fn who_answers(handle: &overwatch_rs::overwatch::handle::OverwatchHandle) -> usize {
+    let relay = handle.relay::<ConnectionService>().connect().await.unwrap();
+    let (sender, receiver) = oneshot::channel();
+    relay.send(WhoAmI(sender)).await.unwrap();
+    receiver.await.unwrap()
+}
+
+#[test]
+fn failover_promotes_initialized_standby() {
+    let overwatch =
+        OverwatchRunner::<StandbyApp>::run(StandbyAppServiceSettings { connection: () }, None)
+            .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.spawn(async move {
+        assert_eq!(who_answers(&handle).await, 0);
+        // primary and standby are both initialized upfront
+        assert_eq!(INSTANCES.load(Ordering::SeqCst), 2);
+
+        handle.failover::<ConnectionService>().await.unwrap();
+        assert_eq!(who_answers(&handle).await, 1);
+        // and a new standby is ready for the next failover
+        assert_eq!(INSTANCES.load(Ordering::SeqCst), 3);
+
+        handle.failover::<ConnectionService>().await.unwrap();
+        assert_eq!(who_answers(&handle).await, 2);
+        handle.shutdown().await;
+    });
+    overwatch.wait_finished();
+}
+
+/// Port the running [`ListenerService`] instance is bound to
+static LISTENER_PORT: AtomicU16 = AtomicU16::new(0);
+
+/// Listens on a port, which its standby instance can't bind as well
+struct ListenerService {
+    service_state: ServiceStateHandle<Self>,
+    _listener: TcpListener,
+}
+
+impl ServiceData for ListenerService {
+    const SERVICE_ID: ServiceId = "listener";
+    const WARM_STANDBY: bool = true;
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = WhoAmI;
+}
+
+#[async_trait]
+impl ServiceCore for ListenerService {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        let listener = TcpListener::bind(("127.0.0.1", LISTENER_PORT.load(Ordering::SeqCst)))?;
+        LISTENER_PORT.store(listener.local_addr()?.port(), Ordering::SeqCst);
+        Ok(Self {
+            service_state,
+            _listener: listener,
+        })
+    }
+
+    async fn run(mut self) -> Result<(), DynError> {
+        while let Some(WhoAmI(reply)) = self.service_state.inbound_relay.recv().await {
+            let _ = reply.send(0);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Services)]
+struct ListenerApp {
+    listener: ServiceHandle<ListenerService>,
+}
+
+#[test]
+fn services_run_without_the_standby_failing_to_initialize() {
+    let overwatch =
+        OverwatchRunner::<ListenerApp>::run(ListenerAppServiceSettings { listener: () }, None)
+            .unwrap();
+    let handle = overwatch.handle().clone();
+
+    overwatch.block_on(async {
+        let relay = handle.relay::<ListenerService>().connect().await.unwrap();
+        let (sender, receiver) = oneshot::channel();
+        relay.send(WhoAmI(sender)).await.unwrap();
+        assert_eq!(receiver.await, Ok(0));
+        assert!(matches!(
+            handle.failover::<ListenerService>().await,
+            Err(Error::NoStandby {
+                service_id: "listener"
+            })
+        ));
+        // the running instance is still the runner's to stop
+        let report = handle.shutdown_and_wait().await.unwrap();
+        let stopped: Vec<_> = report
+            .exits
+            .iter()
+            .map(|(service_id, _)| *service_id)
+            .collect();
+        assert_eq!(stopped, ["listener"]);
+    });
+    overwatch.wait_finished();
+}
@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use overwatch_derive::Services;
+use overwatch_rs::overwatch::events::WatchdogEvent;
+use overwatch_rs::overwatch::watchdog::Watchdog;
+use overwatch_rs::overwatch::OverwatchRunner;
+use overwatch_rs::services::context::ContextConfig;
+use overwatch_rs::services::handle::{ServiceHandle, ServiceStateHandle};
+use overwatch_rs::services::relay::{OutboundRelay, RelayMessage};
+use overwatch_rs::services::state::{NoOperator, NoState};
+use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
+use overwatch_rs::DynError;
+use std::collections::HashSet;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Bounce;
+
+impl RelayMessage for Bounce {}
+
+/// Sends two messages to `peer` for each message it gets, until both relays are full
+async fn bounce<S>(
+    mut service_state: ServiceStateHandle<S>,
+    peer: OutboundRelay<Bounce>,
+) -> Result<(), DynError>
+where
+    S: ServiceCore<Message = Bounce>,
+{
+    service_state.status_handle.updater().running();
+    while let Some(Bounce) = service_state.inbound_relay.recv().await {
+        for _ in 0..2 {
+            peer.send(Bounce).await.map_err(|(e, _)| e)?;
+        }
+    }
+    Ok(())
+}
+
+struct Ping {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Ping {
+    const SERVICE_ID: ServiceId = "ping";
+    const SERVICE_RELAY_BUFFER_SIZE: usize = 2;
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Bounce;
+}
+
+#[async_trait]
+impl ServiceCore for Ping {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let pong = self
+            .service_state
+            .overwatch_handle
+            .relay::<Pong>()
+            .connect()
+            .await?;
+        bounce(self.service_state, pong).await
+    }
+}
+
+struct Pong {
+    service_state: ServiceStateHandle<Self>,
+}
+
+impl ServiceData for Pong {
+    const SERVICE_ID: ServiceId = "pong";
+    const SERVICE_RELAY_BUFFER_SIZE: usize = 2;
+    type Settings = ();
+    type State = NoState<Self::Settings>;
+    type StateOperator = NoOperator<Self::State>;
+    type Message = Bounce;
+}
+
+#[async_trait]
+impl ServiceCore for Pong {
+    fn init(
+        service_state: ServiceStateHandle<Self>,
+        _initial_state: Self::State,
+    ) -> Result<Self, DynError> {
+        Ok(Self { service_state })
+    }
+
+    async fn run(self) -> Result<(), DynError> {
+        let ping = self
+            .service_state
+            .overwatch_handle
+            .relay::<Ping>()
+            .connect()
+            .await?;
+        bounce(self.service_state, ping).await
+    }
+}
+
+#[derive(Services)]
+struct App {
+    ping: ServiceHandle<Ping>,
+    pong: ServiceHandle<Pong>,
+}
+
+#[test]
+fn deadlocked_services_are_reported() {
+    let watchdog = Watchdog::new()
+        .with_interval(Duration::from_millis(10))
+        .with_full_relay_threshold(Duration::from_millis(50))
+        .with_heartbeat(Duration::from_millis(50));
+    let overwatch = OverwatchRunner::<App>::run_with_context_config(
+        AppServiceSettings { ping: (), pong: () },
+        None,
+        ContextConfig::default().with_watchdog(watchdog),
+    )
+    .unwrap();
+    let handle = overwatch.handle().clone();
+    overwatch.spawn(async move {
+        let mut events = Box::pin(handle.watchdog_events());
+        let ping = handle
+            .relay_when_ready::<Ping>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        handle
+            .relay_when_ready::<Pong>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        ping.send(Bounce).await.unwrap();
+
+        let mut reported = HashSet::new();
+        while reported.len() < 4 {
+            let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+                .await
+                .unwrap()
+                .unwrap();
+            match event {
+                WatchdogEvent::RelayFull { service_id, since } => {
+                    assert!(since >= Duration::from_millis(50));
+                    reported.insert(("full", service_id));
+                }
+                WatchdogEvent::Unresponsive { service_id, since } => {
+                    assert!(since >= Duration::from_millis(50));
+                    reported.insert(("unresponsive", service_id));
+                }
+            }
+        }
+        handle.kill().await;
+    });
+    overwatch.wait_finished();
+}